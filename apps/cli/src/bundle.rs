@@ -0,0 +1,288 @@
+use std::{
+    env, fs,
+    path::{Component, Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use daemon_client::{Requester, ServiceInfo};
+use project_settings::try_parse;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::output::Output;
+
+/// Since version 2 the paths inside the bundle root are stored relative to it
+const BUNDLE_VERSION: u32 = 2;
+/// Keys of the settings holding absolute paths on the exporting machine
+const PATH_KEYS: [&str; 2] = ["cwd", "source_path"];
+
+/// Portable set of project settings, settings are kept as stored by the daemon so no key is lost
+#[derive(Debug, Serialize, Deserialize)]
+struct Bundle {
+    version: u32,
+    exported_at: u64,
+    projects: Vec<BundledProject>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BundledProject {
+    settings: Value,
+    /// status of the services at the time of the export, it is informative only and is not restored
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    services: Option<Vec<BundledService>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BundledService {
+    name: String,
+    status: String,
+    pid: i32,
+    start_time: u64,
+    stop_time: u64,
+}
+
+impl From<ServiceInfo> for BundledService {
+    fn from(value: ServiceInfo) -> Self {
+        Self {
+            name: value.name,
+            status: value.status.to_string(),
+            pid: value.pid,
+            start_time: value.start_time,
+            stop_time: value.stop_time,
+        }
+    }
+}
+
+pub struct ExportOptions {
+    pub projects: Vec<String>,
+    pub out: Option<String>,
+    pub status: bool,
+    pub root: Option<String>,
+}
+
+/// Writes settings of the selected projects, or of all projects when none is selected,
+/// into the bundle file or to stdout
+pub fn export_bundle(requester: &Requester, options: ExportOptions) -> Output {
+    let bundle = match create_bundle(requester, &options) {
+        Ok(bundle) => bundle,
+        Err(err) => return Output::Stderr(err),
+    };
+
+    let content = match serde_json::to_string_pretty(&bundle) {
+        Ok(content) => content + "\n",
+        Err(err) => return Output::Stderr(err.to_string()),
+    };
+
+    match options.out {
+        Some(path) => match fs::write(&path, content) {
+            Ok(_) => Output::Stdout(format!(
+                "Exported {} projects to '{}'.",
+                bundle.projects.len(),
+                path
+            )),
+            Err(err) => Output::Stderr(format!("Unable to write '{}': {}", path, err)),
+        },
+        None => Output::Stdout(String::from(content.trim_end())),
+    }
+}
+
+/// Creates or replaces all projects from the bundle, nothing is imported when any of the settings is invalid.
+/// Relative paths of the projects are resolved against the root, the current dir by default.
+pub fn import_bundle(requester: &Requester, path: &str, root: Option<&str>) -> Output {
+    let root = match resolve_root(root) {
+        Ok(root) => root,
+        Err(err) => return Output::Stderr(err),
+    };
+
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) => return Output::Stderr(format!("Unable to read '{}': {}", path, err)),
+    };
+
+    let bundle: Bundle = match serde_json::from_str(&content) {
+        Ok(bundle) => bundle,
+        Err(err) => return Output::Stderr(format!("Invalid bundle '{}': {}", path, err)),
+    };
+    if bundle.version > BUNDLE_VERSION {
+        return Output::Stderr(format!(
+            "Bundle version {} is not supported, the newest supported version is {}.",
+            bundle.version, BUNDLE_VERSION
+        ));
+    }
+
+    let mut projects = vec![];
+    for project in bundle.projects.iter() {
+        let mut settings = project.settings.clone();
+        for key in PATH_KEYS.iter() {
+            if let Some(Value::String(path)) = settings.get_mut(*key) {
+                *path = absolute_path(&root, path);
+            }
+        }
+
+        let json = settings.to_string();
+        match try_parse(&json) {
+            Ok(settings) => projects.push((settings.name, json)),
+            Err(err) => return Output::Stderr(format!("Invalid project in the bundle: {}", err)),
+        }
+    }
+
+    let mut imported = vec![];
+    for (name, json) in projects {
+        if let Err(err) = requester.upsert_project(&json) {
+            return Output::Stderr(format!(
+                "Unable to import the project '{}': {}{}",
+                name,
+                err,
+                format_imported(&imported)
+            ));
+        }
+        imported.push(name);
+    }
+
+    Output::Stdout(format!(
+        "Imported {} projects: {}",
+        imported.len(),
+        imported.join(" ")
+    ))
+}
+
+fn create_bundle(requester: &Requester, options: &ExportOptions) -> Result<Bundle, String> {
+    let root = resolve_root(options.root.as_deref())?;
+
+    let settings = match options.projects.is_empty() {
+        true => requester
+            .get_projects_settings()
+            .map_err(|err| err.to_string())?,
+        false => options
+            .projects
+            .iter()
+            .map(|name| {
+                requester
+                    .get_project_settings(name)
+                    .map(|json| (name.clone(), json))
+                    .map_err(|err| format!("{}: {}", name, err))
+            })
+            .collect::<Result<Vec<(String, String)>, String>>()?,
+    };
+
+    let mut projects = vec![];
+    for (name, json) in settings {
+        let mut settings: Value = serde_json::from_str(&json)
+            .map_err(|err| format!("Invalid settings of the project '{}': {}", name, err))?;
+
+        for key in PATH_KEYS.iter() {
+            if let Some(Value::String(path)) = settings.get_mut(*key) {
+                match relative_path(&root, path) {
+                    Some(relative) => *path = relative,
+                    None => eprintln!(
+                        "warning: {} '{}' of the project '{}' is outside of '{}' and is kept absolute",
+                        key,
+                        path,
+                        name,
+                        root.display()
+                    ),
+                }
+            }
+        }
+
+        let services = match options.status {
+            true => {
+                let info = requester
+                    .get_project_info(&name)
+                    .map_err(|err| format!("{}: {}", name, err))?;
+                Some(info.services.into_iter().map(Into::into).collect())
+            }
+            false => None,
+        };
+
+        projects.push(BundledProject { settings, services });
+    }
+
+    let exported_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+
+    Ok(Bundle {
+        version: BUNDLE_VERSION,
+        exported_at,
+        projects,
+    })
+}
+
+fn resolve_root(root: Option<&str>) -> Result<PathBuf, String> {
+    let root = match root {
+        Some(root) => PathBuf::from(root),
+        None => env::current_dir().map_err(|err| err.to_string())?,
+    };
+    fs::canonicalize(&root).map_err(|err| format!("Unable to access '{}': {}", root.display(), err))
+}
+
+/// Path relative to the root, None when the path is not inside of it
+fn relative_path(root: &Path, path: &str) -> Option<String> {
+    // stored paths may contain '..' when the project was upserted with a relative path
+    let path = fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path));
+    let relative = path.strip_prefix(root).ok()?;
+    if relative
+        .components()
+        .any(|component| component == Component::ParentDir)
+    {
+        return None;
+    }
+
+    match relative.as_os_str().is_empty() {
+        true => Some(String::from(".")),
+        false => relative.to_str().map(String::from),
+    }
+}
+
+/// Absolute paths, e.g. from bundles of version 1, are kept as they are
+fn absolute_path(root: &Path, path: &str) -> String {
+    let path = match path {
+        "." => root.to_path_buf(),
+        path => root.join(path),
+    };
+    path.to_string_lossy().into_owned()
+}
+
+fn format_imported(imported: &[String]) -> String {
+    match imported.is_empty() {
+        true => String::new(),
+        false => format!("\nAlready imported projects: {}", imported.join(" ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_inside_the_root_are_relative() {
+        let root = Path::new("/home/alice/work");
+
+        assert_eq!(
+            relative_path(root, "/home/alice/work/shop").as_deref(),
+            Some("shop")
+        );
+        assert_eq!(
+            relative_path(root, "/home/alice/work/shop/conc.json").as_deref(),
+            Some("shop/conc.json")
+        );
+        assert_eq!(
+            relative_path(root, "/home/alice/work").as_deref(),
+            Some(".")
+        );
+        assert_eq!(relative_path(root, "/home/alice/workshop"), None);
+        assert_eq!(relative_path(root, "/opt/shop"), None);
+        assert_eq!(relative_path(root, "/home/alice/work/../shop"), None);
+    }
+
+    #[test]
+    fn relative_paths_are_resolved_against_the_root() {
+        let root = Path::new("/home/bob/src");
+
+        assert_eq!(absolute_path(root, "shop"), "/home/bob/src/shop");
+        assert_eq!(absolute_path(root, "."), "/home/bob/src");
+        assert_eq!(absolute_path(root, "/opt/shop"), "/opt/shop");
+    }
+}
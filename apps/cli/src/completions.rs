@@ -0,0 +1,193 @@
+use std::convert::TryFrom;
+
+use clap::{Arg, Command, ValueEnum};
+use clap_complete::{generate, Shell};
+use daemon_client::Requester;
+use project_settings::ProjectSettings;
+
+use crate::output::Output;
+
+const BIN_NAME: &str = "concc";
+
+const BASH_DYNAMIC: &str = r#"
+_concc_dynamic() {
+    local candidates
+    if candidates=$(concc __complete "${COMP_CWORD}" "${COMP_WORDS[@]}" 2>/dev/null); then
+        COMPREPLY=( $(compgen -W "${candidates}" -- "${COMP_WORDS[COMP_CWORD]}") )
+        return 0
+    fi
+    _concc "$@"
+}
+
+complete -F _concc_dynamic -o nosort -o bashdefault -o default concc
+"#;
+
+const ZSH_DYNAMIC: &str = r#"
+_concc_dynamic() {
+    local output
+    if output=$(concc __complete $((CURRENT - 1)) "${words[@]}" 2>/dev/null); then
+        local -a candidates
+        candidates=(${(f)output})
+        compadd -a candidates
+        return 0
+    fi
+    _concc "$@"
+}
+
+compdef _concc_dynamic concc
+"#;
+
+const FISH_DYNAMIC: &str = r#"
+complete -c concc -a "(concc __complete (count (commandline -opc)) (commandline -opc) (commandline -ct) 2>/dev/null)"
+"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// Generates the completion script, subcommands and options are completed statically
+/// and names of projects, services, tasks and profiles are requested from 'concc __complete'
+pub fn completion_script(shell: CompletionShell, mut command: Command) -> Output {
+    let (shell, dynamic) = match shell {
+        CompletionShell::Bash => (Shell::Bash, BASH_DYNAMIC),
+        CompletionShell::Zsh => (Shell::Zsh, ZSH_DYNAMIC),
+        CompletionShell::Fish => (Shell::Fish, FISH_DYNAMIC),
+    };
+
+    let mut script = vec![];
+    generate(shell, &mut command, BIN_NAME, &mut script);
+
+    Output::Stdout(String::from_utf8_lossy(&script).to_string() + dynamic)
+}
+
+/// Prints candidates for the word at the index, exits with 1 when the word is not a name
+/// known to the daemon so the static completion is used instead
+pub fn complete(
+    requester: Option<&Requester>,
+    mut command: Command,
+    index: usize,
+    words: &[String],
+) -> Output {
+    // global options are propagated to the subcommands only once the command is built
+    command.build();
+    let previous = words.get(1..index).unwrap_or_default();
+
+    let target = match find_target(command, previous) {
+        Some(target) => target,
+        None => return Output::Status(1, String::new()),
+    };
+
+    // completion should not print errors into the command line so failures give no candidates
+    let candidates = requester
+        .and_then(|requester| target.candidates(requester))
+        .unwrap_or_default();
+
+    Output::Stdout(candidates.join("\n"))
+}
+
+enum Target {
+    Project,
+    Service(String),
+    Task(String),
+    Profile(String),
+}
+
+impl Target {
+    fn candidates(&self, requester: &Requester) -> Option<Vec<String>> {
+        match self {
+            Target::Project => requester.get_project_names().ok(),
+            Target::Service(project) => requester.get_service_names(project).ok(),
+            Target::Task(project) => {
+                settings(requester, project).map(|s| s.tasks.into_iter().map(|t| t.name).collect())
+            }
+            Target::Profile(project) => settings(requester, project).map(|s| s.profile_names()),
+        }
+    }
+}
+
+/// Walks the words the same way as clap parses them to find out which argument is being completed
+fn find_target(mut command: Command, previous: &[String]) -> Option<Target> {
+    let mut positionals: Vec<String> = vec![];
+    let mut pending_option: Option<Arg> = None;
+
+    for word in previous.iter() {
+        if pending_option.take().is_some() {
+            continue;
+        }
+
+        // everything after '--' is passed to other programs
+        if word == "--" {
+            return None;
+        }
+
+        if let Some(long) = word.strip_prefix("--") {
+            let arg = command
+                .get_arguments()
+                .find(|arg| arg.get_long() == Some(long))
+                .cloned();
+            pending_option = arg.filter(|arg| arg.get_action().takes_values());
+            continue;
+        }
+
+        if word.len() == 2 && word.starts_with('-') {
+            let short = word.chars().nth(1);
+            let arg = command
+                .get_arguments()
+                .find(|arg| arg.get_short() == short)
+                .cloned();
+            pending_option = arg.filter(|arg| arg.get_action().takes_values());
+            continue;
+        }
+
+        if word.starts_with('-') {
+            continue;
+        }
+
+        if positionals.is_empty() {
+            if let Some(subcommand) = command.find_subcommand(word).cloned() {
+                command = subcommand;
+                continue;
+            }
+        }
+
+        positionals.push(word.clone());
+    }
+
+    let arg_positionals: Vec<&Arg> = command.get_positionals().collect();
+    let project = arg_positionals
+        .iter()
+        .zip(positionals.iter())
+        .find(|(arg, _)| arg.get_id() == "project")
+        .map(|(_, value)| value.clone());
+
+    let arg_id = match pending_option {
+        Some(arg) => arg.get_id().to_string(),
+        None => {
+            let arg = arg_positionals
+                .get(positionals.len())
+                .or_else(|| arg_positionals.last().filter(|arg| is_multiple(arg)))?;
+            arg.get_id().to_string()
+        }
+    };
+
+    match (arg_id.as_str(), project) {
+        ("project" | "projects", _) => Some(Target::Project),
+        ("service", Some(project)) => Some(Target::Service(project)),
+        ("task", Some(project)) => Some(Target::Task(project)),
+        ("profile", Some(project)) => Some(Target::Profile(project)),
+        _ => None,
+    }
+}
+
+fn is_multiple(arg: &Arg) -> bool {
+    arg.get_num_args()
+        .is_some_and(|range| range.max_values() > 1)
+}
+
+fn settings(requester: &Requester, project: &str) -> Option<ProjectSettings> {
+    let json = requester.get_project_settings(project).ok()?;
+    ProjectSettings::try_from(json.as_str()).ok()
+}
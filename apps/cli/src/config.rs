@@ -0,0 +1,121 @@
+use app_config::{AppConfig, LayeredConfig, UserAppConfig, DEFAULT_CONTEXT};
+use serde_json::Value;
+
+use crate::{
+    output::{format_table, Output},
+    utils::create_socket_client,
+};
+
+const SECRET_OPTION: &str = "daemon_token";
+
+/// Prints the effective config as one option per line, nested options use dotted names
+pub fn show_config(layered: &LayeredConfig, origin: bool) -> Output {
+    let value = match serde_json::to_value(&layered.config) {
+        Ok(value) => value,
+        Err(err) => return Output::Stderr(err.to_string()),
+    };
+
+    let mut options = vec![];
+    flatten(String::new(), value, &mut options);
+
+    let mut names_column = vec![String::from("OPTION")];
+    let mut values_column = vec![String::from("VALUE")];
+    let mut origins_column = vec![String::from("ORIGIN")];
+
+    for (name, value) in options {
+        origins_column.push(layered.origin(&name).to_string());
+        // tokens are secrets, only their presence is shown
+        let value = match name.ends_with(SECRET_OPTION) && value != "null" {
+            true => String::from("********"),
+            false => value,
+        };
+        names_column.push(name);
+        values_column.push(value);
+    }
+
+    let columns = match origin {
+        true => vec![names_column, values_column, origins_column],
+        false => vec![names_column, values_column],
+    };
+
+    Output::Stdout(format_table(columns))
+}
+
+fn flatten(prefix: String, value: Value, options: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                let name = match prefix.is_empty() {
+                    true => key,
+                    false => format!("{}.{}", prefix, key),
+                };
+                flatten(name, value, options);
+            }
+        }
+        Value::String(value) => options.push((prefix, value)),
+        value => options.push((prefix, value.to_string())),
+    }
+}
+
+pub fn list_contexts(config: &AppConfig) -> Output {
+    let mut current_column = vec![String::from("CURRENT")];
+    let mut names_column = vec![String::from("NAME")];
+    let mut sockets_column = vec![String::from("DAEMON SOCKET")];
+    let mut tasks_dirs_column = vec![String::from("TASKS DIR")];
+
+    for name in config.context_names() {
+        let context = match config.context(&name) {
+            Some(context) => context,
+            None => continue,
+        };
+        current_column.push(match name == config.current_context {
+            true => String::from("*"),
+            false => String::new(),
+        });
+        sockets_column.push(context.daemon_socket_path.clone());
+        tasks_dirs_column.push(context.tasks_dir.clone());
+        names_column.push(name);
+    }
+
+    Output::Stdout(format_table(vec![
+        current_column,
+        names_column,
+        sockets_column,
+        tasks_dirs_column,
+    ]))
+}
+
+/// Stores the context as the current one in the user config file, env variables
+/// and flags still take precedence over it
+pub fn use_context(name: &str) -> Output {
+    let mut user_config = match UserAppConfig::load() {
+        Ok(user_config) => user_config,
+        Err(err) => return err.into(),
+    };
+
+    // the context has to be defined in the file the choice is saved to, not only in a project or env layer
+    let mut target = match AppConfig::from_user_config(UserAppConfig {
+        current_context: None,
+        ..user_config.clone()
+    }) {
+        Ok(target) => target,
+        Err(err) => return err.into(),
+    };
+    if let Err(err) = target.use_context(name) {
+        return err.into();
+    }
+
+    user_config.current_context = match name {
+        DEFAULT_CONTEXT => None,
+        name => Some(String::from(name)),
+    };
+
+    match user_config.save() {
+        Ok(_) => Output::Stdout(format!(
+            "Switched to context '{}' using the daemon at {}",
+            name,
+            create_socket_client(&target).address()
+        )),
+        Err(err) => err.into(),
+    }
+}
@@ -0,0 +1,679 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use project_settings::{EnvValue, ServiceSettings};
+use yaml_rust2::{Yaml, YamlLoader};
+
+/// Names of the scripts and targets that usually start a long running process
+const LONG_RUNNING_NAMES: [&str; 6] = ["start", "dev", "serve", "server", "watch", "run"];
+
+const COMPOSE_KNOWN_KEYS: [&str; 3] = ["name", "services", "version"];
+const COMPOSE_SERVICE_KNOWN_KEYS: [&str; 6] = [
+    "command",
+    "environment",
+    "working_dir",
+    "depends_on",
+    "ports",
+    "image",
+];
+
+pub const PROCFILE_NAME: &str = "Procfile";
+pub const COMPOSE_FILE_NAMES: [&str; 4] = [
+    "compose.yaml",
+    "compose.yml",
+    "docker-compose.yaml",
+    "docker-compose.yml",
+];
+
+/// Service proposed from a project file, suggested services are selected by default
+#[derive(Debug)]
+pub struct DetectedService {
+    pub source: String,
+    pub settings: ServiceSettings,
+    pub suggested: bool,
+}
+
+/// Detects services from the known project files in the directory, files that fail to parse are skipped
+pub fn detect_services(dir: &Path) -> Vec<DetectedService> {
+    let mut services = vec![];
+
+    if let Ok(content) = fs::read_to_string(dir.join("package.json")) {
+        services.extend(from_package_json(&content).unwrap_or_default());
+    }
+
+    if let Ok(content) = fs::read_to_string(dir.join(PROCFILE_NAME)) {
+        services.extend(from_procfile(&content));
+    }
+
+    if let Ok(content) = fs::read_to_string(dir.join("Cargo.toml")) {
+        services.extend(from_cargo_toml(&content, dir).unwrap_or_default());
+    }
+
+    for file_name in COMPOSE_FILE_NAMES.iter() {
+        if let Ok(content) = fs::read_to_string(dir.join(file_name)) {
+            services.extend(from_compose(&content, file_name).unwrap_or_default());
+            break;
+        }
+    }
+
+    if let Ok(content) = fs::read_to_string(dir.join("Makefile")) {
+        services.extend(from_makefile(&content));
+    }
+
+    deduplicate_names(services)
+}
+
+pub fn from_package_json(content: &str) -> Result<Vec<DetectedService>, String> {
+    let json: serde_json::Value = serde_json::from_str(content).map_err(|err| err.to_string())?;
+
+    let scripts = match json.get("scripts").and_then(|scripts| scripts.as_object()) {
+        Some(scripts) => scripts,
+        None => return Ok(vec![]),
+    };
+
+    Ok(scripts
+        .keys()
+        .map(|script| DetectedService {
+            source: String::from("package.json"),
+            settings: service(
+                script,
+                vec![String::from("npm"), String::from("run"), script.clone()],
+            ),
+            suggested: is_long_running(script),
+        })
+        .collect())
+}
+
+pub fn from_procfile(content: &str) -> Vec<DetectedService> {
+    parse_procfile(content)
+        .services
+        .into_iter()
+        .map(|settings| DetectedService {
+            source: String::from("Procfile"),
+            settings,
+            suggested: true,
+        })
+        .collect()
+}
+
+/// Parses 'name: command' lines, commands are shell strings so they are run through 'sh'
+pub fn parse_procfile(content: &str) -> ParsedServices {
+    let mut services = vec![];
+    let mut warnings = vec![];
+
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let parsed = line
+            .split_once(':')
+            .map(|(name, command)| (name.trim(), command.trim()))
+            .filter(|(name, command)| !name.is_empty() && !command.is_empty());
+
+        match parsed {
+            Some((name, command)) => services.push(service(name, shell_command(command))),
+            None => warnings.push(format!(
+                "line {} is not in the 'name: command' format and was skipped",
+                i + 1
+            )),
+        }
+    }
+
+    ParsedServices {
+        project_name: None,
+        services,
+        warnings,
+    }
+}
+
+pub fn from_cargo_toml(content: &str, dir: &Path) -> Result<Vec<DetectedService>, String> {
+    let cargo: toml::Table = toml::from_str(content).map_err(|err| err.to_string())?;
+
+    let bins: Vec<String> = cargo
+        .get("bin")
+        .and_then(|bins| bins.as_array())
+        .map(|bins| {
+            bins.iter()
+                .filter_map(|bin| bin.get("name")?.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let package_name = cargo
+        .get("package")
+        .and_then(|package| package.get("name"))
+        .and_then(|name| name.as_str());
+
+    let services = match (bins.is_empty(), package_name) {
+        (false, _) => bins
+            .iter()
+            .map(|bin| {
+                let command = ["cargo", "run", "--bin", bin.as_str()];
+                service(bin, command.iter().map(|s| s.to_string()).collect())
+            })
+            .collect(),
+        (true, Some(name)) if dir.join("src").join("main.rs").is_file() => {
+            vec![service(
+                name,
+                vec![String::from("cargo"), String::from("run")],
+            )]
+        }
+        _ => vec![],
+    };
+
+    Ok(services
+        .into_iter()
+        .map(|settings| DetectedService {
+            source: String::from("Cargo.toml"),
+            settings,
+            suggested: true,
+        })
+        .collect())
+}
+
+/// Services with a command run it directly, other services are started through docker compose
+pub fn from_compose(content: &str, file_name: &str) -> Result<Vec<DetectedService>, String> {
+    let import = parse_compose(content, file_name)?;
+
+    Ok(import
+        .services
+        .into_iter()
+        .map(|settings| DetectedService {
+            source: String::from(file_name),
+            settings,
+            suggested: true,
+        })
+        .collect())
+}
+
+/// Services converted from a project file together with warnings about the parts that could not be converted
+#[derive(Debug)]
+pub struct ParsedServices {
+    pub project_name: Option<String>,
+    pub services: Vec<ServiceSettings>,
+    pub warnings: Vec<String>,
+}
+
+pub fn parse_compose(content: &str, file_name: &str) -> Result<ParsedServices, String> {
+    let documents = YamlLoader::load_from_str(content).map_err(|err| err.to_string())?;
+    let document = match documents.first() {
+        Some(document) => document,
+        None => return Err(format!("'{}' is empty", file_name)),
+    };
+
+    let mut warnings = vec![];
+    if let Yaml::Hash(keys) = document {
+        for key in keys.keys().filter_map(|key| key.as_str()) {
+            if !COMPOSE_KNOWN_KEYS.contains(&key) {
+                warnings.push(format!("top level key '{}' is not supported", key));
+            }
+        }
+    }
+
+    let definitions = match &document["services"] {
+        Yaml::Hash(services) => services,
+        _ => return Err(format!("'{}' does not define any service", file_name)),
+    };
+
+    let mut services = vec![];
+    let mut dependencies: Vec<(String, Vec<String>)> = vec![];
+    for (name, definition) in definitions.iter() {
+        let name = match name.as_str() {
+            Some(name) => name,
+            None => continue,
+        };
+
+        if let Yaml::Hash(keys) = definition {
+            for key in keys.keys().filter_map(|key| key.as_str()) {
+                if !COMPOSE_SERVICE_KNOWN_KEYS.contains(&key) {
+                    warnings.push(format!(
+                        "key '{}' of the service '{}' is not supported",
+                        key, name
+                    ));
+                }
+            }
+        }
+
+        let command = match &definition["command"] {
+            Yaml::String(command) => shell_command(command),
+            Yaml::Array(parts) => parts.iter().filter_map(yaml_to_string).collect(),
+            _ => {
+                warnings.push(format!(
+                    "service '{}' has no command, it is started through docker compose",
+                    name
+                ));
+                ["docker", "compose", "-f", file_name, "up", name]
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect()
+            }
+        };
+
+        let mut settings = service(name, command);
+        settings.env = compose_environment(&definition["environment"]);
+        // a service can declare a port only once, the later declarations are skipped
+        for port in compose_ports(&definition["ports"]) {
+            match settings.ports.contains(&port) {
+                true => warnings.push(format!(
+                    "port {} is declared more than once by the service '{}' and was skipped",
+                    port, name
+                )),
+                false => settings.ports.push(port),
+            }
+        }
+        settings.pwd = definition["working_dir"].as_str().map(String::from);
+
+        let depends_on: Vec<String> = match &definition["depends_on"] {
+            Yaml::Array(names) => names.iter().filter_map(yaml_to_string).collect(),
+            Yaml::Hash(names) => names.keys().filter_map(yaml_to_string).collect(),
+            _ => vec![],
+        };
+        if !depends_on.is_empty() {
+            warnings.push(format!(
+                "dependencies of the service '{}' are only reflected in the order of the services",
+                name
+            ));
+        }
+
+        dependencies.push((
+            settings.name.clone(),
+            depends_on.iter().map(|d| sanitize_name(d)).collect(),
+        ));
+        services.push(settings);
+    }
+
+    let services = match order_by_dependencies(services, &dependencies) {
+        Ok(services) => services,
+        Err(services) => {
+            warnings.push(String::from(
+                "dependencies of the services contain a cycle, services are kept in the declared order",
+            ));
+            services
+        }
+    };
+
+    Ok(ParsedServices {
+        project_name: document["name"].as_str().map(sanitize_name),
+        services,
+        warnings,
+    })
+}
+
+pub fn from_makefile(content: &str) -> Vec<DetectedService> {
+    let mut targets: Vec<&str> = vec![];
+
+    for line in content.lines() {
+        // recipes are indented, variables contain '=' and special targets start with '.'
+        if line.starts_with(['\t', ' ', '.', '#']) {
+            continue;
+        }
+
+        let target = match line.split_once(':') {
+            Some((target, rest)) if !rest.starts_with('=') => target.trim(),
+            _ => continue,
+        };
+
+        let is_valid = !target.is_empty()
+            && target
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+        if is_valid && !targets.contains(&target) {
+            targets.push(target);
+        }
+    }
+
+    targets
+        .into_iter()
+        .map(|target| DetectedService {
+            source: String::from("Makefile"),
+            settings: service(target, vec![String::from("make"), String::from(target)]),
+            suggested: is_long_running(target),
+        })
+        .collect()
+}
+
+/// Converts a name from a project file into a valid service name
+pub fn sanitize_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c.is_alphanumeric() || c == '-' || c == '_' {
+            true => c,
+            false => '-',
+        })
+        .collect()
+}
+
+pub fn shell_command(command: &str) -> Vec<String> {
+    vec![
+        String::from("sh"),
+        String::from("-c"),
+        String::from(command),
+    ]
+}
+
+fn service(name: &str, command: Vec<String>) -> ServiceSettings {
+    ServiceSettings {
+        name: sanitize_name(name),
+        pwd: None,
+        command,
+        env: HashMap::new(),
+        before_start: vec![],
+        ports: vec![],
+    }
+}
+
+fn is_long_running(name: &str) -> bool {
+    LONG_RUNNING_NAMES.iter().any(|long_running| {
+        name == *long_running || name.starts_with(&format!("{}:", long_running))
+    })
+}
+
+/// Same names from different files get the name of the file as a suffix
+fn deduplicate_names(services: Vec<DetectedService>) -> Vec<DetectedService> {
+    let mut names: Vec<String> = vec![];
+
+    services
+        .into_iter()
+        .map(|mut service| {
+            if names.contains(&service.settings.name) {
+                let stem = Path::new(&service.source)
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .unwrap_or_default();
+                let suffix = sanitize_name(&stem.to_lowercase());
+                let mut name = format!("{}-{}", service.settings.name, suffix);
+                let mut counter = 2;
+                while names.contains(&name) {
+                    name = format!("{}-{}-{}", service.settings.name, suffix, counter);
+                    counter += 1;
+                }
+                service.settings.name = name;
+            }
+
+            names.push(service.settings.name.clone());
+            service
+        })
+        .collect()
+}
+
+/// Moves services after the services they depend on, keeping the declared order otherwise.
+/// Services are returned in the declared order when the dependencies contain a cycle.
+fn order_by_dependencies(
+    services: Vec<ServiceSettings>,
+    dependencies: &[(String, Vec<String>)],
+) -> Result<Vec<ServiceSettings>, Vec<ServiceSettings>> {
+    let mut ordered: Vec<String> = vec![];
+
+    while ordered.len() < dependencies.len() {
+        let next = dependencies.iter().find(|(name, depends_on)| {
+            !ordered.contains(name)
+                && depends_on.iter().all(|dependency| {
+                    ordered.contains(dependency)
+                        || !dependencies.iter().any(|(name, _)| name == dependency)
+                })
+        });
+
+        match next {
+            Some((name, _)) => ordered.push(name.clone()),
+            None => return Err(services),
+        }
+    }
+
+    let mut services = services;
+    services.sort_by_key(|service| ordered.iter().position(|name| *name == service.name));
+    Ok(services)
+}
+
+fn compose_environment(environment: &Yaml) -> HashMap<String, EnvValue> {
+    match environment {
+        Yaml::Hash(vars) => vars
+            .iter()
+            .filter_map(|(key, value)| {
+                Some((
+                    yaml_to_string(key)?,
+                    EnvValue::Str(yaml_to_string(value).unwrap_or_default()),
+                ))
+            })
+            .collect(),
+        Yaml::Array(vars) => vars
+            .iter()
+            .filter_map(|var| {
+                let var = var.as_str()?;
+                let (key, value) = var.split_once('=').unwrap_or((var, ""));
+                Some((String::from(key), EnvValue::Str(String::from(value))))
+            })
+            .collect(),
+        _ => HashMap::new(),
+    }
+}
+
+/// Only published host ports are relevant, e.g. '8080:80' or '127.0.0.1:8080:80/tcp'
+fn compose_ports(ports: &Yaml) -> Vec<u16> {
+    let ports = match ports {
+        Yaml::Array(ports) => ports,
+        _ => return vec![],
+    };
+
+    ports
+        .iter()
+        .filter_map(|port| match port {
+            Yaml::String(port) => {
+                let port = port.split('/').next()?;
+                // ports without the host part are published on a random port
+                let parts: Vec<&str> = port.split(':').collect();
+                match parts.len() {
+                    1 => None,
+                    len => parts[len - 2].parse::<u16>().ok(),
+                }
+            }
+            Yaml::Hash(_) => port["published"]
+                .as_i64()
+                .and_then(|port| u16::try_from(port).ok())
+                .or_else(|| port["published"].as_str()?.parse().ok()),
+            _ => None,
+        })
+        .filter(|port| *port != 0)
+        .collect()
+}
+
+fn yaml_to_string(value: &Yaml) -> Option<String> {
+    match value {
+        Yaml::String(value) => Some(value.clone()),
+        Yaml::Integer(value) => Some(value.to_string()),
+        Yaml::Real(value) => Some(value.clone()),
+        Yaml::Boolean(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(services: &[DetectedService]) -> Vec<&str> {
+        services
+            .iter()
+            .map(|service| service.settings.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn package_json_scripts_are_run_through_npm() {
+        let content = r#"{"scripts": {"dev": "vite", "lint": "eslint ."}}"#;
+
+        let services = from_package_json(content).unwrap();
+
+        assert_eq!(names(&services), vec!["dev", "lint"]);
+        assert_eq!(services[0].settings.command, vec!["npm", "run", "dev"]);
+        assert!(services[0].suggested);
+        assert!(!services[1].suggested);
+        assert!(from_package_json(r#"{"name": "app"}"#).unwrap().is_empty());
+        assert!(from_package_json("{").is_err());
+    }
+
+    #[test]
+    fn procfile_skips_invalid_lines_with_a_warning() {
+        let content = "web: bundle exec rails s\n# comment\n\nworker:\nclock: ruby clock.rb\n";
+
+        let parsed = parse_procfile(content);
+
+        let names: Vec<&str> = parsed.services.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["web", "clock"]);
+        assert_eq!(
+            parsed.services[0].command,
+            vec!["sh", "-c", "bundle exec rails s"]
+        );
+        assert_eq!(parsed.warnings.len(), 1);
+        assert!(parsed.warnings[0].contains("line 4"));
+    }
+
+    #[test]
+    fn cargo_toml_bins_are_run_with_cargo() {
+        let content =
+            "[package]\nname = \"app\"\n\n[[bin]]\nname = \"api\"\n\n[[bin]]\nname = \"worker\"\n";
+        let dir = Path::new("/nonexistent");
+
+        let services = from_cargo_toml(content, dir).unwrap();
+
+        assert_eq!(names(&services), vec!["api", "worker"]);
+        assert_eq!(
+            services[0].settings.command,
+            vec!["cargo", "run", "--bin", "api"]
+        );
+        // a package without bins is a service only when it has a main.rs
+        let content = "[package]\nname = \"app\"\n";
+        assert!(from_cargo_toml(content, dir).unwrap().is_empty());
+    }
+
+    #[test]
+    fn makefile_targets_skip_recipes_variables_and_special_targets() {
+        let content = "CC = gcc\nFLAGS := -O2\n.PHONY: run\nrun: build\n\t./app\nbuild:\n\t$(CC) app.c\nbuild:\nout/app.o: app.c\n";
+
+        let services = from_makefile(content);
+
+        assert_eq!(names(&services), vec!["run", "build"]);
+        assert_eq!(services[0].settings.command, vec!["make", "run"]);
+        assert!(services[0].suggested);
+        assert!(!services[1].suggested);
+    }
+
+    #[test]
+    fn compose_services_are_converted() {
+        let content = r#"
+name: shop
+services:
+  web:
+    command: npm start
+    environment:
+      PORT: 8080
+    ports:
+      - "8080:80"
+      - "127.0.0.1:9090:90/tcp"
+      - "3000"
+    depends_on:
+      - db
+  db:
+    image: postgres
+    environment:
+      - POSTGRES_PASSWORD=secret
+    ports:
+      - published: 5432
+        target: 5432
+    healthcheck:
+      test: pg_isready
+"#;
+
+        let parsed = parse_compose(content, "compose.yaml").unwrap();
+
+        assert_eq!(parsed.project_name.as_deref(), Some("shop"));
+        let names: Vec<&str> = parsed.services.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["db", "web"]);
+
+        let db = &parsed.services[0];
+        assert_eq!(
+            db.command,
+            vec!["docker", "compose", "-f", "compose.yaml", "up", "db"]
+        );
+        assert_eq!(db.ports, vec![5432]);
+        assert!(matches!(
+            db.env.get("POSTGRES_PASSWORD"),
+            Some(EnvValue::Str(value)) if value == "secret"
+        ));
+
+        let web = &parsed.services[1];
+        assert_eq!(web.command, vec!["sh", "-c", "npm start"]);
+        assert_eq!(web.ports, vec![8080, 9090]);
+        assert!(matches!(
+            web.env.get("PORT"),
+            Some(EnvValue::Str(value)) if value == "8080"
+        ));
+
+        assert!(parsed
+            .warnings
+            .iter()
+            .any(|warning| warning.contains("'healthcheck'")));
+        assert!(parsed
+            .warnings
+            .iter()
+            .any(|warning| warning.contains("'db' has no command")));
+    }
+
+    #[test]
+    fn compose_ports_declared_twice_by_a_service_are_skipped_with_a_warning() {
+        let content = r#"
+services:
+  blue:
+    command: ["./serve", "--blue"]
+    ports: ["8080:80", "8080:81"]
+  green:
+    command: ["./serve", "--green"]
+    ports: ["8080:80", "8081:80"]
+"#;
+
+        let parsed = parse_compose(content, "compose.yaml").unwrap();
+
+        assert_eq!(parsed.services[0].ports, vec![8080]);
+        assert_eq!(parsed.services[1].ports, vec![8080, 8081]);
+        assert_eq!(
+            parsed.warnings,
+            vec!["port 8080 is declared more than once by the service 'blue' and was skipped"]
+        );
+    }
+
+    #[test]
+    fn compose_dependency_cycle_keeps_the_declared_order() {
+        let content = r#"
+services:
+  a:
+    command: a
+    depends_on: [b]
+  b:
+    command: b
+    depends_on: [a]
+"#;
+
+        let parsed = parse_compose(content, "compose.yaml").unwrap();
+
+        let names: Vec<&str> = parsed.services.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "b"]);
+        assert!(parsed
+            .warnings
+            .iter()
+            .any(|warning| warning.contains("contain a cycle")));
+        assert!(parse_compose("version: '3'\n", "compose.yaml").is_err());
+    }
+
+    #[test]
+    fn same_names_from_different_files_get_a_suffix() {
+        let mut services = from_package_json(r#"{"scripts": {"start": "node ."}}"#).unwrap();
+        services.extend(from_procfile("start: ./run\n"));
+        services.extend(from_makefile("start:\n\t./run\n"));
+
+        let services = deduplicate_names(services);
+
+        assert_eq!(
+            names(&services),
+            vec!["start", "start-procfile", "start-makefile"]
+        );
+    }
+}
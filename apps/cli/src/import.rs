@@ -0,0 +1,179 @@
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use clap::{Args, Subcommand};
+use daemon_client::Requester;
+use project_settings::{try_parse, ProjectSettings, SettingsFormat};
+
+use crate::{
+    detect::{
+        parse_compose, parse_procfile, sanitize_name, ParsedServices, COMPOSE_FILE_NAMES,
+        PROCFILE_NAME,
+    },
+    output::Output,
+};
+
+#[derive(Debug, Subcommand)]
+pub enum ImportSource {
+    /// Import services from a Procfile
+    Procfile(ImportArgs),
+    /// Import services from a docker compose file
+    Compose(ImportArgs),
+}
+
+impl ImportSource {
+    pub fn args(&self) -> &ImportArgs {
+        match self {
+            ImportSource::Procfile(args) | ImportSource::Compose(args) => args,
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct ImportArgs {
+    /// path to the file or directory containing the file, defaults to current dir
+    path: Option<String>,
+    /// name of the project, defaults to the name from the file or the name of the directory
+    #[clap(long, short)]
+    name: Option<String>,
+    /// write conc.json next to the imported file instead of creating the project
+    #[clap(long, short, action)]
+    pub write: bool,
+    /// overwrite existing settings file
+    #[clap(long, short, action, requires = "write")]
+    force: bool,
+}
+
+/// Converts the imported file into project settings and writes them to conc.json next to the file
+pub fn import_to_file(source: &ImportSource) -> Output {
+    let (dir, settings) = match load_settings(source) {
+        Ok(loaded) => loaded,
+        Err(err) => return Output::Stderr(err),
+    };
+
+    let path = dir.join(SettingsFormat::Json.file_name());
+    if path.exists() && !source.args().force {
+        return Output::Stderr(format!(
+            "Settings file '{}' already exists, use --force to overwrite it.",
+            path.display()
+        ));
+    }
+
+    let result = settings
+        .to_file_content(SettingsFormat::Json)
+        .map_err(|err| err.to_string())
+        .and_then(|content| fs::write(&path, content).map_err(|err| err.to_string()));
+
+    match result {
+        Ok(_) => Output::Stdout(format!(
+            "Created '{}' with {} services, run 'concc upsert' to register the project.",
+            path.display(),
+            settings.services.len()
+        )),
+        Err(err) => Output::Stderr(format!("Unable to write '{}': {}", path.display(), err)),
+    }
+}
+
+/// Converts the imported file into project settings and creates or replaces the project
+pub fn import_to_daemon(source: &ImportSource, requester: &Requester) -> Output {
+    let (dir, mut settings) = match load_settings(source) {
+        Ok(loaded) => loaded,
+        Err(err) => return Output::Stderr(err),
+    };
+
+    // services run in the directory of the imported file same as with the settings file placed there
+    settings.cwd = String::from(dir.to_str().unwrap_or_default());
+
+    match String::try_from(&settings) {
+        Ok(json) => requester.upsert_project(&json).into(),
+        Err(err) => err.into(),
+    }
+}
+
+/// Parses the imported file and prints warnings about parts which could not be converted
+fn load_settings(source: &ImportSource) -> Result<(PathBuf, ProjectSettings), String> {
+    let args = source.args();
+    let path = resolve_path(source, args.path.as_deref())?;
+    let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+
+    let content = fs::read_to_string(&path)
+        .map_err(|err| format!("Unable to read '{}': {}", path.display(), err))?;
+
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+    let parsed = match source {
+        ImportSource::Procfile(_) => parse_procfile(&content),
+        ImportSource::Compose(_) => parse_compose(&content, file_name)
+            .map_err(|err| format!("Unable to parse '{}': {}", path.display(), err))?,
+    };
+
+    for warning in parsed.warnings.iter() {
+        eprintln!("warning: {}", warning);
+    }
+
+    let settings = create_settings(parsed, args.name.clone(), &dir)?;
+    Ok((dir, settings))
+}
+
+fn resolve_path(source: &ImportSource, path: Option<&str>) -> Result<PathBuf, String> {
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => env::current_dir().map_err(|err| err.to_string())?,
+    };
+    let path = fs::canonicalize(&path)
+        .map_err(|err| format!("Unable to access '{}': {}", path.display(), err))?;
+
+    if !path.is_dir() {
+        return Ok(path);
+    }
+
+    let file_names = match source {
+        ImportSource::Procfile(_) => vec![PROCFILE_NAME],
+        ImportSource::Compose(_) => COMPOSE_FILE_NAMES.to_vec(),
+    };
+
+    file_names
+        .iter()
+        .map(|name| path.join(name))
+        .find(|path| path.is_file())
+        .ok_or_else(|| {
+            format!(
+                "No file named {} was found in '{}'.",
+                file_names.join(" or "),
+                path.display()
+            )
+        })
+}
+
+fn create_settings(
+    parsed: ParsedServices,
+    name: Option<String>,
+    dir: &Path,
+) -> Result<ProjectSettings, String> {
+    let dir_name = dir
+        .file_name()
+        .and_then(|name| name.to_str())
+        .map(sanitize_name);
+
+    let settings = ProjectSettings {
+        name: name
+            .or(parsed.project_name)
+            .or(dir_name)
+            .unwrap_or_default(),
+        cwd: String::new(),
+        services: parsed.services,
+        env: HashMap::new(),
+        profiles: HashMap::new(),
+        tasks: vec![],
+        source_path: None,
+    };
+
+    // settings are validated the same way as they are when the project is upserted
+    let json = String::try_from(&settings).map_err(|err| err.to_string())?;
+    try_parse(&json).map_err(|err| format!("Imported settings are invalid: {}", err))
+}
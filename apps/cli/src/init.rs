@@ -0,0 +1,227 @@
+use std::{
+    collections::HashMap,
+    env, fs,
+    io::{self, BufRead, Write},
+    path::Path,
+};
+
+use clap::ValueEnum;
+use project_settings::{try_parse, ProjectSettings, ServiceSettings, SettingsFormat};
+
+use crate::{
+    detect::{detect_services, sanitize_name, shell_command},
+    output::Output,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum InitFormat {
+    Json,
+    Toml,
+}
+
+impl From<InitFormat> for SettingsFormat {
+    fn from(value: InitFormat) -> Self {
+        match value {
+            InitFormat::Json => SettingsFormat::Json,
+            InitFormat::Toml => SettingsFormat::Toml,
+        }
+    }
+}
+
+pub struct InitOptions {
+    pub yes: bool,
+    pub force: bool,
+    pub format: Option<InitFormat>,
+}
+
+/// Creates the settings file in the current directory from the detected project files,
+/// proposed services are confirmed by the user unless 'yes' is set
+pub fn init_project(options: InitOptions) -> Output {
+    let dir = match env::current_dir() {
+        Ok(dir) => dir,
+        Err(err) => return Output::Stderr(err.to_string()),
+    };
+
+    let existing = [SettingsFormat::Json, SettingsFormat::Toml]
+        .iter()
+        .map(|format| dir.join(format.file_name()))
+        .find(|path| path.exists());
+    if let (Some(existing), false) = (existing, options.force) {
+        return Output::Stderr(format!(
+            "Settings file '{}' already exists, use --force to overwrite it.",
+            existing.display()
+        ));
+    }
+
+    let mut prompt = Prompt::new(options.yes);
+    match create_settings(&dir, &mut prompt, options.format) {
+        Ok((settings, format)) => write_settings(&dir, &settings, format),
+        Err(err) => Output::Stderr(err),
+    }
+}
+
+fn create_settings(
+    dir: &Path,
+    prompt: &mut Prompt,
+    format: Option<InitFormat>,
+) -> Result<(ProjectSettings, SettingsFormat), String> {
+    let dir_name = dir
+        .file_name()
+        .and_then(|name| name.to_str())
+        .map(sanitize_name)
+        .unwrap_or_default();
+    let name = prompt.text("Project name", &dir_name)?;
+
+    let mut services: Vec<ServiceSettings> = vec![];
+    for detected in detect_services(dir) {
+        let question = format!(
+            "Add service '{}' from {} ({})?",
+            detected.settings.name,
+            detected.source,
+            detected.settings.command.join(" ")
+        );
+        if prompt.confirm(&question, detected.suggested)? {
+            services.push(detected.settings);
+        }
+    }
+
+    if services.is_empty() && prompt.yes {
+        return Err(String::from(
+            "No service was detected in the current directory, run the command without --yes to add services manually.",
+        ));
+    }
+
+    while services.is_empty() || prompt.confirm("Add another service manually?", false)? {
+        let service_name = prompt.required_text("Service name")?;
+        let command = prompt.required_text("Command")?;
+        services.push(ServiceSettings {
+            name: service_name,
+            pwd: None,
+            command: shell_command(&command),
+            env: HashMap::new(),
+            before_start: vec![],
+            ports: vec![],
+        });
+    }
+
+    let format = match format {
+        Some(format) => format,
+        None => match prompt.text("Settings format (json/toml)", "json")?.as_str() {
+            "toml" => InitFormat::Toml,
+            "json" => InitFormat::Json,
+            other => return Err(format!("Unknown settings format '{}'.", other)),
+        },
+    };
+
+    let settings = ProjectSettings {
+        name,
+        cwd: String::new(),
+        services,
+        env: HashMap::new(),
+        profiles: HashMap::new(),
+        tasks: vec![],
+        source_path: None,
+    };
+
+    // settings are validated the same way as they are when the project is upserted
+    let json = String::try_from(&settings).map_err(|err| err.to_string())?;
+    let settings = try_parse(&json).map_err(|err| format!("Invalid settings: {}", err))?;
+
+    Ok((settings, format.into()))
+}
+
+fn write_settings(dir: &Path, settings: &ProjectSettings, format: SettingsFormat) -> Output {
+    let path = dir.join(format.file_name());
+
+    let result = settings
+        .to_file_content(format)
+        .map_err(|err| err.to_string())
+        .and_then(|content| fs::write(&path, content).map_err(|err| err.to_string()));
+
+    match result {
+        Ok(_) => Output::Stdout(format!(
+            "Created '{}' with {} services, run 'concc upsert' to register the project.",
+            path.display(),
+            settings.services.len()
+        )),
+        Err(err) => Output::Stderr(format!("Unable to write '{}': {}", path.display(), err)),
+    }
+}
+
+struct Prompt {
+    yes: bool,
+    input: io::StdinLock<'static>,
+}
+
+impl Prompt {
+    fn new(yes: bool) -> Self {
+        Self {
+            yes,
+            input: io::stdin().lock(),
+        }
+    }
+
+    fn text(&mut self, question: &str, default: &str) -> Result<String, String> {
+        if self.yes && !default.is_empty() {
+            return Ok(String::from(default));
+        }
+
+        let answer = match default.is_empty() {
+            true => self.ask(&format!("{}: ", question))?,
+            false => self.ask(&format!("{} [{}]: ", question, default))?,
+        };
+
+        match answer.is_empty() {
+            true => Ok(String::from(default)),
+            false => Ok(answer),
+        }
+    }
+
+    fn required_text(&mut self, question: &str) -> Result<String, String> {
+        loop {
+            let answer = self.text(question, "")?;
+            if !answer.is_empty() {
+                return Ok(answer);
+            }
+        }
+    }
+
+    fn confirm(&mut self, question: &str, default: bool) -> Result<bool, String> {
+        if self.yes {
+            return Ok(default);
+        }
+
+        let options = match default {
+            true => "Y/n",
+            false => "y/N",
+        };
+
+        loop {
+            let answer = self.ask(&format!("{} [{}] ", question, options))?;
+            match answer.to_lowercase().as_str() {
+                "" => return Ok(default),
+                "y" | "yes" => return Ok(true),
+                "n" | "no" => return Ok(false),
+                _ => continue,
+            }
+        }
+    }
+
+    fn ask(&mut self, question: &str) -> Result<String, String> {
+        print!("{}", question);
+        io::stdout().flush().map_err(|err| err.to_string())?;
+
+        let mut answer = String::new();
+        let read = self
+            .input
+            .read_line(&mut answer)
+            .map_err(|err| err.to_string())?;
+        if read == 0 {
+            return Err(String::from(
+                "Input was closed before all questions were answered.",
+            ));
+        }
+
+        Ok(String::from(answer.trim()))
+    }
+}
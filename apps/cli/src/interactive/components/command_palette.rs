@@ -0,0 +1,147 @@
+use std::cmp::{min, Reverse};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Position, Rect},
+    style::Color,
+    text::Span,
+    widgets::Row,
+};
+
+use super::{centered_area, popup_area, ActiveTable, CommonBlock, Input};
+
+const PALETTE_WIDTH: u16 = 80;
+const MAX_VISIBLE_ENTRIES: u16 = 12;
+const SEARCH_HEIGHT: u16 = 3;
+const TABLE_OVERHEAD: u16 = 3;
+
+pub enum PaletteEvent<T> {
+    None,
+    Close,
+    Run(T),
+}
+
+/// Popup listing labeled entries filtered by a fuzzy search, the selected entry is returned on enter
+#[derive(Debug)]
+pub struct CommandPalette<T> {
+    entries: Vec<(String, T)>,
+    filtered: Vec<usize>,
+    search: Input,
+    table: ActiveTable,
+}
+
+impl<T: Clone> CommandPalette<T> {
+    pub fn new() -> Self {
+        Self {
+            entries: vec![],
+            filtered: vec![],
+            search: Input::new(),
+            table: ActiveTable::new().ad_header(("ACTION", 100)),
+        }
+    }
+
+    pub fn set_entries(&mut self, entries: Vec<(String, T)>) {
+        self.entries = entries;
+        self.search.clear();
+        self.filter();
+    }
+
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) -> PaletteEvent<T> {
+        match key_event.code {
+            KeyCode::Esc => PaletteEvent::Close,
+            KeyCode::Enter => self
+                .table
+                .selected()
+                .and_then(|i| self.filtered.get(i))
+                .map(|i| PaletteEvent::Run(self.entries[*i].1.clone()))
+                .unwrap_or(PaletteEvent::None),
+            KeyCode::Up | KeyCode::Down => {
+                self.table.handle_key_event(key_event, self.filtered.len());
+                PaletteEvent::None
+            }
+            code => {
+                self.search.handle_key_code(code);
+                self.filter();
+                PaletteEvent::None
+            }
+        }
+    }
+
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let popup = popup_area(area, min(PALETTE_WIDTH, area.width), self.height(area), buf);
+        let [search_area, table_area] =
+            Layout::vertical([Constraint::Length(SEARCH_HEIGHT), Constraint::Fill(1)]).areas(popup);
+
+        let search_block = CommonBlock::new(String::from("Command palette"))
+            .set_border_color(Color::LightYellow)
+            .add_instruction(("Run", "enter"))
+            .add_instruction(("Close", "escape"));
+        self.search.render(search_block.into(), search_area, buf);
+
+        let title = format!("Actions ({}/{})", self.filtered.len(), self.entries.len());
+        let block = CommonBlock::new(title).set_border_color(Color::LightYellow);
+        let rows = self
+            .filtered
+            .iter()
+            .map(|i| Row::new(vec![Span::from(self.entries[*i].0.clone())]))
+            .collect();
+
+        self.table.render(rows, block.into(), table_area, buf);
+    }
+
+    pub fn cursor_position(&self, area: Rect) -> Position {
+        let popup = centered_area(area, min(PALETTE_WIDTH, area.width), self.height(area));
+        Position::new(popup.x + self.search.len() as u16 + 1, popup.y + 1)
+    }
+
+    fn height(&self, area: Rect) -> u16 {
+        let rows = (self.filtered.len() as u16).clamp(1, MAX_VISIBLE_ENTRIES);
+        min(SEARCH_HEIGHT + rows + TABLE_OVERHEAD, area.height)
+    }
+
+    fn filter(&mut self) {
+        let query = self.search.value();
+
+        let mut scored: Vec<(usize, i64)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, (label, _))| fuzzy_score(&query, label).map(|score| (i, score)))
+            .collect();
+        // sort is stable so entries with the same score keep their order
+        scored.sort_by_key(|(_, score)| Reverse(*score));
+
+        self.filtered = scored.into_iter().map(|(i, _)| i).collect();
+        self.table.select(None);
+    }
+}
+
+/// Scores how well the query matches the text, every word of the query has to match
+/// the text as a subsequence, consecutive matches and matches at word starts score more
+fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    let mut score = 0;
+    for term in query.to_lowercase().split_whitespace() {
+        let mut position = 0;
+        let mut previous: Option<usize> = None;
+
+        for c in term.chars() {
+            let found = position + text[position..].iter().position(|t| *t == c)?;
+
+            score += 1;
+            if previous == Some(found.wrapping_sub(1)) {
+                score += 5;
+            }
+            if found == 0 || matches!(text[found - 1], ' ' | '/' | '-' | '_') {
+                score += 3;
+            }
+
+            previous = Some(found);
+            position = found + 1;
+        }
+    }
+
+    Some(score)
+}
@@ -1,3 +1,4 @@
+use app_config::{KeybindAction, KeybindScope, Keybinds};
 use ratatui::{
     style::{Color, Stylize},
     symbols::border,
@@ -5,7 +6,7 @@ use ratatui::{
     widgets::Block,
 };
 
-type Instruction = (&'static str, &'static str);
+type Instruction = (&'static str, String);
 
 pub struct CommonBlock {
     title: String,
@@ -29,8 +30,22 @@ impl CommonBlock {
         self
     }
 
-    pub fn add_instruction(mut self, ins: Instruction) -> Self {
-        self.instructions.push(ins);
+    pub fn add_instruction(mut self, (name, key): (&'static str, &str)) -> Self {
+        self.instructions.push((name, key.to_string()));
+        self
+    }
+
+    /// Adds instructions with the first chord of each keybind, unbound keybinds are skipped
+    pub fn add_keybind_instructions(
+        mut self,
+        keybinds: &Keybinds,
+        instructions: &[(&'static str, KeybindScope, KeybindAction)],
+    ) -> Self {
+        for (name, scope, action) in instructions.iter() {
+            if let Some(key) = keybinds.hint(*scope, *action) {
+                self.instructions.push((name, key));
+            }
+        }
         self
     }
 
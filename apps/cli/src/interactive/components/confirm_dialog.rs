@@ -0,0 +1,106 @@
+use std::cmp::min;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Paragraph, Widget, Wrap},
+};
+
+use super::{popup_area, CommonBlock};
+
+const DIALOG_WIDTH: u16 = 60;
+const DIALOG_OVERHEAD: u16 = 4;
+
+pub enum DialogEvent {
+    None,
+    Confirm,
+    Cancel,
+}
+
+/// Modal asking to confirm an action, the value is returned to the caller once confirmed
+pub struct ConfirmDialog<T> {
+    message: String,
+    value: T,
+    confirm_selected: bool,
+}
+
+impl<T> ConfirmDialog<T> {
+    pub fn new(message: String, value: T) -> Self {
+        Self {
+            message,
+            value,
+            confirm_selected: false,
+        }
+    }
+
+    pub fn into_value(self) -> T {
+        self.value
+    }
+
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) -> DialogEvent {
+        match key_event.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => DialogEvent::Confirm,
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc | KeyCode::Char('q') => {
+                DialogEvent::Cancel
+            }
+            KeyCode::Enter => match self.confirm_selected {
+                true => DialogEvent::Confirm,
+                false => DialogEvent::Cancel,
+            },
+            KeyCode::Left
+            | KeyCode::Right
+            | KeyCode::Tab
+            | KeyCode::BackTab
+            | KeyCode::Char('h')
+            | KeyCode::Char('l') => {
+                self.confirm_selected = !self.confirm_selected;
+                DialogEvent::None
+            }
+            _ => DialogEvent::None,
+        }
+    }
+
+    pub fn render(&self, area: Rect, buf: &mut Buffer) {
+        let width = min(DIALOG_WIDTH, area.width);
+        let text_width = width.saturating_sub(4).max(1);
+        let message_height = (self.message.chars().count() as u16).div_ceil(text_width);
+        let height = min(message_height + DIALOG_OVERHEAD, area.height);
+
+        let popup = popup_area(area, width, height, buf);
+        let block: Block<'_> = CommonBlock::new(String::from("Confirm"))
+            .set_border_color(Color::LightRed)
+            .add_instruction(("Confirm", "y"))
+            .add_instruction(("Cancel", "n"))
+            .into();
+
+        let [message_area, _, buttons_area] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(block.inner(popup));
+        block.render(popup, buf);
+
+        Paragraph::new(format!(" {}", self.message))
+            .wrap(Wrap { trim: false })
+            .render(message_area, buf);
+
+        let button = |label: &str, selected: bool| {
+            let span = Span::from(format!("  {}  ", label));
+            match selected {
+                true => span.black().on_light_red().bold(),
+                false => span,
+            }
+        };
+        Line::from(vec![
+            button("Yes", self.confirm_selected),
+            Span::from("   "),
+            button("No", !self.confirm_selected),
+        ])
+        .centered()
+        .render(buttons_area, buf);
+    }
+}
@@ -0,0 +1,44 @@
+use std::cmp::min;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Color,
+    widgets::{Block, Paragraph, Widget, Wrap},
+};
+
+use super::{popup_area, CommonBlock};
+
+const POPUP_WIDTH: u16 = 60;
+const POPUP_OVERHEAD: u16 = 2;
+
+/// Modal showing a warning, it is closed by any key
+pub struct MessagePopup {
+    message: String,
+}
+
+impl MessagePopup {
+    pub fn new(message: String) -> Self {
+        Self { message }
+    }
+
+    pub fn render(&self, area: Rect, buf: &mut Buffer) {
+        let text = format!(" {}", self.message);
+
+        let width = min(POPUP_WIDTH, area.width);
+        let text_width = width.saturating_sub(4).max(1);
+        let text_height = (text.chars().count() as u16).div_ceil(text_width);
+        let height = min(text_height + POPUP_OVERHEAD, area.height);
+
+        let popup = popup_area(area, width, height, buf);
+        let block: Block<'_> = CommonBlock::new(String::from("Warning"))
+            .set_border_color(Color::Yellow)
+            .add_instruction(("Close", "any key"))
+            .into();
+
+        Paragraph::new(text)
+            .wrap(Wrap { trim: false })
+            .block(block)
+            .render(popup, buf);
+    }
+}
@@ -1,7 +1,17 @@
 mod active_table;
+mod command_palette;
 mod common_block;
+mod confirm_dialog;
 mod input;
+mod message_popup;
+mod popup;
+mod progress_popup;
 
 pub(super) use active_table::*;
+pub(super) use command_palette::*;
 pub(super) use common_block::*;
+pub(super) use confirm_dialog::*;
 pub(super) use input::*;
+pub(super) use message_popup::*;
+pub(super) use popup::*;
+pub(super) use progress_popup::*;
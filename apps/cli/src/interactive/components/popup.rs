@@ -0,0 +1,26 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Flex, Layout, Rect},
+    widgets::{Clear, Widget},
+};
+
+/// Computes an area of the provided size centered inside `area` and clears it
+/// so a popup can be rendered over the current content
+pub fn popup_area(area: Rect, width: u16, height: u16, buf: &mut Buffer) -> Rect {
+    let popup = centered_area(area, width, height);
+
+    Clear.render(popup, buf);
+    popup
+}
+
+/// Computes an area of the provided size centered inside `area`
+pub fn centered_area(area: Rect, width: u16, height: u16) -> Rect {
+    let [horizontal] = Layout::horizontal([Constraint::Length(width)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [popup] = Layout::vertical([Constraint::Length(height)])
+        .flex(Flex::Center)
+        .areas(horizontal);
+
+    popup
+}
@@ -0,0 +1,51 @@
+use std::{cmp::min, time::Instant};
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Color,
+    widgets::{Block, Paragraph, Widget, Wrap},
+};
+
+use super::{popup_area, CommonBlock};
+
+const POPUP_WIDTH: u16 = 60;
+const POPUP_OVERHEAD: u16 = 2;
+const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+const SPINNER_FRAME_MS: u128 = 150;
+
+/// Modal shown while a job runs on a worker thread, keys are ignored until it finishes
+pub struct ProgressPopup {
+    message: String,
+    started_at: Instant,
+}
+
+impl ProgressPopup {
+    pub fn new(message: String) -> Self {
+        Self {
+            message,
+            started_at: Instant::now(),
+        }
+    }
+
+    pub fn render(&self, area: Rect, buf: &mut Buffer) {
+        let elapsed = self.started_at.elapsed();
+        let spinner = SPINNER[(elapsed.as_millis() / SPINNER_FRAME_MS) as usize % SPINNER.len()];
+        let text = format!(" {} {} ({}s)", spinner, self.message, elapsed.as_secs());
+
+        let width = min(POPUP_WIDTH, area.width);
+        let text_width = width.saturating_sub(4).max(1);
+        let text_height = (text.chars().count() as u16).div_ceil(text_width);
+        let height = min(text_height + POPUP_OVERHEAD, area.height);
+
+        let popup = popup_area(area, width, height, buf);
+        let block: Block<'_> = CommonBlock::new(String::from("In progress"))
+            .set_border_color(Color::LightBlue)
+            .into();
+
+        Paragraph::new(text)
+            .wrap(Wrap { trim: false })
+            .block(block)
+            .render(popup, buf);
+    }
+}
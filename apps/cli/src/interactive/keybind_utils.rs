@@ -1,16 +1,53 @@
+use app_config::{Key, KeyChord, KeybindAction, KeybindScope, Keybinds};
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
-pub fn is_char_event(k: &KeyEvent, c: char) -> bool {
-    k.code == KeyCode::Char(c)
+pub fn is_keybind_event(
+    k: &KeyEvent,
+    keybinds: &Keybinds,
+    scope: KeybindScope,
+    action: KeybindAction,
+) -> bool {
+    keybinds
+        .chords(scope, action)
+        .iter()
+        .any(|chord| is_chord_event(k, chord))
 }
 
-pub fn is_ctrl_alt_char_event(k: &KeyEvent, c: char) -> bool {
-    k.code == KeyCode::Char(c)
-        && k.modifiers.contains(KeyModifiers::CONTROL)
-        && k.modifiers.contains(KeyModifiers::ALT)
+pub fn is_chord_event(k: &KeyEvent, chord: &KeyChord) -> bool {
+    let ctrl = k.modifiers.contains(KeyModifiers::CONTROL);
+    let alt = k.modifiers.contains(KeyModifiers::ALT);
+    let shift = k.modifiers.contains(KeyModifiers::SHIFT);
+
+    let (key_matches, shift) = match (k.code, chord.key) {
+        (KeyCode::Char(c), Key::Char(key)) if key.is_ascii_alphabetic() => (
+            c.to_ascii_lowercase() == key,
+            shift || c.is_ascii_uppercase(),
+        ),
+        // shift is a part of symbols so it is not compared for them
+        (KeyCode::Char(c), Key::Char(key)) => (c == key, chord.shift),
+        (KeyCode::BackTab, Key::Tab) => (true, true),
+        (code, key) => (code == key_code(key), shift),
+    };
+
+    key_matches && ctrl == chord.ctrl && alt == chord.alt && shift == chord.shift
 }
 
-pub fn is_shift_char_event(k: &KeyEvent, c: char) -> bool {
-    (k.code == KeyCode::Char(c) || k.code == KeyCode::Char(c.to_ascii_uppercase()))
-        && k.modifiers.contains(KeyModifiers::SHIFT)
+fn key_code(key: Key) -> KeyCode {
+    match key {
+        Key::Char(c) => KeyCode::Char(c),
+        Key::Enter => KeyCode::Enter,
+        Key::Esc => KeyCode::Esc,
+        Key::Tab => KeyCode::Tab,
+        Key::Backspace => KeyCode::Backspace,
+        Key::Delete => KeyCode::Delete,
+        Key::Left => KeyCode::Left,
+        Key::Right => KeyCode::Right,
+        Key::Up => KeyCode::Up,
+        Key::Down => KeyCode::Down,
+        Key::Home => KeyCode::Home,
+        Key::End => KeyCode::End,
+        Key::PageUp => KeyCode::PageUp,
+        Key::PageDown => KeyCode::PageDown,
+        Key::F(n) => KeyCode::F(n),
+    }
 }
@@ -1,23 +1,45 @@
-use std::{error::Error, time::Duration};
+use std::{
+    error::Error,
+    sync::mpsc::{Receiver, TryRecvError},
+    time::Duration,
+};
 
-use app_config::{AppConfig, LogPreviewMode};
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use app_config::{AppConfig, KeybindAction, KeybindScope, LogPreviewMode};
+use components::{
+    CommandPalette, ConfirmDialog, DialogEvent, MessagePopup, PaletteEvent, ProgressPopup,
+};
+use crossterm::event::{self, Event, KeyEvent, KeyEventKind};
 use daemon_client::Requester;
 use external_command::{open_log_file, open_string_in_less};
+use keybind_utils::{is_chord_event, is_keybind_event};
 use pages::{Page, PageContext, PageManager};
+use palette::{current_project, palette_entries, run_request_action, PaletteAction};
 use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget, DefaultTerminal, Frame};
+use task_runner::TaskRunner;
 use tui_settings::TuiSettings;
 
+use crate::utils::create_socket_client;
+
 mod components;
 mod external_command;
 mod keybind_utils;
 mod pages;
+mod palette;
 mod tui_settings;
 
-pub fn interact(requester: Requester, config: AppConfig) -> Result<(), Box<dyn Error>> {
+/// Warnings of the config are shown in a popup once the interface starts
+pub fn interact(
+    requester: Requester,
+    config: AppConfig,
+    config_warnings: Vec<String>,
+) -> Result<(), Box<dyn Error>> {
     let mut terminal = ratatui::init();
     terminal.clear().unwrap();
-    let res = App::new(requester, config).run(&mut terminal);
+    let mut app = App::new(requester, config);
+    if !config_warnings.is_empty() {
+        app.warning = Some(MessagePopup::new(config_warnings.join("; ")));
+    }
+    let res = app.run(&mut terminal);
     ratatui::restore();
     res
 }
@@ -28,36 +50,90 @@ enum Action {
     GotoPage(Page),
     OpenLogs(String),
     OpenString(String),
+    Confirm(String, PendingAction),
+    Background(BackgroundJob),
+    Warn(String),
 }
 
 type ActionResult = Result<Action, Box<dyn Error>>;
+type PendingAction = Box<dyn FnOnce(&PageContext) -> ActionResult>;
+
+const EVENT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const JOB_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Action running on a worker thread, its error is returned like the errors of other actions once it finishes
+struct BackgroundJob {
+    progress: ProgressPopup,
+    receiver: Receiver<Result<(), String>>,
+}
+
+impl BackgroundJob {
+    fn new(message: String, receiver: Receiver<Result<(), String>>) -> Self {
+        Self {
+            progress: ProgressPopup::new(message),
+            receiver,
+        }
+    }
+
+    fn result(&self) -> Option<Result<(), Box<dyn Error>>> {
+        match self.receiver.try_recv() {
+            Ok(result) => Some(result.map_err(Into::into)),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                Some(Err("the background job was interrupted".into()))
+            }
+        }
+    }
+}
 
 struct App {
     requester: Requester,
+    task_runner: TaskRunner,
     page_manager: PageManager,
     settings: TuiSettings,
     config: AppConfig,
+    palette: Option<CommandPalette<PaletteAction>>,
+    confirmation: Option<ConfirmDialog<PendingAction>>,
+    job: Option<BackgroundJob>,
+    warning: Option<MessagePopup>,
 }
 
 impl App {
     fn new(requester: Requester, config: AppConfig) -> Self {
         App {
             requester,
+            task_runner: TaskRunner::new(&config.tasks_dir, config.use_caller_env),
             page_manager: PageManager::new(Page::Projects),
             settings: TuiSettings {
                 log_preview: config.default_log_preview_mode.clone(),
+                context: config.current_context.clone(),
             },
             config,
+            palette: None,
+            confirmation: None,
+            job: None,
+            warning: None,
         }
     }
 
     fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<(), Box<dyn Error>> {
         loop {
+            if let Some(result) = self.job.as_ref().and_then(BackgroundJob::result) {
+                self.job = None;
+                result?;
+            }
+
             let context = self.create_page_context();
             self.page_manager.view().update(context)?;
             terminal.draw(|frame| self.draw(frame))?;
 
-            match self.handle_events()? {
+            let mut action = self.handle_events()?;
+            if let Action::Confirm(message, pending) = action {
+                self.confirmation = Some(ConfirmDialog::new(message, pending));
+                action = Action::None;
+            }
+
+            match action {
                 Action::Exit => break Ok(()),
                 Action::GotoPage(page) => {
                     self.page_manager.goto_page(page);
@@ -68,7 +144,13 @@ impl App {
                 Action::OpenString(str) => {
                     open_string_in_less(terminal, str)?;
                 }
-                Action::None => {}
+                Action::Background(job) => {
+                    self.job = Some(job);
+                }
+                Action::Warn(message) => {
+                    self.warning = Some(MessagePopup::new(message));
+                }
+                Action::None | Action::Confirm(..) => {}
             }
         }
     }
@@ -77,7 +159,12 @@ impl App {
         let area = frame.area();
 
         let context = self.create_page_context();
-        let cursor_position = self.page_manager.view().cursor_position(area, context);
+        let cursor_position = match (&self.palette, &self.confirmation) {
+            _ if self.job.is_some() || self.warning.is_some() => None,
+            (_, Some(_)) => None,
+            (Some(palette), None) => Some(palette.cursor_position(area)),
+            (None, None) => self.page_manager.view().cursor_position(area, context),
+        };
         if let Some(cp) = cursor_position {
             frame.set_cursor_position(cp)
         }
@@ -86,7 +173,12 @@ impl App {
     }
 
     fn handle_events(&mut self) -> ActionResult {
-        if event::poll(Duration::from_secs(1))? {
+        // the progress of a background job is redrawn more often
+        let interval = match self.job {
+            Some(_) => JOB_POLL_INTERVAL,
+            None => EVENT_POLL_INTERVAL,
+        };
+        if event::poll(interval)? {
             match event::read()? {
                 Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
                     self.handle_key_event(key_event)
@@ -99,8 +191,45 @@ impl App {
     }
 
     fn handle_key_event(&mut self, key_event: KeyEvent) -> ActionResult {
+        // only quitting is possible until the background job finishes
+        if self.job.is_some() {
+            let quits = is_keybind_event(
+                &key_event,
+                &self.config.keybinds,
+                KeybindScope::Global,
+                KeybindAction::Quit,
+            );
+            return Ok(match quits {
+                true => Action::Exit,
+                false => Action::None,
+            });
+        }
+
+        if self.warning.take().is_some() {
+            return Ok(Action::None);
+        }
+
+        if self.confirmation.is_some() {
+            return self.handle_key_event_confirmation(key_event);
+        }
+
+        if self.palette.is_some() {
+            return self.handle_key_event_palette(key_event);
+        }
+
         if self.page_manager.is_in_raw_mode() {
-            return self.handle_key_event_page(key_event);
+            // palette chords with modifiers can not be typed into inputs so they work in raw mode too
+            let opens_palette = self
+                .config
+                .keybinds
+                .chords(KeybindScope::Global, KeybindAction::OpenCommandPalette)
+                .iter()
+                .any(|chord| (chord.ctrl || chord.alt) && is_chord_event(&key_event, chord));
+
+            return match opens_palette {
+                true => self.open_palette(),
+                false => self.handle_key_event_page(key_event),
+            };
         }
 
         match self.handle_key_event_global(key_event) {
@@ -117,35 +246,172 @@ impl App {
     }
 
     fn handle_key_event_global(&mut self, key_event: KeyEvent) -> Option<ActionResult> {
-        match key_event.code {
-            KeyCode::Char('q') | KeyCode::Esc if key_event.kind == KeyEventKind::Press => {
-                Some(Ok(Action::Exit))
+        let is_bound = |action| {
+            is_keybind_event(
+                &key_event,
+                &self.config.keybinds,
+                KeybindScope::Global,
+                action,
+            )
+        };
+
+        if is_bound(KeybindAction::Quit) {
+            return Some(Ok(Action::Exit));
+        }
+
+        if is_bound(KeybindAction::ShowKeybinds) {
+            let current_page = self.page_manager.current_page().clone();
+
+            return match current_page {
+                Page::Keybinds(_) => None,
+                _ => Some(Ok(Action::GotoPage(Page::Keybinds(Box::new(current_page))))),
+            };
+        }
+
+        if is_bound(KeybindAction::ToggleLogPreview) {
+            self.toggle_log_preview();
+            return Some(Ok(Action::None));
+        }
+
+        if is_bound(KeybindAction::OpenCommandPalette) {
+            return Some(self.open_palette());
+        }
+
+        None
+    }
+
+    fn handle_key_event_confirmation(&mut self, key_event: KeyEvent) -> ActionResult {
+        let mut dialog = match self.confirmation.take() {
+            Some(dialog) => dialog,
+            None => return Ok(Action::None),
+        };
+
+        match dialog.handle_key_event(key_event) {
+            DialogEvent::None => {
+                self.confirmation = Some(dialog);
+                Ok(Action::None)
             }
-            KeyCode::Tab => {
-                let current_page = self.page_manager.current_page().clone();
+            DialogEvent::Cancel => Ok(Action::None),
+            DialogEvent::Confirm => {
+                let context = self.create_page_context();
+                dialog.into_value()(&context)
+            }
+        }
+    }
 
-                match current_page {
-                    Page::Keybinds(_) => None,
-                    _ => Some(Ok(Action::GotoPage(Page::Keybinds(Box::new(current_page))))),
-                }
+    fn handle_key_event_palette(&mut self, key_event: KeyEvent) -> ActionResult {
+        let palette = match &mut self.palette {
+            Some(palette) => palette,
+            None => return Ok(Action::None),
+        };
+
+        match palette.handle_key_event(key_event) {
+            PaletteEvent::None => Ok(Action::None),
+            PaletteEvent::Close => {
+                self.palette = None;
+                Ok(Action::None)
             }
-            KeyCode::Char('i') => {
-                self.settings.log_preview = match self.settings.log_preview {
-                    LogPreviewMode::On => LogPreviewMode::Fit,
-                    LogPreviewMode::Off => LogPreviewMode::On,
-                    LogPreviewMode::Fit => LogPreviewMode::Off,
-                };
-
-                Some(Ok(Action::None))
+            PaletteEvent::Run(action) => {
+                self.palette = None;
+                self.run_palette_action(action)
             }
-            _ => None,
         }
     }
 
+    fn open_palette(&mut self) -> ActionResult {
+        let projects = self.requester.get_projects_info()?;
+        let current_project = current_project(self.page_manager.current_page());
+
+        let other_contexts = self
+            .config
+            .context_names()
+            .into_iter()
+            .filter(|name| *name != self.config.current_context)
+            .collect();
+
+        let mut palette = CommandPalette::new();
+        palette.set_entries(palette_entries(projects, current_project, other_contexts));
+        self.palette = Some(palette);
+
+        Ok(Action::None)
+    }
+
+    fn run_palette_action(&mut self, action: PaletteAction) -> ActionResult {
+        let context = self.create_page_context();
+
+        if let Some(message) = action.confirmation_message() {
+            return context.confirm(message, move |context| {
+                let request_action = run_request_action(&action, context)?;
+                Ok(match action {
+                    PaletteAction::RemoveProject(_) => Action::GotoPage(Page::Projects),
+                    _ => request_action,
+                })
+            });
+        }
+
+        let request_action = run_request_action(&action, &context)?;
+
+        let action = match action {
+            PaletteAction::GotoProjects => Action::GotoPage(Page::Projects),
+            PaletteAction::GotoProject(project) => Action::GotoPage(Page::Project(project)),
+            PaletteAction::OpenServiceLogs(path) => Action::OpenLogs(path),
+            PaletteAction::UseContext(name) => {
+                self.use_context(&name)?;
+                Action::GotoPage(Page::Projects)
+            }
+            PaletteAction::ToggleLogPreview => {
+                self.toggle_log_preview();
+                Action::None
+            }
+            PaletteAction::ShowKeybinds => match self.page_manager.current_page().clone() {
+                Page::Keybinds(_) => Action::None,
+                current_page => Action::GotoPage(Page::Keybinds(Box::new(current_page))),
+            },
+            PaletteAction::Quit => Action::Exit,
+            _ => request_action,
+        };
+
+        Ok(action)
+    }
+
+    /// Connects to the daemon of the context, the app keeps the current one when it is not running
+    fn use_context(&mut self, name: &str) -> Result<(), Box<dyn Error>> {
+        let mut config = self.config.clone();
+        config.use_context(name)?;
+
+        let socket_client = create_socket_client(&config);
+        if let Err(err) = socket_client.health_check() {
+            return Err(format!(
+                "Cannot connect to the daemon of the context '{}' at {}: {}",
+                name,
+                socket_client.address(),
+                err
+            )
+            .into());
+        }
+
+        self.requester = Requester::new(socket_client, config.use_caller_env);
+        self.task_runner = TaskRunner::new(&config.tasks_dir, config.use_caller_env);
+        self.settings.context = config.current_context.clone();
+        self.config = config;
+        Ok(())
+    }
+
+    fn toggle_log_preview(&mut self) {
+        self.settings.log_preview = match self.settings.log_preview {
+            LogPreviewMode::On => LogPreviewMode::Fit,
+            LogPreviewMode::Off => LogPreviewMode::On,
+            LogPreviewMode::Fit => LogPreviewMode::Off,
+        };
+    }
+
     fn create_page_context(&self) -> PageContext {
         PageContext {
             requester: self.requester.clone(),
+            task_runner: self.task_runner.clone(),
             settings: self.settings.clone(),
+            keybinds: self.config.keybinds.clone(),
+            confirm_destructive_actions: self.config.confirm_destructive_actions,
         }
     }
 }
@@ -154,5 +420,21 @@ impl Widget for &mut App {
     fn render(self, area: Rect, buf: &mut Buffer) {
         let context = self.create_page_context();
         self.page_manager.view().render(area, buf, context);
+
+        if let Some(palette) = &mut self.palette {
+            palette.render(area, buf);
+        }
+
+        if let Some(confirmation) = &self.confirmation {
+            confirmation.render(area, buf);
+        }
+
+        if let Some(job) = &self.job {
+            job.progress.render(area, buf);
+        }
+
+        if let Some(warning) = &self.warning {
+            warning.render(area, buf);
+        }
     }
 }
@@ -1,3 +1,4 @@
+use app_config::{Keybind, KeybindAction, KeybindScope, Keybinds};
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
     buffer::Buffer,
@@ -6,7 +7,9 @@ use ratatui::{
     widgets::{Cell, Row, StatefulWidget, Table, TableState},
 };
 
-use crate::interactive::{components::CommonBlock, Action, ActionResult};
+use crate::interactive::{
+    components::CommonBlock, keybind_utils::is_keybind_event, Action, ActionResult,
+};
 
 use super::{Page, PageContext, PageView};
 
@@ -26,11 +29,18 @@ impl KeybindsPage {
 }
 
 impl PageView for KeybindsPage {
-    fn handle_key_event(&mut self, key_event: KeyEvent, _: PageContext) -> ActionResult {
+    fn handle_key_event(&mut self, key_event: KeyEvent, context: PageContext) -> ActionResult {
+        let is_bound =
+            |action| is_keybind_event(&key_event, &context.keybinds, KeybindScope::Global, action);
+
+        if key_event.code == KeyCode::Esc
+            || is_bound(KeybindAction::Quit)
+            || is_bound(KeybindAction::ShowKeybinds)
+        {
+            return Ok(Action::GotoPage(self.for_page.clone()));
+        }
+
         match key_event.code {
-            KeyCode::Tab | KeyCode::Esc | KeyCode::Char('q') => {
-                Ok(Action::GotoPage(self.for_page.clone()))
-            }
             KeyCode::Down | KeyCode::Char('j') => {
                 let offset = self.state.offset() + 1;
                 self.state = self.state.clone().with_offset(offset);
@@ -58,19 +68,34 @@ impl PageView for KeybindsPage {
         true
     }
 
-    fn render(&mut self, area: Rect, buf: &mut Buffer, _: PageContext) {
+    fn render(&mut self, area: Rect, buf: &mut Buffer, context: PageContext) {
+        let keybinds = &context.keybinds;
         let (title, binds) = match &self.for_page {
-            Page::Projects => (String::from("PROJECTS"), get_projects_page_keybinds()),
+            Page::Projects => (
+                String::from("PROJECTS"),
+                get_page_keybinds(
+                    keybinds,
+                    KeybindScope::Projects,
+                    get_table_navigation("Next project", "Previous project"),
+                ),
+            ),
             Page::Project(project) => (
                 format!("PROJECT '{}'", project),
-                get_project_page_keybinds(),
+                get_page_keybinds(
+                    keybinds,
+                    KeybindScope::Project,
+                    get_table_navigation("Next service", "Previous service"),
+                ),
             ),
-            Page::Keybinds(_) => (String::from("keybinds"), get_keybinds_keybinds()),
+            Page::Keybinds(_) => (String::from("keybinds"), get_keybinds_keybinds(keybinds)),
         };
 
         let block = CommonBlock::new(format!("Keybinds for page: {}", title))
             .set_border_color(Color::Green)
-            .add_instruction(("Return", "tab"));
+            .add_keybind_instructions(
+                keybinds,
+                &[("Return", KeybindScope::Global, KeybindAction::ShowKeybinds)],
+            );
 
         let rows = binds.iter().map(|(key, bind)| {
             Row::new(vec![
@@ -86,43 +111,41 @@ impl PageView for KeybindsPage {
     }
 }
 
-fn get_projects_page_keybinds() -> Vec<(&'static str, Vec<&'static str>)> {
-    vec![
-        ("Search projects", vec!["/"]),
-        ("Start project", vec!["s"]),
-        ("Stop project", vec!["d"]),
-        ("Restart project", vec!["r"]),
-        ("Clear project logs", vec!["ctrl+alt+l"]),
-        ("Open project", vec!["enter", "k", "right"]),
-        ("Next project", vec!["j", "down"]),
-        ("Previous project", vec!["l", "up"]),
-        ("Change log preview mode", vec!["i"]),
-        ("Quit app", vec!["q", "esc"]),
-        ("Show keybinds", vec!["tab"]),
-    ]
+/// Keybinds of the page followed by the table navigation and the global keybinds
+fn get_page_keybinds(
+    keybinds: &Keybinds,
+    scope: KeybindScope,
+    navigation: Vec<(&'static str, Vec<String>)>,
+) -> Vec<(&'static str, Vec<String>)> {
+    let to_row = |bind: &Keybind| {
+        let chords = bind.chords.iter().map(|chord| chord.to_string()).collect();
+        (bind.description, chords)
+    };
+
+    keybinds
+        .for_scope(scope)
+        .map(to_row)
+        .chain(navigation)
+        .chain(keybinds.for_scope(KeybindScope::Global).map(to_row))
+        .collect()
 }
 
-fn get_project_page_keybinds() -> Vec<(&'static str, Vec<&'static str>)> {
+fn get_table_navigation(
+    next: &'static str,
+    previous: &'static str,
+) -> Vec<(&'static str, Vec<String>)> {
     vec![
-        ("Search services", vec!["/"]),
-        ("Start service", vec!["s"]),
-        ("Stop service", vec!["d"]),
-        ("Restart service", vec!["r"]),
-        ("Clear service logs", vec!["ctrl+alt+l"]),
-        ("Next service", vec!["j", "down"]),
-        ("Previous service", vec!["l", "up"]),
-        ("Open logs", vec!["enter"]),
-        ("Go back to projects", vec!["j", "left"]),
-        ("Change log preview mode", vec!["i"]),
-        ("Open project settings", vec!["o"]),
-        ("Quit app", vec!["q", "esc"]),
-        ("Start project", vec!["S"]),
-        ("Stop project", vec!["D"]),
-        ("Restart project", vec!["R"]),
-        ("Show keybinds", vec!["tab"]),
+        (next, vec![String::from("j"), String::from("down")]),
+        (previous, vec![String::from("k"), String::from("up")]),
     ]
 }
 
-fn get_keybinds_keybinds() -> Vec<(&'static str, Vec<&'static str>)> {
-    vec![("Go back", vec!["q", "esc", "tab"])]
+fn get_keybinds_keybinds(keybinds: &Keybinds) -> Vec<(&'static str, Vec<String>)> {
+    let chords = [KeybindAction::Quit, KeybindAction::ShowKeybinds]
+        .iter()
+        .flat_map(|action| keybinds.chords(KeybindScope::Global, *action))
+        .map(|chord| chord.to_string())
+        .collect();
+
+    vec![("Go back", chords)]
 }
@@ -1,16 +1,20 @@
-use std::{collections::HashMap, error::Error, hash::Hash, mem};
+use std::{collections::HashMap, error::Error, hash::Hash, mem, sync::mpsc, thread};
 
+use app_config::Keybinds;
 use crossterm::event::KeyEvent;
-use daemon_client::Requester;
+use daemon_client::{ErrorResponse, Requester};
 use keybinds_page::KeybindsPage;
+use port_registry::check_ports;
 use project_page::ProjectPage;
+use project_settings::{ProjectSettings, ProjectSettingsError};
 use projects_page::ProjectsPage;
 use ratatui::{
     buffer::Buffer,
     layout::{Position, Rect},
 };
+use task_runner::TaskRunner;
 
-use super::{tui_settings::TuiSettings, ActionResult};
+use super::{tui_settings::TuiSettings, Action, ActionResult, BackgroundJob};
 
 mod keybinds_page;
 mod project_page;
@@ -27,6 +31,98 @@ pub enum Page {
 pub struct PageContext {
     pub settings: TuiSettings,
     pub requester: Requester,
+    pub task_runner: TaskRunner,
+    pub keybinds: Keybinds,
+    pub confirm_destructive_actions: bool,
+}
+
+impl PageContext {
+    /// Checks that the declared ports are free, runs the 'before_start' tasks of the services about to be started
+    /// and starts them on a worker thread, the app shows the progress until it finishes
+    pub(super) fn start_in_background<F>(
+        &self,
+        project_name: &str,
+        service_names: Option<Vec<String>>,
+        start: F,
+    ) -> ActionResult
+    where
+        F: FnOnce(&Requester) -> Result<(), ErrorResponse> + Send + 'static,
+    {
+        let message = match &service_names {
+            Some(service_names) => format!(
+                "Starting '{}/{}' and its 'before_start' tasks",
+                project_name,
+                service_names.join(", ")
+            ),
+            None => format!("Starting '{}' and its 'before_start' tasks", project_name),
+        };
+
+        let requester = self.requester.clone();
+        let task_runner = self.task_runner.clone();
+        let project_name = String::from(project_name);
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let prepare = || -> Result<(), Box<dyn Error>> {
+                check_ports(&requester, &project_name, service_names.as_deref())?;
+                task_runner.run_before_start(
+                    &requester,
+                    &project_name,
+                    service_names.as_deref(),
+                )?;
+                start(&requester)?;
+                Ok(())
+            };
+            let _ = sender.send(prepare().map_err(|err| err.to_string()));
+        });
+
+        Ok(Action::Background(BackgroundJob::new(message, receiver)))
+    }
+
+    /// Asks for a confirmation before running the action when it is enabled in the config
+    pub(super) fn confirm<F>(&self, message: String, action: F) -> ActionResult
+    where
+        F: FnOnce(&PageContext) -> ActionResult + 'static,
+    {
+        match self.confirm_destructive_actions {
+            true => Ok(Action::Confirm(message, Box::new(action))),
+            false => action(self),
+        }
+    }
+
+    /// Upserts the project again from the settings file it was upserted from
+    /// Replaces the project with the settings read from its file, a moved file only shows a warning
+    pub(super) fn reload_project(&self, project_name: &str) -> ActionResult {
+        let json = self.requester.get_project_settings(project_name)?;
+        let settings = match ProjectSettings::try_from(json.as_str())?.reload() {
+            Ok(settings) => settings,
+            Err(err @ ProjectSettingsError::SourceNotFound { .. }) => {
+                return Ok(Action::Warn(format!(
+                    "{}, the project was left unchanged",
+                    err
+                )))
+            }
+            Err(err) => return Err(err.into()),
+        };
+        self.requester
+            .upsert_project(&String::try_from(&settings)?)?;
+        Ok(Action::None)
+    }
+}
+
+pub fn stop_project_message(project_name: &str) -> String {
+    format!("Stop all services of '{}'?", project_name)
+}
+
+pub fn remove_project_message(project_name: &str) -> String {
+    format!("Stop and remove '{}' with its logs?", project_name)
+}
+
+pub fn clear_project_logs_message(project_name: &str) -> String {
+    format!("Clear logs of all services of '{}'?", project_name)
+}
+
+pub fn clear_service_logs_message(project_name: &str, service_name: &str) -> String {
+    format!("Clear logs of '{}/{}'?", project_name, service_name)
 }
 
 pub trait PageView {
@@ -1,11 +1,13 @@
-use std::{error::Error, vec};
+use std::time::{Duration, Instant};
+use std::{error::Error, thread, vec};
 
 use std::cmp::{max, min};
 
 use ansi_to_tui::IntoText;
-use app_config::LogPreviewMode;
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
-use daemon_client::{ProjectInfo, Requester, ServiceInfo, ServiceStatus};
+use app_config::{KeybindAction, KeybindScope, LogPreviewMode};
+use crossterm::event::{KeyCode, KeyEvent};
+use daemon_client::{ProjectInfo, ServiceInfo, ServiceStatus};
+use process_metrics::{MetricsCollector, MetricsHistory};
 use project_settings::ProjectSettings;
 use ratatui::text::Text;
 use ratatui::{
@@ -15,25 +17,31 @@ use ratatui::{
     text::Span,
     widgets::{Paragraph, Row, Widget},
 };
+use task_runner::{TaskRun, TaskStatus};
 
-use crate::interactive::keybind_utils::{
-    is_char_event, is_ctrl_alt_char_event, is_shift_char_event,
-};
+use crate::interactive::keybind_utils::is_keybind_event;
 use crate::interactive::tui_settings::TuiSettings;
 use crate::{
     interactive::{
-        components::{ActiveTable, CommonBlock, Input},
+        components::{popup_area, ActiveTable, CommonBlock, Input},
         Action, ActionResult,
     },
-    utils::{read_last_n_lines_from_file, start_time_to_age},
+    utils::{read_last_n_lines_from_file, sparkline, start_time_to_age},
+};
+
+use super::{
+    clear_service_logs_message, remove_project_message, stop_project_message, Page, PageContext,
+    PageView,
 };
 
-use super::{Page, PageContext, PageView};
+const KEYBIND_SCOPE: KeybindScope = KeybindScope::Project;
 
 #[derive(Debug, PartialEq)]
 enum Mode {
     Normal,
     Search(Option<usize>),
+    Profiles,
+    Tasks,
 }
 
 #[derive(Debug)]
@@ -45,18 +53,37 @@ pub(super) struct ProjectPage {
     mode: Mode,
     table: ActiveTable,
     search: Input,
+    profiles: Vec<String>,
+    profiles_table: ActiveTable,
+    tasks: Vec<(String, Option<TaskRun>)>,
+    tasks_table: ActiveTable,
+    metrics: MetricsCollector,
+    metrics_history: MetricsHistory,
+    metrics_collected_at: Option<Instant>,
 }
 
 impl ProjectPage {
     pub(super) fn new(project_name: String) -> Self {
         let table = ActiveTable::new()
-            .ad_header(("NAME", 25))
-            .ad_header(("STATUS", 25))
-            .ad_header(("PID", 25))
-            .ad_header(("AGE", 25));
+            .ad_header(("NAME", 20))
+            .ad_header(("STATUS", 12))
+            .ad_header(("PID", 8))
+            .ad_header(("AGE", 14))
+            .ad_header(("CPU", 8))
+            .ad_header(("RSS", 10))
+            .ad_header(("THREADS", 8))
+            .ad_header(("FDS", 6))
+            .ad_header(("CPU HISTORY", 14));
 
         let input = Input::new();
 
+        let profiles_table = ActiveTable::new().ad_header(("PROFILE", 100));
+        let tasks_table = ActiveTable::new()
+            .ad_header(("NAME", 30))
+            .ad_header(("STATUS", 25))
+            .ad_header(("EXIT CODE", 20))
+            .ad_header(("LAST RUN", 25));
+
         Self {
             project_name,
             project: None,
@@ -64,6 +91,13 @@ impl ProjectPage {
             mode: Mode::Normal,
             search: input,
             logs: vec![],
+            profiles: vec![],
+            profiles_table,
+            tasks: vec![],
+            tasks_table,
+            metrics: MetricsCollector::new(),
+            metrics_history: MetricsHistory::new(METRICS_HISTORY_LENGTH),
+            metrics_collected_at: None,
         }
     }
 }
@@ -71,8 +105,17 @@ impl ProjectPage {
 impl PageView for ProjectPage {
     fn update(&mut self, context: PageContext) -> Result<(), Box<dyn Error>> {
         let project = context.requester.get_project_info(&self.project_name)?;
+        self.collect_metrics(&project);
         self.project = Some(project);
 
+        if self.mode == Mode::Tasks {
+            for (task_name, run) in self.tasks.iter_mut() {
+                *run = context
+                    .task_runner
+                    .last_run(&self.project_name, task_name)?;
+            }
+        }
+
         if let Some(selected_service) = self.get_selected_service() {
             if context.settings.log_preview != LogPreviewMode::Off {
                 let lines = read_last_n_lines_from_file(&selected_service.logfile_path, 50)?;
@@ -85,14 +128,16 @@ impl PageView for ProjectPage {
 
     fn handle_key_event(&mut self, key_event: KeyEvent, context: PageContext) -> ActionResult {
         match self.mode {
-            Mode::Normal => self.handle_key_event(key_event, &context.requester),
+            Mode::Normal => self.handle_key_event(key_event, &context),
             Mode::Search(prev_selected) => self.handle_key_event_search(key_event, prev_selected),
+            Mode::Profiles => self.handle_key_event_profiles(key_event, &context),
+            Mode::Tasks => self.handle_key_event_tasks(key_event, &context),
         }
     }
 
     fn cursor_position(&self, area: Rect, context: PageContext) -> Option<Position> {
         match self.mode {
-            Mode::Normal => None,
+            Mode::Normal | Mode::Profiles | Mode::Tasks => None,
             Mode::Search(_) => {
                 let layout = PageLayout::from(self, area, &context.settings);
                 layout.search_area.map(|search_area| {
@@ -113,7 +158,7 @@ impl PageView for ProjectPage {
     fn is_in_raw_mode(&self) -> bool {
         match self.mode {
             Mode::Normal => false,
-            Mode::Search(_) => true,
+            Mode::Search(_) | Mode::Profiles | Mode::Tasks => true,
         }
     }
 
@@ -128,66 +173,137 @@ impl PageView for ProjectPage {
         if let Some(logs_area) = layout.logs_area {
             self.render_logs(logs_area, buf);
         }
+
+        match self.mode {
+            Mode::Profiles => self.render_profiles(area, buf),
+            Mode::Tasks => self.render_tasks(area, buf),
+            _ => {}
+        }
     }
 }
 
 impl ProjectPage {
-    fn handle_key_event(&mut self, key_event: KeyEvent, requester: &Requester) -> ActionResult {
+    fn handle_key_event(&mut self, key_event: KeyEvent, context: &PageContext) -> ActionResult {
         let selected_service = self.get_selected_service();
-
-        if is_char_event(&key_event, 's') {
-            if let Some(service) = selected_service {
-                requester.start_service(&self.project_name, &service.name)?;
-            }
-            return Ok(Action::None);
+        let requester = &context.requester;
+        let task_runner = &context.task_runner;
+        let is_bound =
+            |action| is_keybind_event(&key_event, &context.keybinds, KEYBIND_SCOPE, action);
+
+        if is_bound(KeybindAction::Start) {
+            return match selected_service {
+                Some(service) => {
+                    let project_name = self.project_name.clone();
+                    let service_names = Some(vec![service.name.clone()]);
+                    context.start_in_background(&self.project_name, service_names, move |r| {
+                        r.start_service(&project_name, &service.name).map(|_| ())
+                    })
+                }
+                None => Ok(Action::None),
+            };
         }
 
-        if is_char_event(&key_event, 'd') {
+        if is_bound(KeybindAction::Stop) {
             if let Some(service) = selected_service {
                 requester.stop_service(&self.project_name, &service.name)?;
             }
             return Ok(Action::None);
         }
 
-        if is_char_event(&key_event, 'r') {
-            if let Some(service) = selected_service {
-                requester.restart_service(&self.project_name, &service.name)?;
-            }
-            return Ok(Action::None);
+        if is_bound(KeybindAction::Restart) {
+            return match selected_service {
+                Some(service) => {
+                    let project_name = self.project_name.clone();
+                    let service_names = Some(vec![service.name.clone()]);
+                    context.start_in_background(&self.project_name, service_names, move |r| {
+                        r.restart_service(&project_name, &service.name).map(|_| ())
+                    })
+                }
+                None => Ok(Action::None),
+            };
         }
 
-        if is_ctrl_alt_char_event(&key_event, 'l') {
-            if let Some(service) = selected_service {
-                if key_event.modifiers.contains(KeyModifiers::CONTROL) {
-                    requester.clear_service_logs(&self.project_name, &service.name)?;
+        if is_bound(KeybindAction::ClearLogs) {
+            return match selected_service {
+                Some(service) => {
+                    let project_name = self.project_name.clone();
+                    let message = clear_service_logs_message(&project_name, &service.name);
+                    context.confirm(message, move |c| {
+                        c.requester
+                            .clear_service_logs(&project_name, &service.name)?;
+                        Ok(Action::None)
+                    })
                 }
-            }
-            return Ok(Action::None);
+                None => Ok(Action::None),
+            };
         }
 
-        if is_shift_char_event(&key_event, 's') {
-            requester.start_project(&self.project_name)?;
-            return Ok(Action::None);
+        if is_bound(KeybindAction::StartProject) {
+            let project_name = self.project_name.clone();
+            return context.start_in_background(&self.project_name, None, move |r| {
+                r.start_project(&project_name).map(|_| ())
+            });
+        }
+
+        if is_bound(KeybindAction::StopProject) {
+            let project_name = self.project_name.clone();
+            return context.confirm(stop_project_message(&project_name), move |c| {
+                c.requester.stop_project(&project_name)?;
+                Ok(Action::None)
+            });
+        }
+
+        if is_bound(KeybindAction::RemoveProject) {
+            let project_name = self.project_name.clone();
+            return context.confirm(remove_project_message(&project_name), move |c| {
+                c.requester.remove_project(&project_name)?;
+                Ok(Action::GotoPage(Page::Projects))
+            });
+        }
+
+        if is_bound(KeybindAction::RestartProject) {
+            let project_name = self.project_name.clone();
+            return context.start_in_background(&self.project_name, None, move |r| {
+                r.restart_project(&project_name).map(|_| ())
+            });
         }
 
-        if is_shift_char_event(&key_event, 'd') {
-            requester.stop_project(&self.project_name)?;
+        if is_bound(KeybindAction::Reload) {
+            return context.reload_project(&self.project_name);
+        }
+
+        if is_bound(KeybindAction::StartProfile) {
+            let settings = requester.get_project_settings(&self.project_name)?;
+            self.profiles = ProjectSettings::try_from(settings.as_str())?.profile_names();
+            self.profiles_table.select(None);
+            self.mode = Mode::Profiles;
             return Ok(Action::None);
         }
 
-        if is_shift_char_event(&key_event, 'r') {
-            requester.restart_project(&self.project_name)?;
+        if is_bound(KeybindAction::ShowTasks) {
+            let settings = requester.get_project_settings(&self.project_name)?;
+            self.tasks = ProjectSettings::try_from(settings.as_str())?
+                .tasks
+                .iter()
+                .map(|task| {
+                    task_runner
+                        .last_run(&self.project_name, &task.name)
+                        .map(|run| (task.name.clone(), run))
+                })
+                .collect::<Result<_, _>>()?;
+            self.tasks_table.select(None);
+            self.mode = Mode::Tasks;
             return Ok(Action::None);
         }
 
-        if is_char_event(&key_event, 'o') {
+        if is_bound(KeybindAction::OpenSettings) {
             let settings = requester.get_project_settings(&self.project_name)?;
             return Ok(Action::OpenString(ProjectSettings::prettify_json(
                 &settings,
             )?));
         }
 
-        if key_event.code == KeyCode::Enter {
+        if is_bound(KeybindAction::Open) {
             let action = selected_service
                 .map(|service| Action::OpenLogs(service.logfile_path))
                 .unwrap_or(Action::None);
@@ -195,11 +311,11 @@ impl ProjectPage {
             return Ok(action);
         }
 
-        if key_event.code == KeyCode::Left || is_char_event(&key_event, 'h') {
+        if is_bound(KeybindAction::GoBack) {
             return Ok(Action::GotoPage(Page::Projects));
         }
 
-        if is_char_event(&key_event, '/') {
+        if is_bound(KeybindAction::Search) {
             self.mode = Mode::Search(self.table.selected());
             return Ok(Action::None);
         }
@@ -240,6 +356,97 @@ impl ProjectPage {
         Ok(Action::None)
     }
 
+    fn handle_key_event_profiles(
+        &mut self,
+        key_event: KeyEvent,
+        context: &PageContext,
+    ) -> ActionResult {
+        let requester = &context.requester;
+
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('p') => {
+                self.mode = Mode::Normal;
+            }
+            KeyCode::Enter => {
+                let selected_profile = self
+                    .profiles_table
+                    .selected()
+                    .and_then(|i| self.profiles.get(i));
+
+                self.mode = Mode::Normal;
+                if let Some(profile) = selected_profile {
+                    let settings = requester.get_project_settings(&self.project_name)?;
+                    let services =
+                        ProjectSettings::try_from(settings.as_str())?.profile_services(profile)?;
+                    let project_name = self.project_name.clone();
+                    let service_names = Some(services.clone());
+                    return context.start_in_background(
+                        &self.project_name,
+                        service_names,
+                        move |r| {
+                            r.start_project_services(&project_name, &services)
+                                .map(|_| ())
+                        },
+                    );
+                }
+            }
+            _ => self
+                .profiles_table
+                .handle_key_event(key_event, self.profiles.len()),
+        }
+
+        Ok(Action::None)
+    }
+
+    fn handle_key_event_tasks(
+        &mut self,
+        key_event: KeyEvent,
+        context: &PageContext,
+    ) -> ActionResult {
+        let selected_task = self
+            .tasks_table
+            .selected()
+            .and_then(|i| self.tasks.get(i))
+            .cloned();
+
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('t') => {
+                self.mode = Mode::Normal;
+            }
+            KeyCode::Enter => {
+                if let Some((_, Some(run))) = selected_task {
+                    return Ok(Action::OpenLogs(run.logfile_path));
+                }
+            }
+            KeyCode::Char('r') => {
+                let is_running = matches!(
+                    selected_task,
+                    Some((
+                        _,
+                        Some(TaskRun {
+                            status: TaskStatus::Running,
+                            ..
+                        })
+                    ))
+                );
+
+                if let (Some((task_name, _)), false) = (selected_task, is_running) {
+                    let json = context.requester.get_project_settings(&self.project_name)?;
+                    let settings = ProjectSettings::try_from(json.as_str())?;
+                    let task_runner = context.task_runner.clone();
+
+                    // tasks may take a long time so they are not awaited, their state is refreshed in the update
+                    thread::spawn(move || task_runner.run(&settings, &task_name));
+                }
+            }
+            _ => self
+                .tasks_table
+                .handle_key_event(key_event, self.tasks.len()),
+        }
+
+        Ok(Action::None)
+    }
+
     fn render_search(&mut self, area: Rect, buf: &mut Buffer) {
         let block = CommonBlock::new(String::from("Search"))
             .set_border_color(Color::LightRed)
@@ -263,10 +470,19 @@ impl ProjectPage {
         let block = CommonBlock::new(title)
             .add_top_info(context.settings.to_info())
             .set_border_color(Color::LightBlue)
-            .add_instruction(("Show keybinds", "tab"))
-            .add_instruction(("Start", "s"))
-            .add_instruction(("Stop", "d"))
-            .add_instruction(("Logs", "enter"));
+            .add_keybind_instructions(
+                &context.keybinds,
+                &[
+                    (
+                        "Show keybinds",
+                        KeybindScope::Global,
+                        KeybindAction::ShowKeybinds,
+                    ),
+                    ("Start", KEYBIND_SCOPE, KeybindAction::Start),
+                    ("Stop", KEYBIND_SCOPE, KeybindAction::Stop),
+                    ("Logs", KEYBIND_SCOPE, KeybindAction::Open),
+                ],
+            );
 
         let rows = self
             .get_filtered_services()
@@ -282,7 +498,36 @@ impl ProjectPage {
                 }
                 .into();
 
-                let mut row = Row::new(vec![name, status, pid, age]);
+                let metrics = self.metrics_history.last(&service.name);
+                let cpu: Span = metrics.map(|m| m.format_cpu()).unwrap_or_default().into();
+                let rss: Span = metrics.map(|m| m.format_rss()).unwrap_or_default().into();
+                let threads: Span = metrics
+                    .map(|m| m.threads.to_string())
+                    .unwrap_or_default()
+                    .into();
+                let fds: Span = metrics
+                    .map(|m| m.fds.to_string())
+                    .unwrap_or_default()
+                    .into();
+                let cpu_history: Vec<f64> = self
+                    .metrics_history
+                    .get(&service.name)
+                    .iter()
+                    .map(|m| m.cpu_percent)
+                    .collect();
+                let cpu_history: Span = sparkline(&cpu_history).into();
+
+                let mut row = Row::new(vec![
+                    name,
+                    status,
+                    pid,
+                    age,
+                    cpu,
+                    rss,
+                    threads,
+                    fds,
+                    cpu_history,
+                ]);
                 if service.status == ServiceStatus::RUNNING {
                     row = row.green();
                 }
@@ -315,10 +560,112 @@ impl ProjectPage {
 }
 
 impl ProjectPage {
+    fn render_profiles(&mut self, area: Rect, buf: &mut Buffer) {
+        let height = min(
+            max(self.profiles.len() as u16, 1) + TABLE_OVERHEAD,
+            area.height,
+        );
+        let popup = popup_area(area, min(PROFILES_POPUP_WIDTH, area.width), height, buf);
+
+        let title = format!("Profiles ({})", self.profiles.len());
+        let block = CommonBlock::new(title)
+            .set_border_color(Color::LightMagenta)
+            .add_instruction(("Start", "enter"))
+            .add_instruction(("Close", "escape"));
+
+        let rows = self
+            .profiles
+            .iter()
+            .enumerate()
+            .map(|(i, profile)| Row::new(vec![Span::from(format!("{}. {}", i + 1, profile))]))
+            .collect();
+
+        self.profiles_table.render(rows, block.into(), popup, buf);
+    }
+}
+
+impl ProjectPage {
+    fn render_tasks(&mut self, area: Rect, buf: &mut Buffer) {
+        let height = min(
+            max(self.tasks.len() as u16, 1) + TABLE_OVERHEAD,
+            area.height,
+        );
+        let popup = popup_area(area, min(TASKS_POPUP_WIDTH, area.width), height, buf);
+
+        let title = format!("Tasks ({})", self.tasks.len());
+        let block = CommonBlock::new(title)
+            .set_border_color(Color::LightMagenta)
+            .add_instruction(("Run", "r"))
+            .add_instruction(("Logs", "enter"))
+            .add_instruction(("Close", "escape"));
+
+        let rows = self
+            .tasks
+            .iter()
+            .enumerate()
+            .map(|(i, (task_name, run))| {
+                let name: Span = format!("{}. {}", i + 1, task_name).into();
+                let row = match run {
+                    Some(run) => {
+                        let status: Span = run.status.to_string().into();
+                        let exit_code: Span = run
+                            .exit_code
+                            .map(|code| code.to_string())
+                            .unwrap_or_default()
+                            .into();
+                        let age: Span = format!("{} ago", start_time_to_age(run.started_at)).into();
+                        let row = Row::new(vec![name, status, exit_code, age]);
+                        match run.status {
+                            TaskStatus::Running => row.blue(),
+                            TaskStatus::Succeeded => row.green(),
+                            TaskStatus::Failed | TaskStatus::Interrupted => row.red(),
+                        }
+                    }
+                    None => Row::new(vec![name, Span::from("Never run")]),
+                };
+
+                row
+            })
+            .collect();
+
+        self.tasks_table.render(rows, block.into(), popup, buf);
+    }
+}
+
+impl ProjectPage {
+    /// Metrics are collected in a fixed interval so the history is not affected by the key events
+    fn collect_metrics(&mut self, project: &ProjectInfo) {
+        let is_due = match self.metrics_collected_at {
+            Some(collected_at) => collected_at.elapsed() >= METRICS_INTERVAL,
+            None => true,
+        };
+        if !is_due {
+            return;
+        }
+        self.metrics_collected_at = Some(Instant::now());
+
+        let mut pids = vec![];
+        for service in project.services.iter() {
+            let metrics = match service.status {
+                ServiceStatus::RUNNING => self.metrics.collect(service.pid),
+                _ => None,
+            };
+
+            match metrics {
+                Some(metrics) => {
+                    pids.push(service.pid);
+                    self.metrics_history.push(&service.name, metrics);
+                }
+                None => self.metrics_history.clear(&service.name),
+            }
+        }
+        self.metrics.retain(&pids);
+    }
+
     fn get_filtered_services(&self) -> Vec<ServiceInfo> {
         match &self.project {
             Some(project) => match self.mode {
-                Mode::Normal => project.services.clone(),
+                Mode::Normal | Mode::Profiles | Mode::Tasks => project.services.clone(),
                 Mode::Search(_) => project
                     .services
                     .iter()
@@ -373,6 +720,10 @@ static MAX_FORCED_TABLE_LINE_COUNT: u16 = TABLE_OVERHEAD + 6;
 static MIN_FORCED_TABLE_LINE_COUNT: u16 = TABLE_OVERHEAD + 1;
 static MIN_LOGS_HEIGHT: u16 = 5;
 static SEARCH_BAR_HEIGHT: u16 = 3;
+static PROFILES_POPUP_WIDTH: u16 = 50;
+static TASKS_POPUP_WIDTH: u16 = 90;
+static METRICS_HISTORY_LENGTH: usize = 12;
+static METRICS_INTERVAL: Duration = Duration::from_millis(900);
 
 impl PageLayout {
     fn from(page: &ProjectPage, area: Rect, settings: &TuiSettings) -> Self {
@@ -384,7 +735,7 @@ impl PageLayout {
 
         let max_table_line_count = max(TABLE_OVERHEAD + service_count, MIN_FORCED_TABLE_LINE_COUNT);
 
-        let show_search = page.is_in_raw_mode();
+        let show_search = matches!(page.mode, Mode::Search(_));
 
         let table_line_display_count = match settings.log_preview {
             LogPreviewMode::On => min(MAX_FORCED_TABLE_LINE_COUNT, max_table_line_count),
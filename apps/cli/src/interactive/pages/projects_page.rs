@@ -1,7 +1,8 @@
 use std::error::Error;
 
+use app_config::{KeybindAction, KeybindScope};
 use crossterm::event::{KeyCode, KeyEvent};
-use daemon_client::{ProjectInfo, Requester};
+use daemon_client::ProjectInfo;
 use ratatui::{
     buffer::Buffer,
     layout::{Constraint, Layout, Position, Rect},
@@ -13,13 +14,18 @@ use ratatui::{
 use crate::{
     interactive::{
         components::{ActiveTable, CommonBlock, Input},
-        keybind_utils::{is_char_event, is_ctrl_alt_char_event},
+        keybind_utils::is_keybind_event,
         Action, ActionResult,
     },
     utils::start_time_to_age,
 };
 
-use super::{Page, PageContext, PageView};
+use super::{
+    clear_project_logs_message, remove_project_message, stop_project_message, Page, PageContext,
+    PageView,
+};
+
+const KEYBIND_SCOPE: KeybindScope = KeybindScope::Projects;
 
 #[derive(Debug, PartialEq)]
 enum Mode {
@@ -62,7 +68,7 @@ impl PageView for ProjectsPage {
 
     fn handle_key_event(&mut self, key_event: KeyEvent, context: PageContext) -> ActionResult {
         match self.mode {
-            Mode::Normal => self.handle_key_event(key_event, &context.requester),
+            Mode::Normal => self.handle_key_event(key_event, &context),
             Mode::Search(prev_selected) => self.handle_key_event_search(key_event, prev_selected),
         }
     }
@@ -102,42 +108,83 @@ impl PageView for ProjectsPage {
 }
 
 impl ProjectsPage {
-    fn handle_key_event(&mut self, key_event: KeyEvent, requester: &Requester) -> ActionResult {
+    fn handle_key_event(&mut self, key_event: KeyEvent, context: &PageContext) -> ActionResult {
         let selected_project = self.get_selected_project();
+        let is_bound =
+            |action| is_keybind_event(&key_event, &context.keybinds, KEYBIND_SCOPE, action);
 
-        if is_char_event(&key_event, 's') {
-            selected_project.map(|project| requester.start_project(&project.name).unwrap());
-            return Ok(Action::None);
+        if is_bound(KeybindAction::Start) {
+            return match selected_project {
+                Some(project) => {
+                    let project_name = project.name.clone();
+                    context.start_in_background(&project.name, None, move |r| {
+                        r.start_project(&project_name).map(|_| ())
+                    })
+                }
+                None => Ok(Action::None),
+            };
         }
 
-        if is_char_event(&key_event, 'd') {
-            selected_project.map(|project| requester.stop_project(&project.name).unwrap());
-            return Ok(Action::None);
+        if is_bound(KeybindAction::Stop) {
+            return match selected_project {
+                Some(project) => context.confirm(stop_project_message(&project.name), move |c| {
+                    c.requester.stop_project(&project.name)?;
+                    Ok(Action::None)
+                }),
+                None => Ok(Action::None),
+            };
         }
 
-        if is_char_event(&key_event, 'r') {
-            selected_project.map(|project| requester.restart_project(&project.name).unwrap());
-            return Ok(Action::None);
+        if is_bound(KeybindAction::Restart) {
+            return match selected_project {
+                Some(project) => {
+                    let project_name = project.name.clone();
+                    context.start_in_background(&project.name, None, move |r| {
+                        r.restart_project(&project_name).map(|_| ())
+                    })
+                }
+                None => Ok(Action::None),
+            };
         }
 
-        if is_ctrl_alt_char_event(&key_event, 'l') {
-            if let Some(project) = selected_project {
-                requester.clear_project_logs(&project.name).unwrap();
-            }
-            return Ok(Action::None);
+        if is_bound(KeybindAction::ClearLogs) {
+            return match selected_project {
+                Some(project) => {
+                    let message = clear_project_logs_message(&project.name);
+                    context.confirm(message, move |c| {
+                        c.requester.clear_project_logs(&project.name)?;
+                        Ok(Action::None)
+                    })
+                }
+                None => Ok(Action::None),
+            };
+        }
+
+        if is_bound(KeybindAction::Remove) {
+            return match selected_project {
+                Some(project) => context.confirm(remove_project_message(&project.name), move |c| {
+                    c.requester.remove_project(&project.name)?;
+                    Ok(Action::None)
+                }),
+                None => Ok(Action::None),
+            };
+        }
+
+        if is_bound(KeybindAction::Reload) {
+            return match selected_project {
+                Some(project) => context.reload_project(&project.name),
+                None => Ok(Action::None),
+            };
         }
 
-        if key_event.code == KeyCode::Enter
-            || key_event.code == KeyCode::Right
-            || is_char_event(&key_event, 'l')
-        {
+        if is_bound(KeybindAction::Open) {
             return match selected_project {
                 Some(project) => Ok(Action::GotoPage(Page::Project(project.name))),
                 None => Ok(Action::None),
             };
         }
 
-        if is_char_event(&key_event, '/') {
+        if is_bound(KeybindAction::Search) {
             self.mode = Mode::Search(self.table.selected());
             return Ok(Action::None);
         }
@@ -193,9 +240,18 @@ impl ProjectsPage {
         let block = CommonBlock::new(title)
             .add_top_info(context.settings.to_info())
             .set_border_color(Color::LightYellow)
-            .add_instruction(("Show keybinds", "tab"))
-            .add_instruction(("Start", "s"))
-            .add_instruction(("Stop", "d"));
+            .add_keybind_instructions(
+                &context.keybinds,
+                &[
+                    (
+                        "Show keybinds",
+                        KeybindScope::Global,
+                        KeybindAction::ShowKeybinds,
+                    ),
+                    ("Start", KEYBIND_SCOPE, KeybindAction::Start),
+                    ("Stop", KEYBIND_SCOPE, KeybindAction::Stop),
+                ],
+            );
 
         let rows = self
             .get_filtered_projects()
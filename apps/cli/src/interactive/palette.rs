@@ -0,0 +1,202 @@
+use daemon_client::ProjectInfo;
+
+use super::{
+    pages::{
+        clear_project_logs_message, clear_service_logs_message, remove_project_message,
+        stop_project_message, Page, PageContext,
+    },
+    Action, ActionResult,
+};
+
+/// Action offered in the command palette, actions run through the same requests as the keybinds
+#[derive(Debug, Clone)]
+pub enum PaletteAction {
+    GotoProjects,
+    GotoProject(String),
+    StartProject(String),
+    StopProject(String),
+    RestartProject(String),
+    ReloadProject(String),
+    RemoveProject(String),
+    ClearProjectLogs(String),
+    StartService(String, String),
+    StopService(String, String),
+    RestartService(String, String),
+    ClearServiceLogs(String, String),
+    OpenServiceLogs(String),
+    UseContext(String),
+    ToggleLogPreview,
+    ShowKeybinds,
+    Quit,
+}
+
+/// Creates entries for all projects and their services, entries of the current project are listed first,
+/// the other contexts can be switched to
+pub fn palette_entries(
+    projects: Vec<ProjectInfo>,
+    current_project: Option<&str>,
+    other_contexts: Vec<String>,
+) -> Vec<(String, PaletteAction)> {
+    let mut entries = vec![];
+
+    let (current, others): (Vec<ProjectInfo>, Vec<ProjectInfo>) = projects
+        .into_iter()
+        .partition(|project| Some(project.name.as_str()) == current_project);
+
+    for project in current.into_iter().chain(others) {
+        let name = &project.name;
+        entries.extend([
+            (
+                format!("goto project {}", name),
+                PaletteAction::GotoProject(name.clone()),
+            ),
+            (
+                format!("start project {}", name),
+                PaletteAction::StartProject(name.clone()),
+            ),
+            (
+                format!("stop project {}", name),
+                PaletteAction::StopProject(name.clone()),
+            ),
+            (
+                format!("restart project {}", name),
+                PaletteAction::RestartProject(name.clone()),
+            ),
+            (
+                format!("reload project {}", name),
+                PaletteAction::ReloadProject(name.clone()),
+            ),
+            (
+                format!("clear logs {}", name),
+                PaletteAction::ClearProjectLogs(name.clone()),
+            ),
+            (
+                format!("remove project {}", name),
+                PaletteAction::RemoveProject(name.clone()),
+            ),
+        ]);
+
+        for service in project.services.iter() {
+            let service_ref = format!("{}/{}", name, service.name);
+            let pair = (name.clone(), service.name.clone());
+            entries.extend([
+                (
+                    format!("start {}", service_ref),
+                    PaletteAction::StartService(pair.0.clone(), pair.1.clone()),
+                ),
+                (
+                    format!("stop {}", service_ref),
+                    PaletteAction::StopService(pair.0.clone(), pair.1.clone()),
+                ),
+                (
+                    format!("restart {}", service_ref),
+                    PaletteAction::RestartService(pair.0.clone(), pair.1.clone()),
+                ),
+                (
+                    format!("clear logs {}", service_ref),
+                    PaletteAction::ClearServiceLogs(pair.0, pair.1),
+                ),
+                (
+                    format!("open logs {}", service_ref),
+                    PaletteAction::OpenServiceLogs(service.logfile_path.clone()),
+                ),
+            ]);
+        }
+    }
+
+    for context in other_contexts {
+        entries.push((
+            format!("use context {}", context),
+            PaletteAction::UseContext(context),
+        ));
+    }
+
+    entries.extend([
+        (String::from("goto projects"), PaletteAction::GotoProjects),
+        (
+            String::from("toggle log preview"),
+            PaletteAction::ToggleLogPreview,
+        ),
+        (String::from("show keybinds"), PaletteAction::ShowKeybinds),
+        (String::from("quit"), PaletteAction::Quit),
+    ]);
+
+    entries
+}
+
+impl PaletteAction {
+    /// Message of the confirmation asked before running destructive actions
+    pub fn confirmation_message(&self) -> Option<String> {
+        match self {
+            PaletteAction::StopProject(project) => Some(stop_project_message(project)),
+            PaletteAction::RemoveProject(project) => Some(remove_project_message(project)),
+            PaletteAction::ClearProjectLogs(project) => Some(clear_project_logs_message(project)),
+            PaletteAction::ClearServiceLogs(project, service) => {
+                Some(clear_service_logs_message(project, service))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Runs actions which only call the daemon, other actions are handled by the app,
+/// starts are run in the background
+pub fn run_request_action(action: &PaletteAction, context: &PageContext) -> ActionResult {
+    let requester = &context.requester;
+
+    match action {
+        PaletteAction::StartProject(project) => {
+            let project_name = project.clone();
+            return context.start_in_background(project, None, move |r| {
+                r.start_project(&project_name).map(|_| ())
+            });
+        }
+        PaletteAction::StopProject(project) => {
+            requester.stop_project(project)?;
+        }
+        PaletteAction::RestartProject(project) => {
+            let project_name = project.clone();
+            return context.start_in_background(project, None, move |r| {
+                r.restart_project(&project_name).map(|_| ())
+            });
+        }
+        PaletteAction::ReloadProject(project) => return context.reload_project(project),
+        PaletteAction::RemoveProject(project) => requester.remove_project(project)?,
+        PaletteAction::ClearProjectLogs(project) => requester.clear_project_logs(project)?,
+        PaletteAction::StartService(project, service) => {
+            let (project_name, service_name) = (project.clone(), service.clone());
+            return context.start_in_background(project, Some(vec![service.clone()]), move |r| {
+                r.start_service(&project_name, &service_name).map(|_| ())
+            });
+        }
+        PaletteAction::StopService(project, service) => {
+            requester.stop_service(project, service)?;
+        }
+        PaletteAction::RestartService(project, service) => {
+            let (project_name, service_name) = (project.clone(), service.clone());
+            return context.start_in_background(project, Some(vec![service.clone()]), move |r| {
+                r.restart_service(&project_name, &service_name).map(|_| ())
+            });
+        }
+        PaletteAction::ClearServiceLogs(project, service) => {
+            requester.clear_service_logs(project, service)?
+        }
+        PaletteAction::GotoProjects
+        | PaletteAction::GotoProject(_)
+        | PaletteAction::OpenServiceLogs(_)
+        | PaletteAction::UseContext(_)
+        | PaletteAction::ToggleLogPreview
+        | PaletteAction::ShowKeybinds
+        | PaletteAction::Quit => {}
+    }
+
+    Ok(Action::None)
+}
+
+pub fn current_project(page: &Page) -> Option<&str> {
+    match page {
+        Page::Project(project) => Some(project),
+        Page::Keybinds(page) => current_project(page),
+        Page::Projects => None,
+    }
+}
@@ -3,14 +3,16 @@ use app_config::LogPreviewMode;
 #[derive(Debug, Clone)]
 pub struct TuiSettings {
     pub log_preview: LogPreviewMode,
+    pub context: String,
 }
 
 impl TuiSettings {
     pub fn to_info(&self) -> String {
-        match self.log_preview {
-            LogPreviewMode::On => String::from("Log preview: ON"),
-            LogPreviewMode::Off => String::from("Log preview: OFF"),
-            LogPreviewMode::Fit => String::from("Log preview: FIT"),
-        }
+        let log_preview = match self.log_preview {
+            LogPreviewMode::On => "ON",
+            LogPreviewMode::Off => "OFF",
+            LogPreviewMode::Fit => "FIT",
+        };
+        format!("Context: {} | Log preview: {}", self.context, log_preview)
     }
 }
@@ -1,35 +1,126 @@
-use app_config::AppConfig;
-use clap::{Parser, Subcommand};
-use daemon_client::{Requester, SocketClient};
+use app_config::{AppConfig, ConfigOverrides, LayeredConfig};
+use bundle::{export_bundle, import_bundle, ExportOptions};
+use clap::{CommandFactory, Parser, Subcommand};
+use completions::{complete, completion_script, CompletionShell};
+use config::{list_contexts, show_config, use_context};
+use daemon_client::Requester;
+use import::{import_to_daemon, import_to_file, ImportSource};
+use init::{init_project, InitFormat, InitOptions};
 use interactive::interact;
-use output::Output;
-use process::execute_tail;
-use project_settings::ProjectSettings;
+use metrics::{serve_metrics, MetricsOptions};
+use output::{Output, Wide};
+use port_registry::{check_ports, list_ports};
+use process::{execute_command, execute_tail};
+use project_settings::{ProjectSettings, ProjectSettingsError};
+use std::{net::SocketAddr, time::Duration};
+use task_runner::TaskRunner;
+use utils::{create_socket_client, parse_duration};
+use wait::{wait_for, WaitOptions, WaitState};
 
+mod bundle;
+mod completions;
+mod config;
+mod detect;
+mod import;
+mod init;
 mod interactive;
+mod metrics;
 mod output;
 mod process;
 mod utils;
+mod wait;
 
 /// Simple process manager
 #[derive(Debug, Parser)]
 #[command(version)]
 struct Cli {
+    /// path to the socket of the daemon, overrides the configured one
+    #[clap(long, global = true)]
+    socket: Option<String>,
+    /// path to a config file (json or toml) applied over the other config files and env variables
+    #[clap(long, global = true)]
+    config: Option<String>,
+    /// name of the context whose daemon should be used instead of the current one
+    #[clap(long, global = true)]
+    context: Option<String>,
     #[command(subcommand)]
     command: Command,
 }
 
 #[derive(Debug, Subcommand)]
 enum Command {
+    #[command(flatten)]
+    Daemon(DaemonCommand),
+    /// Create the settings file in the current dir from detected project files
+    Init {
+        /// accept the suggested services and defaults without asking
+        #[clap(long, short, action)]
+        yes: bool,
+        /// overwrite existing settings file
+        #[clap(long, short, action)]
+        force: bool,
+        /// format of the created settings file
+        #[clap(long, value_enum)]
+        format: Option<InitFormat>,
+    },
+    /// Create projects from a bundle made by 'export', or a project from a Procfile or a docker compose file
+    ///
+    /// Parts of Procfile and docker compose files which cannot be converted are reported as warnings
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+    Import {
+        #[command(subcommand)]
+        source: Option<ImportSource>,
+        /// path to the bundle file
+        #[clap(required = true)]
+        bundle: Option<String>,
+        /// directory the relative paths of the bundled projects are resolved against, defaults to current dir
+        #[clap(long)]
+        root: Option<String>,
+    },
+    /// List the daemon contexts or switch the current one
+    Context {
+        #[command(subcommand)]
+        command: ContextCommand,
+    },
+    /// Inspect the effective configuration
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+    /// Print the shell completion script, e.g. 'source <(concc completions bash)'
+    Completions {
+        /// shell to generate the script for
+        #[clap(value_enum)]
+        shell: CompletionShell,
+    },
+    /// Print candidates for the completed word, used by the completion scripts
+    #[command(name = "__complete", hide = true)]
+    Complete {
+        /// index of the completed word
+        index: usize,
+        /// all words of the command line including the program name
+        #[clap(allow_hyphen_values = true, trailing_var_arg = true)]
+        words: Vec<String>,
+    },
+}
+
+/// Commands which are sent to the daemon
+#[derive(Debug, Subcommand)]
+enum DaemonCommand {
     /// Opens a interactive mode
     #[clap(visible_alias("i"))]
     Interactive,
     /// Create new project or replaces existing
     #[clap(visible_alias("up"))]
     Upsert {
-        /// path to the settings file or directory containing the settings file (conc.json), defaults to current dir
+        /// path to the settings file or directory containing the settings file (conc.json or conc.toml), defaults to current dir
         settings_path: Option<String>,
     },
+    /// Read the settings of a project again from the file it was upserted from and replace the project
+    Reload {
+        /// name of the project
+        project: String,
+    },
     /// Get space delimited list of all project
     Projects,
     /// Get space delimited list of all service under project
@@ -43,6 +134,9 @@ enum Command {
         project: Option<String>,
         /// name of the service
         service: Option<String>,
+        /// show resource usage of the running services, CPU usage is averaged over their lifetime
+        #[clap(long, short, action)]
+        wide: bool,
     },
     /// Open logs for a project or a service in 'tail -f' command
     Logs {
@@ -54,12 +148,15 @@ enum Command {
         #[clap(long, short, action)]
         raw: bool,
     },
-    /// Start a project or a service
+    /// Start a project, a service or a profile of a project
     Start {
         /// name of the project
         project: String,
         /// name of the service
         service: Option<String>,
+        /// name of the profile whose services should be started
+        #[clap(long, short, conflicts_with = "service")]
+        profile: Option<String>,
     },
     /// Restart a project or a service
     Restart {
@@ -75,6 +172,24 @@ enum Command {
         /// name of the service
         service: Option<String>,
     },
+    /// Wait until a project or a service reaches the provided state
+    ///
+    /// Exits with 0 on success, 2 on timeout and 3 when a service exits while waiting for it to run
+    Wait {
+        /// name of the project
+        project: String,
+        /// name of the service
+        service: Option<String>,
+        /// state to wait for
+        #[clap(long = "for", value_enum, default_value_t = WaitState::Running)]
+        state: WaitState,
+        /// maximal time to wait for, e.g. '500ms', '30s', '5m'
+        #[clap(long, short, default_value = "30s", value_parser = parse_duration)]
+        timeout: Duration,
+        /// regex that has to match a line in the log of every awaited service, only lines logged after the wait started are checked
+        #[clap(long)]
+        log_match: Option<String>,
+    },
     /// Clear logs of a project or a service
     #[clap(visible_alias("cl"))]
     ClearLogs {
@@ -83,11 +198,48 @@ enum Command {
         /// name of the service
         service: Option<String>,
     },
+    /// Run a one-off command with the working directory and environment of a project or its service
+    Exec {
+        /// name of the project
+        project: String,
+        /// name of the service whose context should be used
+        #[clap(long, short)]
+        service: Option<String>,
+        /// command to run, separated from the other arguments by '--'
+        #[clap(last = true, required = true)]
+        command: Vec<String>,
+    },
+    /// Run and inspect one-shot tasks of a project
+    Task {
+        #[command(subcommand)]
+        command: TaskCommand,
+    },
+    /// List ports declared by the services and ports bound by the running services
+    Ports,
+    /// Export metrics of the services for monitoring systems
+    Metrics {
+        #[command(subcommand)]
+        command: MetricsCommand,
+    },
     /// Get project settings
     Settings {
         /// name of the project
         project: String,
     },
+    /// Export settings of projects, or of all projects when none is specified, as a bundle for 'import'
+    Export {
+        /// names of the projects
+        projects: Vec<String>,
+        /// path to the bundle file, the bundle is printed to stdout when not specified
+        #[clap(long, short)]
+        out: Option<String>,
+        /// include status of the services at the time of the export
+        #[clap(long, short, action)]
+        status: bool,
+        /// directory the paths of the projects inside of it are stored relative to, defaults to current dir
+        #[clap(long)]
+        root: Option<String>,
+    },
     /// Remove a project
     #[clap(visible_alias("rm"))]
     Remove {
@@ -96,6 +248,60 @@ enum Command {
     },
 }
 
+#[derive(Debug, Subcommand)]
+enum ContextCommand {
+    /// List all contexts, the current one is marked with '*'
+    #[clap(visible_alias("ls"))]
+    List,
+    /// Set the current context in the user config file
+    Use {
+        /// name of the context
+        name: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ConfigCommand {
+    /// Print the effective value of every config option
+    Show {
+        /// print the layer every value was read from
+        #[clap(long, action)]
+        origin: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum MetricsCommand {
+    /// Poll the daemon and serve the metrics in the Prometheus text format on '/metrics'
+    ///
+    /// Restarts are counted from the start of the exporter, CPU and memory are reported for the running services
+    Serve {
+        /// address to listen on
+        #[clap(long, default_value = "127.0.0.1:9464")]
+        listen: SocketAddr,
+        /// how often the daemon is polled, e.g. '500ms', '5s', '1m'
+        #[clap(long, short, default_value = "5s", value_parser = parse_duration)]
+        interval: Duration,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum TaskCommand {
+    /// Run a task and wait for it to finish
+    Run {
+        /// name of the project
+        project: String,
+        /// name of the task
+        task: String,
+    },
+    /// Get status of the last run of all tasks in a project
+    #[clap(visible_alias("ls"))]
+    List {
+        /// name of the project
+        project: String,
+    },
+}
+
 fn main() {
     match run() {
         Output::Stdout(res) => {
@@ -108,90 +314,313 @@ fn main() {
             eprintln!("{}", res);
             std::process::exit(-1);
         }
+        Output::Status(code, res) => {
+            if !res.is_empty() {
+                eprintln!("{}", res);
+            }
+            std::process::exit(code);
+        }
     }
 }
 
 fn run() -> Output {
-    let config = match AppConfig::new() {
-        Ok(config) => config,
+    let cli = Cli::parse();
+
+    let overrides = ConfigOverrides {
+        config_path: cli.config.clone(),
+        socket_path: cli.socket.clone(),
+        context: cli.context.clone(),
+    };
+    let layered = match LayeredConfig::load(&overrides) {
+        Ok(layered) => layered,
         Err(err) => return err.into(),
     };
+    let config = layered.config.clone();
+    // the interactive mode takes over the terminal, so it shows the warnings itself
+    let mut warnings = layered.warnings.clone();
+    if !matches!(cli.command, Command::Daemon(DaemonCommand::Interactive)) {
+        for warning in warnings.drain(..) {
+            eprintln!("Warning: {}", warning);
+        }
+    }
 
-    let cli = Cli::parse();
+    match cli.command {
+        Command::Daemon(command) => connect(&config)
+            .map(|requester| run_daemon_command(command, requester, config, warnings))
+            .unwrap_or_else(|err| err),
+
+        Command::Init { yes, force, format } => init_project(InitOptions { yes, force, format }),
+
+        Command::Import {
+            source: Some(source),
+            ..
+        } if source.args().write => import_to_file(&source),
+
+        Command::Import {
+            source: Some(source),
+            ..
+        } => connect(&config)
+            .map(|requester| import_to_daemon(&source, &requester))
+            .unwrap_or_else(|err| err),
 
-    let socket_client = SocketClient::new(&config.daemon_socket_path);
-    if !socket_client.is_alive() {
-        return Output::socket_not_alive(&socket_client.socket_path);
+        Command::Import {
+            source: None,
+            bundle: Some(bundle),
+            root,
+        } => connect(&config)
+            .map(|requester| import_bundle(&requester, &bundle, root.as_deref()))
+            .unwrap_or_else(|err| err),
+
+        Command::Import {
+            source: None,
+            bundle: None,
+            ..
+        } => Output::Stderr(String::from(
+            "Path to a bundle or a source to import from is required",
+        )),
+
+        Command::Context {
+            command: ContextCommand::List,
+        } => list_contexts(&config),
+
+        Command::Context {
+            command: ContextCommand::Use { name },
+        } => use_context(&name),
+
+        Command::Config {
+            command: ConfigCommand::Show { origin },
+        } => show_config(&layered, origin),
+
+        Command::Completions { shell } => completion_script(shell, Cli::command()),
+
+        Command::Complete { index, words } => {
+            let socket_client = create_socket_client(&config);
+            let requester = socket_client
+                .is_alive()
+                .then(|| Requester::new(socket_client, config.use_caller_env));
+            complete(requester.as_ref(), Cli::command(), index, &words)
+        }
     }
-    let requester = Requester::new(socket_client, config.use_caller_env);
+}
 
-    match cli.command {
-        Command::Interactive => interact(requester, config).into(),
+/// Connects to the daemon of the current context
+fn connect(config: &AppConfig) -> Result<Requester, Output> {
+    let socket_client = create_socket_client(config);
+    if let Err(err) = socket_client.health_check() {
+        return Err(Output::socket_not_alive(&socket_client, &err));
+    }
+    Ok(Requester::new(socket_client, config.use_caller_env))
+}
+
+fn run_daemon_command(
+    command: DaemonCommand,
+    requester: Requester,
+    config: AppConfig,
+    config_warnings: Vec<String>,
+) -> Output {
+    let task_runner = TaskRunner::new(&config.tasks_dir, config.use_caller_env);
 
-        Command::Projects => requester.get_project_names().into(),
+    match command {
+        DaemonCommand::Interactive => interact(requester, config, config_warnings).into(),
 
-        Command::Services { project } => requester.get_service_names(&project).into(),
+        DaemonCommand::Projects => requester.get_project_names().into(),
 
-        Command::Ps {
+        DaemonCommand::Services { project } => requester.get_service_names(&project).into(),
+
+        DaemonCommand::Ps {
             project: Some(project),
             service: Some(service),
-        } => requester.get_services_info(&project, &service).into(),
+            wide,
+        } => match wide {
+            true => requester
+                .get_services_info(&project, &service)
+                .map(Wide)
+                .into(),
+            false => requester.get_services_info(&project, &service).into(),
+        },
 
-        Command::Ps {
+        DaemonCommand::Ps {
             project: Some(project),
             service: None,
-        } => requester.get_project_info(&project).into(),
+            wide,
+        } => match wide {
+            true => requester.get_project_info(&project).map(Wide).into(),
+            false => requester.get_project_info(&project).into(),
+        },
 
-        Command::Ps {
+        DaemonCommand::Ps {
             project: None,
             service: _,
-        } => requester.get_projects_info().into(),
+            wide,
+        } => match wide {
+            true => requester.get_projects_info().map(Wide).into(),
+            false => requester.get_projects_info().into(),
+        },
 
-        Command::Start {
+        DaemonCommand::Start {
             project,
             service: Some(service),
-        } => requester.start_service(&project, &service).into(),
+            profile: _,
+        } => prepare_start(
+            &requester,
+            &task_runner,
+            &project,
+            Some(std::slice::from_ref(&service)),
+        )
+        .map(|_| requester.start_service(&project, &service).into())
+        .unwrap_or_else(|err| err),
+
+        DaemonCommand::Start {
+            project,
+            service: None,
+            profile: Some(profile),
+        } => requester
+            .get_project_settings(&project)
+            .map_err(Output::from)
+            .and_then(|json| {
+                ProjectSettings::try_from(json.as_str())
+                    .and_then(|settings| settings.profile_services(&profile))
+                    .map_err(Output::from)
+            })
+            .and_then(|services| {
+                prepare_start(&requester, &task_runner, &project, Some(&services))?;
+                Ok(requester.start_project_services(&project, &services).into())
+            })
+            .unwrap_or_else(|err| err),
 
-        Command::Start {
+        DaemonCommand::Start {
             project,
             service: None,
-        } => requester.start_project(&project).into(),
+            profile: None,
+        } => prepare_start(&requester, &task_runner, &project, None)
+            .map(|_| requester.start_project(&project).into())
+            .unwrap_or_else(|err| err),
 
-        Command::Restart {
+        DaemonCommand::Restart {
             project,
             service: Some(service),
-        } => requester.restart_service(&project, &service).into(),
+        } => prepare_start(
+            &requester,
+            &task_runner,
+            &project,
+            Some(std::slice::from_ref(&service)),
+        )
+        .map(|_| requester.restart_service(&project, &service).into())
+        .unwrap_or_else(|err| err),
 
-        Command::Restart {
+        DaemonCommand::Restart {
             project,
             service: None,
-        } => requester.restart_project(&project).into(),
+        } => prepare_start(&requester, &task_runner, &project, None)
+            .map(|_| requester.restart_project(&project).into())
+            .unwrap_or_else(|err| err),
 
-        Command::Stop {
+        DaemonCommand::Stop {
             project,
             service: Some(service),
         } => requester.stop_service(&project, &service).into(),
 
-        Command::Stop {
+        DaemonCommand::Stop {
             project,
             service: None,
         } => requester.stop_project(&project).into(),
 
-        Command::Remove { project } => requester.remove_project(&project).into(),
+        DaemonCommand::Wait {
+            project,
+            service,
+            state,
+            timeout,
+            log_match,
+        } => match WaitOptions::new(state, timeout, log_match) {
+            Ok(options) => wait_for(&requester, &project, service.as_deref(), options),
+            Err(err) => Output::Stderr(err),
+        },
+
+        DaemonCommand::Remove { project } => requester.remove_project(&project).into(),
 
-        Command::ClearLogs {
+        DaemonCommand::ClearLogs {
             project,
             service: None,
         } => requester.clear_project_logs(&project).into(),
 
-        Command::ClearLogs {
+        DaemonCommand::ClearLogs {
             project,
             service: Some(service),
         } => requester.clear_service_logs(&project, &service).into(),
 
-        Command::Settings { project } => requester.get_project_settings(&project).into(),
+        DaemonCommand::Exec {
+            project,
+            service,
+            command,
+        } => requester
+            .get_project_settings(&project)
+            .map_err(Output::from)
+            .and_then(|json| {
+                ProjectSettings::try_from(json.as_str())
+                    .and_then(|settings| settings.execution_context(service.as_deref()))
+                    .map_err(Output::from)
+            })
+            .map(|context| {
+                let program = command.first().cloned().unwrap_or_default();
+                let error =
+                    execute_command(command, context.pwd, context.env, config.use_caller_env);
+                Output::Stderr(format!("Unable to execute '{}': {}", program, error))
+            })
+            .unwrap_or_else(|err| err),
+
+        DaemonCommand::Task {
+            command: TaskCommand::Run { project, task },
+        } => requester
+            .get_project_settings(&project)
+            .map_err(Output::from)
+            .and_then(|json| ProjectSettings::try_from(json.as_str()).map_err(Output::from))
+            .map(|settings| task_runner.run(&settings, &task).into())
+            .unwrap_or_else(|err| err),
+
+        DaemonCommand::Task {
+            command: TaskCommand::List { project },
+        } => requester
+            .get_project_settings(&project)
+            .map_err(Output::from)
+            .and_then(|json| ProjectSettings::try_from(json.as_str()).map_err(Output::from))
+            .map(|settings| {
+                settings
+                    .tasks
+                    .iter()
+                    .map(|task| {
+                        task_runner
+                            .last_run(&project, &task.name)
+                            .map(|run| (task.name.clone(), run))
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+                    .into()
+            })
+            .unwrap_or_else(|err| err),
 
-        Command::Upsert { settings_path } => {
+        DaemonCommand::Ports => list_ports(&requester).into(),
+
+        DaemonCommand::Metrics {
+            command: MetricsCommand::Serve { listen, interval },
+        } => serve_metrics(requester, MetricsOptions { listen, interval }),
+
+        DaemonCommand::Settings { project } => requester.get_project_settings(&project).into(),
+
+        DaemonCommand::Export {
+            projects,
+            out,
+            status,
+            root,
+        } => export_bundle(
+            &requester,
+            ExportOptions {
+                projects,
+                out,
+                status,
+                root,
+            },
+        ),
+
+        DaemonCommand::Upsert { settings_path } => {
             let settings = ProjectSettings::find_parse_and_populate(settings_path)
                 .and_then(|settings| String::try_from(&settings));
 
@@ -201,7 +630,25 @@ fn run() -> Output {
             }
         }
 
-        Command::Logs {
+        DaemonCommand::Reload { project } => {
+            let settings = requester.get_project_settings(&project).map(|json| {
+                ProjectSettings::try_from(json.as_str())
+                    .and_then(|settings| settings.reload())
+                    .and_then(|settings| String::try_from(&settings))
+            });
+
+            match settings {
+                Ok(Ok(json)) => requester.upsert_project(&json).into(),
+                Ok(Err(err @ ProjectSettingsError::SourceNotFound { .. })) => Output::Status(
+                    0,
+                    format!("Warning: {}, the project was left unchanged", err),
+                ),
+                Ok(Err(err)) => err.into(),
+                Err(err) => err.into(),
+            }
+        }
+
+        DaemonCommand::Logs {
             project,
             service,
             raw,
@@ -227,3 +674,15 @@ fn run() -> Output {
         .unwrap_or_else(|err| err.into()),
     }
 }
+
+/// Checks that the declared ports are free and runs the 'before_start' tasks of the services about to be started
+fn prepare_start(
+    requester: &Requester,
+    task_runner: &TaskRunner,
+    project_name: &str,
+    service_names: Option<&[String]>,
+) -> Result<(), Output> {
+    check_ports(requester, project_name, service_names)?;
+    task_runner.run_before_start(requester, project_name, service_names)?;
+    Ok(())
+}
@@ -0,0 +1,264 @@
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use daemon_client::{ProjectInfo, Requester, ServiceStatus};
+use process_metrics::collect_once;
+use tiny_http::{Header, Method, Response, Server};
+
+use crate::output::Output;
+
+/// Name, type, help and value of a metric, services without the value are skipped
+type Family = (
+    &'static str,
+    &'static str,
+    &'static str,
+    fn(&Sample) -> Option<String>,
+);
+
+pub struct MetricsOptions {
+    pub listen: SocketAddr,
+    pub interval: Duration,
+}
+
+/// State of a service at the previous poll, a start is detected by the change of the pid or the start time
+/// of the running service, the start time catches a pid reused by the new process
+struct ObservedService {
+    run: Option<(i32, u64)>,
+    restarts: u64,
+}
+
+struct Sample {
+    project: String,
+    service: String,
+    up: bool,
+    start_time: u64,
+    restarts: u64,
+    cpu_seconds: Option<f64>,
+    rss_bytes: Option<u64>,
+}
+
+/// Services seen by the exporter, restarts are counted from the first poll of the exporter
+#[derive(Default)]
+struct Observer {
+    services: HashMap<(String, String), ObservedService>,
+}
+
+impl Observer {
+    fn observe(&mut self, projects: Vec<ProjectInfo>) -> Vec<Sample> {
+        let mut samples = vec![];
+        let mut services = HashMap::new();
+
+        for project in projects {
+            for service in project.services {
+                let key = (project.name.clone(), service.name.clone());
+                let up = service.status == ServiceStatus::RUNNING;
+                let run = up.then_some((service.pid, service.start_time));
+
+                let restarts = match self.services.remove(&key) {
+                    Some(previous) if run.is_some() && previous.run != run => previous.restarts + 1,
+                    Some(previous) => previous.restarts,
+                    None => 0,
+                };
+                // services are started in their own session so the pid is the id of their process group
+                let metrics = run.and_then(|(pid, _)| collect_once(pid));
+
+                samples.push(Sample {
+                    project: project.name.clone(),
+                    service: service.name,
+                    up,
+                    start_time: service.start_time,
+                    restarts,
+                    cpu_seconds: metrics.map(|m| m.cpu_seconds),
+                    rss_bytes: metrics.map(|m| m.rss_bytes),
+                });
+                services.insert(key, ObservedService { run, restarts });
+            }
+        }
+
+        // removed services are forgotten, their counters start from zero when they are added again
+        self.services = services;
+        samples
+    }
+}
+
+/// Polls the daemon and serves the metrics in the Prometheus text format on '/metrics' until the process is killed
+pub fn serve_metrics(requester: Requester, options: MetricsOptions) -> Output {
+    let server = match Server::http(options.listen) {
+        Ok(server) => server,
+        Err(err) => {
+            return Output::Stderr(format!("Unable to listen on '{}': {}", options.listen, err))
+        }
+    };
+    let address = server
+        .server_addr()
+        .to_ip()
+        .map(|addr| addr.to_string())
+        .unwrap_or_default();
+
+    let exposition = Arc::new(Mutex::new(String::new()));
+    let mut observer = Observer::default();
+    let mut poll = {
+        let exposition = exposition.clone();
+        move || {
+            let text = match requester.get_projects_info() {
+                Ok(projects) => render(true, &observer.observe(projects)),
+                Err(_) => render(false, &[]),
+            };
+            *exposition.lock().unwrap_or_else(|err| err.into_inner()) = text;
+        }
+    };
+    // the first scrape already has the metrics
+    poll();
+    thread::spawn(move || loop {
+        thread::sleep(options.interval);
+        poll();
+    });
+
+    println!("Serving metrics on http://{}/metrics", address);
+
+    let content_type = Header::from_bytes("Content-Type", "text/plain; version=0.0.4").unwrap();
+    for request in server.incoming_requests() {
+        let response = match (request.method(), request.url()) {
+            (Method::Get, "/metrics") => {
+                let text = exposition
+                    .lock()
+                    .unwrap_or_else(|err| err.into_inner())
+                    .clone();
+                Response::from_string(text).with_header(content_type.clone())
+            }
+            _ => Response::from_string("Metrics are served on '/metrics'.\n").with_status_code(404),
+        };
+        let _ = request.respond(response);
+    }
+
+    Output::Stdout(String::new())
+}
+
+fn render(daemon_up: bool, samples: &[Sample]) -> String {
+    let mut text = String::new();
+
+    header(
+        &mut text,
+        "conc_daemon_up",
+        "gauge",
+        "Whether the last poll of the daemon succeeded.",
+    );
+    let _ = writeln!(text, "conc_daemon_up {}", daemon_up as u8);
+
+    let families: [Family; 5] = [
+        (
+            "conc_service_up",
+            "gauge",
+            "Whether the service is running.",
+            |s| Some((s.up as u8).to_string()),
+        ),
+        (
+            "conc_service_start_time_seconds",
+            "gauge",
+            "Unix time of the last start of the service, 0 when it was never started.",
+            |s| Some(s.start_time.to_string()),
+        ),
+        (
+            "conc_service_restarts_total",
+            "counter",
+            "Starts of the service observed since the exporter was started.",
+            |s| Some(s.restarts.to_string()),
+        ),
+        (
+            "conc_service_cpu_seconds",
+            "gauge",
+            "CPU time used by the living processes of the running service, it drops when a process exits.",
+            |s| s.cpu_seconds.map(|value| format!("{:.2}", value)),
+        ),
+        (
+            "conc_service_resident_memory_bytes",
+            "gauge",
+            "Resident memory of the processes of the running service.",
+            |s| s.rss_bytes.map(|value| value.to_string()),
+        ),
+    ];
+
+    for (name, kind, help, value) in families {
+        header(&mut text, name, kind, help);
+        for sample in samples {
+            if let Some(value) = value(sample) {
+                let _ = writeln!(
+                    text,
+                    "{}{{project=\"{}\",service=\"{}\"}} {}",
+                    name,
+                    escape_label(&sample.project),
+                    escape_label(&sample.service),
+                    value
+                );
+            }
+        }
+    }
+
+    text
+}
+
+fn header(text: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(text, "# HELP {} {}", name, help);
+    let _ = writeln!(text, "# TYPE {} {}", name, kind);
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use daemon_client::ServiceInfo;
+
+    use super::*;
+
+    fn projects(status: ServiceStatus, pid: i32, start_time: u64) -> Vec<ProjectInfo> {
+        vec![ProjectInfo {
+            name: String::from("shop"),
+            services: vec![ServiceInfo {
+                name: String::from("api"),
+                status,
+                pid,
+                start_time,
+                stop_time: 0,
+                logfile_path: String::new(),
+            }],
+        }]
+    }
+
+    fn restarts(observer: &mut Observer, status: ServiceStatus, pid: i32, start_time: u64) -> u64 {
+        observer.observe(projects(status, pid, start_time))[0].restarts
+    }
+
+    #[test]
+    fn restart_with_a_reused_pid_is_counted() {
+        let mut observer = Observer::default();
+
+        assert_eq!(
+            restarts(&mut observer, ServiceStatus::RUNNING, i32::MAX, 100),
+            0
+        );
+        assert_eq!(
+            restarts(&mut observer, ServiceStatus::RUNNING, i32::MAX, 100),
+            0
+        );
+        assert_eq!(
+            restarts(&mut observer, ServiceStatus::RUNNING, i32::MAX, 200),
+            1
+        );
+        assert_eq!(restarts(&mut observer, ServiceStatus::STOPPED, 0, 200), 1);
+        assert_eq!(
+            restarts(&mut observer, ServiceStatus::RUNNING, i32::MAX, 200),
+            2
+        );
+    }
+}
@@ -1,20 +1,30 @@
 use app_config::AppConfigError;
-use daemon_client::{ErrorResponse, ProjectInfo, ServiceInfo, ServiceStatus};
+use daemon_client::{ErrorResponse, ProjectInfo, ServiceInfo, ServiceStatus, SocketClient};
+use port_registry::{PortEntry, PortRegistryError};
+use process_metrics::collect_once;
 use project_settings::ProjectSettingsError;
-use std::{error::Error, vec};
+use std::{error::Error, io, vec};
+use task_runner::{TaskRun, TaskRunnerError, TaskStatus};
 
 use crate::utils::start_time_to_age;
 
 pub enum Output {
     Stdout(String),
     Stderr(String),
+    /// Output printed to stderr with a specific exit code of the program
+    Status(i32, String),
 }
 
+/// Info extended by the resource usage of the running services
+pub struct Wide<T>(pub T);
+
 impl Output {
-    pub fn socket_not_alive(socket_path: &str) -> Self {
+    pub fn socket_not_alive(socket_client: &SocketClient, err: &io::Error) -> Self {
         Self::Stderr(format!(
-            "Cannot connect to the Conc daemon at unix://{}. Daemon is not running or is using different work directory.",
-            socket_path
+            "Cannot connect to the Conc daemon at {}: {}. {}",
+            socket_client.address(),
+            err.to_string().trim_end_matches('.'),
+            socket_client.unreachable_hint()
         ))
     }
 }
@@ -64,7 +74,7 @@ impl From<Result<Vec<String>, ErrorResponse>> for Output {
 impl From<Result<ServiceInfo, ErrorResponse>> for Output {
     fn from(value: Result<ServiceInfo, ErrorResponse>) -> Self {
         match value {
-            Ok(val) => Self::Stdout(format_services_info(vec![val])),
+            Ok(val) => Self::Stdout(format_services_info(vec![val], false)),
             Err(err) => err.into(),
         }
     }
@@ -73,7 +83,7 @@ impl From<Result<ServiceInfo, ErrorResponse>> for Output {
 impl From<Result<ProjectInfo, ErrorResponse>> for Output {
     fn from(value: Result<ProjectInfo, ErrorResponse>) -> Self {
         match value {
-            Ok(val) => Self::Stdout(format_project_info(val)),
+            Ok(val) => Self::Stdout(format_project_info(val, false)),
             Err(err) => err.into(),
         }
     }
@@ -82,7 +92,34 @@ impl From<Result<ProjectInfo, ErrorResponse>> for Output {
 impl From<Result<Vec<ProjectInfo>, ErrorResponse>> for Output {
     fn from(value: Result<Vec<ProjectInfo>, ErrorResponse>) -> Self {
         match value {
-            Ok(val) => Self::Stdout(format_projects_info(val)),
+            Ok(val) => Self::Stdout(format_projects_info(val, false)),
+            Err(err) => err.into(),
+        }
+    }
+}
+
+impl From<Result<Wide<ServiceInfo>, ErrorResponse>> for Output {
+    fn from(value: Result<Wide<ServiceInfo>, ErrorResponse>) -> Self {
+        match value {
+            Ok(val) => Self::Stdout(format_services_info(vec![val.0], true)),
+            Err(err) => err.into(),
+        }
+    }
+}
+
+impl From<Result<Wide<ProjectInfo>, ErrorResponse>> for Output {
+    fn from(value: Result<Wide<ProjectInfo>, ErrorResponse>) -> Self {
+        match value {
+            Ok(val) => Self::Stdout(format_project_info(val.0, true)),
+            Err(err) => err.into(),
+        }
+    }
+}
+
+impl From<Result<Wide<Vec<ProjectInfo>>, ErrorResponse>> for Output {
+    fn from(value: Result<Wide<Vec<ProjectInfo>>, ErrorResponse>) -> Self {
+        match value {
+            Ok(val) => Self::Stdout(format_projects_info(val.0, true)),
             Err(err) => err.into(),
         }
     }
@@ -94,17 +131,69 @@ impl From<ProjectSettingsError> for Output {
     }
 }
 
+impl From<TaskRunnerError> for Output {
+    fn from(value: TaskRunnerError) -> Self {
+        Self::Stderr(value.to_string())
+    }
+}
+
+impl From<Result<TaskRun, TaskRunnerError>> for Output {
+    fn from(value: Result<TaskRun, TaskRunnerError>) -> Self {
+        match value {
+            Ok(run) => {
+                let message = format!(
+                    "Task '{}' {}, logs: {}",
+                    run.task_name,
+                    run.describe_result(),
+                    run.logfile_path
+                );
+
+                match (run.status, run.exit_code) {
+                    (TaskStatus::Succeeded, _) => Self::Stdout(message),
+                    (_, Some(code)) => Self::Status(code, message),
+                    (_, None) => Self::Stderr(message),
+                }
+            }
+            Err(err) => err.into(),
+        }
+    }
+}
+
+impl From<Result<Vec<(String, Option<TaskRun>)>, TaskRunnerError>> for Output {
+    fn from(value: Result<Vec<(String, Option<TaskRun>)>, TaskRunnerError>) -> Self {
+        match value {
+            Ok(val) => Self::Stdout(format_task_runs(val)),
+            Err(err) => err.into(),
+        }
+    }
+}
+
+impl From<PortRegistryError> for Output {
+    fn from(value: PortRegistryError) -> Self {
+        Self::Stderr(value.to_string())
+    }
+}
+
+impl From<Result<Vec<PortEntry>, PortRegistryError>> for Output {
+    fn from(value: Result<Vec<PortEntry>, PortRegistryError>) -> Self {
+        match value {
+            Ok(val) => Self::Stdout(format_ports(val)),
+            Err(err) => err.into(),
+        }
+    }
+}
+
 impl From<AppConfigError> for Output {
     fn from(value: AppConfigError) -> Self {
         Self::Stderr(value.to_string())
     }
 }
 
-fn format_projects_info(projects: Vec<ProjectInfo>) -> String {
+fn format_projects_info(projects: Vec<ProjectInfo>, wide: bool) -> String {
     let mut output = vec![];
 
     for project in projects {
-        output.push(format_project_info(project));
+        output.push(format_project_info(project, wide));
     }
 
     if output.is_empty() {
@@ -114,24 +203,28 @@ fn format_projects_info(projects: Vec<ProjectInfo>) -> String {
     output.join("\n\n")
 }
 
-fn format_project_info(project: ProjectInfo) -> String {
+fn format_project_info(project: ProjectInfo, wide: bool) -> String {
     let mut output = format!(
         "Project: {}, {}/{} Running\n",
         project.name,
         project.running_service_count(),
         project.service_count()
     );
-    let service_table = format_services_info(project.services);
+    let service_table = format_services_info(project.services, wide);
     output.push_str(&service_table);
 
     output
 }
 
-fn format_services_info(services: Vec<ServiceInfo>) -> String {
+fn format_services_info(services: Vec<ServiceInfo>, wide: bool) -> String {
     let mut service_names_column = vec![String::from("NAME")];
     let mut service_statuses_column = vec![String::from("STATUS")];
     let mut service_pids_column = vec![String::from("PID")];
     let mut service_ages_column = vec![String::from("AGE")];
+    let mut service_cpus_column = vec![String::from("CPU")];
+    let mut service_rss_column = vec![String::from("RSS")];
+    let mut service_threads_column = vec![String::from("THREADS")];
+    let mut service_fds_column = vec![String::from("FDS")];
 
     for service in services {
         service_names_column.push(service.name);
@@ -143,17 +236,133 @@ fn format_services_info(services: Vec<ServiceInfo>) -> String {
             _ => String::new(),
         };
         service_ages_column.push(age);
+
+        let metrics = match (wide, service.status) {
+            (true, ServiceStatus::RUNNING) => collect_once(service.pid),
+            _ => None,
+        };
+        match metrics {
+            Some(metrics) => {
+                service_cpus_column.push(metrics.format_cpu());
+                service_rss_column.push(metrics.format_rss());
+                service_threads_column.push(metrics.threads.to_string());
+                service_fds_column.push(metrics.fds.to_string());
+            }
+            None => {
+                service_cpus_column.push(String::new());
+                service_rss_column.push(String::new());
+                service_threads_column.push(String::new());
+                service_fds_column.push(String::new());
+            }
+        }
     }
 
-    format_table(vec![
+    let mut columns = vec![
         service_names_column,
         service_statuses_column,
         service_pids_column,
         service_ages_column,
+    ];
+    if wide {
+        columns.extend(vec![
+            service_cpus_column,
+            service_rss_column,
+            service_threads_column,
+            service_fds_column,
+        ]);
+    }
+
+    format_table(columns)
+}
+
+fn format_task_runs(tasks: Vec<(String, Option<TaskRun>)>) -> String {
+    if tasks.is_empty() {
+        return String::from("No task was found.");
+    }
+
+    let mut task_names_column = vec![String::from("NAME")];
+    let mut task_statuses_column = vec![String::from("STATUS")];
+    let mut task_exit_codes_column = vec![String::from("EXIT CODE")];
+    let mut task_ages_column = vec![String::from("LAST RUN")];
+    let mut task_logfiles_column = vec![String::from("LOGFILE")];
+
+    for (name, run) in tasks {
+        task_names_column.push(name);
+
+        match run {
+            Some(run) => {
+                task_statuses_column.push(run.status.to_string());
+                task_exit_codes_column.push(
+                    run.exit_code
+                        .map(|code| code.to_string())
+                        .unwrap_or_default(),
+                );
+                task_ages_column.push(format!("{} ago", start_time_to_age(run.started_at)));
+                task_logfiles_column.push(run.logfile_path);
+            }
+            None => {
+                task_statuses_column.push(String::from("Never run"));
+                task_exit_codes_column.push(String::new());
+                task_ages_column.push(String::new());
+                task_logfiles_column.push(String::new());
+            }
+        }
+    }
+
+    format_table(vec![
+        task_names_column,
+        task_statuses_column,
+        task_exit_codes_column,
+        task_ages_column,
+        task_logfiles_column,
+    ])
+}
+
+fn format_ports(ports: Vec<PortEntry>) -> String {
+    if ports.is_empty() {
+        return String::from("No port was found.");
+    }
+
+    let mut ports_column = vec![String::from("PORT")];
+    let mut declared_by_column = vec![String::from("DECLARED BY")];
+    let mut bound_by_column = vec![String::from("BOUND BY")];
+    let mut pids_column = vec![String::from("PID")];
+
+    for entry in ports {
+        ports_column.push(entry.port.to_string());
+        declared_by_column.push(
+            entry
+                .declared_by
+                .iter()
+                .map(|service| service.to_string())
+                .collect::<Vec<String>>()
+                .join(", "),
+        );
+
+        let (bound_by, pid) = match entry.owner {
+            Some(owner) => {
+                let bound_by = match (owner.service, owner.process_name) {
+                    (Some(service), _) => service.to_string(),
+                    (None, Some(process_name)) => format!("{} (foreign)", process_name),
+                    (None, None) => String::from("unknown (foreign)"),
+                };
+                (bound_by, owner.pid.map(|pid| pid.to_string()))
+            }
+            None => (String::from("free"), None),
+        };
+        bound_by_column.push(bound_by);
+        pids_column.push(pid.unwrap_or_default());
+    }
+
+    format_table(vec![
+        ports_column,
+        declared_by_column,
+        bound_by_column,
+        pids_column,
     ])
 }
 
-fn format_table(columns: Vec<Vec<String>>) -> String {
+pub fn format_table(columns: Vec<Vec<String>>) -> String {
     if columns.is_empty() {
         return String::new();
     };
@@ -1,4 +1,4 @@
-use std::io::Error;
+use std::io::{Error, ErrorKind};
 use std::os::unix::process::CommandExt;
 use std::process::Command;
 
@@ -12,3 +12,27 @@ pub fn execute_tail(files: Vec<String>) -> Error {
         .args(files)
         .exec()
 }
+
+/// Replaces the current process with the command, variables already present
+/// in the caller environment are kept when `keep_caller_env` is set
+pub fn execute_command(
+    command: Vec<String>,
+    pwd: String,
+    env: Vec<(String, String)>,
+    keep_caller_env: bool,
+) -> Error {
+    let (program, args) = match command.split_first() {
+        Some(parts) => parts,
+        None => return Error::new(ErrorKind::InvalidInput, "no command was provided"),
+    };
+
+    let env = env
+        .into_iter()
+        .filter(|(key, _)| !keep_caller_env || std::env::var_os(key).is_none());
+
+    Command::new(program)
+        .args(args)
+        .current_dir(pwd)
+        .envs(env)
+        .exec()
+}
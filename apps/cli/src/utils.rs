@@ -1,16 +1,22 @@
 use std::{
     fs::File,
     io::{BufRead, Error},
-    time::{SystemTime, UNIX_EPOCH},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
+use app_config::AppConfig;
+use daemon_client::{SocketClient, TransportOptions};
 use rev_buf_reader::RevBufReader;
 
-pub fn start_time_to_age(started_time: u64) -> String {
-    let now = SystemTime::now()
+pub fn now_secs() -> u64 {
+    SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap()
-        .as_secs();
+        .as_secs()
+}
+
+pub fn start_time_to_age(started_time: u64) -> String {
+    let now = now_secs();
 
     let elapsed = match started_time >= now {
         true => 0,
@@ -57,3 +63,103 @@ pub fn read_last_n_lines_from_file(file_path: &str, limit: u16) -> Result<Vec<St
 
     Ok(lines)
 }
+
+/// Parses durations like '500ms', '30s', '5m' or '1h', plain numbers are treated as seconds
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split_at = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split_at);
+
+    let amount = amount
+        .parse::<u64>()
+        .map_err(|_| format!("invalid duration '{}'", value))?;
+
+    let secs = |multiplier: u64| {
+        amount
+            .checked_mul(multiplier)
+            .map(Duration::from_secs)
+            .ok_or_else(|| format!("duration '{}' is too long", value))
+    };
+
+    match unit {
+        "ms" => Ok(Duration::from_millis(amount)),
+        "" | "s" => secs(1),
+        "m" => secs(60),
+        "h" => secs(3600),
+        _ => Err(format!(
+            "invalid duration unit '{}', expected one of 'ms', 's', 'm', 'h'",
+            unit
+        )),
+    }
+}
+
+/// Renders values as a line of block characters scaled to the highest value, at least to 1
+pub fn sparkline(values: &[f64]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+    let max = values.iter().copied().fold(1.0, f64::max);
+    values
+        .iter()
+        .map(|value| {
+            let level = (value.max(0.0) / max * (BARS.len() - 1) as f64).round() as usize;
+            BARS[level.min(BARS.len() - 1)]
+        })
+        .collect()
+}
+
+/// Client of the daemon of the current context, the token and the CA file are used by remote transports
+pub fn create_socket_client(config: &AppConfig) -> SocketClient {
+    let options = TransportOptions {
+        token: config.daemon_token.clone(),
+        ca_file: config.daemon_ca_file.clone(),
+    };
+    SocketClient::with_options(&config.daemon_socket_path, &options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_duration_with_units() {
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("30s"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("5m"), Ok(Duration::from_secs(300)));
+        assert_eq!(parse_duration("2h"), Ok(Duration::from_secs(7200)));
+    }
+
+    #[test]
+    fn parses_duration_without_unit_as_seconds() {
+        assert_eq!(parse_duration(" 15 "), Ok(Duration::from_secs(15)));
+    }
+
+    #[test]
+    fn rejects_invalid_duration() {
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("s").is_err());
+        assert!(parse_duration("-5s").is_err());
+        assert!(parse_duration("1.5s").is_err());
+        assert_eq!(
+            parse_duration("10d"),
+            Err(String::from(
+                "invalid duration unit 'd', expected one of 'ms', 's', 'm', 'h'"
+            ))
+        );
+    }
+
+    #[test]
+    fn rejects_overflowing_duration() {
+        let hours = format!("{}h", u64::MAX / 60);
+        assert_eq!(
+            parse_duration(&hours),
+            Err(format!("duration '{}' is too long", hours))
+        );
+        assert!(parse_duration(&format!("{}m", u64::MAX)).is_err());
+        assert_eq!(
+            parse_duration(&format!("{}s", u64::MAX)),
+            Ok(Duration::from_secs(u64::MAX))
+        );
+    }
+}
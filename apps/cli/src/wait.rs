@@ -0,0 +1,346 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    io::{BufRead, BufReader, Seek, SeekFrom},
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+use clap::ValueEnum;
+use daemon_client::{Requester, ServiceInfo, ServiceStatus};
+use regex::Regex;
+
+use crate::{output::Output, utils::now_secs};
+
+const EXIT_CODE_TIMEOUT: i32 = 2;
+const EXIT_CODE_UNEXPECTED_EXIT: i32 = 3;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+const HEALTHY_MIN_UPTIME_SECS: u64 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum WaitState {
+    /// service is running
+    Running,
+    /// service is not running anymore, either it exited or it was stopped
+    Exited,
+    /// service is running for at least 3 seconds
+    Healthy,
+}
+
+pub struct WaitOptions {
+    state: WaitState,
+    timeout: Duration,
+    log_match: Option<Regex>,
+}
+
+impl WaitOptions {
+    pub fn new(
+        state: WaitState,
+        timeout: Duration,
+        log_match: Option<String>,
+    ) -> Result<Self, String> {
+        let log_match = log_match
+            .map(|pattern| Regex::new(&pattern))
+            .transpose()
+            .map_err(|err| format!("invalid log match pattern: {}", err))?;
+
+        Ok(Self {
+            state,
+            timeout,
+            log_match,
+        })
+    }
+}
+
+enum Progress {
+    Pending,
+    Done,
+    UnexpectedExit(String),
+}
+
+pub fn wait_for(
+    requester: &Requester,
+    project_name: &str,
+    service_name: Option<&str>,
+    options: WaitOptions,
+) -> Output {
+    let waiting_since = now_secs();
+    // timeouts too long to be represented are waited for without a deadline
+    let deadline = Instant::now().checked_add(options.timeout);
+    let mut log_matcher = options.log_match.as_ref().map(LogMatcher::new);
+    let mut seen_running = HashSet::new();
+
+    loop {
+        let services = match service_name {
+            Some(service_name) => requester
+                .get_services_info(project_name, service_name)
+                .map(|service| vec![service]),
+            None => requester
+                .get_project_info(project_name)
+                .map(|project| project.services),
+        };
+
+        let services = match services {
+            Ok(services) => services,
+            Err(err) => return err.into(),
+        };
+
+        let mut progress = Progress::Done;
+        for service in services.iter() {
+            if service.status == ServiceStatus::RUNNING {
+                seen_running.insert(service.name.clone());
+            }
+
+            let was_running = seen_running.contains(&service.name);
+            match check_service(
+                service,
+                options.state,
+                waiting_since,
+                was_running,
+                &mut log_matcher,
+            ) {
+                Progress::Done => {}
+                Progress::Pending => progress = Progress::Pending,
+                exit => {
+                    progress = exit;
+                    break;
+                }
+            }
+        }
+
+        match progress {
+            Progress::Done => return Output::Stdout(String::new()),
+            Progress::UnexpectedExit(service_name) => {
+                return Output::Status(
+                    EXIT_CODE_UNEXPECTED_EXIT,
+                    format!(
+                        "Service '{}/{}' is not running anymore.",
+                        project_name, service_name
+                    ),
+                )
+            }
+            Progress::Pending => {}
+        }
+
+        let remaining = match deadline {
+            Some(deadline) => deadline.saturating_duration_since(Instant::now()),
+            None => POLL_INTERVAL,
+        };
+        if remaining.is_zero() {
+            return Output::Status(
+                EXIT_CODE_TIMEOUT,
+                format!(
+                    "Timed out after {:?} while waiting for the state '{:?}'.",
+                    options.timeout, options.state
+                ),
+            );
+        }
+
+        sleep(POLL_INTERVAL.min(remaining));
+    }
+}
+
+fn check_service(
+    service: &ServiceInfo,
+    state: WaitState,
+    waiting_since: u64,
+    was_running: bool,
+    log_matcher: &mut Option<LogMatcher>,
+) -> Progress {
+    let is_running = service.status == ServiceStatus::RUNNING;
+
+    let reached = match state {
+        WaitState::Running => is_running,
+        WaitState::Healthy => {
+            is_running && now_secs().saturating_sub(service.start_time) >= HEALTHY_MIN_UPTIME_SECS
+        }
+        WaitState::Exited => matches!(
+            service.status,
+            ServiceStatus::EXITED | ServiceStatus::STOPPED
+        ),
+    };
+
+    // services that were not running before the waiting started may still be started by someone else,
+    // daemon does not track the time of a natural exit so the start time is checked as well
+    let exited_while_waiting = !is_running
+        && service.status != ServiceStatus::IDLE
+        && (was_running
+            || service.start_time >= waiting_since
+            || service.stop_time >= waiting_since);
+    if state != WaitState::Exited && exited_while_waiting {
+        return Progress::UnexpectedExit(service.name.clone());
+    }
+
+    let log_matched = match log_matcher {
+        Some(matcher) => matcher.matches(&service.logfile_path),
+        None => true,
+    };
+
+    match reached && log_matched {
+        true => Progress::Done,
+        false => Progress::Pending,
+    }
+}
+
+struct LogMatcher<'a> {
+    regex: &'a Regex,
+    offsets: HashMap<String, u64>,
+    matched: HashMap<String, bool>,
+}
+
+impl<'a> LogMatcher<'a> {
+    fn new(regex: &'a Regex) -> Self {
+        Self {
+            regex,
+            offsets: HashMap::new(),
+            matched: HashMap::new(),
+        }
+    }
+
+    /// Reads lines appended to the logfile since the last call and checks them against the regex,
+    /// the first call only remembers the length as logs of the previous runs are kept in the same file.
+    /// A logfile missing on the first call is read from the start once it is created.
+    fn matches(&mut self, logfile_path: &str) -> bool {
+        if self.matched.get(logfile_path).copied().unwrap_or(false) {
+            return true;
+        }
+
+        let file = match File::open(logfile_path) {
+            Ok(file) => file,
+            Err(_) => {
+                self.offsets.entry(String::from(logfile_path)).or_insert(0);
+                return false;
+            }
+        };
+
+        let len = file.metadata().map(|meta| meta.len()).unwrap_or_default();
+        let mut offset = *self
+            .offsets
+            .entry(String::from(logfile_path))
+            .or_insert(len);
+        // logfile was truncated, e.g. by clearing the logs
+        if len < offset {
+            offset = 0;
+        }
+
+        let mut reader = BufReader::new(file);
+        if reader.seek(SeekFrom::Start(offset)).is_err() {
+            return false;
+        }
+
+        let mut matched = false;
+        let mut line = Vec::new();
+        while let Ok(read) = reader.read_until(b'\n', &mut line) {
+            // incomplete lines are read again on the next call
+            if read == 0 || line.last() != Some(&b'\n') {
+                break;
+            }
+
+            offset += read as u64;
+            // the line ending is not a part of the line so '$' matches its end
+            let text = String::from_utf8_lossy(&line);
+            if self.regex.is_match(text.trim_end_matches(['\n', '\r'])) {
+                matched = true;
+                break;
+            }
+            line.clear();
+        }
+
+        self.offsets.insert(String::from(logfile_path), offset);
+        self.matched.insert(String::from(logfile_path), matched);
+        matched
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, io::Write, path::PathBuf};
+
+    use super::*;
+
+    fn logfile(name: &str, content: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("conc-wait-{}-{}.log", name, std::process::id()));
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn append(path: &PathBuf, content: &str) {
+        let mut file = fs::OpenOptions::new().append(true).open(path).unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn log_matcher_skips_lines_logged_before_the_wait() {
+        let path = logfile("previous", "listening on 8080\n");
+        let regex = Regex::new("listening").unwrap();
+        let mut matcher = LogMatcher::new(&regex);
+        let path_str = path.to_str().unwrap();
+
+        assert!(!matcher.matches(path_str));
+
+        append(&path, "starting\n");
+        assert!(!matcher.matches(path_str));
+
+        append(&path, "listening on 8080\n");
+        assert!(matcher.matches(path_str));
+
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn log_matcher_waits_for_complete_lines() {
+        let path = logfile("partial", "");
+        let regex = Regex::new("^ready$").unwrap();
+        let mut matcher = LogMatcher::new(&regex);
+        let path_str = path.to_str().unwrap();
+        assert!(!matcher.matches(path_str));
+
+        append(&path, "rea");
+        assert!(!matcher.matches(path_str));
+
+        append(&path, "dy\n");
+        assert!(matcher.matches(path_str));
+
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn log_matcher_reads_truncated_logfile_from_start() {
+        let path = logfile("truncated", "some old output\n");
+        let regex = Regex::new("ready").unwrap();
+        let mut matcher = LogMatcher::new(&regex);
+        let path_str = path.to_str().unwrap();
+        assert!(!matcher.matches(path_str));
+
+        fs::write(&path, "ready\n").unwrap();
+        assert!(matcher.matches(path_str));
+
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn log_matcher_reads_logfile_created_after_the_first_call() {
+        let path =
+            std::env::temp_dir().join(format!("conc-wait-created-{}.log", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let regex = Regex::new("ready").unwrap();
+        let mut matcher = LogMatcher::new(&regex);
+        let path_str = path.to_str().unwrap();
+        assert!(!matcher.matches(path_str));
+
+        fs::write(&path, "starting\nready\n").unwrap();
+        assert!(matcher.matches(path_str));
+
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn log_matcher_ignores_missing_logfile() {
+        let regex = Regex::new("ready").unwrap();
+        let mut matcher = LogMatcher::new(&regex);
+
+        assert!(!matcher.matches("/nonexistent/conc/log"));
+    }
+}
@@ -0,0 +1,115 @@
+use std::{
+    path::PathBuf,
+    process::{Command, Output},
+};
+
+use daemon_client::{
+    mock::{MockDaemon, MockProject},
+    ServiceStatus,
+};
+
+fn concc(daemon: &MockDaemon, args: &[&str]) -> Output {
+    concc_at(daemon.socket_path(), args)
+}
+
+/// Runs concc against the socket, the config of the user running the tests is not read
+fn concc_at(socket_path: &str, args: &[&str]) -> Output {
+    let home = std::env::temp_dir().join(format!("conc-cli-tests-{}", std::process::id()));
+    std::fs::create_dir_all(&home).unwrap();
+
+    Command::new(PathBuf::from(env!("CARGO_BIN_EXE_concc")))
+        .args(["--socket", socket_path])
+        .args(args)
+        .current_dir(&home)
+        .env_clear()
+        .env("HOME", &home)
+        .env("XDG_CONFIG_HOME", home.join(".config"))
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).to_string()
+}
+
+fn shop_daemon() -> MockDaemon {
+    MockDaemon::start()
+        .unwrap()
+        .with_project(MockProject::new("shop", &["api", "web"]))
+}
+
+#[test]
+fn prints_project_names() {
+    let daemon = shop_daemon().with_project(MockProject::new("blog", &["app"]));
+
+    let output = concc(&daemon, &["projects"]);
+
+    assert!(output.status.success());
+    assert_eq!(stdout(&output).trim(), "shop blog");
+}
+
+#[test]
+fn prints_status_of_project() {
+    let daemon = shop_daemon();
+    daemon.set_service_status("shop", "web", ServiceStatus::RUNNING);
+
+    let output = concc(&daemon, &["ps", "shop"]);
+    let text = stdout(&output);
+
+    assert!(output.status.success());
+    assert!(text.contains("Project: shop, 1/2 Running"));
+    assert!(text
+        .lines()
+        .any(|line| line.starts_with("api") && line.contains("Idle")));
+    assert!(text
+        .lines()
+        .any(|line| line.starts_with("web") && line.contains("Running")));
+}
+
+#[test]
+fn starts_service_of_project() {
+    let daemon = shop_daemon();
+
+    let output = concc(&daemon, &["start", "shop", "api"]);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    let request = daemon
+        .requests()
+        .into_iter()
+        .find(|request| request.command == "SERVICE-START")
+        .unwrap();
+    assert_eq!(request.args[..2], ["shop", "api"]);
+    assert_eq!(
+        daemon.projects()[0].services[0].status,
+        ServiceStatus::RUNNING
+    );
+}
+
+#[test]
+fn reports_unknown_project() {
+    let daemon = shop_daemon();
+
+    let output = concc(&daemon, &["stop", "missing"]);
+
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("Provided project was not found."));
+}
+
+#[test]
+fn reports_daemon_which_is_not_running() {
+    let daemon = shop_daemon();
+    let socket_path = daemon.socket_path().to_string();
+    drop(daemon);
+
+    let output = concc_at(&socket_path, &["projects"]);
+
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains(&format!(
+        "Cannot connect to the Conc daemon at unix://{}",
+        socket_path
+    )));
+}
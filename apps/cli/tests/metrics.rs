@@ -0,0 +1,150 @@
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpStream,
+    path::PathBuf,
+    process::{Child, Command, Stdio},
+    thread::sleep,
+    time::Duration,
+};
+
+use daemon_client::{
+    mock::{MockDaemon, MockProject},
+    ServiceStatus,
+};
+
+const POLL_WAIT: Duration = Duration::from_millis(400);
+
+/// Running 'concc metrics serve', it is killed when dropped
+struct Exporter {
+    child: Child,
+    address: String,
+}
+
+impl Drop for Exporter {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Starts the exporter on a free port polling the daemon every 100ms, the config of the user running the tests is not read
+fn start_exporter(daemon: &MockDaemon) -> Exporter {
+    let home = std::env::temp_dir().join(format!("conc-cli-metrics-{}", std::process::id()));
+    std::fs::create_dir_all(&home).unwrap();
+
+    let mut child = Command::new(PathBuf::from(env!("CARGO_BIN_EXE_concc")))
+        .args(["--socket", daemon.socket_path()])
+        .args(["metrics", "serve", "--listen", "127.0.0.1:0", "-i", "100ms"])
+        .current_dir(&home)
+        .env_clear()
+        .env("HOME", &home)
+        .env("XDG_CONFIG_HOME", home.join(".config"))
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    let mut line = String::new();
+    BufReader::new(child.stdout.take().unwrap())
+        .read_line(&mut line)
+        .unwrap();
+    let address = line
+        .trim()
+        .strip_prefix("Serving metrics on http://")
+        .and_then(|rest| rest.strip_suffix("/metrics"))
+        .unwrap_or_else(|| panic!("unexpected output '{}'", line))
+        .to_string();
+
+    Exporter { child, address }
+}
+
+fn scrape(exporter: &Exporter) -> String {
+    let mut stream = TcpStream::connect(&exporter.address).unwrap();
+    write!(
+        stream,
+        "GET /metrics HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n"
+    )
+    .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    assert!(head.starts_with("HTTP/1.1 200"), "{}", head);
+    body.to_string()
+}
+
+fn value(metrics: &str, sample: &str) -> Option<String> {
+    metrics
+        .lines()
+        .find_map(|line| line.strip_prefix(sample)?.strip_prefix(' '))
+        .map(String::from)
+}
+
+#[test]
+fn exports_status_of_services() {
+    let daemon = MockDaemon::start()
+        .unwrap()
+        .with_project(MockProject::new("shop", &["api", "web"]));
+    daemon.set_service_status("shop", "web", ServiceStatus::RUNNING);
+    let exporter = start_exporter(&daemon);
+
+    let metrics = scrape(&exporter);
+
+    assert_eq!(value(&metrics, "conc_daemon_up").as_deref(), Some("1"));
+    assert_eq!(
+        value(&metrics, r#"conc_service_up{project="shop",service="api"}"#).as_deref(),
+        Some("0")
+    );
+    assert_eq!(
+        value(&metrics, r#"conc_service_up{project="shop",service="web"}"#).as_deref(),
+        Some("1")
+    );
+    assert_ne!(
+        value(
+            &metrics,
+            r#"conc_service_start_time_seconds{project="shop",service="web"}"#
+        )
+        .as_deref(),
+        Some("0")
+    );
+    assert!(metrics.contains("# TYPE conc_service_restarts_total counter"));
+    // the pid of the mock service is not a real process
+    assert!(!metrics.contains("conc_service_resident_memory_bytes{"));
+}
+
+#[test]
+fn counts_restarts_from_the_first_poll() {
+    let daemon = MockDaemon::start()
+        .unwrap()
+        .with_project(MockProject::new("shop", &["api"]));
+    daemon.set_service_status("shop", "api", ServiceStatus::RUNNING);
+    let exporter = start_exporter(&daemon);
+    let restarts = r#"conc_service_restarts_total{project="shop",service="api"}"#;
+
+    assert_eq!(value(&scrape(&exporter), restarts).as_deref(), Some("0"));
+
+    daemon.set_service_status("shop", "api", ServiceStatus::EXITED);
+    sleep(POLL_WAIT);
+    daemon.set_service_status("shop", "api", ServiceStatus::RUNNING);
+    sleep(POLL_WAIT);
+    assert_eq!(value(&scrape(&exporter), restarts).as_deref(), Some("1"));
+
+    daemon.set_service_status("shop", "api", ServiceStatus::RUNNING);
+    sleep(POLL_WAIT);
+    assert_eq!(value(&scrape(&exporter), restarts).as_deref(), Some("2"));
+}
+
+#[test]
+fn reports_unreachable_daemon() {
+    let daemon = MockDaemon::start()
+        .unwrap()
+        .with_project(MockProject::new("shop", &["api"]));
+    let exporter = start_exporter(&daemon);
+    drop(daemon);
+    sleep(POLL_WAIT);
+
+    let metrics = scrape(&exporter);
+
+    assert_eq!(value(&metrics, "conc_daemon_up").as_deref(), Some("0"));
+    assert!(!metrics.contains("conc_service_up{"));
+}
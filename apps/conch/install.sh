@@ -0,0 +1,16 @@
+#! /usr/bin/env bash
+
+set -eu
+
+BIN=$PREFIX/conch
+
+if [[ -z "${PREFIX}" ]]; then
+    echo "PREFIX environment must be set"
+    exit 1
+fi
+
+echo "[GATEWAY] Installing into ${BIN} ..."
+
+sudo cp -f ../../target/release/conch "$BIN"
+
+echo "[GATEWAY] Installed!"
@@ -0,0 +1,131 @@
+use std::{
+    fs::File,
+    io::{self, ErrorKind, Read, Seek, SeekFrom, Write},
+    thread,
+    time::{Duration, Instant},
+};
+
+const CHUNK_SIZE: u64 = 8192;
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Server-sent events with the lines of a logfile, the last lines are sent first and then the appended ones
+pub struct LogStream {
+    logfile_path: String,
+    lines: usize,
+}
+
+impl LogStream {
+    pub fn new(logfile_path: String, lines: usize) -> Self {
+        Self {
+            logfile_path,
+            lines,
+        }
+    }
+
+    /// Follows the logfile until writing to the client fails, a cleared logfile is reported by the 'cleared' event
+    pub fn stream(&self, writer: &mut dyn Write) -> io::Result<()> {
+        let mut position = file_len(&self.logfile_path)?;
+        let lines = match File::open(&self.logfile_path) {
+            Ok(mut file) => read_lines_before(&mut file, position, self.lines)?,
+            Err(err) if err.kind() == ErrorKind::NotFound => vec![],
+            Err(err) => return Err(err),
+        };
+        for line in lines {
+            write_line(writer, &line)?;
+        }
+        writer.flush()?;
+
+        let mut partial = vec![];
+        let mut last_write = Instant::now();
+        loop {
+            thread::sleep(POLL_INTERVAL);
+
+            let mut written = false;
+            let len = file_len(&self.logfile_path)?;
+            if len < position {
+                position = 0;
+                partial.clear();
+                writer.write_all(b"event: cleared\ndata:\n\n")?;
+                written = true;
+            }
+            if len > position {
+                let mut file = File::open(&self.logfile_path)?;
+                file.seek(SeekFrom::Start(position))?;
+                file.take(len - position).read_to_end(&mut partial)?;
+                position = len;
+
+                // the last line is sent once it is complete
+                let complete = partial.iter().rposition(|b| *b == b'\n').map(|i| i + 1);
+                if let Some(complete) = complete {
+                    let data: Vec<u8> = partial.drain(..complete).collect();
+                    for line in String::from_utf8_lossy(&data).lines() {
+                        write_line(writer, line)?;
+                        written = true;
+                    }
+                }
+            }
+
+            // comments keep proxies from closing the idle stream and detect disconnected clients
+            if !written && last_write.elapsed() >= KEEP_ALIVE_INTERVAL {
+                writer.write_all(b": keep-alive\n\n")?;
+                written = true;
+            }
+            if written {
+                writer.flush()?;
+                last_write = Instant::now();
+            }
+        }
+    }
+}
+
+/// Last lines of the logfile, a missing logfile has no lines
+pub fn read_last_lines(logfile_path: &str, limit: usize) -> io::Result<Vec<String>> {
+    match File::open(logfile_path) {
+        Ok(mut file) => {
+            let len = file.metadata()?.len();
+            read_lines_before(&mut file, len, limit)
+        }
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(vec![]),
+        Err(err) => Err(err),
+    }
+}
+
+/// Reads up to 'limit' last lines of the first 'end' bytes, the file is read backwards in chunks
+fn read_lines_before(file: &mut File, end: u64, limit: usize) -> io::Result<Vec<String>> {
+    let mut start = end;
+    let mut data = vec![];
+    let mut newlines = 0;
+
+    // one more newline is needed to know the first of the lines is complete
+    while start > 0 && newlines <= limit {
+        let len = CHUNK_SIZE.min(start);
+        start -= len;
+
+        let mut chunk = vec![0; len as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut chunk)?;
+        newlines += chunk.iter().filter(|b| **b == b'\n').count();
+        chunk.extend_from_slice(&data);
+        data = chunk;
+    }
+
+    let text = String::from_utf8_lossy(&data);
+    let lines: Vec<&str> = text.lines().collect();
+    let skip = lines.len().saturating_sub(limit);
+    Ok(lines[skip..].iter().map(|line| line.to_string()).collect())
+}
+
+fn file_len(path: &str) -> io::Result<u64> {
+    match std::fs::metadata(path) {
+        Ok(metadata) => Ok(metadata.len()),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(0),
+        Err(err) => Err(err),
+    }
+}
+
+fn write_line(writer: &mut dyn Write, line: &str) -> io::Result<()> {
+    writer.write_all(b"data: ")?;
+    writer.write_all(line.as_bytes())?;
+    writer.write_all(b"\n\n")
+}
@@ -0,0 +1,130 @@
+use std::{fs::File, io::Read, net::SocketAddr};
+
+use app_config::{AppConfigError, ConfigOverrides, LayeredConfig};
+use clap::Parser;
+use daemon_client::{Requester, SocketClient, TransportOptions};
+use server::{Gateway, OPENAPI};
+use task_runner::TaskRunner;
+use tiny_http::Server;
+
+mod logs;
+mod routes;
+mod server;
+
+/// Local HTTP/JSON gateway to the conc daemon for browser dashboards and editor extensions
+#[derive(Debug, Parser)]
+#[command(version)]
+struct Cli {
+    /// path to the socket of the daemon, overrides the configured one
+    #[clap(long)]
+    socket: Option<String>,
+    /// path to a config file (json or toml) applied over the other config files and env variables
+    #[clap(long)]
+    config: Option<String>,
+    /// name of the context whose daemon should be used instead of the current one
+    #[clap(long)]
+    context: Option<String>,
+    /// address to listen on, only loopback addresses are allowed
+    #[clap(long, default_value = "127.0.0.1:7080")]
+    listen: SocketAddr,
+    /// token the clients have to send as 'Authorization: Bearer <token>', a random one is generated and printed when not set
+    #[clap(long, env = "CONCH_TOKEN", hide_env_values = true)]
+    token: Option<String>,
+    /// origin of browser pages allowed to call the gateway, e.g. 'http://localhost:5173', can be repeated
+    #[clap(long)]
+    allow_origin: Vec<String>,
+    /// print the OpenAPI description of the gateway and exit
+    #[clap(long, action)]
+    openapi: bool,
+}
+
+#[derive(Debug, thiserror::Error)]
+enum GatewayError {
+    #[error("{0}")]
+    Config(#[from] AppConfigError),
+    #[error("Refusing to listen on '{0}', the gateway listens only on loopback addresses.")]
+    NotLoopback(SocketAddr),
+    #[error("Unable to listen on '{address}': {inner}")]
+    Listen { address: SocketAddr, inner: String },
+    #[error("Unable to generate the token: {0}")]
+    Token(std::io::Error),
+}
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("{}", err);
+        std::process::exit(-1);
+    }
+}
+
+fn run() -> Result<(), GatewayError> {
+    let cli = Cli::parse();
+    if cli.openapi {
+        println!("{}", OPENAPI.trim_end());
+        return Ok(());
+    }
+
+    let overrides = ConfigOverrides {
+        config_path: cli.config,
+        socket_path: cli.socket,
+        context: cli.context,
+    };
+    let layered = LayeredConfig::load(&overrides)?;
+    for warning in layered.warnings.iter() {
+        eprintln!("Warning: {}", warning);
+    }
+    let config = layered.config;
+
+    if !cli.listen.ip().is_loopback() {
+        return Err(GatewayError::NotLoopback(cli.listen));
+    }
+    let (token, generated) = match cli.token {
+        Some(token) if !token.is_empty() => (token, false),
+        _ => (generate_token().map_err(GatewayError::Token)?, true),
+    };
+
+    let server = Server::http(cli.listen).map_err(|err| GatewayError::Listen {
+        address: cli.listen,
+        inner: err.to_string(),
+    })?;
+    let address = server
+        .server_addr()
+        .to_ip()
+        .map(|addr| addr.to_string())
+        .unwrap_or_default();
+
+    let options = TransportOptions {
+        token: config.daemon_token.clone(),
+        ca_file: config.daemon_ca_file.clone(),
+    };
+    let socket_client = SocketClient::with_options(&config.daemon_socket_path, &options);
+    if let Err(err) = socket_client.health_check() {
+        eprintln!(
+            "Warning: the daemon at {} is not reachable yet: {}",
+            socket_client.address(),
+            err
+        );
+    }
+
+    println!("Listening on http://{}", address);
+    if generated {
+        println!("Token: {}", token);
+    }
+
+    Gateway {
+        requester: Requester::new(socket_client, config.use_caller_env),
+        task_runner: TaskRunner::new(&config.tasks_dir, config.use_caller_env),
+        token,
+        allowed_origins: cli.allow_origin,
+    }
+    .serve(server);
+
+    Ok(())
+}
+
+/// 32 hex characters from the random bytes of the kernel
+fn generate_token() -> std::io::Result<String> {
+    let mut bytes = [0; 16];
+    File::open("/dev/urandom")?.read_exact(&mut bytes)?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
@@ -0,0 +1,275 @@
+use daemon_client::{ErrorResponse, ProjectInfo, Requester, ServiceInfo};
+use port_registry::{check_ports, PortRegistryError};
+use serde::Serialize;
+use serde_json::{json, Value};
+use task_runner::{TaskRunner, TaskRunnerError};
+use tiny_http::Method;
+
+use crate::logs::{read_last_lines, LogStream};
+
+const DEFAULT_LOG_LINES: usize = 100;
+const MAX_LOG_LINES: usize = 10_000;
+
+/// What the gateway responds with, events are streamed until the client disconnects
+pub enum Reply {
+    Json(u16, Value),
+    NoContent,
+    Events(LogStream),
+}
+
+#[derive(Debug, Serialize)]
+struct ProjectBody {
+    name: String,
+    services: Vec<ServiceBody>,
+}
+
+#[derive(Debug, Serialize)]
+struct ServiceBody {
+    name: String,
+    status: String,
+    pid: i32,
+    start_time: u64,
+    stop_time: u64,
+    logfile_path: String,
+}
+
+impl From<ProjectInfo> for ProjectBody {
+    fn from(value: ProjectInfo) -> Self {
+        Self {
+            name: value.name,
+            services: value.services.into_iter().map(ServiceBody::from).collect(),
+        }
+    }
+}
+
+impl From<ServiceInfo> for ServiceBody {
+    fn from(value: ServiceInfo) -> Self {
+        Self {
+            name: value.name,
+            status: value.status.to_string(),
+            pid: value.pid,
+            start_time: value.start_time,
+            stop_time: value.stop_time,
+            logfile_path: value.logfile_path,
+        }
+    }
+}
+
+/// Error returned as '{"error": code, "message": message}' with the status
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: u16,
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn new(status: u16, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn into_reply(self) -> Reply {
+        Reply::Json(
+            self.status,
+            json!({ "error": self.code, "message": self.message }),
+        )
+    }
+}
+
+impl From<ErrorResponse> for ApiError {
+    fn from(value: ErrorResponse) -> Self {
+        let (status, code) = match value {
+            ErrorResponse::Socket { .. } => (503, "daemon_unavailable"),
+            ErrorResponse::Client(_) => (400, "bad_request"),
+            ErrorResponse::Daemon(_) => (500, "daemon_error"),
+            ErrorResponse::Malformed(_) => (502, "malformed_response"),
+            ErrorResponse::ProjectNotFound(_) => (404, "project_not_found"),
+            ErrorResponse::ServiceNotFound(_) => (404, "service_not_found"),
+            ErrorResponse::Unauthorized(_) => (502, "daemon_unauthorized"),
+        };
+        Self::new(status, code, value.to_string())
+    }
+}
+
+impl From<PortRegistryError> for ApiError {
+    fn from(value: PortRegistryError) -> Self {
+        match value {
+            PortRegistryError::RequestError { inner } => inner.into(),
+            PortRegistryError::SettingsError { .. } => {
+                Self::new(422, "invalid_settings", value.to_string())
+            }
+            PortRegistryError::PortConflict { .. } => {
+                Self::new(409, "port_conflict", value.to_string())
+            }
+        }
+    }
+}
+
+impl From<TaskRunnerError> for ApiError {
+    fn from(value: TaskRunnerError) -> Self {
+        match value {
+            TaskRunnerError::RequestError { inner } => inner.into(),
+            TaskRunnerError::SettingsError { .. } => {
+                Self::new(422, "invalid_settings", value.to_string())
+            }
+            TaskRunnerError::TaskFailed { .. } => {
+                Self::new(409, "before_start_failed", value.to_string())
+            }
+            _ => Self::new(500, "task_error", value.to_string()),
+        }
+    }
+}
+
+type Res = Result<Reply, ApiError>;
+
+pub struct Router<'a> {
+    pub requester: &'a Requester,
+    pub task_runner: &'a TaskRunner,
+}
+
+impl Router<'_> {
+    /// Resolves the request to a daemon call, 'path' is split on '/' without the empty segments
+    pub fn route(
+        &self,
+        method: &Method,
+        path: &[&str],
+        query: &[(String, String)],
+        body: &str,
+    ) -> Res {
+        let requester = self.requester;
+
+        match (method, path) {
+            (Method::Get, ["health"]) => Ok(ok(json!({ "daemon": requester.client().is_alive() }))),
+
+            (Method::Get, ["projects"]) => projects(requester.get_projects_info()?),
+            (Method::Post, ["projects"]) => project(requester.upsert_project(body)?),
+            (Method::Get, ["projects", p]) => project(requester.get_project_info(p)?),
+            (Method::Delete, ["projects", p]) => {
+                requester.remove_project(p)?;
+                Ok(Reply::NoContent)
+            }
+            (Method::Get, ["projects", p, "settings"]) => {
+                let settings = requester.get_project_settings(p)?;
+                serde_json::from_str(&settings)
+                    .map(ok)
+                    .map_err(|err| ApiError::new(502, "malformed_response", err.to_string()))
+            }
+            (Method::Post, ["projects", p, "start"]) => {
+                self.prepare_start(p, None)?;
+                project(requester.start_project(p)?)
+            }
+            (Method::Post, ["projects", p, "restart"]) => {
+                self.prepare_start(p, None)?;
+                project(requester.restart_project(p)?)
+            }
+            (Method::Post, ["projects", p, "stop"]) => project(requester.stop_project(p)?),
+            (Method::Delete, ["projects", p, "logs"]) => {
+                requester.clear_project_logs(p)?;
+                Ok(Reply::NoContent)
+            }
+
+            (Method::Get, ["projects", p, "services"]) => {
+                let services = requester.get_project_info(p)?.services;
+                to_json(
+                    services
+                        .into_iter()
+                        .map(ServiceBody::from)
+                        .collect::<Vec<_>>(),
+                )
+            }
+            (Method::Get, ["projects", p, "services", s]) => {
+                service(requester.get_services_info(p, s)?)
+            }
+            (Method::Post, ["projects", p, "services", s, "start"]) => {
+                self.prepare_start(p, Some(s))?;
+                service(requester.start_service(p, s)?)
+            }
+            (Method::Post, ["projects", p, "services", s, "restart"]) => {
+                self.prepare_start(p, Some(s))?;
+                service(requester.restart_service(p, s)?)
+            }
+            (Method::Post, ["projects", p, "services", s, "stop"]) => {
+                service(requester.stop_service(p, s)?)
+            }
+            (Method::Get, ["projects", p, "services", s, "logs"]) => {
+                let path = requester.get_services_info(p, s)?.logfile_path;
+                service_logs(path, query)
+            }
+            (Method::Delete, ["projects", p, "services", s, "logs"]) => {
+                requester.clear_service_logs(p, s)?;
+                Ok(Reply::NoContent)
+            }
+
+            _ => Err(ApiError::new(404, "route_not_found", "No such route.")),
+        }
+    }
+
+    /// Same checks as 'concc start' does, the declared ports have to be free and the 'before_start' tasks have to pass
+    fn prepare_start(
+        &self,
+        project_name: &str,
+        service_name: Option<&str>,
+    ) -> Result<(), ApiError> {
+        let service_names = service_name.map(|name| vec![String::from(name)]);
+        check_ports(self.requester, project_name, service_names.as_deref())?;
+        self.task_runner.run_before_start(
+            self.requester,
+            project_name,
+            service_names.as_deref(),
+        )?;
+        Ok(())
+    }
+}
+
+fn service_logs(logfile_path: String, query: &[(String, String)]) -> Res {
+    let lines = match query_value(query, "lines") {
+        Some(value) => value
+            .parse::<usize>()
+            .map(|lines| lines.min(MAX_LOG_LINES))
+            .map_err(|_| ApiError::new(400, "bad_request", "'lines' has to be a number."))?,
+        None => DEFAULT_LOG_LINES,
+    };
+    let follow = matches!(query_value(query, "follow"), Some("1" | "true"));
+
+    match follow {
+        true => Ok(Reply::Events(LogStream::new(logfile_path, lines))),
+        false => {
+            let lines = read_last_lines(&logfile_path, lines)
+                .map_err(|err| ApiError::new(500, "log_unreadable", err.to_string()))?;
+            Ok(ok(json!({ "logfile_path": logfile_path, "lines": lines })))
+        }
+    }
+}
+
+pub fn query_value<'a>(query: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    query
+        .iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, value)| value.as_str())
+}
+
+fn projects(value: Vec<ProjectInfo>) -> Res {
+    to_json(value.into_iter().map(ProjectBody::from).collect::<Vec<_>>())
+}
+
+fn project(value: ProjectInfo) -> Res {
+    to_json(ProjectBody::from(value))
+}
+
+fn service(value: ServiceInfo) -> Res {
+    to_json(ServiceBody::from(value))
+}
+
+fn to_json(value: impl Serialize) -> Res {
+    serde_json::to_value(value)
+        .map(ok)
+        .map_err(|err| ApiError::new(500, "serialization_failed", err.to_string()))
+}
+
+fn ok(value: Value) -> Reply {
+    Reply::Json(200, value)
+}
@@ -0,0 +1,187 @@
+use std::{
+    io::{Read, Write},
+    sync::Arc,
+    thread,
+};
+
+use daemon_client::Requester;
+use serde_json::Value;
+use task_runner::TaskRunner;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::routes::{query_value, ApiError, Reply, Router};
+
+pub const OPENAPI: &str = include_str!("openapi.json");
+
+const MAX_BODY_SIZE: u64 = 1024 * 1024;
+
+pub struct Gateway {
+    pub requester: Requester,
+    pub task_runner: TaskRunner,
+    pub token: String,
+    pub allowed_origins: Vec<String>,
+}
+
+impl Gateway {
+    /// Handles every request in its own thread, log streams keep their thread until the client disconnects
+    pub fn serve(self, server: Server) {
+        let gateway = Arc::new(self);
+        for request in server.incoming_requests() {
+            let gateway = gateway.clone();
+            thread::spawn(move || gateway.handle(request));
+        }
+    }
+
+    fn handle(&self, mut request: Request) {
+        let (segments, query) = split_url(request.url());
+        let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+        let origin = self.allowed_origin(&request);
+
+        let reply = match (request.method(), segments.as_slice()) {
+            (Method::Options, _) => Reply::NoContent,
+            (Method::Get, ["openapi.json"]) => match serde_json::from_str::<Value>(OPENAPI) {
+                Ok(spec) => Reply::Json(200, spec),
+                Err(err) => ApiError::new(500, "invalid_openapi", err.to_string()).into_reply(),
+            },
+            _ if !self.is_authorized(&request, &query) => ApiError::new(
+                401,
+                "unauthorized",
+                "Missing or invalid token, send it as 'Authorization: Bearer <token>'.",
+            )
+            .into_reply(),
+            _ => {
+                let mut body = String::new();
+                let read = request
+                    .as_reader()
+                    .take(MAX_BODY_SIZE)
+                    .read_to_string(&mut body);
+
+                let router = Router {
+                    requester: &self.requester,
+                    task_runner: &self.task_runner,
+                };
+                read.map_err(|err| ApiError::new(400, "bad_request", err.to_string()))
+                    .and_then(|_| router.route(request.method(), &segments, &query, &body))
+                    .unwrap_or_else(ApiError::into_reply)
+            }
+        };
+
+        let _ = respond(request, reply, origin);
+    }
+
+    fn is_authorized(&self, request: &Request, query: &[(String, String)]) -> bool {
+        // EventSource of browsers can not send headers, so the token is accepted in the query as well
+        let header = request
+            .headers()
+            .iter()
+            .find(|h| h.field.equiv("Authorization"))
+            .and_then(|h| h.value.as_str().strip_prefix("Bearer "));
+
+        header
+            .or_else(|| query_value(query, "token"))
+            .map(|token| constant_time_eq(token.trim().as_bytes(), self.token.as_bytes()))
+            .unwrap_or(false)
+    }
+
+    fn allowed_origin(&self, request: &Request) -> Option<String> {
+        request
+            .headers()
+            .iter()
+            .find(|h| h.field.equiv("Origin"))
+            .map(|h| h.value.to_string())
+            .filter(|origin| self.allowed_origins.iter().any(|o| o == origin || o == "*"))
+    }
+}
+
+fn respond(request: Request, reply: Reply, origin: Option<String>) -> std::io::Result<()> {
+    let mut headers = vec![];
+    if let Some(origin) = &origin {
+        headers.push(format!("Access-Control-Allow-Origin: {}", origin));
+        headers.push(String::from(
+            "Access-Control-Allow-Headers: Authorization, Content-Type",
+        ));
+        headers.push(String::from(
+            "Access-Control-Allow-Methods: GET, POST, DELETE, OPTIONS",
+        ));
+    }
+    let headers = headers.iter().filter_map(|h| h.parse::<Header>().ok());
+
+    match reply {
+        Reply::Json(status, body) => {
+            let json = Header::from_bytes("Content-Type", "application/json").unwrap();
+            let mut response = Response::from_string(body.to_string())
+                .with_status_code(status)
+                .with_header(json);
+            for header in headers {
+                response.add_header(header);
+            }
+            request.respond(response)
+        }
+        Reply::NoContent => {
+            let mut response = Response::empty(204);
+            for header in headers {
+                response.add_header(header);
+            }
+            request.respond(response)
+        }
+        Reply::Events(stream) => {
+            let mut head = String::from(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n",
+            );
+            for header in headers {
+                head.push_str(&format!("{}: {}\r\n", header.field, header.value));
+            }
+            head.push_str("\r\n");
+
+            let mut writer = request.into_writer();
+            writer.write_all(head.as_bytes())?;
+            stream.stream(&mut writer)
+        }
+    }
+}
+
+/// Splits the url into the decoded path segments and query parameters
+fn split_url(url: &str) -> (Vec<String>, Vec<(String, String)>) {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect();
+    let segments = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(percent_decode)
+        .collect();
+    (segments, query)
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
@@ -0,0 +1,290 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpStream,
+    path::PathBuf,
+    process::{Child, Command, Stdio},
+    time::Duration,
+};
+
+use daemon_client::mock::{MockDaemon, MockProject};
+use serde_json::Value;
+
+const TOKEN: &str = "test-token";
+
+/// Running conch, it is killed when dropped
+struct Gateway {
+    child: Child,
+    address: String,
+}
+
+impl Drop for Gateway {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn home_dir() -> PathBuf {
+    let home = std::env::temp_dir().join(format!("conc-conch-tests-{}", std::process::id()));
+    fs::create_dir_all(&home).unwrap();
+    home
+}
+
+fn conch(args: &[&str]) -> Command {
+    let home = home_dir();
+    let mut command = Command::new(PathBuf::from(env!("CARGO_BIN_EXE_conch")));
+    command
+        .args(args)
+        .current_dir(&home)
+        .env_clear()
+        .env("HOME", &home)
+        .env("XDG_CONFIG_HOME", home.join(".config"));
+    command
+}
+
+/// Starts conch on a free port against the daemon, the config of the user running the tests is not read
+fn start_gateway(daemon: &MockDaemon) -> Gateway {
+    let mut child = conch(&[
+        "--socket",
+        daemon.socket_path(),
+        "--listen",
+        "127.0.0.1:0",
+        "--token",
+        TOKEN,
+    ])
+    .stdout(Stdio::piped())
+    .stderr(Stdio::null())
+    .spawn()
+    .unwrap();
+
+    let mut line = String::new();
+    BufReader::new(child.stdout.take().unwrap())
+        .read_line(&mut line)
+        .unwrap();
+    let address = line
+        .trim()
+        .strip_prefix("Listening on http://")
+        .unwrap_or_else(|| panic!("unexpected output '{}'", line))
+        .to_string();
+
+    Gateway { child, address }
+}
+
+fn shop_daemon() -> MockDaemon {
+    MockDaemon::start()
+        .unwrap()
+        .with_project(MockProject::new("shop", &["api", "web"]))
+}
+
+fn send(gateway: &Gateway, method: &str, path: &str, token: Option<&str>, body: &str) -> TcpStream {
+    let mut stream = TcpStream::connect(&gateway.address).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(10)))
+        .unwrap();
+    let auth = token
+        .map(|token| format!("Authorization: Bearer {}\r\n", token))
+        .unwrap_or_default();
+    write!(
+        stream,
+        "{} {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n{}Content-Length: {}\r\n\r\n{}",
+        method,
+        path,
+        auth,
+        body.len(),
+        body
+    )
+    .unwrap();
+    stream
+}
+
+/// Sends the request and returns the status and the JSON body, an empty body is returned as null
+fn request(gateway: &Gateway, method: &str, path: &str, body: &str) -> (u16, Value) {
+    request_with(gateway, method, path, Some(TOKEN), body)
+}
+
+fn request_with(
+    gateway: &Gateway,
+    method: &str,
+    path: &str,
+    token: Option<&str>,
+    body: &str,
+) -> (u16, Value) {
+    let mut response = String::new();
+    send(gateway, method, path, token, body)
+        .read_to_string(&mut response)
+        .unwrap();
+
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+    let body = match body.is_empty() {
+        true => Value::Null,
+        false => serde_json::from_str(body).unwrap(),
+    };
+    (status, body)
+}
+
+#[test]
+fn rejects_requests_without_token() {
+    let daemon = shop_daemon();
+    let gateway = start_gateway(&daemon);
+
+    let (status, body) = request_with(&gateway, "GET", "/projects", None, "");
+    assert_eq!(status, 401);
+    assert_eq!(body["error"], "unauthorized");
+
+    let (status, _) = request_with(&gateway, "GET", "/projects", Some("wrong"), "");
+    assert_eq!(status, 401);
+
+    let (status, _) = request_with(&gateway, "GET", "/health?token=test-token", None, "");
+    assert_eq!(status, 200);
+    assert!(daemon.requests().is_empty());
+}
+
+#[test]
+fn serves_openapi_description() {
+    let daemon = shop_daemon();
+    let gateway = start_gateway(&daemon);
+
+    let (status, body) = request_with(&gateway, "GET", "/openapi.json", None, "");
+
+    assert_eq!(status, 200);
+    assert_eq!(body["openapi"], "3.0.3");
+    assert!(body["paths"]["/projects/{project}/services/{service}/logs"].is_object());
+}
+
+#[test]
+fn lists_projects() {
+    let daemon = shop_daemon().with_project(MockProject::new("blog", &["app"]));
+    let gateway = start_gateway(&daemon);
+
+    let (status, body) = request(&gateway, "GET", "/projects", "");
+
+    assert_eq!(status, 200);
+    assert_eq!(body[0]["name"], "shop");
+    assert_eq!(body[0]["services"][1]["name"], "web");
+    assert_eq!(body[0]["services"][1]["status"], "Idle");
+    assert_eq!(body[1]["name"], "blog");
+}
+
+#[test]
+fn starts_and_stops_project() {
+    let daemon = shop_daemon();
+    let gateway = start_gateway(&daemon);
+
+    let (status, body) = request(&gateway, "POST", "/projects/shop/start", "");
+    assert_eq!(status, 200);
+    assert_eq!(body["services"][0]["status"], "Running");
+
+    let (status, body) = request(&gateway, "POST", "/projects/shop/services/web/stop", "");
+    assert_eq!(status, 200);
+    assert_eq!(body["status"], "Stopped");
+
+    let commands: Vec<String> = daemon.requests().into_iter().map(|r| r.command).collect();
+    assert!(commands.contains(&String::from("PROJECT-START")));
+    assert_eq!(commands.last().unwrap(), "SERVICE-STOP");
+}
+
+#[test]
+fn upserts_and_removes_project() {
+    let daemon = MockDaemon::start().unwrap();
+    let gateway = start_gateway(&daemon);
+    let settings = r#"{"name":"docs","services":[{"name":"serve","command":["true"]}]}"#;
+
+    let (status, body) = request(&gateway, "POST", "/projects", settings);
+    assert_eq!(status, 200);
+    assert_eq!(body["name"], "docs");
+
+    let (status, body) = request(&gateway, "GET", "/projects/docs/settings", "");
+    assert_eq!(status, 200);
+    assert_eq!(body["services"][0]["name"], "serve");
+
+    let (status, body) = request(&gateway, "DELETE", "/projects/docs", "");
+    assert_eq!(status, 204);
+    assert_eq!(body, Value::Null);
+    assert!(daemon.projects().is_empty());
+}
+
+#[test]
+fn errors_mirror_error_responses() {
+    let daemon = shop_daemon();
+    let gateway = start_gateway(&daemon);
+
+    let (status, body) = request(&gateway, "GET", "/projects/missing", "");
+    assert_eq!(status, 404);
+    assert_eq!(body["error"], "project_not_found");
+
+    let (status, body) = request(&gateway, "GET", "/projects/shop/services/missing", "");
+    assert_eq!(status, 404);
+    assert_eq!(body["error"], "service_not_found");
+
+    daemon.respond_with("PROJECT-CLEAR-LOGS", "ERROR\u{11}driver_error");
+    let (status, body) = request(&gateway, "DELETE", "/projects/shop/logs", "");
+    assert_eq!(status, 500);
+    assert_eq!(body["error"], "daemon_error");
+
+    let (status, body) = request(&gateway, "GET", "/unknown", "");
+    assert_eq!(status, 404);
+    assert_eq!(body["error"], "route_not_found");
+}
+
+#[test]
+fn returns_and_follows_service_logs() {
+    let daemon = shop_daemon();
+    let gateway = start_gateway(&daemon);
+
+    let logfile = home_dir().join("api.log");
+    fs::write(&logfile, "first\nsecond\nthird\n").unwrap();
+    daemon.respond_with(
+        "SERVICE-INFO",
+        &format!("OK\u{11}api RUNNING 10 1 0 {}", logfile.display()),
+    );
+
+    let (status, body) = request(
+        &gateway,
+        "GET",
+        "/projects/shop/services/api/logs?lines=2",
+        "",
+    );
+    assert_eq!(status, 200);
+    assert_eq!(body["lines"], serde_json::json!(["second", "third"]));
+
+    let stream = send(
+        &gateway,
+        "GET",
+        "/projects/shop/services/api/logs?follow=1&lines=1",
+        Some(TOKEN),
+        "",
+    );
+    let mut reader = BufReader::new(stream);
+    let mut next_line = || {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        line.trim_end().to_string()
+    };
+
+    assert_eq!(next_line(), "HTTP/1.1 200 OK");
+    while !next_line().is_empty() {}
+    assert_eq!(next_line(), "data: third");
+    assert_eq!(next_line(), "");
+
+    let mut file = OpenOptions::new().append(true).open(&logfile).unwrap();
+    file.write_all(b"fourth\n").unwrap();
+    assert_eq!(next_line(), "data: fourth");
+
+    fs::write(&logfile, "").unwrap();
+    assert_eq!(next_line(), "");
+    assert_eq!(next_line(), "event: cleared");
+
+    let _ = fs::remove_file(&logfile);
+}
+
+#[test]
+fn refuses_to_listen_on_other_than_loopback() {
+    let output = conch(&["--listen", "0.0.0.0:0", "--token", TOKEN])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("only on loopback addresses"));
+}
@@ -1,6 +1,7 @@
 #include <unistd.h>
 #include <string.h>
 #include <stdlib.h>
+#include <ctype.h>
 
 #include "utils/memory.h"
 #include "utils/fs.h"
@@ -15,6 +16,7 @@
 
 static char *error_message_create(char *app_name, char *error);
 static bool try_parse_log_level(char *str, enum log_level *level);
+static char *read_token(char *path, char **token);
 
 char *
 app_config_init(int argc, char **argv, struct app_config *config)
@@ -24,6 +26,10 @@ app_config_init(int argc, char **argv, struct app_config *config)
     config->print_help = false;
     config->print_version = false;
     config->work_dir = NULL;
+    config->tcp_address = NULL;
+    config->token = NULL;
+
+    char *token_file = NULL;
 
     for (int i = 1; i < argc; i++)
     {
@@ -55,6 +61,26 @@ app_config_init(int argc, char **argv, struct app_config *config)
             else
                 config->work_dir = argv[i];
         }
+        else if (strcmp(argv[i], "-t") == 0 || strcmp(argv[i], "--tcp") == 0)
+        {
+            i++;
+            bool was_last = i >= argc;
+            if (was_last || strlen(argv[i]) == 0)
+                error = str_dup("Expected a tcp address");
+            else if (strrchr(argv[i], ':') == NULL)
+                error = str_printf("Invalid tcp address '%s', expected <host>:<port>", argv[i]);
+            else
+                config->tcp_address = argv[i];
+        }
+        else if (strcmp(argv[i], "--token-file") == 0)
+        {
+            i++;
+            bool was_last = i >= argc;
+            if (was_last || strlen(argv[i]) == 0)
+                error = str_dup("Expected a token file");
+            else
+                token_file = argv[i];
+        }
         else
             error = str_printf("Invalid argument '%s'", argv[i]);
 
@@ -62,9 +88,28 @@ app_config_init(int argc, char **argv, struct app_config *config)
             return error_message_create(argv[0], error);
     }
 
+    if (config->tcp_address)
+    {
+        scoped char *error = NULL;
+        if (token_file == NULL)
+            error = str_dup("Flag --tcp requires --token-file");
+        else
+            error = read_token(token_file, &config->token);
+
+        if (error)
+            return error_message_create(argv[0], error);
+    }
+
     return NULL;
 }
 
+void
+app_config_free(struct app_config *config)
+{
+    free(config->token);
+    config->token = NULL;
+}
+
 char *
 get_help_message(char *app_name)
 {
@@ -74,13 +119,18 @@ Flags:\n\
     -l, --log-level <T|D|I|W|E|C>     Changes default log level\n\
     -h, --help                        Prints help\n\
     -d, --daemon                      Forces the app to run in daemon mode (defaults to true when run outside tty)\n\
-    -w, --work-dir                    Working directory (for relative paths it is relative to the current work directory)\n\n\
+    -w, --work-dir                    Working directory (for relative paths it is relative to the current work directory)\n\
+    -t, --tcp <host:port>             Accepts requests over TCP as well, clients have to send the token first\n\
+        --token-file <path>           File with the token required by the TCP listener\n\n\
 Examples:\n\
     %s --log-level I                  Starts services with log level set to INFO\n\
     %s --log-level E                  Starts services with log level set to ERROR\n\
     %s --work-dir /var/lib/conc       Starts services with root work directory in /var/lib/conc\n\
-    %s --daemon                       Starts services as a daemon\n",
-                      app_name, app_name, app_name, app_name, app_name);
+    %s --daemon                       Starts services as a daemon\n\
+    %s --tcp 127.0.0.1:7070 --token-file /etc/conc/token\n\
+                                      Starts services accepting requests over TCP as well, the connection is not\n\
+                                      encrypted so remote clients should use a TLS proxy in front of it or ssh://\n",
+                      app_name, app_name, app_name, app_name, app_name, app_name);
 }
 
 #ifndef __GIT_COMMIT_HASH
@@ -105,6 +155,38 @@ error_message_create(char *app_name, char *error)
     return str_printf("%s\nUsage: %s [options]..., run again with flag --help for more details\n", error, app_name);
 }
 
+// Reads the token from the file, surrounding whitespace is ignored
+static char *
+read_token(char *path, char **token)
+{
+    FILE *fp = fopen(path, "r");
+    if (fp == NULL)
+        return str_printf("Unable to read the token file '%s'", path);
+
+    char *content = get_file_content(fp);
+    fclose(fp);
+    if (content == NULL)
+        return str_printf("Unable to read the token file '%s'", path);
+
+    size_t start = 0;
+    size_t end = strlen(content);
+    while (start < end && isspace((unsigned char)content[start]))
+        start++;
+    while (end > start && isspace((unsigned char)content[end - 1]))
+        end--;
+
+    if (start == end)
+    {
+        free(content);
+        return str_printf("Token file '%s' is empty", path);
+    }
+
+    memmove(content, content + start, end - start);
+    content[end - start] = '\0';
+    *token = content;
+    return NULL;
+}
+
 static bool
 try_parse_log_level(char *str, enum log_level *level)
 {
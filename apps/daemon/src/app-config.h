@@ -12,9 +12,13 @@ struct app_config
     bool print_help;
     bool print_version;
     char *work_dir;
+    // TCP listener is enabled only together with the token clients have to send
+    char *tcp_address;
+    char *token;
 };
 
 char *app_config_init(int argc, char **argv, struct app_config *config);
+void app_config_free(struct app_config *config);
 char *get_help_message(char *app_name);
 const char *get_version();
 
@@ -66,8 +66,12 @@ main(int argc, char **argv)
             return 1;
         }
 
+        scoped char *unauthorized_response = create_unauthorized_response();
         struct server_options server_opts = {
             .dispatch = dispatch_command,
+            .tcp_address = config.tcp_address,
+            .token = config.token,
+            .unauthorized_response = unauthorized_response,
         };
 
         server = server_run_async(server_opts);
@@ -77,6 +81,7 @@ main(int argc, char **argv)
         manager_stop();
     } while (restarting);
 
+    app_config_free(&config);
     return 0;
 }
 
@@ -44,6 +44,12 @@ static char *handle_error_results(enum m_result resp);
 static char *format_list(char **lines);
 static char *format_service_info(struct service_info info);
 
+char *
+create_unauthorized_response()
+{
+    return resp_error("unauthorized");
+}
+
 char *
 dispatch_command(const char *input)
 {
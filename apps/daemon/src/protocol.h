@@ -2,5 +2,6 @@
 #define PROTOCOL__H
 
 char *dispatch_command(const char *input);
+char *create_unauthorized_response();
 
 #endif
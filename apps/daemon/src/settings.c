@@ -32,9 +32,17 @@ static struct env_variable *env_variable_dup(struct env_variable *env);
 static void service_settings_free(struct service_settings settings);
 static void env_variable_free(struct env_variable e);
 
+static char *extensions_stringify(struct cJSON *js, const char **known_keys);
+static void extensions_merge(struct cJSON *target, const char *extensions);
+
 static bool is_name_valid(const char *name);
 static bool is_cwd_valid(const char *cwd);
 
+// Keys not listed here are not interpreted by the daemon but are kept as 'extensions'
+// so the frontends can round-trip their own settings through the stored project
+static const char *PROJECT_KNOWN_KEYS[] = { "name", "cwd", "env", "services", NULL };
+static const char *SERVICE_KNOWN_KEYS[] = { "name", "pwd", "env", "command", NULL };
+
 char *
 project_settings_parse(const char *data, struct project_settings *settings)
 {
@@ -99,6 +107,8 @@ project_settings_parse(const char *data, struct project_settings *settings)
     if (settings->env == NULL)
         settings->env = vec_create(struct env_variable);
 
+    settings->extensions = extensions_stringify(json->child, PROJECT_KNOWN_KEYS);
+
     cJSON_Delete(json);
 
     if (!is_name_valid(settings->name))
@@ -166,8 +176,12 @@ project_settings_stringify(const struct project_settings settings)
             struct env_variable e = service_settings.env[j];
             cJSON_AddItemToObject(env, e.key, cJSON_CreateString(e.value));
         }
+
+        extensions_merge(service, service_settings.extensions);
     }
 
+    extensions_merge(root, settings.extensions);
+
     char *result = cJSON_PrintUnformatted(root);
     cJSON_Delete(root);
 
@@ -181,6 +195,8 @@ project_settings_dup(const struct project_settings settings)
     copy.name = str_dup(settings.name);
     copy.cwd = str_dup(settings.cwd);
     copy.env = env_variable_dup(settings.env);
+    if (settings.extensions)
+        copy.extensions = str_dup(settings.extensions);
 
     size_t service_count = vec_length(settings.services);
     copy.services = vec_create_prealloc(struct service_settings, service_count);
@@ -208,11 +224,13 @@ project_settings_free(struct project_settings settings)
     }
     free(settings.name);
     free(settings.cwd);
+    free(settings.extensions);
 
     settings.name = NULL;
     settings.env = NULL;
     settings.cwd = NULL;
     settings.services = NULL;
+    settings.extensions = NULL;
 }
 
 void
@@ -252,6 +270,8 @@ service_settings_parse(struct cJSON *json)
     if (settings.env == NULL)
         settings.env = vec_create(struct env_variable);
 
+    settings.extensions = extensions_stringify(json->child, SERVICE_KNOWN_KEYS);
+
     return settings;
 }
 
@@ -283,6 +303,8 @@ service_settings_dup(const struct service_settings settings)
     copy.name = str_dup(settings.name);
     if (settings.pwd)
         copy.pwd = str_dup(settings.pwd);
+    if (settings.extensions)
+        copy.extensions = str_dup(settings.extensions);
     copy.env = env_variable_dup(settings.env);
 
     size_t command_len = vec_length(settings.command);
@@ -318,6 +340,7 @@ service_settings_free(struct service_settings settings)
 {
     free(settings.name);
     free(settings.pwd);
+    free(settings.extensions);
     if (settings.env != NULL)
     {
         vec_for_each(settings.env, env_variable_free);
@@ -333,6 +356,7 @@ service_settings_free(struct service_settings settings)
     settings.pwd = NULL;
     settings.command = NULL;
     settings.env = NULL;
+    settings.extensions = NULL;
 }
 
 static void
@@ -345,6 +369,54 @@ env_variable_free(struct env_variable e)
     e.value = NULL;
 }
 
+static char *
+extensions_stringify(struct cJSON *js, const char **known_keys)
+{
+    cJSON *extensions = NULL;
+
+    for (; js; js = js->next)
+    {
+        bool is_known = false;
+        for (size_t i = 0; known_keys[i]; i++)
+            is_known = is_known || strcmp(js->string, known_keys[i]) == 0;
+
+        if (is_known)
+            continue;
+
+        if (extensions == NULL)
+            extensions = cJSON_CreateObject();
+        cJSON_AddItemToObject(extensions, js->string, cJSON_Duplicate(js, true));
+    }
+
+    if (extensions == NULL)
+        return NULL;
+
+    char *result = cJSON_PrintUnformatted(extensions);
+    cJSON_Delete(extensions);
+
+    return result;
+}
+
+static void
+extensions_merge(struct cJSON *target, const char *extensions)
+{
+    if (extensions == NULL)
+        return;
+
+    cJSON *json = cJSON_Parse(extensions);
+    if (json == NULL)
+        return;
+
+    cJSON *item;
+    while ((item = json->child))
+    {
+        cJSON_DetachItemViaPointer(json, item);
+        cJSON_AddItemToObject(target, item->string, item);
+    }
+
+    cJSON_Delete(json);
+}
+
 static bool
 is_name_valid(const char *name)
 {
@@ -13,6 +13,7 @@ struct service_settings
     char *pwd;
     struct env_variable *env;
     char **command;
+    char *extensions;
 };
 
 struct project_settings
@@ -21,6 +22,7 @@ struct project_settings
     char *cwd;
     struct env_variable *env;
     struct service_settings *services;
+    char *extensions;
 };
 
 char *project_settings_parse(const char *data, struct project_settings *settings);
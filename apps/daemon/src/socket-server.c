@@ -7,10 +7,13 @@
 #include <sys/types.h>
 #include <sys/socket.h>
 #include <sys/un.h>
+#include <sys/time.h>
+#include <netdb.h>
 
 #include "utils/log.h"
 #include "utils/memory.h"
 #include "utils/thread-pool.h"
+#include "utils/string.h"
 
 #include "socket-server.h"
 
@@ -18,6 +21,9 @@
 
 #define BUFFER_SIZE 1024
 #define MAX_WAITING_REQUESTS 10
+#define MAX_TOKEN_LENGTH 1024
+// a TCP client which stops sending is dropped instead of holding a thread of the pool
+#define TCP_READ_TIMEOUT_SECONDS 10
 
 #define THREAD_POOL_CONCURRENCY 5
 #define THREAD_POOL_QUEUE_CAPACITY 1024
@@ -36,10 +42,18 @@ struct handler_options
 {
     Dispatch dispatch;
     int client_socket;
+    // set only for TCP connections
+    const char *token;
+    const char *unauthorized_response;
 };
 
 static void *client_socket_handle(void *data);
 static char *read_input(int client_socket, int *totalLength);
+static void close_client_socket(int client_socket);
+static bool read_and_check_token(int client_socket, const char *token);
+static int unix_listen();
+static int tcp_listen(const char *address);
+static void accept_connection(struct server *server, struct thread_pool *pool, int server_socket, bool is_tcp);
 
 static void *server_run(void *data);
 
@@ -91,25 +105,22 @@ server_run(void *data)
 {
     struct server *server = data;
 
-    int server_socket = socket(AF_UNIX, SOCK_STREAM, 0);
-    if (server_socket < 0)
-    {
-        log_critical("Unable to create server_socket FD\n");
+    int unix_socket = unix_listen();
+    if (unix_socket < 0)
         return NULL;
-    }
 
-    struct sockaddr_un server_addr = { .sun_family = AF_UNIX };
-    strcpy(server_addr.sun_path, SOCKET_PATH);
-
-    unlink(SOCKET_PATH);
-    if (bind(server_socket, (struct sockaddr *)&server_addr, sizeof(server_addr)) < 0)
+    // poll ignores negative descriptors so the TCP slot stays unused without the listener
+    int tcp_socket = -1;
+    if (server->opts.tcp_address)
     {
-        log_critical("Unable to bind socket server port\n");
-        return NULL;
+        tcp_socket = tcp_listen(server->opts.tcp_address);
+        if (tcp_socket < 0)
+        {
+            close(unix_socket);
+            return NULL;
+        }
     }
 
-    listen(server_socket, MAX_WAITING_REQUESTS);
-
     log_info("Socket server started\n");
 
     struct thread_pool *pool = thread_pool_create(THREAD_POOL_CONCURRENCY, THREAD_POOL_QUEUE_CAPACITY, TRACE_NAME);
@@ -117,35 +128,24 @@ server_run(void *data)
 
     while (server->running)
     {
-        struct pollfd fds[2] = {
-            { .fd = server_socket, .events = POLLIN },
+        struct pollfd fds[3] = {
             { .fd = server->pipe_fds[0], .events = POLLIN },
+            { .fd = unix_socket, .events = POLLIN },
+            { .fd = tcp_socket, .events = POLLIN },
         };
 
-        int poll_status = poll(fds, 2, -1);
-        if (poll_status <= 0 || !(fds[0].revents & POLLIN) || !server->running)
+        int poll_status = poll(fds, 3, -1);
+        if (poll_status <= 0 || !server->running)
         {
             if (poll_status < 0)
                 log_error("Socket server poll returned %d while polling for data from socket.\n", poll_status);
             continue;
         }
 
-        struct sockaddr_un client_addr;
-        unsigned int clen = sizeof(client_addr);
-
-        int client_socket = accept(server_socket, (struct sockaddr *)&client_addr, &clen);
-        log_trace(TRACE_NAME, "Accepted socket connection '%d'\n", client_socket);
-
-        struct handler_options *handler_opts = malloc(sizeof(struct handler_options));
-        handler_opts->dispatch = server->opts.dispatch;
-        handler_opts->client_socket = client_socket;
-
-        if (thread_pool_queue_job(pool, NULL, client_socket_handle, handler_opts) != 0)
-        {
-            log_critical("Unable to queue client socket handle to on threadpool.");
-            shutdown(client_socket, SHUT_WR);
-            close(client_socket);
-        }
+        if (fds[1].revents & POLLIN)
+            accept_connection(server, pool, unix_socket, false);
+        if (fds[2].revents & POLLIN)
+            accept_connection(server, pool, tcp_socket, true);
     }
 
     log_info("Socket server stopping\n");
@@ -153,21 +153,158 @@ server_run(void *data)
     thread_pool_finish_and_stop(pool);
     thread_pool_free(pool);
 
+    close(unix_socket);
+    if (tcp_socket >= 0)
+        close(tcp_socket);
+
     return NULL;
 }
 
+static int
+unix_listen()
+{
+    int server_socket = socket(AF_UNIX, SOCK_STREAM, 0);
+    if (server_socket < 0)
+    {
+        log_critical("Unable to create server_socket FD\n");
+        return -1;
+    }
+
+    struct sockaddr_un server_addr = { .sun_family = AF_UNIX };
+    strcpy(server_addr.sun_path, SOCKET_PATH);
+
+    unlink(SOCKET_PATH);
+    if (bind(server_socket, (struct sockaddr *)&server_addr, sizeof(server_addr)) < 0)
+    {
+        log_critical("Unable to bind socket server port\n");
+        close(server_socket);
+        return -1;
+    }
+
+    listen(server_socket, MAX_WAITING_REQUESTS);
+    return server_socket;
+}
+
+static int
+tcp_listen(const char *address)
+{
+    // the port follows the last ':' so IPv6 hosts can be written as '[::1]:7070'
+    scoped char *host = str_dup(address);
+    char *port = strrchr(host, ':');
+    *port = '\0';
+    port++;
+
+    char *host_start = host;
+    size_t host_len = strlen(host);
+    if (host_len >= 2 && host[0] == '[' && host[host_len - 1] == ']')
+    {
+        host[host_len - 1] = '\0';
+        host_start = host + 1;
+    }
+
+    struct addrinfo hints = {
+        .ai_family = AF_UNSPEC,
+        .ai_socktype = SOCK_STREAM,
+        .ai_flags = AI_PASSIVE,
+    };
+    struct addrinfo *addresses = NULL;
+    int status = getaddrinfo(strlen(host_start) > 0 ? host_start : NULL, port, &hints, &addresses);
+    if (status != 0)
+    {
+        log_critical("Unable to resolve the tcp address '%s': %s\n", address, gai_strerror(status));
+        return -1;
+    }
+
+    int server_socket = -1;
+    for (struct addrinfo *addr = addresses; addr != NULL; addr = addr->ai_next)
+    {
+        server_socket = socket(addr->ai_family, addr->ai_socktype, addr->ai_protocol);
+        if (server_socket < 0)
+            continue;
+
+        int reuse = 1;
+        setsockopt(server_socket, SOL_SOCKET, SO_REUSEADDR, &reuse, sizeof(reuse));
+        if (bind(server_socket, addr->ai_addr, addr->ai_addrlen) == 0)
+            break;
+
+        close(server_socket);
+        server_socket = -1;
+    }
+    freeaddrinfo(addresses);
+
+    if (server_socket < 0)
+    {
+        log_critical("Unable to bind the tcp address '%s'\n", address);
+        return -1;
+    }
+
+    listen(server_socket, MAX_WAITING_REQUESTS);
+    log_info("Accepting requests over TCP at '%s'\n", address);
+    return server_socket;
+}
+
+static void
+accept_connection(struct server *server, struct thread_pool *pool, int server_socket, bool is_tcp)
+{
+    int client_socket = accept(server_socket, NULL, NULL);
+    if (client_socket < 0)
+    {
+        log_error("Unable to accept socket connection\n");
+        return;
+    }
+    log_trace(TRACE_NAME, "Accepted %s connection '%d'\n", is_tcp ? "tcp" : "socket", client_socket);
+
+    if (is_tcp)
+    {
+        struct timeval timeout = {.tv_sec = TCP_READ_TIMEOUT_SECONDS};
+        if (setsockopt(client_socket, SOL_SOCKET, SO_RCVTIMEO, &timeout, sizeof(timeout)) != 0)
+            log_error("Unable to set the read timeout of connection '%d'\n", client_socket);
+    }
+
+    struct handler_options *handler_opts = malloc(sizeof(struct handler_options));
+    handler_opts->dispatch = server->opts.dispatch;
+    handler_opts->client_socket = client_socket;
+    handler_opts->token = is_tcp ? server->opts.token : NULL;
+    handler_opts->unauthorized_response = server->opts.unauthorized_response;
+
+    if (thread_pool_queue_job(pool, NULL, client_socket_handle, handler_opts) != 0)
+    {
+        log_critical("Unable to queue client socket handle to on threadpool.");
+        shutdown(client_socket, SHUT_WR);
+        close(client_socket);
+        free(handler_opts);
+    }
+}
+
 static void *
 client_socket_handle(void *data)
 {
     scoped struct handler_options *opts = data;
 
+    scoped char *response = NULL;
+    bool is_authorized = opts->token == NULL || read_and_check_token(opts->client_socket, opts->token);
+    // the message of a client with a wrong token is never read
+    if (!is_authorized)
+    {
+        log_warn("Rejected connection '%d' with an invalid token\n", opts->client_socket);
+        response = str_dup(opts->unauthorized_response);
+        write(opts->client_socket, response, strlen(response) + 1);
+        close_client_socket(opts->client_socket);
+        return NULL;
+    }
+
     int total_length = 0;
     scoped char *input = read_input(opts->client_socket, &total_length);
     shutdown(opts->client_socket, SHUT_RD);
+    if (input == NULL)
+    {
+        log_warn("Dropped connection '%d' which did not send a complete message\n", opts->client_socket);
+        close_client_socket(opts->client_socket);
+        return NULL;
+    }
 
     // one character message containing just '\0' is threated as a health check
     bool is_health_check = input[0] == '\0';
-    scoped char *response = NULL;
     if (is_health_check)
     {
         log_trace(TRACE_NAME, "Received health check from connection '%d'\n", opts->client_socket);
@@ -181,15 +318,52 @@ client_socket_handle(void *data)
     }
 
     write(opts->client_socket, response, strlen(response) + 1); // we also want to send '\0' as a end of message indicator
-    shutdown(opts->client_socket, SHUT_WR);
-
-    log_trace(TRACE_NAME, "Closing socket connection '%d'\n", opts->client_socket);
-    if (close(opts->client_socket) != 0)
-        log_error("Unable to close client socket '%d'\n", opts->client_socket);
+    close_client_socket(opts->client_socket);
 
     return NULL;
 }
 
+static void
+close_client_socket(int client_socket)
+{
+    shutdown(client_socket, SHUT_WR);
+
+    log_trace(TRACE_NAME, "Closing socket connection '%d'\n", client_socket);
+    if (close(client_socket) != 0)
+        log_error("Unable to close client socket '%d'\n", client_socket);
+}
+
+// Reads the token frame which precedes the message on TCP connections and compares it with the expected token
+static bool
+read_and_check_token(int client_socket, const char *token)
+{
+    char received[MAX_TOKEN_LENGTH + 1];
+    size_t len = 0;
+    bool terminated = false;
+    char c;
+
+    while (read(client_socket, &c, 1) == 1)
+    {
+        if (c == '\0')
+        {
+            terminated = true;
+            break;
+        }
+        if (len == MAX_TOKEN_LENGTH)
+            return false;
+        received[len++] = c;
+    }
+    received[len] = '\0';
+
+    size_t token_len = strlen(token);
+    // every byte is compared so the time of the check does not reveal the length of the matching prefix
+    unsigned char diff = len != token_len;
+    for (size_t i = 0; i < len && i < token_len; i++)
+        diff |= received[i] ^ token[i];
+
+    return terminated && diff == 0;
+}
+
 static char *
 read_input(int client_socket, int *totalLength)
 {
@@ -208,5 +382,12 @@ read_input(int client_socket, int *totalLength)
             break;
     }
 
+    // the read timed out or failed in the middle of the message
+    if (len < 0)
+    {
+        free(input);
+        return NULL;
+    }
+
     return input;
 }
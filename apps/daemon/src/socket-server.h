@@ -9,6 +9,11 @@ typedef char *(*Dispatch)(const char *command);
 struct server_options
 {
     Dispatch dispatch;
+    // optional '<host>:<port>' of the TCP listener, connections over TCP start with the token
+    const char *tcp_address;
+    const char *token;
+    // response sent to TCP connections which did not send the right token
+    const char *unauthorized_response;
 };
 
 struct server;
@@ -1,9 +1,10 @@
 use daemon_client::{ProjectInfo, ServiceInfo, ServiceStatus};
 use iced::{
-    widget::{button, button::Status, row, text},
-    Background, Border, Color, Element, Shadow, Theme,
+    widget::{button, button::Status, pick_list, row, text},
+    Alignment, Background, Border, Color, Element, Shadow, Theme,
 };
 use iced_fonts::{bootstrap::icon_to_string, Bootstrap, BOOTSTRAP_FONT};
+use task_runner::{TaskRun, TaskStatus};
 
 use crate::message::Message;
 
@@ -59,11 +60,20 @@ impl<'a> From<ServiceActionButtons<'a>> for Element<'a, Message> {
 
 pub struct ProjectActionButtons<'a> {
     project: &'a ProjectInfo,
+    profiles: Vec<String>,
 }
 
 impl<'a> ProjectActionButtons<'a> {
     pub fn new(project: &'a ProjectInfo) -> Self {
-        Self { project }
+        Self {
+            project,
+            profiles: vec![],
+        }
+    }
+
+    pub fn profiles(mut self, profiles: Vec<String>) -> Self {
+        self.profiles = profiles;
+        self
     }
 }
 
@@ -90,13 +100,71 @@ impl<'a> From<ProjectActionButtons<'a>> for Element<'a, Message> {
             false => None,
         };
 
-        row![
+        let mut buttons = row![
             start_action_button(start_message),
             stop_action_button(stop_message),
             restart_action_button(restart_message),
         ]
         .spacing(10)
-        .into()
+        .align_y(Alignment::Center);
+
+        if !value.profiles.is_empty() {
+            let project_name = value.project.name.clone();
+            let profile_picker = pick_list(value.profiles, None::<String>, move |profile_name| {
+                Message::StartProfile {
+                    project_name: project_name.clone(),
+                    profile_name,
+                }
+            })
+            .placeholder("Start profile")
+            .text_size(14);
+
+            buttons = buttons.push(profile_picker);
+        }
+
+        buttons.into()
+    }
+}
+
+pub struct TaskActionButtons<'a> {
+    project_name: &'a str,
+    task_name: &'a str,
+    run: Option<&'a TaskRun>,
+}
+
+impl<'a> TaskActionButtons<'a> {
+    pub fn new(project_name: &'a str, task_name: &'a str, run: Option<&'a TaskRun>) -> Self {
+        Self {
+            project_name,
+            task_name,
+            run,
+        }
+    }
+}
+
+impl<'a> From<TaskActionButtons<'a>> for Element<'a, Message> {
+    fn from(value: TaskActionButtons<'a>) -> Self {
+        let is_running = value
+            .run
+            .is_some_and(|run| run.status == TaskStatus::Running);
+
+        let run_message = match is_running {
+            true => None,
+            false => Some(Message::RunTask {
+                project_name: value.project_name.to_string(),
+                task_name: value.task_name.to_string(),
+            }),
+        };
+
+        let mut buttons = row![start_action_button(run_message)].spacing(10);
+
+        if let Some(run) = value.run {
+            let copy_button =
+                CopyToClipboardButton::new(String::from("logfile path"), run.logfile_path.clone());
+            buttons = buttons.push(copy_button);
+        }
+
+        buttons.into()
     }
 }
 
@@ -27,9 +27,9 @@ impl From<Menu> for Element<'_, Message> {
         let mut project_panel = column![].spacing(8);
         for project in value.projects.iter() {
             let is_active = match &value.current_page {
-                Page::Project(project_name) | Page::Service(project_name, _) => {
-                    project_name == project
-                }
+                Page::Project(project_name)
+                | Page::Service(project_name, _)
+                | Page::ProjectEditor(Some(project_name)) => project_name == project,
                 _ => false,
             };
 
@@ -3,11 +3,15 @@ mod layout;
 mod menu;
 mod status_bar;
 mod table;
+mod text_button;
 mod title;
 
-pub use action_buttons::{CopyToClipboardButton, ProjectActionButtons, ServiceActionButtons};
+pub use action_buttons::{
+    CopyToClipboardButton, ProjectActionButtons, ServiceActionButtons, TaskActionButtons,
+};
 pub use layout::Section;
 pub use menu::Menu;
 pub use status_bar::{StatusErrorBar, StatusInfoBar};
 pub use table::InfoTable;
+pub use text_button::TextButton;
 pub use title::PageTitle;
@@ -1,6 +1,6 @@
 use chrono::{DateTime, Local};
 use iced::{
-    widget::{container, row, text},
+    widget::{container, pick_list, row, text},
     Alignment, Background, Border, Element, Length, Shadow, Theme,
 };
 
@@ -45,25 +45,41 @@ impl From<StatusErrorBar> for Element<'_, Message> {
 }
 
 pub struct StatusInfoBar {
-    socket_path: String,
+    address: String,
+    context: String,
+    contexts: Vec<String>,
 }
 
 impl StatusInfoBar {
-    pub fn new(socket_path: String) -> Self {
-        Self { socket_path }
+    pub fn new(address: String, context: String, contexts: Vec<String>) -> Self {
+        Self {
+            address,
+            context,
+            contexts,
+        }
     }
 }
 
 impl From<StatusInfoBar> for Element<'_, Message> {
     fn from(value: StatusInfoBar) -> Self {
         let formatted_version = text(format!("v{}", env!("CARGO_PKG_VERSION")));
-        let socket = format!("Using the daemon socket at unix://{}", value.socket_path);
-        let status_bar = row![formatted_version, text("|").size(16), text(socket).size(16)]
-            .height(32)
-            .align_y(Alignment::Center)
-            .width(Length::Fill)
-            .spacing(10)
-            .padding([0, 8]);
+        let socket = format!("Using the daemon at {}", value.address);
+        let context = pick_list(value.contexts, Some(value.context), Message::UseContext)
+            .text_size(14)
+            .padding([2, 8]);
+        let status_bar = row![
+            formatted_version,
+            text("|").size(16),
+            text("Context").size(16),
+            context,
+            text("|").size(16),
+            text(socket).size(16)
+        ]
+        .height(32)
+        .align_y(Alignment::Center)
+        .width(Length::Fill)
+        .spacing(10)
+        .padding([0, 8]);
 
         container(status_bar).style(info_container_style).into()
     }
@@ -12,6 +12,7 @@ pub struct InfoTable<'a, F: Fn(&str) -> Message> {
     statuses: Vec<String>,
     actions: Vec<Element<'a, Message>>,
     name_to_message: F,
+    additional_column: Option<(&'a str, Vec<String>)>,
 }
 
 impl<'a, F: Fn(&str) -> Message> InfoTable<'a, F> {
@@ -28,8 +29,15 @@ impl<'a, F: Fn(&str) -> Message> InfoTable<'a, F> {
             statuses,
             actions,
             name_to_message,
+            additional_column: None,
         }
     }
+
+    /// Column displayed between the statuses and the actions
+    pub fn additional_column(mut self, header: &'a str, values: Vec<String>) -> Self {
+        self.additional_column = Some((header, values));
+        self
+    }
 }
 
 impl<'a, F: Fn(&str) -> Message> From<InfoTable<'a, F>> for Element<'a, Message> {
@@ -50,16 +58,17 @@ impl<'a, F: Fn(&str) -> Message> From<InfoTable<'a, F>> for Element<'a, Message>
             actions = actions.push(cell(action));
         }
 
-        let rows = scrollable(
-            row![
-                names,
-                horizontal_space(),
-                statuses,
-                horizontal_space(),
-                actions
-            ]
-            .spacing(8),
-        );
+        let mut content = row![names, horizontal_space(), statuses, horizontal_space()].spacing(8);
+
+        if let Some((header, values)) = value.additional_column {
+            let mut column = column![column_tile(header, 0)].spacing(10);
+            for item in values {
+                column = column.push(cell(text(item).size(18).into()));
+            }
+            content = content.push(column).push(horizontal_space());
+        }
+
+        let rows = scrollable(content.push(actions));
 
         column![value.title, rows].spacing(12).into()
     }
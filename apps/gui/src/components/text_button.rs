@@ -0,0 +1,102 @@
+use iced::{
+    widget::{button, button::Status, container, row, text},
+    Alignment, Background, Border, Color, Element, Padding, Shadow, Theme,
+};
+use iced_fonts::{bootstrap::icon_to_string, Bootstrap, BOOTSTRAP_FONT};
+
+use crate::message::Message;
+
+/// Bordered button with a label and optional icon, it is disabled when there is no message
+pub struct TextButton<'a> {
+    label: &'a str,
+    message: Option<Message>,
+    icon: Option<Bootstrap>,
+    danger: bool,
+}
+
+impl<'a> TextButton<'a> {
+    pub fn new(label: &'a str, message: Option<Message>) -> Self {
+        Self {
+            label,
+            message,
+            icon: None,
+            danger: false,
+        }
+    }
+
+    pub fn icon(mut self, icon: Bootstrap) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    pub fn danger(mut self) -> Self {
+        self.danger = true;
+        self
+    }
+}
+
+impl<'a> From<TextButton<'a>> for Element<'a, Message> {
+    fn from(value: TextButton<'a>) -> Self {
+        let mut content = row![].spacing(6).align_y(Alignment::Center);
+        if let Some(icon) = value.icon {
+            content = content.push(
+                container(text(icon_to_string(icon)).font(BOOTSTRAP_FONT).size(14))
+                    .padding(Padding::default().bottom(2)),
+            );
+        }
+        if !value.label.is_empty() {
+            content = content.push(text(value.label).size(14));
+        }
+
+        let mut butt = button(content).padding([4, 10]);
+        butt = match value.danger {
+            true => butt.style(danger_text_button_style),
+            false => butt.style(text_button_style),
+        };
+
+        if let Some(message) = value.message {
+            butt = butt.on_press(message);
+        }
+
+        butt.into()
+    }
+}
+
+fn text_button_style(theme: &Theme, status: Status) -> button::Style {
+    let palette = theme.extended_palette();
+    bordered_button_style(
+        status,
+        palette.background.base.text,
+        palette.primary.weak.color,
+    )
+}
+
+fn danger_text_button_style(theme: &Theme, status: Status) -> button::Style {
+    let palette = theme.extended_palette();
+    bordered_button_style(status, palette.danger.base.color, palette.danger.base.color)
+}
+
+fn bordered_button_style(status: Status, text_color: Color, accent_color: Color) -> button::Style {
+    let base = button::Style {
+        background: Some(Background::Color(Color::TRANSPARENT)),
+        text_color,
+        border: Border::default()
+            .rounded(10)
+            .width(1)
+            .color(accent_color.scale_alpha(0.5)),
+        shadow: Shadow::default(),
+    };
+
+    match status {
+        Status::Active => base,
+        Status::Hovered | Status::Pressed => button::Style {
+            background: Some(Background::Color(accent_color.scale_alpha(0.25))),
+            ..base
+        },
+        Status::Disabled => button::Style {
+            text_color: text_color.scale_alpha(0.3),
+            border: base.border.color(accent_color.scale_alpha(0.15)),
+            ..base
+        },
+    }
+}
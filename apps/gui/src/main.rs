@@ -1,14 +1,19 @@
-use std::time::Duration;
+use std::{future::Future, thread, time::Duration};
 
-use app_config::AppConfig;
+use app_config::{AppConfig, ConfigOverrides, LayeredConfig, UserAppConfig, UserGuiConfig};
 use chrono::{DateTime, Local};
 use components::{Menu, StatusErrorBar, StatusInfoBar};
-use daemon_client::{Requester, SocketClient};
+use daemon_client::{ErrorResponse, Requester};
 use iced::widget::{column, row};
-use iced::{Element, Task, Theme};
+use iced::{futures::channel::oneshot, Element, Task, Theme};
 use message::Message;
-use pages::{get_page, Page, PageData, PageView};
+use pages::{get_page, Page, PageData, PageView, ProjectEdit};
+use port_registry::check_ports;
+use project_settings::{ProjectSettings, ProjectSettingsError};
+use rfd::FileHandle;
+use task_runner::TaskRunner;
 use tokio::time::sleep;
+use utils::create_socket_client;
 
 mod components;
 mod message;
@@ -17,11 +22,15 @@ mod utils;
 
 pub fn main() -> iced::Result {
     tracing_subscriber::fmt::init();
-    let config = AppConfig::new().unwrap();
+    let LayeredConfig {
+        config, warnings, ..
+    } = LayeredConfig::load(&ConfigOverrides::default()).unwrap();
+    let window_size = (config.gui.window_width, config.gui.window_height);
     iced::application(App::title, App::update, App::view)
         .font(iced_fonts::BOOTSTRAP_FONT_BYTES)
         .theme(App::theme)
-        .run_with(|| App::new(config))
+        .window_size(window_size)
+        .run_with(|| App::new(config, warnings))
 }
 
 struct App {
@@ -29,6 +38,7 @@ struct App {
     config: AppConfig,
 
     requester: Requester,
+    task_runner: TaskRunner,
     project_names: Vec<String>,
     page_view: Box<dyn PageView>,
 
@@ -38,10 +48,12 @@ struct App {
 }
 
 impl App {
-    fn new(config: AppConfig) -> (Self, Task<Message>) {
-        let socket_client = SocketClient::new(&config.daemon_socket_path);
+    /// Warnings of the config replace the start message
+    fn new(config: AppConfig, config_warnings: Vec<String>) -> (Self, Task<Message>) {
+        let socket_client = create_socket_client(&config);
         let requester = Requester::new(socket_client, config.use_caller_env);
-        let theme = Theme::Dark;
+        let task_runner = TaskRunner::new(&config.tasks_dir, config.use_caller_env);
+        let theme = theme_from_name(&config.gui.theme);
 
         let page_view = get_page(Page::Projects);
 
@@ -50,11 +62,15 @@ impl App {
             config,
 
             requester,
+            task_runner,
             project_names: vec![],
             page_view,
 
             last_action_at: Local::now(),
-            last_action_result: Ok(String::from("Started")),
+            last_action_result: Ok(match config_warnings.is_empty() {
+                true => String::from("Started"),
+                false => format!("Warning: {}", config_warnings.join("; ")),
+            }),
             refresh_loop_error: None,
         };
 
@@ -74,6 +90,7 @@ impl App {
     fn create_page_data(&self) -> PageData {
         PageData {
             requester: self.requester.clone(),
+            task_runner: self.task_runner.clone(),
             theme: self.theme.clone(),
             config: self.config.clone(),
         }
@@ -96,6 +113,10 @@ impl App {
 
         match res.unwrap() {
             UpdateAction::None => Task::none(),
+            UpdateAction::Warning(warning) => {
+                self.last_action_result = Ok(warning);
+                Task::none()
+            }
             UpdateAction::Task(task) => task,
             UpdateAction::Refresh => {
                 match self.requester.get_project_names() {
@@ -115,7 +136,10 @@ impl App {
                 };
 
                 match is_refresh_loop {
-                    true => Task::perform(sleep(Duration::from_secs(1)), |_| Message::RefreshLoop),
+                    true => {
+                        let interval = Duration::from_millis(self.config.gui.refresh_interval_ms);
+                        Task::perform(sleep(interval), |_| Message::RefreshLoop)
+                    }
                     false => Task::none(),
                 }
             }
@@ -128,7 +152,11 @@ impl App {
         let menu = Menu::new(self.project_names.clone(), self.page_view.page());
         let body = row![menu, view];
 
-        let info_bar = StatusInfoBar::new(self.requester.client().socket_path.clone());
+        let info_bar = StatusInfoBar::new(
+            self.requester.client().address(),
+            self.config.current_context.clone(),
+            self.config.context_names(),
+        );
         let status: Result<String, String> = match &self.refresh_loop_error {
             Some(err) => Err(err.clone()),
             None => self.last_action_result.clone(),
@@ -143,6 +171,8 @@ impl App {
 enum UpdateAction {
     Task(Task<Message>),
     Refresh,
+    /// Nothing was changed, the warning replaces the success message
+    Warning(String),
     None,
 }
 
@@ -167,6 +197,25 @@ fn handle_message(app: &mut App, message: &Message) -> Result<UpdateAction, Stri
 
         Message::ThemeChanged(theme) => {
             app.theme = theme.clone();
+            app.config.gui.theme = theme.to_string();
+            app.page_view.update(message)?;
+
+            let mut user_config = UserAppConfig::load().map_err(|err| err.to_string())?;
+            user_config
+                .gui
+                .get_or_insert_with(UserGuiConfig::default)
+                .theme = Some(theme.to_string());
+            user_config.save().map_err(|err| err.to_string())?;
+
+            Ok(UpdateAction::Refresh)
+        }
+
+        Message::UseContext(name) => {
+            let mut config = app.config.clone();
+            config.use_context(name).map_err(|err| err.to_string())?;
+            connect(app, config);
+
+            app.page_view = get_page(Page::Projects);
             Ok(UpdateAction::Refresh)
         }
 
@@ -174,17 +223,25 @@ fn handle_message(app: &mut App, message: &Message) -> Result<UpdateAction, Stri
             Ok(iced::clipboard::write(data.clone()).into())
         }
 
-        Message::StartProject { project_name } => app
-            .requester
-            .start_project(project_name)
-            .map(|_| UpdateAction::Refresh)
-            .map_err(|err| err.to_string()),
+        Message::StartProject { project_name } => {
+            let name = project_name.clone();
+            Ok(
+                start_in_background(app, message, project_name, None, move |r| {
+                    r.start_project(&name).map(|_| ())
+                })
+                .into(),
+            )
+        }
 
-        Message::RestartProject { project_name } => app
-            .requester
-            .restart_project(project_name)
-            .map(|_| UpdateAction::Refresh)
-            .map_err(|err| err.to_string()),
+        Message::RestartProject { project_name } => {
+            let name = project_name.clone();
+            Ok(
+                start_in_background(app, message, project_name, None, move |r| {
+                    r.restart_project(&name).map(|_| ())
+                })
+                .into(),
+            )
+        }
 
         Message::StopProject { project_name } => app
             .requester
@@ -192,23 +249,61 @@ fn handle_message(app: &mut App, message: &Message) -> Result<UpdateAction, Stri
             .map(|_| UpdateAction::Refresh)
             .map_err(|err| err.to_string()),
 
-        Message::StartService {
+        Message::StartProfile {
             project_name,
-            service_name,
+            profile_name,
         } => app
             .requester
-            .start_service(project_name, service_name)
-            .map(|_| UpdateAction::Refresh)
-            .map_err(|err| err.to_string()),
+            .get_project_settings(project_name)
+            .map_err(|err| err.to_string())
+            .and_then(|json| {
+                ProjectSettings::try_from(json.as_str())
+                    .and_then(|settings| settings.profile_services(profile_name))
+                    .map_err(|err| err.to_string())
+            })
+            .map(|services| {
+                let name = project_name.clone();
+                start_in_background(
+                    app,
+                    message,
+                    project_name,
+                    Some(services.clone()),
+                    move |r| r.start_project_services(&name, &services).map(|_| ()),
+                )
+                .into()
+            }),
+
+        Message::StartFinished { action: _, result } => {
+            result.clone().map(|_| UpdateAction::Refresh)
+        }
+
+        Message::StartService {
+            project_name,
+            service_name,
+        } => {
+            let (name, service) = (project_name.clone(), service_name.clone());
+            let service_names = Some(vec![service_name.clone()]);
+            Ok(
+                start_in_background(app, message, project_name, service_names, move |r| {
+                    r.start_service(&name, &service).map(|_| ())
+                })
+                .into(),
+            )
+        }
 
         Message::RestartService {
             project_name,
             service_name,
-        } => app
-            .requester
-            .restart_service(project_name, service_name)
-            .map(|_| UpdateAction::Refresh)
-            .map_err(|err| err.to_string()),
+        } => {
+            let (name, service) = (project_name.clone(), service_name.clone());
+            let service_names = Some(vec![service_name.clone()]);
+            Ok(
+                start_in_background(app, message, project_name, service_names, move |r| {
+                    r.restart_service(&name, &service).map(|_| ())
+                })
+                .into(),
+            )
+        }
 
         Message::StopService {
             project_name,
@@ -223,5 +318,203 @@ fn handle_message(app: &mut App, message: &Message) -> Result<UpdateAction, Stri
                     project_name, service_name, err
                 )
             }),
+
+        Message::RunTask {
+            project_name,
+            task_name,
+        } => {
+            let json = app
+                .requester
+                .get_project_settings(project_name)
+                .map_err(|err| err.to_string())?;
+            let settings =
+                ProjectSettings::try_from(json.as_str()).map_err(|err| err.to_string())?;
+            settings.task(task_name).map_err(|err| err.to_string())?;
+
+            // task state is written to the tasks directory and picked up by the page refresh
+            let task_runner = app.task_runner.clone();
+            let task_name = task_name.clone();
+            thread::spawn(move || task_runner.run(&settings, &task_name));
+
+            Ok(UpdateAction::Refresh)
+        }
+
+        Message::PickProjectFile => {
+            let dialog = rfd::AsyncFileDialog::new()
+                .set_title("Select project settings")
+                .add_filter("Project settings", &["json", "toml"])
+                .pick_file();
+            Ok(pick_path(dialog, |settings_path| Message::UpsertProject {
+                settings_path,
+            })
+            .into())
+        }
+
+        Message::PickProjectDirectory => {
+            let dialog = rfd::AsyncFileDialog::new()
+                .set_title("Select project directory")
+                .pick_folder();
+            Ok(pick_path(dialog, |settings_path| Message::UpsertProject {
+                settings_path,
+            })
+            .into())
+        }
+
+        Message::PickWorkingDirectory => {
+            let dialog = rfd::AsyncFileDialog::new()
+                .set_title("Select working directory")
+                .pick_folder();
+            Ok(pick_path(dialog, |cwd| Message::EditProject(ProjectEdit::Cwd(cwd))).into())
+        }
+
+        Message::UpsertProject { settings_path } => {
+            let settings = ProjectSettings::find_parse_and_populate(Some(settings_path.clone()))
+                .map_err(|err| err.to_string())?;
+            let json = String::try_from(&settings).map_err(|err| err.to_string())?;
+            let project = app
+                .requester
+                .upsert_project(&json)
+                .map_err(|err| err.to_string())?;
+
+            app.page_view = get_page(Page::Project(project.name));
+            Ok(UpdateAction::Refresh)
+        }
+
+        Message::EditProject(_)
+        | Message::AskProjectRemoval
+        | Message::CancelProjectRemoval
+        | Message::EditSettings(_) => {
+            app.page_view.update(message)?;
+            Ok(UpdateAction::None)
+        }
+
+        Message::SaveProject {
+            project_name,
+            settings_json,
+        } => {
+            app.requester
+                .upsert_project(settings_json)
+                .map_err(|err| err.to_string())?;
+
+            app.page_view = get_page(Page::Project(project_name.clone()));
+            Ok(UpdateAction::Refresh)
+        }
+
+        Message::ReloadProject { project_name } => {
+            let json = app
+                .requester
+                .get_project_settings(project_name)
+                .map_err(|err| err.to_string())?;
+            let settings = match ProjectSettings::try_from(json.as_str())
+                .and_then(|settings| settings.reload())
+            {
+                Err(err @ ProjectSettingsError::SourceNotFound { .. }) => {
+                    return Ok(UpdateAction::Warning(format!(
+                        "Warning: {}, the project was left unchanged",
+                        err
+                    )))
+                }
+                settings => settings
+                    .and_then(|settings| String::try_from(&settings))
+                    .map_err(|err| err.to_string())?,
+            };
+            app.requester
+                .upsert_project(&settings)
+                .map_err(|err| err.to_string())?;
+
+            Ok(UpdateAction::Refresh)
+        }
+
+        Message::RemoveProject { project_name } => {
+            app.requester
+                .remove_project(project_name)
+                .map_err(|err| err.to_string())?;
+
+            app.page_view = get_page(Page::Projects);
+            Ok(UpdateAction::Refresh)
+        }
+
+        Message::SaveSettings(user_config) => {
+            save_settings(app, user_config)?;
+            Ok(UpdateAction::Refresh)
+        }
     }
 }
+
+/// Saves the config and applies it, the daemon connection is created again as its settings may have changed
+fn save_settings(app: &mut App, user_config: &UserAppConfig) -> Result<(), String> {
+    let mut config =
+        AppConfig::from_user_config(user_config.clone()).map_err(|err| err.to_string())?;
+    user_config.save().map_err(|err| err.to_string())?;
+
+    // context switched at runtime is kept unless it was removed from the config
+    let _ = config.use_context(&app.config.current_context);
+
+    app.theme = theme_from_name(&config.gui.theme);
+    connect(app, config);
+    app.page_view = get_page(Page::Settings);
+    Ok(())
+}
+
+/// Applies the config and creates the daemon connection of its current context
+fn connect(app: &mut App, config: AppConfig) {
+    let socket_client = create_socket_client(&config);
+    app.requester = Requester::new(socket_client, config.use_caller_env);
+    app.task_runner = TaskRunner::new(&config.tasks_dir, config.use_caller_env);
+    app.config = config;
+}
+
+/// Finds the theme by the name it is displayed with, unknown names fall back to the dark theme
+fn theme_from_name(name: &str) -> Theme {
+    Theme::ALL
+        .iter()
+        .find(|theme| theme.to_string() == name)
+        .cloned()
+        .unwrap_or(Theme::Dark)
+}
+
+/// Opens the file dialog and maps the selected path to the message, nothing happens when the dialog is closed
+fn pick_path(
+    dialog: impl Future<Output = Option<FileHandle>> + Send + 'static,
+    to_message: impl Fn(String) -> Message + Send + 'static,
+) -> Task<Message> {
+    Task::perform(dialog, move |handle| {
+        handle.map(|handle| to_message(handle.path().to_string_lossy().to_string()))
+    })
+    .and_then(Task::done)
+}
+
+/// Checks that the declared ports are free, runs the 'before_start' tasks of the services about to be started
+/// and starts them on a worker thread, the result is reported by 'StartFinished' with the original message
+fn start_in_background<F>(
+    app: &App,
+    message: &Message,
+    project_name: &str,
+    service_names: Option<Vec<String>>,
+    start: F,
+) -> Task<Message>
+where
+    F: FnOnce(&Requester) -> Result<(), ErrorResponse> + Send + 'static,
+{
+    let project_name = String::from(project_name);
+    let requester = app.requester.clone();
+    let task_runner = app.task_runner.clone();
+    let (sender, receiver) = oneshot::channel();
+    thread::spawn(move || {
+        let result = check_ports(&requester, &project_name, service_names.as_deref())
+            .map_err(|err| err.to_string())
+            .and_then(|_| {
+                task_runner
+                    .run_before_start(&requester, &project_name, service_names.as_deref())
+                    .map_err(|err| err.to_string())
+            })
+            .and_then(|_| start(&requester).map_err(|err| err.to_string()));
+        let _ = sender.send(result);
+    });
+
+    let action = Box::new(message.clone());
+    Task::perform(receiver, move |result| Message::StartFinished {
+        action: action.clone(),
+        result: result.unwrap_or_else(|_| Err(String::from("the start was interrupted"))),
+    })
+}
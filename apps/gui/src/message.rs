@@ -1,12 +1,14 @@
+use app_config::UserAppConfig;
 use iced::Theme;
 
-use crate::pages::Page;
+use crate::pages::{Page, ProjectEdit, SettingsEdit};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Message {
     GotoPage(Page),
     OpenUrl(String),
     ThemeChanged(Theme),
+    UseContext(String),
     CopyToClipboard {
         name: String,
         data: String,
@@ -21,6 +23,10 @@ pub enum Message {
     StopProject {
         project_name: String,
     },
+    StartProfile {
+        project_name: String,
+        profile_name: String,
+    },
     StartService {
         project_name: String,
         service_name: String,
@@ -33,6 +39,36 @@ pub enum Message {
         project_name: String,
         service_name: String,
     },
+    RunTask {
+        project_name: String,
+        task_name: String,
+    },
+    PickProjectFile,
+    PickProjectDirectory,
+    PickWorkingDirectory,
+    UpsertProject {
+        settings_path: String,
+    },
+    EditProject(ProjectEdit),
+    SaveProject {
+        project_name: String,
+        settings_json: String,
+    },
+    ReloadProject {
+        project_name: String,
+    },
+    AskProjectRemoval,
+    CancelProjectRemoval,
+    RemoveProject {
+        project_name: String,
+    },
+    EditSettings(SettingsEdit),
+    SaveSettings(Box<UserAppConfig>),
+    /// Start of the action which was run in the background finished
+    StartFinished {
+        action: Box<Message>,
+        result: Result<(), String>,
+    },
 }
 
 impl Message {
@@ -41,39 +77,110 @@ impl Message {
             Message::GotoPage(page) => format!("Navigated to the page '{}'", page),
             Message::OpenUrl(url) => format!("Opened the external url '{}'", url),
             Message::ThemeChanged(theme) => format!("Changed theme to '{}'", theme),
+            Message::UseContext(name) => format!("Switched to the context '{}'", name),
             Message::RefreshLoop => String::from("Performed the refresh loop"),
             Message::StartProject { project_name } => {
-                format!("Started the project '{}'", project_name)
+                format!("Starting the project '{}'", project_name)
             }
             Message::RestartProject { project_name } => {
-                format!("Restarted the project '{}'", project_name)
+                format!("Restarting the project '{}'", project_name)
             }
             Message::StopProject { project_name } => {
                 format!("Stopped the project '{}'", project_name)
             }
+            Message::StartProfile {
+                project_name,
+                profile_name,
+            } => format!(
+                "Starting the profile '{}' of the project '{}'",
+                profile_name, project_name
+            ),
             Message::StartService {
                 project_name,
                 service_name,
-            } => format!("Started the service '{}/{}'", project_name, service_name),
+            } => format!("Starting the service '{}/{}'", project_name, service_name),
             Message::RestartService {
                 project_name,
                 service_name,
-            } => format!("Restarted the service '{}/{}'", project_name, service_name),
+            } => format!("Restarting the service '{}/{}'", project_name, service_name),
             Message::StopService {
                 project_name,
                 service_name,
             } => format!("Restarted the service '{}/{}'", project_name, service_name),
+            Message::RunTask {
+                project_name,
+                task_name,
+            } => format!(
+                "Started the task '{}' of the project '{}'",
+                task_name, project_name
+            ),
             Message::CopyToClipboard { name, data: _ } => {
                 format!("Copied '{}' to the clipboard.", name)
             }
+            Message::PickProjectFile => String::from("Opened the settings file picker"),
+            Message::PickProjectDirectory | Message::PickWorkingDirectory => {
+                String::from("Opened the directory picker")
+            }
+            Message::UpsertProject { settings_path } => {
+                format!("Upserted the project from '{}'", settings_path)
+            }
+            Message::EditProject(_) => String::from("Edited the project settings"),
+            Message::SaveProject {
+                project_name,
+                settings_json: _,
+            } => format!("Saved the project '{}'", project_name),
+            Message::ReloadProject { project_name } => {
+                format!(
+                    "Reloaded the project '{}' from its settings file",
+                    project_name
+                )
+            }
+            Message::AskProjectRemoval => String::from("Confirm the removal of the project"),
+            Message::CancelProjectRemoval => String::from("Cancelled the removal of the project"),
+            Message::RemoveProject { project_name } => {
+                format!("Removed the project '{}'", project_name)
+            }
+            Message::EditSettings(_) => String::from("Edited the settings"),
+            Message::SaveSettings(_) => String::from("Saved the settings"),
+            Message::StartFinished { action, result: _ } => action.to_finished_message(),
+        }
+    }
+
+    /// Message of a start run in the background once it finished
+    fn to_finished_message(&self) -> String {
+        match self {
+            Message::StartProject { project_name } => {
+                format!("Started the project '{}'", project_name)
+            }
+            Message::RestartProject { project_name } => {
+                format!("Restarted the project '{}'", project_name)
+            }
+            Message::StartProfile {
+                project_name,
+                profile_name,
+            } => format!(
+                "Started the profile '{}' of the project '{}'",
+                profile_name, project_name
+            ),
+            Message::StartService {
+                project_name,
+                service_name,
+            } => format!("Started the service '{}/{}'", project_name, service_name),
+            Message::RestartService {
+                project_name,
+                service_name,
+            } => format!("Restarted the service '{}/{}'", project_name, service_name),
+            _ => self.to_success_message(),
         }
     }
 
     pub fn to_error_message(&self, error: &str) -> String {
         let message = match self {
+            Message::StartFinished { action, result: _ } => return action.to_error_message(error),
             Message::GotoPage(page) => format!("Unable to navigate to the page '{}'", page),
             Message::OpenUrl(url) => format!("Opened the external url '{}'", url),
-            Message::ThemeChanged(theme) => format!("Changed theme to '{}'", theme),
+            Message::ThemeChanged(theme) => format!("Unable to save the theme '{}'", theme),
+            Message::UseContext(name) => format!("Unable to switch to the context '{}'", name),
             Message::RefreshLoop => String::from("Unable to perform the refresh loop"),
             Message::StartProject { project_name } => {
                 format!("Unable to start the project '{}'", project_name)
@@ -84,6 +191,13 @@ impl Message {
             Message::StopProject { project_name } => {
                 format!("Unable to stop the project '{}'", project_name)
             }
+            Message::StartProfile {
+                project_name,
+                profile_name,
+            } => format!(
+                "Unable to start the profile '{}' of the project '{}'",
+                profile_name, project_name
+            ),
             Message::StartService {
                 project_name,
                 service_name,
@@ -105,9 +219,40 @@ impl Message {
                 "Unable to stop the service '{}/{}'",
                 project_name, service_name
             ),
+            Message::RunTask {
+                project_name,
+                task_name,
+            } => format!(
+                "Unable to run the task '{}' of the project '{}'",
+                task_name, project_name
+            ),
             Message::CopyToClipboard { name, data: _ } => {
                 format!("Unable to copy '{}' to the clipboard.", name)
             }
+            Message::PickProjectFile => String::from("Unable to open the settings file picker"),
+            Message::PickProjectDirectory | Message::PickWorkingDirectory => {
+                String::from("Unable to open the directory picker")
+            }
+            Message::UpsertProject { settings_path } => {
+                format!("Unable to upsert the project from '{}'", settings_path)
+            }
+            Message::EditProject(_) => String::from("Unable to edit the project settings"),
+            Message::SaveProject {
+                project_name,
+                settings_json: _,
+            } => format!("Unable to save the project '{}'", project_name),
+            Message::ReloadProject { project_name } => format!(
+                "Unable to reload the project '{}' from its settings file",
+                project_name
+            ),
+            Message::AskProjectRemoval | Message::CancelProjectRemoval => {
+                String::from("Unable to change the removal confirmation")
+            }
+            Message::RemoveProject { project_name } => {
+                format!("Unable to remove the project '{}'", project_name)
+            }
+            Message::EditSettings(_) => String::from("Unable to edit the settings"),
+            Message::SaveSettings(_) => String::from("Unable to save the settings"),
         };
 
         format!("{}, error: {}", message, error)
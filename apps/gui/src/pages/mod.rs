@@ -3,13 +3,18 @@ use std::fmt::Display;
 use app_config::AppConfig;
 use daemon_client::Requester;
 use iced::{Element, Theme};
+pub use project_editor_page::ProjectEdit;
+use project_editor_page::ProjectEditorPage;
 use project_page::ProjectPage;
 use projects_page::ProjectsPage;
 use service_page::ServicePage;
+pub use settings_page::SettingsEdit;
 use settings_page::SettingsPage;
+use task_runner::TaskRunner;
 
 use crate::message::Message;
 
+mod project_editor_page;
 mod project_page;
 mod projects_page;
 mod service_page;
@@ -20,6 +25,8 @@ pub enum Page {
     Projects,
     Project(String),
     Service(String, String),
+    /// Editor of an existing project or of a new one when the name is not set
+    ProjectEditor(Option<String>),
     Settings,
 }
 
@@ -31,6 +38,10 @@ impl Display for Page {
             Page::Service(project, service) => {
                 f.write_str(&format!("Service - {}/{}", project, service))
             }
+            Page::ProjectEditor(Some(project)) => {
+                f.write_str(&format!("Edit project - {}", project))
+            }
+            Page::ProjectEditor(None) => f.write_str("New project"),
             Page::Settings => f.write_str("Settings"),
         }
     }
@@ -42,12 +53,17 @@ pub trait PageView {
     }
     fn page(&self) -> Page;
     fn refresh(&mut self, data: PageData) -> Result<(), String>;
+    /// Handles messages which change only the state of the page
+    fn update(&mut self, _message: &Message) -> Result<(), String> {
+        Ok(())
+    }
     fn view(&self) -> Element<'_, Message>;
 }
 
 #[derive(Clone)]
 pub struct PageData {
     pub requester: Requester,
+    pub task_runner: TaskRunner,
     pub theme: Theme,
     pub config: AppConfig,
 }
@@ -57,6 +73,7 @@ pub fn get_page(page_transition: Page) -> Box<dyn PageView> {
         Page::Projects => Box::new(ProjectsPage::new()),
         Page::Project(project) => Box::new(ProjectPage::new(project)),
         Page::Service(project, service) => Box::new(ServicePage::new(project, service)),
+        Page::ProjectEditor(project) => Box::new(ProjectEditorPage::new(project)),
         Page::Settings => Box::new(SettingsPage::new()),
     }
 }
@@ -0,0 +1,489 @@
+use std::{collections::HashMap, path::Path};
+
+use iced::widget::{column, container, row, scrollable, text, text_input, Column};
+use iced::{Alignment, Element, Padding};
+use iced_fonts::Bootstrap;
+use project_settings::{try_parse, EnvValue, ProjectSettings, ServiceSettings};
+
+use crate::components::{PageTitle, Section, TextButton};
+use crate::message::Message;
+
+use super::{Page, PageData, PageView};
+
+/// Change of a single field of the edited project, env changes target the project env when
+/// the service index is not set
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProjectEdit {
+    Name(String),
+    Cwd(String),
+    AddService,
+    RemoveService(usize),
+    ServiceName(usize, String),
+    ServicePwd(usize, String),
+    AddArgument(usize),
+    Argument(usize, usize, String),
+    RemoveArgument(usize, usize),
+    AddEnv(Option<usize>),
+    EnvKey(Option<usize>, usize, String),
+    EnvValue(Option<usize>, usize, String),
+    RemoveEnv(Option<usize>, usize),
+}
+
+pub struct ProjectEditorPage {
+    project_name: Option<String>,
+    draft: Option<ProjectDraft>,
+}
+
+impl ProjectEditorPage {
+    pub fn new(project_name: Option<String>) -> Self {
+        let draft = match project_name {
+            Some(_) => None,
+            None => Some(ProjectDraft::new()),
+        };
+
+        Self {
+            project_name,
+            draft,
+        }
+    }
+}
+
+impl PageView for ProjectEditorPage {
+    fn page(&self) -> Page {
+        Page::ProjectEditor(self.project_name.clone())
+    }
+
+    fn refresh(&mut self, data: PageData) -> Result<(), String> {
+        // settings are loaded only once so the refresh loop does not overwrite the edited values
+        if let (None, Some(project_name)) = (&self.draft, &self.project_name) {
+            let json = data
+                .requester
+                .get_project_settings(project_name)
+                .map_err(|err| err.to_string())?;
+            let settings =
+                ProjectSettings::try_from(json.as_str()).map_err(|err| err.to_string())?;
+            self.draft = Some(ProjectDraft::from(settings));
+        }
+
+        Ok(())
+    }
+
+    fn update(&mut self, message: &Message) -> Result<(), String> {
+        if let (Message::EditProject(edit), Some(draft)) = (message, self.draft.as_mut()) {
+            draft.apply(edit.clone());
+        }
+
+        Ok(())
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        let draft = match &self.draft {
+            Some(draft) => draft,
+            None => return column![].into(),
+        };
+
+        let validation = draft.validate();
+
+        let save_message = validation.as_ref().ok().map(|json| Message::SaveProject {
+            project_name: draft.name.clone(),
+            settings_json: json.clone(),
+        });
+        let cancel_page = match &self.project_name {
+            Some(project_name) => Page::Project(project_name.clone()),
+            None => Page::Projects,
+        };
+        let buttons = row![
+            TextButton::new("Save", save_message).icon(Bootstrap::Floppy),
+            TextButton::new("Cancel", Some(Message::GotoPage(cancel_page))).icon(Bootstrap::XLg),
+        ]
+        .spacing(10);
+
+        let title: Element<'_, Message> = PageTitle::new(self.title())
+            .additional_content(buttons.into())
+            .into();
+
+        let validation_text = match &validation {
+            Ok(_) => text("Settings are valid").style(text::success),
+            Err(err) => text(format!("Settings are invalid: {}", err)).style(text::danger),
+        };
+
+        // project name is the identifier of the project so it can be set only for new projects
+        let mut name_input = text_input("project-name", &draft.name);
+        if self.project_name.is_none() {
+            name_input = name_input.on_input(|name| Message::EditProject(ProjectEdit::Name(name)));
+        }
+
+        let cwd_row = row![
+            text_input("/absolute/path/to/the/project", &draft.cwd)
+                .on_input(|cwd| Message::EditProject(ProjectEdit::Cwd(cwd))),
+            TextButton::new("Browse", Some(Message::PickWorkingDirectory))
+                .icon(Bootstrap::FolderFill),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
+        let mut form = column![
+            title,
+            validation_text,
+            field("Name", name_input.into()),
+            field("Working directory", cwd_row.into()),
+            field("Environment", env_view(None, &draft.env)),
+        ]
+        .spacing(16);
+
+        for (index, service) in draft.services.iter().enumerate() {
+            form = form.push(service_view(index, service));
+        }
+
+        form = form.push(TextButton::new(
+            "Add service",
+            Some(Message::EditProject(ProjectEdit::AddService)),
+        ));
+
+        let content = scrollable(container(form).padding(Padding::default().right(16)));
+        Section::new(content.into()).into()
+    }
+}
+
+#[derive(Debug)]
+struct ProjectDraft {
+    name: String,
+    cwd: String,
+    env: Vec<(String, String)>,
+    services: Vec<ServiceDraft>,
+    /// parsed settings whose fields that are not edited are kept as they are
+    base: Option<ProjectSettings>,
+}
+
+#[derive(Debug)]
+struct ServiceDraft {
+    name: String,
+    pwd: String,
+    command: Vec<String>,
+    env: Vec<(String, String)>,
+    base: Option<ServiceSettings>,
+}
+
+impl ProjectDraft {
+    fn new() -> Self {
+        Self {
+            name: String::new(),
+            cwd: String::new(),
+            env: vec![],
+            services: vec![ServiceDraft::new()],
+            base: None,
+        }
+    }
+
+    fn apply(&mut self, edit: ProjectEdit) {
+        match edit {
+            ProjectEdit::Name(name) => self.name = name,
+            ProjectEdit::Cwd(cwd) => self.cwd = cwd,
+            ProjectEdit::AddService => self.services.push(ServiceDraft::new()),
+            ProjectEdit::RemoveService(index) => {
+                if index < self.services.len() {
+                    self.services.remove(index);
+                }
+            }
+            ProjectEdit::ServiceName(index, name) => {
+                if let Some(service) = self.services.get_mut(index) {
+                    service.name = name;
+                }
+            }
+            ProjectEdit::ServicePwd(index, pwd) => {
+                if let Some(service) = self.services.get_mut(index) {
+                    service.pwd = pwd;
+                }
+            }
+            ProjectEdit::AddArgument(index) => {
+                if let Some(service) = self.services.get_mut(index) {
+                    service.command.push(String::new());
+                }
+            }
+            ProjectEdit::Argument(index, argument_index, argument) => {
+                if let Some(value) = self
+                    .services
+                    .get_mut(index)
+                    .and_then(|service| service.command.get_mut(argument_index))
+                {
+                    *value = argument;
+                }
+            }
+            ProjectEdit::RemoveArgument(index, argument_index) => {
+                if let Some(service) = self.services.get_mut(index) {
+                    if argument_index < service.command.len() {
+                        service.command.remove(argument_index);
+                    }
+                }
+            }
+            ProjectEdit::AddEnv(service_index) => {
+                if let Some(env) = self.env_mut(service_index) {
+                    env.push((String::new(), String::new()));
+                }
+            }
+            ProjectEdit::EnvKey(service_index, env_index, key) => {
+                if let Some(entry) = self
+                    .env_mut(service_index)
+                    .and_then(|env| env.get_mut(env_index))
+                {
+                    entry.0 = key;
+                }
+            }
+            ProjectEdit::EnvValue(service_index, env_index, value) => {
+                if let Some(entry) = self
+                    .env_mut(service_index)
+                    .and_then(|env| env.get_mut(env_index))
+                {
+                    entry.1 = value;
+                }
+            }
+            ProjectEdit::RemoveEnv(service_index, env_index) => {
+                if let Some(env) = self.env_mut(service_index) {
+                    if env_index < env.len() {
+                        env.remove(env_index);
+                    }
+                }
+            }
+        }
+    }
+
+    fn env_mut(&mut self, service_index: Option<usize>) -> Option<&mut Vec<(String, String)>> {
+        match service_index {
+            Some(index) => self.services.get_mut(index).map(|service| &mut service.env),
+            None => Some(&mut self.env),
+        }
+    }
+
+    /// Returns json of the settings when they are valid, same validation as for the settings file is used
+    fn validate(&self) -> Result<String, String> {
+        if self.cwd.is_empty() {
+            return Err(String::from("working directory is empty"));
+        }
+        if !Path::new(&self.cwd).is_absolute() {
+            return Err(String::from("working directory must be an absolute path"));
+        }
+
+        let mut settings = self.base.clone().unwrap_or_else(|| ProjectSettings {
+            name: String::new(),
+            cwd: String::new(),
+            services: vec![],
+            env: HashMap::new(),
+            profiles: HashMap::new(),
+            tasks: vec![],
+            source_path: None,
+        });
+
+        settings.name = self.name.clone();
+        settings.cwd = self.cwd.clone();
+        settings.env = to_env_map(&self.env)?;
+        settings.services = self
+            .services
+            .iter()
+            .map(ServiceDraft::to_settings)
+            .collect::<Result<_, _>>()?;
+
+        let json = String::try_from(&settings).map_err(|err| err.to_string())?;
+        try_parse(&json).map_err(|err| err.to_string())?;
+
+        Ok(json)
+    }
+}
+
+impl From<ProjectSettings> for ProjectDraft {
+    fn from(value: ProjectSettings) -> Self {
+        Self {
+            name: value.name.clone(),
+            cwd: value.cwd.clone(),
+            env: to_env_rows(&value.env),
+            services: value
+                .services
+                .iter()
+                .cloned()
+                .map(ServiceDraft::from)
+                .collect(),
+            base: Some(value),
+        }
+    }
+}
+
+impl ServiceDraft {
+    fn new() -> Self {
+        Self {
+            name: String::new(),
+            pwd: String::new(),
+            command: vec![String::new()],
+            env: vec![],
+            base: None,
+        }
+    }
+
+    fn to_settings(&self) -> Result<ServiceSettings, String> {
+        let mut settings = self.base.clone().unwrap_or_else(|| ServiceSettings {
+            name: String::new(),
+            pwd: None,
+            command: vec![],
+            env: HashMap::new(),
+            before_start: vec![],
+            ports: vec![],
+        });
+
+        settings.name = self.name.clone();
+        settings.pwd = match self.pwd.is_empty() {
+            true => None,
+            false => Some(self.pwd.clone()),
+        };
+        // empty arguments are left over from adding new ones and are not meant to be passed
+        settings.command = self
+            .command
+            .iter()
+            .filter(|argument| !argument.is_empty())
+            .cloned()
+            .collect();
+        settings.env = to_env_map(&self.env)?;
+
+        Ok(settings)
+    }
+}
+
+impl From<ServiceSettings> for ServiceDraft {
+    fn from(value: ServiceSettings) -> Self {
+        Self {
+            name: value.name.clone(),
+            pwd: value.pwd.clone().unwrap_or_default(),
+            command: value.command.clone(),
+            env: to_env_rows(&value.env),
+            base: Some(value),
+        }
+    }
+}
+
+fn to_env_rows(env: &HashMap<String, EnvValue>) -> Vec<(String, String)> {
+    let mut rows: Vec<(String, String)> = env
+        .iter()
+        .map(|(key, value)| {
+            let value = match value {
+                EnvValue::Str(value) => value.clone(),
+                EnvValue::Num(value) => value.to_string(),
+            };
+            (key.clone(), value)
+        })
+        .collect();
+    rows.sort();
+    rows
+}
+
+fn to_env_map(rows: &[(String, String)]) -> Result<HashMap<String, EnvValue>, String> {
+    let mut env = HashMap::new();
+    for (key, value) in rows.iter() {
+        if key.is_empty() {
+            return Err(String::from("environment variable name is empty"));
+        }
+        if env
+            .insert(key.clone(), EnvValue::Str(value.clone()))
+            .is_some()
+        {
+            return Err(format!(
+                "environment variable '{}' is declared more than once",
+                key
+            ));
+        }
+    }
+    Ok(env)
+}
+
+fn service_view(index: usize, service: &ServiceDraft) -> Element<'_, Message> {
+    let title = row![
+        text(format!("Service #{}", index + 1)).size(22),
+        TextButton::new(
+            "Remove service",
+            Some(Message::EditProject(ProjectEdit::RemoveService(index))),
+        )
+        .icon(Bootstrap::Trash)
+        .danger(),
+    ]
+    .spacing(12)
+    .align_y(Alignment::Center);
+
+    let name_input = text_input("service-name", &service.name)
+        .on_input(move |name| Message::EditProject(ProjectEdit::ServiceName(index, name)));
+    let pwd_input = text_input("relative to the project working directory", &service.pwd)
+        .on_input(move |pwd| Message::EditProject(ProjectEdit::ServicePwd(index, pwd)));
+
+    let mut arguments = Column::new().spacing(6);
+    for (argument_index, argument) in service.command.iter().enumerate() {
+        arguments = arguments.push(
+            row![
+                text_input("argument", argument).on_input(move |argument| {
+                    Message::EditProject(ProjectEdit::Argument(index, argument_index, argument))
+                }),
+                TextButton::new(
+                    "",
+                    Some(Message::EditProject(ProjectEdit::RemoveArgument(
+                        index,
+                        argument_index,
+                    ))),
+                )
+                .icon(Bootstrap::XLg),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+        );
+    }
+    arguments = arguments.push(
+        TextButton::new(
+            "Add argument",
+            Some(Message::EditProject(ProjectEdit::AddArgument(index))),
+        )
+        .icon(Bootstrap::PlusLg),
+    );
+
+    column![
+        title,
+        field("Name", name_input.into()),
+        field("Working directory", pwd_input.into()),
+        field("Command", arguments.into()),
+        field("Environment", env_view(Some(index), &service.env)),
+    ]
+    .spacing(12)
+    .padding(Padding::default().left(16))
+    .into()
+}
+
+fn env_view(service_index: Option<usize>, env: &[(String, String)]) -> Element<'_, Message> {
+    let mut rows = Column::new().spacing(6);
+    for (env_index, (key, value)) in env.iter().enumerate() {
+        rows = rows.push(
+            row![
+                text_input("NAME", key).on_input(move |key| {
+                    Message::EditProject(ProjectEdit::EnvKey(service_index, env_index, key))
+                }),
+                text_input("value", value).on_input(move |value| {
+                    Message::EditProject(ProjectEdit::EnvValue(service_index, env_index, value))
+                }),
+                TextButton::new(
+                    "",
+                    Some(Message::EditProject(ProjectEdit::RemoveEnv(
+                        service_index,
+                        env_index,
+                    ))),
+                )
+                .icon(Bootstrap::XLg),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+        );
+    }
+
+    rows.push(
+        TextButton::new(
+            "Add variable",
+            Some(Message::EditProject(ProjectEdit::AddEnv(service_index))),
+        )
+        .icon(Bootstrap::PlusLg),
+    )
+    .into()
+}
+
+fn field<'a>(label: &'a str, content: Element<'a, Message>) -> Element<'a, Message> {
+    column![text(label).size(16), content].spacing(6).into()
+}
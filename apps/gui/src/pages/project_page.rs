@@ -1,11 +1,15 @@
-use daemon_client::ProjectInfo;
+use daemon_client::{ProjectInfo, ServiceStatus};
 use iced::widget::{column, container, row, scrollable, text};
-use iced::{Element, Length};
+use iced::{Alignment, Element, Length};
+use iced_fonts::Bootstrap;
+use process_metrics::MetricsCollector;
 use project_settings::ProjectSettings;
+use std::collections::HashMap;
+use task_runner::TaskRun;
 
 use crate::components::{
     CopyToClipboardButton, InfoTable, PageTitle, ProjectActionButtons, Section,
-    ServiceActionButtons,
+    ServiceActionButtons, TaskActionButtons, TextButton,
 };
 use crate::message::Message;
 
@@ -14,6 +18,10 @@ use super::{Page, PageData, PageView};
 pub struct ProjectPage {
     project_name: String,
     project: Option<(ProjectInfo, String)>,
+    tasks: Vec<(String, Option<TaskRun>, String)>,
+    metrics: MetricsCollector,
+    resources: HashMap<String, String>,
+    confirming_removal: bool,
 }
 
 impl ProjectPage {
@@ -21,6 +29,10 @@ impl ProjectPage {
         Self {
             project_name,
             project: None,
+            tasks: vec![],
+            metrics: MetricsCollector::new(),
+            resources: HashMap::new(),
+            confirming_removal: false,
         }
     }
 }
@@ -39,13 +51,52 @@ impl PageView for ProjectPage {
                 Ok((project, settings))
             });
 
-        match result {
-            Ok(project) => {
-                self.project = Some(project);
-                Ok(())
+        let (project, settings) = result.map_err(|err| err.to_string())?;
+
+        self.resources.clear();
+        for service in project.services.iter() {
+            let metrics = match service.status {
+                ServiceStatus::RUNNING => self.metrics.collect(service.pid),
+                _ => None,
+            };
+            if let Some(metrics) = metrics {
+                let resources = format!(
+                    "{} · {} · {} thr · {} fds",
+                    metrics.format_cpu(),
+                    metrics.format_rss(),
+                    metrics.threads,
+                    metrics.fds
+                );
+                self.resources.insert(service.name.clone(), resources);
             }
-            Err(err) => Err(err.to_string()),
         }
+
+        let tasks = ProjectSettings::try_from(settings.as_str())
+            .map(|settings| settings.tasks)
+            .unwrap_or_default();
+        self.tasks = tasks
+            .iter()
+            .map(|task| {
+                let run = data.task_runner.last_run(&self.project_name, &task.name)?;
+                let logfile_path = data
+                    .task_runner
+                    .logfile_path(&self.project_name, &task.name);
+                Ok((task.name.clone(), run, logfile_path))
+            })
+            .collect::<Result<_, task_runner::TaskRunnerError>>()
+            .map_err(|err| err.to_string())?;
+
+        self.project = Some((project, settings));
+        Ok(())
+    }
+
+    fn update(&mut self, message: &Message) -> Result<(), String> {
+        match message {
+            Message::AskProjectRemoval => self.confirming_removal = true,
+            Message::CancelProjectRemoval => self.confirming_removal = false,
+            _ => {}
+        }
+        Ok(())
     }
 
     fn view(&self) -> Element<'_, Message> {
@@ -57,10 +108,17 @@ impl PageView for ProjectPage {
 
         let mut names = vec![];
         let mut statuses = vec![];
+        let mut resources = vec![];
         let mut actions = vec![];
         for service in project.services.iter() {
             names.push(service.name.clone());
             statuses.push(service.status.to_string());
+            resources.push(
+                self.resources
+                    .get(&service.name)
+                    .cloned()
+                    .unwrap_or_default(),
+            );
             actions.push(ServiceActionButtons::new(&project.name, service).into());
         }
 
@@ -69,8 +127,17 @@ impl PageView for ProjectPage {
 
         let copy_button =
             CopyToClipboardButton::new(String::from("project settings"), pretty_settings);
-        let action_buttons = ProjectActionButtons::new(project);
-        let button_line = row![action_buttons, copy_button].spacing(10).into();
+        let parsed_settings = ProjectSettings::try_from(settings.as_str()).ok();
+        let profiles = parsed_settings
+            .as_ref()
+            .map(|settings| settings.profile_names())
+            .unwrap_or_default();
+        let has_source = parsed_settings.is_some_and(|settings| settings.source_path.is_some());
+        let action_buttons = ProjectActionButtons::new(project).profiles(profiles);
+        let button_line = row![action_buttons, copy_button, self.edit_buttons(has_source)]
+            .spacing(10)
+            .align_y(Alignment::Center)
+            .into();
 
         let title = PageTitle::new(self.title())
             .additional_content(button_line)
@@ -78,13 +145,96 @@ impl PageView for ProjectPage {
         let name_to_message = |service: &str| {
             Message::GotoPage(Page::Service(project.name.clone(), service.to_string()))
         };
-        let table = InfoTable::new(title, names, statuses, actions, name_to_message);
+        let table = InfoTable::new(title, names, statuses, actions, name_to_message)
+            .additional_column("RESOURCES", resources);
 
         let project_view = container(table).height(Length::Fill).width(Length::Fill);
 
         view = view.push(Section::new(project_view.into()));
+
+        if !self.tasks.is_empty() {
+            view = view.push(Section::new(self.tasks_view()));
+        }
         view = view.push(Section::new(json_view.into()));
 
         view.into()
     }
 }
+
+impl ProjectPage {
+    fn edit_buttons(&self, has_source: bool) -> Element<'_, Message> {
+        // projects created in the editor or imported from other files have no settings file to reload from
+        let reload_message = has_source.then(|| Message::ReloadProject {
+            project_name: self.project_name.clone(),
+        });
+        let reload_button = TextButton::new("Reload", reload_message).icon(Bootstrap::ArrowRepeat);
+
+        let edit_button = TextButton::new(
+            "Edit",
+            Some(Message::GotoPage(Page::ProjectEditor(Some(
+                self.project_name.clone(),
+            )))),
+        )
+        .icon(Bootstrap::PencilSquare);
+
+        let buttons = match self.confirming_removal {
+            false => row![
+                reload_button,
+                edit_button,
+                TextButton::new("Remove", Some(Message::AskProjectRemoval))
+                    .icon(Bootstrap::Trash)
+                    .danger(),
+            ],
+            true => row![
+                text(format!(
+                    "Stop and remove '{}' with its logs?",
+                    self.project_name
+                ))
+                .size(16),
+                TextButton::new(
+                    "Remove",
+                    Some(Message::RemoveProject {
+                        project_name: self.project_name.clone(),
+                    }),
+                )
+                .icon(Bootstrap::Trash)
+                .danger(),
+                TextButton::new("Cancel", Some(Message::CancelProjectRemoval)),
+            ],
+        };
+
+        buttons.spacing(10).align_y(Alignment::Center).into()
+    }
+
+    fn tasks_view(&self) -> Element<'_, Message> {
+        let mut names = vec![];
+        let mut statuses = vec![];
+        let mut actions = vec![];
+        for (task_name, run, _) in self.tasks.iter() {
+            names.push(task_name.clone());
+            statuses.push(match run {
+                Some(run) => match run.exit_code {
+                    Some(code) => format!("{} ({})", run.status, code),
+                    None => run.status.to_string(),
+                },
+                None => String::from("Never run"),
+            });
+            actions
+                .push(TaskActionButtons::new(&self.project_name, task_name, run.as_ref()).into());
+        }
+
+        let title = PageTitle::new(String::from("Tasks")).into();
+        let task_to_message = |task_name: &str| {
+            let logfile_path = self
+                .tasks
+                .iter()
+                .find(|(name, _, _)| name == task_name)
+                .map(|(_, _, logfile_path)| logfile_path.clone())
+                .unwrap_or_default();
+            Message::OpenUrl(logfile_path)
+        };
+        let table = InfoTable::new(title, names, statuses, actions, task_to_message);
+
+        container(table).width(Length::Fill).into()
+    }
+}
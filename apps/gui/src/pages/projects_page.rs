@@ -1,19 +1,27 @@
+use std::collections::HashMap;
+
 use daemon_client::ProjectInfo;
-use iced::widget::container;
+use iced::widget::{container, row};
 use iced::{Element, Length};
+use iced_fonts::Bootstrap;
+use project_settings::ProjectSettings;
 
-use crate::components::{InfoTable, PageTitle, ProjectActionButtons, Section};
+use crate::components::{InfoTable, PageTitle, ProjectActionButtons, Section, TextButton};
 use crate::message::Message;
 
 use super::{Page, PageData, PageView};
 
 pub struct ProjectsPage {
     projects: Vec<ProjectInfo>,
+    profiles: HashMap<String, Vec<String>>,
 }
 
 impl ProjectsPage {
     pub fn new() -> Self {
-        Self { projects: vec![] }
+        Self {
+            projects: vec![],
+            profiles: HashMap::new(),
+        }
     }
 }
 
@@ -23,9 +31,22 @@ impl PageView for ProjectsPage {
     }
 
     fn refresh(&mut self, data: PageData) -> Result<(), String> {
-        match data.requester.get_projects_info() {
-            Ok(projects) => {
+        let result = data.requester.get_projects_info().and_then(|projects| {
+            let settings = data.requester.get_projects_settings()?;
+            Ok((projects, settings))
+        });
+
+        match result {
+            Ok((projects, settings)) => {
                 self.projects = projects;
+                self.profiles = settings
+                    .into_iter()
+                    .filter_map(|(name, json)| {
+                        ProjectSettings::try_from(json.as_str())
+                            .map(|settings| (name, settings.profile_names()))
+                            .ok()
+                    })
+                    .collect();
                 Ok(())
             }
             Err(err) => Err(err.to_string()),
@@ -43,10 +64,30 @@ impl PageView for ProjectsPage {
                 project.running_service_count(),
                 project.service_count()
             ));
-            actions.push(ProjectActionButtons::new(project).into());
+            let profiles = self
+                .profiles
+                .get(&project.name)
+                .cloned()
+                .unwrap_or_default();
+            actions.push(ProjectActionButtons::new(project).profiles(profiles).into());
         }
 
-        let title = PageTitle::new(self.title()).into();
+        let create_buttons = row![
+            TextButton::new(
+                "New project",
+                Some(Message::GotoPage(Page::ProjectEditor(None)))
+            )
+            .icon(Bootstrap::PlusLg),
+            TextButton::new("Add from file", Some(Message::PickProjectFile))
+                .icon(Bootstrap::FileEarmarkPlus),
+            TextButton::new("Add from directory", Some(Message::PickProjectDirectory))
+                .icon(Bootstrap::FolderPlus),
+        ]
+        .spacing(10);
+
+        let title = PageTitle::new(self.title())
+            .additional_content(create_buttons.into())
+            .into();
         let name_to_message = |project: &str| Message::GotoPage(Page::Project(project.to_string()));
         let table = InfoTable::new(title, names, statuses, actions, name_to_message);
 
@@ -35,12 +35,13 @@ impl PageView for ServicePage {
             .requester
             .get_services_info(&self.project_name, &self.service_name)
             .map(|service| {
+                let line_count = data.config.gui.log_line_count;
                 // TODO: Use some more efficient way to read just last n lines
                 let log_data = std::fs::read_to_string(Path::new(&service.logfile_path))
                     .map(|text| {
                         text.lines()
                             .rev()
-                            .take(200)
+                            .take(line_count)
                             .collect::<Vec<&str>>()
                             .join("\n")
                     })
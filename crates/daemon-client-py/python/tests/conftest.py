@@ -0,0 +1,143 @@
+"""Minimal in-process daemon speaking the conc socket protocol, enough for the client tests."""
+
+from __future__ import annotations
+
+import json
+import os
+import socket
+import tempfile
+import threading
+from typing import Dict, List
+
+import pytest
+
+SEPARATOR = "\x11"
+
+
+class FakeDaemon:
+    def __init__(self) -> None:
+        self._dir = tempfile.TemporaryDirectory(prefix="conc-py-")
+        self.socket_path = os.path.join(self._dir.name, "conc.sock")
+        self.projects: Dict[str, Dict[str, List[str]]] = {}
+        self.settings: Dict[str, str] = {}
+        self.requests: List[List[str]] = []
+        self._next_pid = 1000
+        self._server = socket.socket(socket.AF_UNIX, socket.SOCK_STREAM)
+        self._server.bind(self.socket_path)
+        self._server.listen()
+        self._thread = threading.Thread(target=self._serve, daemon=True)
+        self._thread.start()
+
+    def add_project(self, name: str, services: List[str]) -> None:
+        self.settings[name] = json.dumps(
+            {"name": name, "services": [{"name": s, "command": ["true"]} for s in services]}
+        )
+        self.projects[name] = {s: ["IDLE", "-1", "0", "0"] for s in services}
+
+    def close(self) -> None:
+        self._server.close()
+        self._dir.cleanup()
+
+    def _serve(self) -> None:
+        while True:
+            try:
+                connection, _ = self._server.accept()
+            except OSError:
+                return
+            with connection:
+                data = b""
+                while b"\0" not in data:
+                    chunk = connection.recv(4096)
+                    if not chunk:
+                        break
+                    data += chunk
+                message = data.split(b"\0")[0].decode()
+                response = self._dispatch(message) if message else ""
+                connection.sendall(response.encode() + b"\0")
+
+    def _dispatch(self, message: str) -> str:
+        command, *args = message.split(SEPARATOR)
+        self.requests.append([command, *args])
+        try:
+            return SEPARATOR.join(["OK", *self._handle(command, args)])
+        except KeyError as err:
+            return SEPARATOR.join(["ERROR", err.args[0]])
+
+    def _project(self, name: str) -> Dict[str, List[str]]:
+        if name not in self.projects:
+            raise KeyError("project_not_found")
+        return self.projects[name]
+
+    def _service(self, project: str, service: str) -> List[str]:
+        services = self._project(project)
+        if service not in services:
+            raise KeyError("service_not_found")
+        return services[service]
+
+    def _set_running(self, state: List[str], running: bool) -> None:
+        if running:
+            self._next_pid += 1
+            state[:] = ["RUNNING", str(self._next_pid), "1", "0"]
+        elif state[0] == "RUNNING":
+            state[:] = ["STOPPED", "0", state[2], "2"]
+
+    def _format_service(self, name: str, state: List[str]) -> str:
+        return " ".join([name, *state, "/dev/null"])
+
+    def _format_project(self, name: str) -> List[str]:
+        services = self._project(name)
+        return [name, *(self._format_service(s, state) for s, state in services.items())]
+
+    def _handle(self, command: str, args: List[str]) -> List[str]:
+        if command == "PROJECTS-NAMES":
+            return list(self.projects)
+        if command == "PROJECTS-SETTINGS":
+            return [f"{name} {settings}" for name, settings in self.settings.items()]
+        if command == "PROJECTS-INFO":
+            parts: List[str] = []
+            for name in self.projects:
+                parts.extend([""] if parts else [])
+                parts.extend(self._format_project(name))
+            return parts
+        if command == "PROJECT-SETTINGS":
+            self._project(args[0])
+            return [self.settings[args[0]]]
+        if command == "PROJECT-INFO":
+            return self._format_project(args[0])
+        if command == "PROJECT-UPSERT":
+            settings = json.loads(args[0])
+            self.add_project(settings["name"], [s["name"] for s in settings["services"]])
+            self.settings[settings["name"]] = args[0]
+            return self._format_project(settings["name"])
+        if command in ("PROJECT-START", "PROJECT-STOP"):
+            for state in self._project(args[0]).values():
+                self._set_running(state, command == "PROJECT-START")
+            return self._format_project(args[0])
+        if command == "PROJECT-REMOVE":
+            self._project(args[0])
+            del self.projects[args[0]]
+            del self.settings[args[0]]
+            return []
+        if command in ("SERVICE-START", "SERVICE-STOP"):
+            state = self._service(args[0], args[1])
+            self._set_running(state, command == "SERVICE-START")
+            return [self._format_service(args[1], state)]
+        if command == "SERVICE-INFO":
+            return [self._format_service(args[1], self._service(args[0], args[1]))]
+        raise KeyError("unknown_command")
+
+
+@pytest.fixture
+def daemon():
+    fake = FakeDaemon()
+    fake.add_project("shop", ["api", "web"])
+    yield fake
+    fake.close()
+
+
+@pytest.fixture
+def client(daemon: FakeDaemon):
+    import conc
+
+    return conc.Client(daemon.socket_path)
+
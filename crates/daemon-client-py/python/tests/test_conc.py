@@ -0,0 +1,105 @@
+import pytest
+
+import conc
+
+
+def test_lists_projects(client):
+    assert client.project_names() == ["shop"]
+
+    (shop,) = client.projects()
+    assert shop.name == "shop"
+    assert [s.name for s in shop.services] == ["api", "web"]
+    assert all(s.status is conc.ServiceStatus.IDLE for s in shop.services)
+
+
+def test_returns_dataclasses(client):
+    shop = client.start_project("shop")
+
+    assert isinstance(shop, conc.ProjectInfo)
+    assert shop.all_services_running
+    assert shop.running_service_count == 2
+    assert shop.service("web").pid > 0
+    assert shop.service("web").logfile_path == "/dev/null"
+    with pytest.raises(AttributeError):
+        shop.name = "other"
+
+
+def test_starts_only_selected_services(client, daemon):
+    shop = client.start_project("shop", ["web"])
+
+    assert shop.running_service_count == 1
+    assert shop.service("web").is_running
+    assert [r[:3] for r in daemon.requests] == [
+        ["SERVICE-START", "shop", "web"],
+        ["PROJECT-INFO", "shop"],
+    ]
+
+
+def test_manages_services(client):
+    assert client.start_service("shop", "api").status is conc.ServiceStatus.RUNNING
+    assert client.service("shop", "api").is_running
+    assert client.stop_service("shop", "api").status is conc.ServiceStatus.STOPPED
+
+
+def test_reads_settings(client):
+    assert client.project_settings("shop")["services"][0]["name"] == "api"
+    assert list(client.projects_settings()) == ["shop"]
+
+
+def test_project_running_stops_on_exit(client):
+    with client.project_running("shop") as shop:
+        assert shop.all_services_running
+
+    stopped = client.project("shop")
+    assert all(s.status is conc.ServiceStatus.STOPPED for s in stopped.services)
+
+
+def test_project_running_stops_on_error(client):
+    with pytest.raises(RuntimeError):
+        with conc.project_running("shop", client=client):
+            raise RuntimeError("failed test")
+
+    assert client.project("shop").running_service_count == 0
+
+
+def test_project_running_stops_only_the_started_services(client, daemon):
+    daemon.add_project("shop", ["api", "web", "worker"])
+    client.start_service("shop", "api")
+
+    with client.project_running("shop", ["api", "web"]) as shop:
+        assert shop.service("web").is_running
+        del daemon.requests[:]
+
+    assert daemon.requests == [["SERVICE-STOP", "shop", "web"]]
+    after = client.project("shop")
+    assert after.service("api").is_running
+    assert after.service("web").status is conc.ServiceStatus.STOPPED
+    assert after.service("worker").status is conc.ServiceStatus.IDLE
+
+
+def test_project_upserted_removes_on_exit(client):
+    settings = {"name": "docs", "services": [{"name": "serve", "command": ["true"]}]}
+
+    with conc.project_upserted(settings, client=client) as docs:
+        assert docs.name == "docs"
+        assert client.project_names() == ["shop", "docs"]
+
+    assert client.project_names() == ["shop"]
+
+
+def test_raises_typed_errors(client):
+    with pytest.raises(conc.ProjectNotFoundError):
+        client.project("missing")
+    with pytest.raises(conc.ServiceNotFoundError) as err:
+        client.service("shop", "missing")
+    assert isinstance(err.value, conc.ConcError)
+    with pytest.raises(conc.ServiceNotFoundError):
+        client.project("shop").service("missing")
+
+
+def test_unreachable_daemon_is_a_socket_error(daemon):
+    client = conc.Client(daemon.socket_path + ".missing")
+
+    assert not client.is_alive()
+    with pytest.raises(conc.SocketError):
+        client.project_names()
@@ -0,0 +1,300 @@
+//! Native part of the `conc` Python package, values are returned as plain tuples and turned
+//! into dataclasses by `python/conc/__init__.py`.
+
+use app_config::{ConfigOverrides, LayeredConfig};
+use daemon_client::{
+    ErrorResponse, ProjectInfo, Requester as DaemonRequester, ServiceInfo, SocketClient,
+    TransportOptions,
+};
+use pyo3::{create_exception, exceptions::PyException, prelude::*};
+
+create_exception!(
+    conc._native,
+    ConcError,
+    PyException,
+    "Base of the errors raised by conc."
+);
+create_exception!(
+    conc._native,
+    ConfigError,
+    ConcError,
+    "The conc config can not be read."
+);
+create_exception!(
+    conc._native,
+    SocketError,
+    ConcError,
+    "The daemon can not be reached."
+);
+create_exception!(
+    conc._native,
+    ClientError,
+    ConcError,
+    "The daemon rejected the request."
+);
+create_exception!(
+    conc._native,
+    DaemonError,
+    ConcError,
+    "The daemon failed to handle the request."
+);
+create_exception!(
+    conc._native,
+    MalformedResponseError,
+    ConcError,
+    "The daemon response can not be parsed."
+);
+create_exception!(
+    conc._native,
+    ProjectNotFoundError,
+    ConcError,
+    "The project does not exist."
+);
+create_exception!(
+    conc._native,
+    ServiceNotFoundError,
+    ConcError,
+    "The service does not exist in the project."
+);
+create_exception!(
+    conc._native,
+    UnauthorizedError,
+    ConcError,
+    "The daemon rejected the token."
+);
+
+/// name, status, pid, start time, stop time and logfile path
+type ServiceTuple = (String, String, i32, u64, u64, String);
+type ProjectTuple = (String, Vec<ServiceTuple>);
+/// address, token, ca file, use_caller_env and the warnings of the config
+type ConnectionTuple = (String, Option<String>, Option<String>, bool, Vec<String>);
+
+fn to_py_err(err: ErrorResponse) -> PyErr {
+    let message = err.to_string();
+    match err {
+        ErrorResponse::Socket { .. } => SocketError::new_err(message),
+        ErrorResponse::Client(_) => ClientError::new_err(message),
+        ErrorResponse::Daemon(_) => DaemonError::new_err(message),
+        ErrorResponse::Malformed(_) => MalformedResponseError::new_err(message),
+        ErrorResponse::ProjectNotFound(_) => ProjectNotFoundError::new_err(message),
+        ErrorResponse::ServiceNotFound(_) => ServiceNotFoundError::new_err(message),
+        ErrorResponse::Unauthorized(_) => UnauthorizedError::new_err(message),
+    }
+}
+
+fn service_tuple(info: ServiceInfo) -> ServiceTuple {
+    (
+        info.name,
+        format!("{:?}", info.status),
+        info.pid,
+        info.start_time,
+        info.stop_time,
+        info.logfile_path,
+    )
+}
+
+fn project_tuple(info: ProjectInfo) -> ProjectTuple {
+    (
+        info.name,
+        info.services.into_iter().map(service_tuple).collect(),
+    )
+}
+
+/// Connection to the daemon, requests release the GIL while they wait for the daemon
+#[pyclass(module = "conc._native", frozen)]
+struct Requester {
+    requester: DaemonRequester,
+}
+
+impl Requester {
+    fn send<T: Send>(
+        &self,
+        py: Python<'_>,
+        request: impl FnOnce(&DaemonRequester) -> Result<T, ErrorResponse> + Send,
+    ) -> PyResult<T> {
+        py.detach(|| request(&self.requester)).map_err(to_py_err)
+    }
+}
+
+#[pymethods]
+impl Requester {
+    #[new]
+    #[pyo3(signature = (address, token=None, ca_file=None, use_caller_env=false))]
+    fn new(
+        address: &str,
+        token: Option<String>,
+        ca_file: Option<String>,
+        use_caller_env: bool,
+    ) -> Self {
+        let options = TransportOptions { token, ca_file };
+        let client = SocketClient::with_options(address, &options);
+        Self {
+            requester: DaemonRequester::new(client, use_caller_env),
+        }
+    }
+
+    #[getter]
+    fn address(&self) -> String {
+        self.requester.client().address()
+    }
+
+    fn is_alive(&self, py: Python<'_>) -> bool {
+        py.detach(|| self.requester.client().is_alive())
+    }
+
+    fn get_project_names(&self, py: Python<'_>) -> PyResult<Vec<String>> {
+        self.send(py, |r| r.get_project_names())
+    }
+
+    fn get_projects_settings(&self, py: Python<'_>) -> PyResult<Vec<(String, String)>> {
+        self.send(py, |r| r.get_projects_settings())
+    }
+
+    fn get_projects_info(&self, py: Python<'_>) -> PyResult<Vec<ProjectTuple>> {
+        self.send(py, |r| r.get_projects_info())
+            .map(|projects| projects.into_iter().map(project_tuple).collect())
+    }
+
+    fn upsert_project(&self, py: Python<'_>, settings_json: &str) -> PyResult<ProjectTuple> {
+        self.send(py, |r| r.upsert_project(settings_json))
+            .map(project_tuple)
+    }
+
+    fn get_project_settings(&self, py: Python<'_>, project: &str) -> PyResult<String> {
+        self.send(py, |r| r.get_project_settings(project))
+    }
+
+    fn get_project_info(&self, py: Python<'_>, project: &str) -> PyResult<ProjectTuple> {
+        self.send(py, |r| r.get_project_info(project))
+            .map(project_tuple)
+    }
+
+    fn start_project(&self, py: Python<'_>, project: &str) -> PyResult<ProjectTuple> {
+        self.send(py, |r| r.start_project(project))
+            .map(project_tuple)
+    }
+
+    fn start_project_services(
+        &self,
+        py: Python<'_>,
+        project: &str,
+        services: Vec<String>,
+    ) -> PyResult<ProjectTuple> {
+        self.send(py, |r| r.start_project_services(project, &services))
+            .map(project_tuple)
+    }
+
+    fn restart_project(&self, py: Python<'_>, project: &str) -> PyResult<ProjectTuple> {
+        self.send(py, |r| r.restart_project(project))
+            .map(project_tuple)
+    }
+
+    fn stop_project(&self, py: Python<'_>, project: &str) -> PyResult<ProjectTuple> {
+        self.send(py, |r| r.stop_project(project))
+            .map(project_tuple)
+    }
+
+    fn remove_project(&self, py: Python<'_>, project: &str) -> PyResult<()> {
+        self.send(py, |r| r.remove_project(project))
+    }
+
+    fn clear_project_logs(&self, py: Python<'_>, project: &str) -> PyResult<()> {
+        self.send(py, |r| r.clear_project_logs(project))
+    }
+
+    fn get_service_names(&self, py: Python<'_>, project: &str) -> PyResult<Vec<String>> {
+        self.send(py, |r| r.get_service_names(project))
+    }
+
+    fn get_service_info(
+        &self,
+        py: Python<'_>,
+        project: &str,
+        service: &str,
+    ) -> PyResult<ServiceTuple> {
+        self.send(py, |r| r.get_services_info(project, service))
+            .map(service_tuple)
+    }
+
+    fn start_service(
+        &self,
+        py: Python<'_>,
+        project: &str,
+        service: &str,
+    ) -> PyResult<ServiceTuple> {
+        self.send(py, |r| r.start_service(project, service))
+            .map(service_tuple)
+    }
+
+    fn restart_service(
+        &self,
+        py: Python<'_>,
+        project: &str,
+        service: &str,
+    ) -> PyResult<ServiceTuple> {
+        self.send(py, |r| r.restart_service(project, service))
+            .map(service_tuple)
+    }
+
+    fn stop_service(&self, py: Python<'_>, project: &str, service: &str) -> PyResult<ServiceTuple> {
+        self.send(py, |r| r.stop_service(project, service))
+            .map(service_tuple)
+    }
+
+    fn clear_service_logs(&self, py: Python<'_>, project: &str, service: &str) -> PyResult<()> {
+        self.send(py, |r| r.clear_service_logs(project, service))
+    }
+}
+
+/// Reads the daemon connection from the layered config, the same way `concc` does,
+/// together with the warnings of the config
+#[pyfunction]
+#[pyo3(signature = (context=None, config_path=None))]
+fn config_connection(
+    context: Option<String>,
+    config_path: Option<String>,
+) -> PyResult<ConnectionTuple> {
+    let overrides = ConfigOverrides {
+        config_path,
+        socket_path: None,
+        context,
+    };
+    let LayeredConfig {
+        config, warnings, ..
+    } = LayeredConfig::load(&overrides).map_err(|err| ConfigError::new_err(err.to_string()))?;
+
+    Ok((
+        config.daemon_socket_path,
+        config.daemon_token,
+        config.daemon_ca_file,
+        config.use_caller_env,
+        warnings,
+    ))
+}
+
+#[pymodule]
+fn _native(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add_class::<Requester>()?;
+    m.add_function(wrap_pyfunction!(config_connection, m)?)?;
+
+    m.add("ConcError", py.get_type::<ConcError>())?;
+    m.add("ConfigError", py.get_type::<ConfigError>())?;
+    m.add("SocketError", py.get_type::<SocketError>())?;
+    m.add("ClientError", py.get_type::<ClientError>())?;
+    m.add("DaemonError", py.get_type::<DaemonError>())?;
+    m.add(
+        "MalformedResponseError",
+        py.get_type::<MalformedResponseError>(),
+    )?;
+    m.add(
+        "ProjectNotFoundError",
+        py.get_type::<ProjectNotFoundError>(),
+    )?;
+    m.add(
+        "ServiceNotFoundError",
+        py.get_type::<ServiceNotFoundError>(),
+    )?;
+    m.add("UnauthorizedError", py.get_type::<UnauthorizedError>())?;
+    Ok(())
+}
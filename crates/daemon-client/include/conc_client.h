@@ -0,0 +1,268 @@
+/* C API of the conc daemon client, generated by cbindgen from src/ffi.rs, do not edit. */
+
+#ifndef CONC_CLIENT_H
+#define CONC_CLIENT_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Version of the C API, it changes only when existing functions or structs change
+#define CONC_ABI_VERSION 1
+
+// Error codes mirror the variants of `ErrorResponse`
+typedef enum ConcErrorCode {
+  CONC_ERROR_CODE_OK = 0,
+  CONC_ERROR_CODE_SOCKET = 1,
+  CONC_ERROR_CODE_CLIENT = 2,
+  CONC_ERROR_CODE_DAEMON = 3,
+  CONC_ERROR_CODE_MALFORMED = 4,
+  CONC_ERROR_CODE_PROJECT_NOT_FOUND = 5,
+  CONC_ERROR_CODE_SERVICE_NOT_FOUND = 6,
+  CONC_ERROR_CODE_UNAUTHORIZED = 7,
+  // null pointer or a string which is not valid UTF-8 was passed to the function
+  CONC_ERROR_CODE_INVALID_ARGUMENT = 8,
+  // unexpected failure of the library itself
+  CONC_ERROR_CODE_INTERNAL = 9,
+} ConcErrorCode;
+
+typedef enum ConcServiceStatus {
+  CONC_SERVICE_STATUS_IDLE = 0,
+  CONC_SERVICE_STATUS_RUNNING = 1,
+  CONC_SERVICE_STATUS_STOPPED = 2,
+  CONC_SERVICE_STATUS_EXITED = 3,
+} ConcServiceStatus;
+
+// Handle of a daemon connection, created by `conc_requester_new`
+typedef struct ConcRequester ConcRequester;
+
+typedef struct ConcStringList {
+  char **values;
+  size_t count;
+} ConcStringList;
+
+typedef struct ConcServiceInfo {
+  char *name;
+  enum ConcServiceStatus status;
+  int32_t pid;
+  uint64_t start_time;
+  uint64_t stop_time;
+  char *logfile_path;
+} ConcServiceInfo;
+
+typedef struct ConcProjectInfo {
+  char *name;
+  struct ConcServiceInfo *services;
+  size_t service_count;
+} ConcProjectInfo;
+
+typedef struct ConcProjectInfoList {
+  struct ConcProjectInfo *projects;
+  size_t count;
+} ConcProjectInfoList;
+
+typedef struct ConcProjectSettings {
+  char *name;
+  char *settings_json;
+} ConcProjectSettings;
+
+typedef struct ConcProjectSettingsList {
+  struct ConcProjectSettings *projects;
+  size_t count;
+} ConcProjectSettingsList;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+uint32_t conc_abi_version(void);
+
+// Creates the handle for the daemon at the socket path or address, e.g. `tcp://host:7070`.
+// `token` and `ca_file` may be null. Returns null when the address is not a valid string.
+//
+// # Safety
+//
+// `address`, `token` and `ca_file` have to be null or valid null terminated strings.
+struct ConcRequester *conc_requester_new(const char *address,
+                                         const char *token,
+                                         const char *ca_file,
+                                         bool use_caller_env);
+
+// # Safety
+//
+// `requester` has to be null or a handle created by `conc_requester_new` which was not freed yet.
+void conc_requester_free(struct ConcRequester *requester);
+
+// Returns true when the daemon responds to the health check
+//
+// # Safety
+//
+// `requester` has to be null or a valid handle.
+bool conc_requester_is_alive(const struct ConcRequester *requester);
+
+// Returns the message of the last error on the calling thread or null when there was none,
+// the message has to be freed by `conc_string_free`
+char *conc_last_error_message(void);
+
+// # Safety
+//
+// `requester` has to be a valid handle and `out` a valid pointer.
+enum ConcErrorCode conc_get_project_names(const struct ConcRequester *requester,
+                                          struct ConcStringList *out);
+
+// # Safety
+//
+// `requester` has to be a valid handle and `out` a valid pointer.
+enum ConcErrorCode conc_get_projects_info(const struct ConcRequester *requester,
+                                          struct ConcProjectInfoList *out);
+
+// # Safety
+//
+// `requester` has to be a valid handle and `out` a valid pointer.
+enum ConcErrorCode conc_get_projects_settings(const struct ConcRequester *requester,
+                                              struct ConcProjectSettingsList *out);
+
+// # Safety
+//
+// `requester` has to be a valid handle, `settings_json` a valid string and `out` a valid pointer.
+enum ConcErrorCode conc_upsert_project(const struct ConcRequester *requester,
+                                       const char *settings_json,
+                                       struct ConcProjectInfo *out);
+
+// Writes the settings JSON of the project, it has to be freed by `conc_string_free`
+//
+// # Safety
+//
+// `requester` has to be a valid handle, `project` a valid string and `out` a valid pointer.
+enum ConcErrorCode conc_get_project_settings(const struct ConcRequester *requester,
+                                             const char *project,
+                                             char **out);
+
+// # Safety
+//
+// `requester` has to be a valid handle, `project` a valid string and `out` a valid pointer.
+enum ConcErrorCode conc_get_project_info(const struct ConcRequester *requester,
+                                         const char *project,
+                                         struct ConcProjectInfo *out);
+
+// # Safety
+//
+// `requester` has to be a valid handle, `project` a valid string and `out` a valid pointer.
+enum ConcErrorCode conc_start_project(const struct ConcRequester *requester,
+                                      const char *project,
+                                      struct ConcProjectInfo *out);
+
+// # Safety
+//
+// `requester` has to be a valid handle, `project` a valid string and `out` a valid pointer.
+enum ConcErrorCode conc_restart_project(const struct ConcRequester *requester,
+                                        const char *project,
+                                        struct ConcProjectInfo *out);
+
+// # Safety
+//
+// `requester` has to be a valid handle, `project` a valid string and `out` a valid pointer.
+enum ConcErrorCode conc_stop_project(const struct ConcRequester *requester,
+                                     const char *project,
+                                     struct ConcProjectInfo *out);
+
+// # Safety
+//
+// `requester` has to be a valid handle and `project` a valid string.
+enum ConcErrorCode conc_remove_project(const struct ConcRequester *requester, const char *project);
+
+// # Safety
+//
+// `requester` has to be a valid handle and `project` a valid string.
+enum ConcErrorCode conc_clear_project_logs(const struct ConcRequester *requester,
+                                           const char *project);
+
+// # Safety
+//
+// `requester` has to be a valid handle, `project` a valid string and `out` a valid pointer.
+enum ConcErrorCode conc_get_service_names(const struct ConcRequester *requester,
+                                          const char *project,
+                                          struct ConcStringList *out);
+
+// # Safety
+//
+// `requester` has to be a valid handle, `project` and `service` valid strings and `out` a valid pointer.
+enum ConcErrorCode conc_get_service_info(const struct ConcRequester *requester,
+                                         const char *project,
+                                         const char *service,
+                                         struct ConcServiceInfo *out);
+
+// # Safety
+//
+// `requester` has to be a valid handle, `project` and `service` valid strings and `out` a valid pointer.
+enum ConcErrorCode conc_start_service(const struct ConcRequester *requester,
+                                      const char *project,
+                                      const char *service,
+                                      struct ConcServiceInfo *out);
+
+// # Safety
+//
+// `requester` has to be a valid handle, `project` and `service` valid strings and `out` a valid pointer.
+enum ConcErrorCode conc_restart_service(const struct ConcRequester *requester,
+                                        const char *project,
+                                        const char *service,
+                                        struct ConcServiceInfo *out);
+
+// # Safety
+//
+// `requester` has to be a valid handle, `project` and `service` valid strings and `out` a valid pointer.
+enum ConcErrorCode conc_stop_service(const struct ConcRequester *requester,
+                                     const char *project,
+                                     const char *service,
+                                     struct ConcServiceInfo *out);
+
+// # Safety
+//
+// `requester` has to be a valid handle, `project` and `service` valid strings.
+enum ConcErrorCode conc_clear_service_logs(const struct ConcRequester *requester,
+                                           const char *project,
+                                           const char *service);
+
+// # Safety
+//
+// `value` has to be null or a string returned by this library which was not freed yet.
+void conc_string_free(char *value);
+
+// Frees the values of the list, the list itself is owned by the caller
+//
+// # Safety
+//
+// `list` has to be null or a list filled by this library which was not freed yet.
+void conc_string_list_free(struct ConcStringList *list);
+
+// Frees the strings and services of the project, the struct itself is owned by the caller
+//
+// # Safety
+//
+// `info` has to be null or a project filled by this library which was not freed yet.
+void conc_project_info_free(struct ConcProjectInfo *info);
+
+// # Safety
+//
+// `list` has to be null or a list filled by this library which was not freed yet.
+void conc_project_info_list_free(struct ConcProjectInfoList *list);
+
+// # Safety
+//
+// `list` has to be null or a list filled by this library which was not freed yet.
+void conc_project_settings_list_free(struct ConcProjectSettingsList *list);
+
+// Frees the strings of the service, the struct itself is owned by the caller
+//
+// # Safety
+//
+// `info` has to be null or a service filled by this library which was not freed yet.
+void conc_service_info_free(struct ConcServiceInfo *info);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CONC_CLIENT_H */
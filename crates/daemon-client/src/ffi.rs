@@ -0,0 +1,708 @@
+//! C API of the client, its header `include/conc_client.h` is generated by cbindgen with `make header`
+//! and checked by `tests/header.rs`.
+//!
+//! Every request returns a `ConcErrorCode` and writes its result to the out pointer, results
+//! are owned by the caller and released by the matching `conc_*_free` function. Message of the
+//! last error on the calling thread is returned by `conc_last_error_message`.
+
+use std::{
+    cell::RefCell,
+    ffi::{CStr, CString},
+    os::raw::c_char,
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr,
+};
+
+use crate::{
+    ErrorResponse, ProjectInfo, Requester, ServiceInfo, ServiceStatus, SocketClient,
+    TransportOptions,
+};
+
+/// Version of the C API, it changes only when existing functions or structs change
+pub const CONC_ABI_VERSION: u32 = 1;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Error codes mirror the variants of `ErrorResponse`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConcErrorCode {
+    Ok = 0,
+    Socket = 1,
+    Client = 2,
+    Daemon = 3,
+    Malformed = 4,
+    ProjectNotFound = 5,
+    ServiceNotFound = 6,
+    Unauthorized = 7,
+    /// null pointer or a string which is not valid UTF-8 was passed to the function
+    InvalidArgument = 8,
+    /// unexpected failure of the library itself
+    Internal = 9,
+}
+
+impl From<&ErrorResponse> for ConcErrorCode {
+    fn from(err: &ErrorResponse) -> Self {
+        match err {
+            ErrorResponse::Socket { .. } => Self::Socket,
+            ErrorResponse::Client(_) => Self::Client,
+            ErrorResponse::Daemon(_) => Self::Daemon,
+            ErrorResponse::Malformed(_) => Self::Malformed,
+            ErrorResponse::ProjectNotFound(_) => Self::ProjectNotFound,
+            ErrorResponse::ServiceNotFound(_) => Self::ServiceNotFound,
+            ErrorResponse::Unauthorized(_) => Self::Unauthorized,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConcServiceStatus {
+    Idle = 0,
+    Running = 1,
+    Stopped = 2,
+    Exited = 3,
+}
+
+impl From<ServiceStatus> for ConcServiceStatus {
+    fn from(status: ServiceStatus) -> Self {
+        match status {
+            ServiceStatus::IDLE => Self::Idle,
+            ServiceStatus::RUNNING => Self::Running,
+            ServiceStatus::STOPPED => Self::Stopped,
+            ServiceStatus::EXITED => Self::Exited,
+        }
+    }
+}
+
+/// Handle of a daemon connection, created by `conc_requester_new`
+pub struct ConcRequester {
+    requester: Requester,
+}
+
+#[repr(C)]
+pub struct ConcServiceInfo {
+    pub name: *mut c_char,
+    pub status: ConcServiceStatus,
+    pub pid: i32,
+    pub start_time: u64,
+    pub stop_time: u64,
+    pub logfile_path: *mut c_char,
+}
+
+#[repr(C)]
+pub struct ConcProjectInfo {
+    pub name: *mut c_char,
+    pub services: *mut ConcServiceInfo,
+    pub service_count: usize,
+}
+
+#[repr(C)]
+pub struct ConcProjectInfoList {
+    pub projects: *mut ConcProjectInfo,
+    pub count: usize,
+}
+
+#[repr(C)]
+pub struct ConcProjectSettings {
+    pub name: *mut c_char,
+    pub settings_json: *mut c_char,
+}
+
+#[repr(C)]
+pub struct ConcProjectSettingsList {
+    pub projects: *mut ConcProjectSettings,
+    pub count: usize,
+}
+
+#[repr(C)]
+pub struct ConcStringList {
+    pub values: *mut *mut c_char,
+    pub count: usize,
+}
+
+#[no_mangle]
+pub extern "C" fn conc_abi_version() -> u32 {
+    CONC_ABI_VERSION
+}
+
+/// Creates the handle for the daemon at the socket path or address, e.g. `tcp://host:7070`.
+/// `token` and `ca_file` may be null. Returns null when the address is not a valid string.
+///
+/// # Safety
+///
+/// `address`, `token` and `ca_file` have to be null or valid null terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn conc_requester_new(
+    address: *const c_char,
+    token: *const c_char,
+    ca_file: *const c_char,
+    use_caller_env: bool,
+) -> *mut ConcRequester {
+    let result = guard(|| {
+        let address = read_str(address)?;
+        let options = TransportOptions {
+            token: read_optional_str(token)?,
+            ca_file: read_optional_str(ca_file)?,
+        };
+        let client = SocketClient::with_options(address, &options);
+        Ok(Box::into_raw(Box::new(ConcRequester {
+            requester: Requester::new(client, use_caller_env),
+        })))
+    });
+
+    match result {
+        Ok(requester) => requester,
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// # Safety
+///
+/// `requester` has to be null or a handle created by `conc_requester_new` which was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn conc_requester_free(requester: *mut ConcRequester) {
+    if !requester.is_null() {
+        drop(Box::from_raw(requester));
+    }
+}
+
+/// Returns true when the daemon responds to the health check
+///
+/// # Safety
+///
+/// `requester` has to be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn conc_requester_is_alive(requester: *const ConcRequester) -> bool {
+    match requester.as_ref() {
+        Some(handle) => guard(|| Ok(handle.requester.client().is_alive())).unwrap_or(false),
+        None => false,
+    }
+}
+
+/// Returns the message of the last error on the calling thread or null when there was none,
+/// the message has to be freed by `conc_string_free`
+#[no_mangle]
+pub extern "C" fn conc_last_error_message() -> *mut c_char {
+    LAST_ERROR.with(|last| match last.borrow().as_deref() {
+        Some(message) => to_c_string(message),
+        None => ptr::null_mut(),
+    })
+}
+
+/// # Safety
+///
+/// `requester` has to be a valid handle and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn conc_get_project_names(
+    requester: *const ConcRequester,
+    out: *mut ConcStringList,
+) -> ConcErrorCode {
+    request(requester, out, |r| {
+        r.get_project_names().map(|names| to_string_list(&names))
+    })
+}
+
+/// # Safety
+///
+/// `requester` has to be a valid handle and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn conc_get_projects_info(
+    requester: *const ConcRequester,
+    out: *mut ConcProjectInfoList,
+) -> ConcErrorCode {
+    request(requester, out, |r| {
+        r.get_projects_info().map(|projects| {
+            let (projects, count) = to_c_array(projects.iter().map(to_project_info).collect());
+            ConcProjectInfoList { projects, count }
+        })
+    })
+}
+
+/// # Safety
+///
+/// `requester` has to be a valid handle and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn conc_get_projects_settings(
+    requester: *const ConcRequester,
+    out: *mut ConcProjectSettingsList,
+) -> ConcErrorCode {
+    request(requester, out, |r| {
+        r.get_projects_settings().map(|settings| {
+            let settings = settings
+                .iter()
+                .map(|(name, json)| ConcProjectSettings {
+                    name: to_c_string(name),
+                    settings_json: to_c_string(json),
+                })
+                .collect();
+            let (projects, count) = to_c_array(settings);
+            ConcProjectSettingsList { projects, count }
+        })
+    })
+}
+
+/// # Safety
+///
+/// `requester` has to be a valid handle, `settings_json` a valid string and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn conc_upsert_project(
+    requester: *const ConcRequester,
+    settings_json: *const c_char,
+    out: *mut ConcProjectInfo,
+) -> ConcErrorCode {
+    let settings_json = match read_str(settings_json) {
+        Ok(value) => value,
+        Err(code) => return code,
+    };
+    request(requester, out, |r| {
+        r.upsert_project(settings_json)
+            .map(|info| to_project_info(&info))
+    })
+}
+
+/// Writes the settings JSON of the project, it has to be freed by `conc_string_free`
+///
+/// # Safety
+///
+/// `requester` has to be a valid handle, `project` a valid string and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn conc_get_project_settings(
+    requester: *const ConcRequester,
+    project: *const c_char,
+    out: *mut *mut c_char,
+) -> ConcErrorCode {
+    let project = match read_str(project) {
+        Ok(value) => value,
+        Err(code) => return code,
+    };
+    request(requester, out, |r| {
+        r.get_project_settings(project)
+            .map(|json| to_c_string(&json))
+    })
+}
+
+/// # Safety
+///
+/// `requester` has to be a valid handle, `project` a valid string and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn conc_get_project_info(
+    requester: *const ConcRequester,
+    project: *const c_char,
+    out: *mut ConcProjectInfo,
+) -> ConcErrorCode {
+    project_request(requester, project, out, Requester::get_project_info)
+}
+
+/// # Safety
+///
+/// `requester` has to be a valid handle, `project` a valid string and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn conc_start_project(
+    requester: *const ConcRequester,
+    project: *const c_char,
+    out: *mut ConcProjectInfo,
+) -> ConcErrorCode {
+    project_request(requester, project, out, Requester::start_project)
+}
+
+/// # Safety
+///
+/// `requester` has to be a valid handle, `project` a valid string and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn conc_restart_project(
+    requester: *const ConcRequester,
+    project: *const c_char,
+    out: *mut ConcProjectInfo,
+) -> ConcErrorCode {
+    project_request(requester, project, out, Requester::restart_project)
+}
+
+/// # Safety
+///
+/// `requester` has to be a valid handle, `project` a valid string and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn conc_stop_project(
+    requester: *const ConcRequester,
+    project: *const c_char,
+    out: *mut ConcProjectInfo,
+) -> ConcErrorCode {
+    project_request(requester, project, out, Requester::stop_project)
+}
+
+/// # Safety
+///
+/// `requester` has to be a valid handle and `project` a valid string.
+#[no_mangle]
+pub unsafe extern "C" fn conc_remove_project(
+    requester: *const ConcRequester,
+    project: *const c_char,
+) -> ConcErrorCode {
+    let mut out = ();
+    project_request(requester, project, &mut out, Requester::remove_project)
+}
+
+/// # Safety
+///
+/// `requester` has to be a valid handle and `project` a valid string.
+#[no_mangle]
+pub unsafe extern "C" fn conc_clear_project_logs(
+    requester: *const ConcRequester,
+    project: *const c_char,
+) -> ConcErrorCode {
+    let mut out = ();
+    project_request(requester, project, &mut out, Requester::clear_project_logs)
+}
+
+/// # Safety
+///
+/// `requester` has to be a valid handle, `project` a valid string and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn conc_get_service_names(
+    requester: *const ConcRequester,
+    project: *const c_char,
+    out: *mut ConcStringList,
+) -> ConcErrorCode {
+    let project = match read_str(project) {
+        Ok(value) => value,
+        Err(code) => return code,
+    };
+    request(requester, out, |r| {
+        r.get_service_names(project)
+            .map(|names| to_string_list(&names))
+    })
+}
+
+/// # Safety
+///
+/// `requester` has to be a valid handle, `project` and `service` valid strings and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn conc_get_service_info(
+    requester: *const ConcRequester,
+    project: *const c_char,
+    service: *const c_char,
+    out: *mut ConcServiceInfo,
+) -> ConcErrorCode {
+    service_request(
+        requester,
+        project,
+        service,
+        out,
+        Requester::get_services_info,
+    )
+}
+
+/// # Safety
+///
+/// `requester` has to be a valid handle, `project` and `service` valid strings and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn conc_start_service(
+    requester: *const ConcRequester,
+    project: *const c_char,
+    service: *const c_char,
+    out: *mut ConcServiceInfo,
+) -> ConcErrorCode {
+    service_request(requester, project, service, out, Requester::start_service)
+}
+
+/// # Safety
+///
+/// `requester` has to be a valid handle, `project` and `service` valid strings and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn conc_restart_service(
+    requester: *const ConcRequester,
+    project: *const c_char,
+    service: *const c_char,
+    out: *mut ConcServiceInfo,
+) -> ConcErrorCode {
+    service_request(requester, project, service, out, Requester::restart_service)
+}
+
+/// # Safety
+///
+/// `requester` has to be a valid handle, `project` and `service` valid strings and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn conc_stop_service(
+    requester: *const ConcRequester,
+    project: *const c_char,
+    service: *const c_char,
+    out: *mut ConcServiceInfo,
+) -> ConcErrorCode {
+    service_request(requester, project, service, out, Requester::stop_service)
+}
+
+/// # Safety
+///
+/// `requester` has to be a valid handle, `project` and `service` valid strings.
+#[no_mangle]
+pub unsafe extern "C" fn conc_clear_service_logs(
+    requester: *const ConcRequester,
+    project: *const c_char,
+    service: *const c_char,
+) -> ConcErrorCode {
+    let mut out = ();
+    service_request(
+        requester,
+        project,
+        service,
+        &mut out,
+        Requester::clear_service_logs,
+    )
+}
+
+/// # Safety
+///
+/// `value` has to be null or a string returned by this library which was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn conc_string_free(value: *mut c_char) {
+    if !value.is_null() {
+        drop(CString::from_raw(value));
+    }
+}
+
+/// Frees the values of the list, the list itself is owned by the caller
+///
+/// # Safety
+///
+/// `list` has to be null or a list filled by this library which was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn conc_string_list_free(list: *mut ConcStringList) {
+    if let Some(list) = list.as_mut() {
+        for value in take_c_array(&mut list.values, &mut list.count) {
+            conc_string_free(value);
+        }
+    }
+}
+
+/// Frees the strings and services of the project, the struct itself is owned by the caller
+///
+/// # Safety
+///
+/// `info` has to be null or a project filled by this library which was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn conc_project_info_free(info: *mut ConcProjectInfo) {
+    if let Some(info) = info.as_mut() {
+        conc_string_free(info.name);
+        info.name = ptr::null_mut();
+        for mut service in take_c_array(&mut info.services, &mut info.service_count) {
+            conc_service_info_free(&mut service);
+        }
+    }
+}
+
+/// # Safety
+///
+/// `list` has to be null or a list filled by this library which was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn conc_project_info_list_free(list: *mut ConcProjectInfoList) {
+    if let Some(list) = list.as_mut() {
+        for mut project in take_c_array(&mut list.projects, &mut list.count) {
+            conc_project_info_free(&mut project);
+        }
+    }
+}
+
+/// # Safety
+///
+/// `list` has to be null or a list filled by this library which was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn conc_project_settings_list_free(list: *mut ConcProjectSettingsList) {
+    if let Some(list) = list.as_mut() {
+        for settings in take_c_array(&mut list.projects, &mut list.count) {
+            conc_string_free(settings.name);
+            conc_string_free(settings.settings_json);
+        }
+    }
+}
+
+/// Frees the strings of the service, the struct itself is owned by the caller
+///
+/// # Safety
+///
+/// `info` has to be null or a service filled by this library which was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn conc_service_info_free(info: *mut ConcServiceInfo) {
+    if let Some(info) = info.as_mut() {
+        conc_string_free(info.name);
+        conc_string_free(info.logfile_path);
+        info.name = ptr::null_mut();
+        info.logfile_path = ptr::null_mut();
+    }
+}
+
+unsafe fn project_request<T, O>(
+    requester: *const ConcRequester,
+    project: *const c_char,
+    out: *mut O,
+    send: impl FnOnce(&Requester, &str) -> Result<T, ErrorResponse>,
+) -> ConcErrorCode
+where
+    T: IntoC<O>,
+{
+    let project = match read_str(project) {
+        Ok(value) => value,
+        Err(code) => return code,
+    };
+    request(requester, out, |r| send(r, project).map(IntoC::into_c))
+}
+
+unsafe fn service_request<T, O>(
+    requester: *const ConcRequester,
+    project: *const c_char,
+    service: *const c_char,
+    out: *mut O,
+    send: impl FnOnce(&Requester, &str, &str) -> Result<T, ErrorResponse>,
+) -> ConcErrorCode
+where
+    T: IntoC<O>,
+{
+    let (project, service) = match (read_str(project), read_str(service)) {
+        (Ok(project), Ok(service)) => (project, service),
+        (Err(code), _) | (_, Err(code)) => return code,
+    };
+    request(requester, out, |r| {
+        send(r, project, service).map(IntoC::into_c)
+    })
+}
+
+/// Sends the request and writes its result, errors are stored as the last error of the thread
+unsafe fn request<O>(
+    requester: *const ConcRequester,
+    out: *mut O,
+    send: impl FnOnce(&Requester) -> Result<O, ErrorResponse>,
+) -> ConcErrorCode {
+    let handle = match requester.as_ref() {
+        Some(handle) => handle,
+        None => return invalid_argument("requester is null"),
+    };
+    if out.is_null() {
+        return invalid_argument("out pointer is null");
+    }
+
+    match guard(|| Ok(send(&handle.requester))) {
+        Ok(Ok(value)) => {
+            out.write(value);
+            clear_last_error();
+            ConcErrorCode::Ok
+        }
+        Ok(Err(err)) => {
+            set_last_error(err.to_string());
+            ConcErrorCode::from(&err)
+        }
+        Err(code) => code,
+    }
+}
+
+/// Panics must not unwind into the caller, they are returned as internal errors
+fn guard<T>(f: impl FnOnce() -> Result<T, ConcErrorCode>) -> Result<T, ConcErrorCode> {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(result) => result,
+        Err(_) => {
+            set_last_error(String::from("unexpected panic in the conc client"));
+            Err(ConcErrorCode::Internal)
+        }
+    }
+}
+
+trait IntoC<O> {
+    fn into_c(self) -> O;
+}
+
+impl IntoC<ConcProjectInfo> for ProjectInfo {
+    fn into_c(self) -> ConcProjectInfo {
+        to_project_info(&self)
+    }
+}
+
+impl IntoC<ConcServiceInfo> for ServiceInfo {
+    fn into_c(self) -> ConcServiceInfo {
+        to_service_info(&self)
+    }
+}
+
+impl IntoC<()> for () {
+    fn into_c(self) {}
+}
+
+fn to_project_info(info: &ProjectInfo) -> ConcProjectInfo {
+    let (services, service_count) = to_c_array(info.services.iter().map(to_service_info).collect());
+    ConcProjectInfo {
+        name: to_c_string(&info.name),
+        services,
+        service_count,
+    }
+}
+
+fn to_service_info(info: &ServiceInfo) -> ConcServiceInfo {
+    ConcServiceInfo {
+        name: to_c_string(&info.name),
+        status: ConcServiceStatus::from(info.status),
+        pid: info.pid,
+        start_time: info.start_time,
+        stop_time: info.stop_time,
+        logfile_path: to_c_string(&info.logfile_path),
+    }
+}
+
+fn to_string_list(values: &[String]) -> ConcStringList {
+    let (values, count) = to_c_array(values.iter().map(|value| to_c_string(value)).collect());
+    ConcStringList { values, count }
+}
+
+/// Arrays are passed as a boxed slice, empty arrays are null
+fn to_c_array<T>(values: Vec<T>) -> (*mut T, usize) {
+    if values.is_empty() {
+        return (ptr::null_mut(), 0);
+    }
+
+    let count = values.len();
+    (Box::into_raw(values.into_boxed_slice()) as *mut T, count)
+}
+
+/// Takes the ownership of the array back and resets the pointer, so it can not be freed twice
+unsafe fn take_c_array<T>(values: &mut *mut T, count: &mut usize) -> Vec<T> {
+    let array = match values.is_null() {
+        true => vec![],
+        false => Box::from_raw(ptr::slice_from_raw_parts_mut(*values, *count)).into_vec(),
+    };
+    *values = ptr::null_mut();
+    *count = 0;
+    array
+}
+
+/// Daemon values never contain the null character, it is dropped if they do
+fn to_c_string(value: &str) -> *mut c_char {
+    let value = CString::new(value.replace('\0', "")).unwrap_or_default();
+    value.into_raw()
+}
+
+unsafe fn read_str<'a>(value: *const c_char) -> Result<&'a str, ConcErrorCode> {
+    if value.is_null() {
+        return Err(invalid_argument("string argument is null"));
+    }
+    CStr::from_ptr(value)
+        .to_str()
+        .map_err(|_| invalid_argument("string argument is not valid UTF-8"))
+}
+
+unsafe fn read_optional_str(value: *const c_char) -> Result<Option<String>, ConcErrorCode> {
+    match value.is_null() {
+        true => Ok(None),
+        false => read_str(value).map(|value| Some(value.to_string())),
+    }
+}
+
+fn invalid_argument(message: &str) -> ConcErrorCode {
+    set_last_error(message.to_string());
+    ConcErrorCode::InvalidArgument
+}
+
+fn set_last_error(message: String) {
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+fn clear_last_error() {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+}
@@ -1,8 +1,13 @@
 mod extensions;
+pub mod ffi;
+#[cfg(feature = "mock")]
+pub mod mock;
 mod protocol;
 mod requester;
 mod socket_client;
+mod transport;
 
 pub use protocol::responses::*;
 pub use requester::Requester;
 pub use socket_client::SocketClient;
+pub use transport::{Connection, Transport, TransportOptions};
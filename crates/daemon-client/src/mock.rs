@@ -0,0 +1,499 @@
+//! In-process daemon speaking the protocol of `concd`, used to test frontends without the C daemon.
+//! Projects live in memory, starting a service only changes its status.
+
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Read, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    thread::{self, JoinHandle},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::Value;
+
+use crate::{
+    protocol::ARG_SEPARATOR_STR, ProjectInfo, Requester, ServiceInfo, ServiceStatus, SocketClient,
+};
+
+static NEXT_MOCK_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Request received by the mock, health checks are not recorded
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockRequest {
+    pub command: String,
+    pub args: Vec<String>,
+}
+
+/// Project known to the mock, its settings are returned as they were upserted
+#[derive(Debug, Clone)]
+pub struct MockProject {
+    pub info: ProjectInfo,
+    pub settings_json: String,
+}
+
+impl MockProject {
+    /// Project with idle services and settings which run `true` for every service
+    pub fn new(name: &str, service_names: &[&str]) -> Self {
+        let services: Vec<Value> = service_names
+            .iter()
+            .map(|service| serde_json::json!({ "name": service, "command": ["true"] }))
+            .collect();
+        let settings = serde_json::json!({ "name": name, "services": services });
+
+        Self {
+            info: ProjectInfo {
+                name: String::from(name),
+                services: service_names
+                    .iter()
+                    .map(|name| idle_service(name))
+                    .collect(),
+            },
+            settings_json: settings.to_string(),
+        }
+    }
+
+    /// Project created from the settings the same way as by the upsert request
+    pub fn from_settings(settings_json: &str) -> Result<Self, String> {
+        let settings: Value =
+            serde_json::from_str(settings_json).map_err(|_| String::from("settings.parse"))?;
+
+        let name = match settings.get("name").and_then(Value::as_str) {
+            Some(name) if is_valid_name(name) => name,
+            _ => return Err(String::from("settings.name.invalid")),
+        };
+        let services = match settings.get("services").and_then(Value::as_array) {
+            Some(services) if !services.is_empty() => services,
+            _ => return Err(String::from("settings.services.missing")),
+        };
+
+        let mut info = ProjectInfo {
+            name: String::from(name),
+            services: vec![],
+        };
+        for service in services {
+            let service_name = service.get("name").and_then(Value::as_str).unwrap_or("");
+            if !is_valid_name(service_name) {
+                return Err(format!("settings.service.{}.name.invalid", service_name));
+            }
+            if info.services.iter().any(|s| s.name == service_name) {
+                return Err(format!("settings.service.{}.name.duplicate", service_name));
+            }
+            info.services.push(idle_service(service_name));
+        }
+
+        Ok(Self {
+            info,
+            settings_json: settings_json.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    projects: Vec<MockProject>,
+    requests: Vec<MockRequest>,
+    /// responses returned instead of handling the command, e.g. to script errors
+    scripted: HashMap<String, String>,
+    next_pid: i32,
+}
+
+/// Daemon listening on a Unix socket in a temp directory, the socket is removed when it is dropped
+pub struct MockDaemon {
+    dir: PathBuf,
+    socket_path: PathBuf,
+    state: Arc<Mutex<MockState>>,
+    stopped: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl MockDaemon {
+    pub fn start() -> io::Result<Self> {
+        let id = NEXT_MOCK_ID.fetch_add(1, Ordering::SeqCst);
+        let dir = std::env::temp_dir().join(format!("conc-mock-{}-{}", std::process::id(), id));
+        fs::create_dir_all(&dir)?;
+        let socket_path = dir.join("conc.sock");
+        let listener = UnixListener::bind(&socket_path)?;
+
+        let state = Arc::new(Mutex::new(MockState {
+            next_pid: 1000,
+            ..MockState::default()
+        }));
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let handle = {
+            let state = state.clone();
+            let stopped = stopped.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if stopped.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    if let Ok(stream) = stream {
+                        let _ = handle_connection(stream, &state);
+                    }
+                }
+            })
+        };
+
+        Ok(Self {
+            dir,
+            socket_path,
+            state,
+            stopped,
+            handle: Some(handle),
+        })
+    }
+
+    pub fn with_project(self, project: MockProject) -> Self {
+        self.add_project(project);
+        self
+    }
+
+    pub fn add_project(&self, project: MockProject) {
+        let mut state = self.lock();
+        state.projects.retain(|p| p.info.name != project.info.name);
+        state.projects.push(project);
+    }
+
+    pub fn socket_path(&self) -> &str {
+        self.socket_path.to_str().unwrap_or_default()
+    }
+
+    pub fn client(&self) -> SocketClient {
+        SocketClient::new(self.socket_path())
+    }
+
+    pub fn requester(&self) -> Requester {
+        Requester::new(self.client(), false)
+    }
+
+    pub fn projects(&self) -> Vec<ProjectInfo> {
+        self.lock()
+            .projects
+            .iter()
+            .map(|p| p.info.clone())
+            .collect()
+    }
+
+    /// Changes the status of the service as if its process changed it, returns false when it does not exist
+    pub fn set_service_status(&self, project: &str, service: &str, status: ServiceStatus) -> bool {
+        let mut guard = self.lock();
+        let state = &mut *guard;
+        let pid = state.next_pid;
+        match find_service(&mut state.projects, project, service) {
+            Some(info) => {
+                set_status(info, status, pid);
+                state.next_pid += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Responds to every request with the command by the raw response, e.g. `ERROR\u{11}driver_error`
+    pub fn respond_with(&self, command: &str, response: &str) {
+        self.lock()
+            .scripted
+            .insert(command.to_string(), response.to_string());
+    }
+
+    /// Requests received so far, in the order they were received
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.lock().requests.clone()
+    }
+
+    pub fn clear_requests(&self) {
+        self.lock().requests.clear();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, MockState> {
+        lock_state(&self.state)
+    }
+}
+
+impl Drop for MockDaemon {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        // wakes up the accept loop so it can see the stop flag
+        let _ = UnixStream::connect(&self.socket_path);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn handle_connection(mut stream: UnixStream, state: &Mutex<MockState>) -> io::Result<()> {
+    let mut input = vec![];
+    let mut buffer = [0; 4096];
+    loop {
+        let len = stream.read(&mut buffer)?;
+        if len == 0 {
+            break;
+        }
+        input.extend_from_slice(&buffer[..len]);
+        if input.contains(&b'\0') {
+            break;
+        }
+    }
+
+    let end = input
+        .iter()
+        .position(|b| *b == b'\0')
+        .unwrap_or(input.len());
+    let message = String::from_utf8_lossy(&input[..end]).to_string();
+
+    // empty message is the health check, the daemon responds with an empty one
+    let response = match message.is_empty() {
+        true => String::new(),
+        false => dispatch(&message, &mut lock_state(state)),
+    };
+
+    stream.write_all(response.as_bytes())?;
+    stream.write_all(b"\0")?;
+    stream.flush()
+}
+
+fn dispatch(message: &str, state: &mut MockState) -> String {
+    let mut parts = message.split(ARG_SEPARATOR_STR).map(String::from);
+    let command = parts.next().unwrap_or_default();
+    let args: Vec<String> = parts.collect();
+    state.requests.push(MockRequest {
+        command: command.clone(),
+        args: args.clone(),
+    });
+
+    if let Some(response) = state.scripted.get(&command) {
+        return response.clone();
+    }
+
+    let argc = match command.as_str() {
+        "PROJECTS-NAMES" | "PROJECTS-SETTINGS" | "PROJECTS-INFO" => 0,
+        "PROJECT-SETTINGS" | "PROJECT-INFO" | "PROJECT-UPSERT" | "PROJECT-STOP"
+        | "PROJECT-REMOVE" | "PROJECT-CLEAR-LOGS" | "SERVICES-NAMES" => 1,
+        "PROJECT-START" | "PROJECT-RESTART" | "SERVICE-INFO" | "SERVICE-STOP"
+        | "SERVICE-CLEAR-LOGS" => 2,
+        "SERVICE-START" | "SERVICE-RESTART" => 3,
+        _ => return error("unknown_command"),
+    };
+    if args.len() != argc {
+        return error("invalid_argument_count");
+    }
+
+    match handle_command(&command, &args, state) {
+        Ok(response) => response,
+        Err(code) => error(&code),
+    }
+}
+
+fn handle_command(command: &str, args: &[String], state: &mut MockState) -> Result<String, String> {
+    let projects = &mut state.projects;
+
+    match command {
+        "PROJECTS-NAMES" => Ok(ok(projects.iter().map(|p| p.info.name.clone()))),
+        "PROJECTS-SETTINGS" => Ok(ok(projects
+            .iter()
+            .map(|p| format!("{} {}", p.info.name, p.settings_json)))),
+        "PROJECTS-INFO" => {
+            let mut parts = vec![];
+            for (i, project) in projects.iter().enumerate() {
+                if i > 0 {
+                    parts.push(String::new());
+                }
+                parts.extend(format_project(&project.info));
+            }
+            Ok(ok(parts))
+        }
+        "PROJECT-SETTINGS" => Ok(ok(vec![find_project(projects, &args[0])?
+            .settings_json
+            .clone()])),
+        "PROJECT-INFO" => Ok(ok(format_project(&find_project(projects, &args[0])?.info))),
+        "PROJECT-UPSERT" => {
+            let project = MockProject::from_settings(&args[0])?;
+            let info = format_project(&project.info);
+            match projects
+                .iter_mut()
+                .find(|p| p.info.name == project.info.name)
+            {
+                Some(existing) => *existing = project,
+                None => projects.push(project),
+            }
+            Ok(ok(info))
+        }
+        "PROJECT-START" | "PROJECT-RESTART" => {
+            parse_env(&args[1])?;
+            let project = find_project(projects, &args[0])?;
+            for service in project.info.services.iter_mut() {
+                set_status(service, ServiceStatus::RUNNING, state.next_pid);
+                state.next_pid += 1;
+            }
+            Ok(ok(format_project(&project.info)))
+        }
+        "PROJECT-STOP" => {
+            let project = find_project(projects, &args[0])?;
+            for service in project.info.services.iter_mut() {
+                if service.status == ServiceStatus::RUNNING {
+                    set_status(service, ServiceStatus::STOPPED, 0);
+                }
+            }
+            Ok(ok(format_project(&project.info)))
+        }
+        "PROJECT-REMOVE" => {
+            find_project(projects, &args[0])?;
+            projects.retain(|p| p.info.name != args[0]);
+            Ok(ok(vec![]))
+        }
+        "PROJECT-CLEAR-LOGS" => find_project(projects, &args[0]).map(|_| ok(vec![])),
+        "SERVICES-NAMES" => Ok(ok(find_project(projects, &args[0])?
+            .info
+            .services
+            .iter()
+            .map(|s| s.name.clone()))),
+        "SERVICE-INFO" => Ok(ok(vec![format_service(find_service_or_error(
+            projects, &args[0], &args[1],
+        )?)])),
+        "SERVICE-START" | "SERVICE-RESTART" => {
+            parse_env(&args[2])?;
+            let service = find_service_or_error(projects, &args[0], &args[1])?;
+            set_status(service, ServiceStatus::RUNNING, state.next_pid);
+            state.next_pid += 1;
+            Ok(ok(vec![format_service(service)]))
+        }
+        "SERVICE-STOP" => {
+            let service = find_service_or_error(projects, &args[0], &args[1])?;
+            if service.status == ServiceStatus::RUNNING {
+                set_status(service, ServiceStatus::STOPPED, 0);
+            }
+            Ok(ok(vec![format_service(service)]))
+        }
+        "SERVICE-CLEAR-LOGS" => {
+            find_service_or_error(projects, &args[0], &args[1]).map(|_| ok(vec![]))
+        }
+        _ => Err(String::from("unknown_command")),
+    }
+}
+
+fn find_project<'a>(
+    projects: &'a mut [MockProject],
+    name: &str,
+) -> Result<&'a mut MockProject, String> {
+    projects
+        .iter_mut()
+        .find(|p| p.info.name == name)
+        .ok_or_else(|| String::from("project_not_found"))
+}
+
+fn find_service<'a>(
+    projects: &'a mut [MockProject],
+    project: &str,
+    service: &str,
+) -> Option<&'a mut ServiceInfo> {
+    projects
+        .iter_mut()
+        .find(|p| p.info.name == project)?
+        .info
+        .services
+        .iter_mut()
+        .find(|s| s.name == service)
+}
+
+fn find_service_or_error<'a>(
+    projects: &'a mut [MockProject],
+    project: &str,
+    service: &str,
+) -> Result<&'a mut ServiceInfo, String> {
+    find_project(projects, project)?;
+    find_service(projects, project, service).ok_or_else(|| String::from("service_not_found"))
+}
+
+fn set_status(service: &mut ServiceInfo, status: ServiceStatus, pid: i32) {
+    let now = now_secs();
+    match status {
+        ServiceStatus::RUNNING => {
+            service.pid = pid;
+            service.start_time = now;
+        }
+        ServiceStatus::STOPPED | ServiceStatus::EXITED => {
+            service.pid = 0;
+            service.stop_time = now;
+        }
+        ServiceStatus::IDLE => {
+            service.pid = -1;
+            service.start_time = 0;
+            service.stop_time = 0;
+        }
+    }
+    service.status = status;
+}
+
+fn parse_env(env: &str) -> Result<(), String> {
+    match serde_json::from_str::<HashMap<String, String>>(env) {
+        Ok(_) => Ok(()),
+        Err(_) => Err(String::from("env.parse")),
+    }
+}
+
+fn idle_service(name: &str) -> ServiceInfo {
+    ServiceInfo {
+        name: String::from(name),
+        status: ServiceStatus::IDLE,
+        pid: -1,
+        start_time: 0,
+        stop_time: 0,
+        logfile_path: String::from("/dev/null"),
+    }
+}
+
+/// Same restriction as the daemon has, names are used in file paths and space separated responses
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn format_project(info: &ProjectInfo) -> Vec<String> {
+    let mut parts = vec![info.name.clone()];
+    parts.extend(info.services.iter().map(format_service));
+    parts
+}
+
+fn format_service(info: &ServiceInfo) -> String {
+    format!(
+        "{} {} {} {} {} {}",
+        info.name,
+        info.status.to_string().to_uppercase(),
+        info.pid,
+        info.start_time,
+        info.stop_time,
+        info.logfile_path
+    )
+}
+
+fn ok(parts: impl IntoIterator<Item = String>) -> String {
+    let mut response = vec![String::from("OK")];
+    response.extend(parts);
+    response.join(ARG_SEPARATOR_STR)
+}
+
+fn error(code: &str) -> String {
+    ["ERROR", code].join(ARG_SEPARATOR_STR)
+}
+
+fn lock_state(state: &Mutex<MockState>) -> MutexGuard<'_, MockState> {
+    // a panicking test must not break the other requests
+    state.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
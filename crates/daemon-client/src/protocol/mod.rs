@@ -2,3 +2,6 @@ pub(crate) mod requests;
 pub mod responses;
 
 pub const ARG_SEPARATOR_STR: &str = "\u{11}";
+
+#[cfg(test)]
+mod tests;
@@ -4,6 +4,8 @@ use std::{
     vec,
 };
 
+use crate::protocol::ARG_SEPARATOR_STR;
+
 #[derive(Debug, thiserror::Error)]
 pub enum ErrorResponse {
     #[error("Error occurred while trying to communicate with daemon socket: {inner}")]
@@ -18,6 +20,8 @@ pub enum ErrorResponse {
     ProjectNotFound(String),
     #[error("Provided service was not found in provided project.")]
     ServiceNotFound(String),
+    #[error("The daemon rejected the token, check the configured daemon token.")]
+    Unauthorized(String),
 }
 
 impl From<std::io::Error> for ErrorResponse {
@@ -38,6 +42,7 @@ impl From<Vec<String>> for ErrorResponse {
         match err_name {
             "project_not_found" => Self::ProjectNotFound(raw),
             "service_not_found" => Self::ServiceNotFound(raw),
+            "unauthorized" => Self::Unauthorized(raw),
             x if x.starts_with("settings.")
                 || x.starts_with("env.")
                 || x == "unknown_command"
@@ -53,6 +58,13 @@ impl From<Vec<String>> for ErrorResponse {
     }
 }
 
+/// Parses the raw daemon response, responses which do not match the expected type are
+/// returned as the error they contain or as a malformed response
+pub(crate) fn parse_response<R: Response>(raw: &str) -> Result<R, ErrorResponse> {
+    let parts: Vec<String> = raw.split(ARG_SEPARATOR_STR).map(String::from).collect();
+    R::try_from(parts.clone()).map_err(|_| ErrorResponse::from(parts))
+}
+
 #[derive(Debug)]
 pub(crate) struct NameListResponse {
     pub values: Vec<String>,
@@ -113,7 +125,7 @@ impl TryFrom<Vec<String>> for ProjectsSettingsResponse {
         let mut values = vec![];
 
         for line in lines.iter() {
-            let parts: Vec<&str> = line.split("").collect();
+            let parts: Vec<&str> = line.splitn(2, ' ').collect();
             if parts.len() != 2 {
                 return Err(());
             }
@@ -293,8 +305,9 @@ impl TryFrom<&str> for ServiceInfo {
     type Error = ();
 
     fn try_from(data: &str) -> Result<Self, Self::Error> {
-        let parts: Vec<&str> = data.split(" ").collect();
-        if parts.len() < 5 {
+        // logfile path is the last part and it may contain spaces
+        let parts: Vec<&str> = data.splitn(6, ' ').collect();
+        if parts.len() < 6 {
             return Err(());
         }
 
@@ -0,0 +1,584 @@
+//! Conformance of the client with the protocol of the daemon, transcripts are copied from
+//! the responses of `concd` and the edge cases it can produce.
+
+use std::convert::TryFrom;
+
+use proptest::prelude::*;
+
+use super::{
+    requests::*,
+    responses::{
+        parse_response, ErrorResponse, NameListResponse, NoContentResponse, ProjectInfoResponse,
+        ProjectSettingsResponse, ProjectsInfoResponse, ProjectsSettingsResponse, Response,
+        ServiceInfo, ServiceInfoResponse, ServiceStatus,
+    },
+    ARG_SEPARATOR_STR,
+};
+
+type ServiceFields = (String, ServiceStatus, i32, u64, u64, String);
+
+fn error_kind(err: &ErrorResponse) -> &'static str {
+    match err {
+        ErrorResponse::Socket { .. } => "socket",
+        ErrorResponse::Client(_) => "client",
+        ErrorResponse::Daemon(_) => "daemon",
+        ErrorResponse::Malformed(_) => "malformed",
+        ErrorResponse::ProjectNotFound(_) => "project_not_found",
+        ErrorResponse::ServiceNotFound(_) => "service_not_found",
+        ErrorResponse::Unauthorized(_) => "unauthorized",
+    }
+}
+
+/// Checks every transcript, the expected value is compared in the form returned by `extract`
+fn check_transcripts<R, T>(transcripts: &[(&str, Result<T, &str>)], extract: impl Fn(R) -> T)
+where
+    R: Response,
+    T: PartialEq + std::fmt::Debug,
+{
+    for (raw, expected) in transcripts {
+        let actual = parse_response::<R>(raw)
+            .map(&extract)
+            .map_err(|err| error_kind(&err));
+        assert_eq!(&actual, expected, "response {:?}", raw);
+    }
+}
+
+fn service_fields(info: ServiceInfo) -> ServiceFields {
+    (
+        info.name,
+        info.status,
+        info.pid,
+        info.start_time,
+        info.stop_time,
+        info.logfile_path,
+    )
+}
+
+fn service(name: &str, status: ServiceStatus, pid: i32, logfile_path: &str) -> ServiceFields {
+    let (start_time, stop_time) = match status {
+        ServiceStatus::IDLE => (0, 0),
+        _ => (1700000000, 1700000100),
+    };
+    (
+        name.to_string(),
+        status,
+        pid,
+        start_time,
+        stop_time,
+        logfile_path.to_string(),
+    )
+}
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+#[test]
+fn requests_are_serialized() {
+    let env = String::from(r#"{"PATH":"/bin"}"#);
+    let transcripts = vec![
+        (ProjectsNamesRequest.serialize(), "PROJECTS-NAMES"),
+        (ProjectsSettingsRequest.serialize(), "PROJECTS-SETTINGS"),
+        (ProjectsInfoRequest.serialize(), "PROJECTS-INFO"),
+        (
+            ProjectSettingsRequest {
+                project_name: "shop",
+            }
+            .serialize(),
+            "PROJECT-SETTINGS\u{11}shop",
+        ),
+        (
+            ProjectInfoRequest {
+                project_name: "šop",
+            }
+            .serialize(),
+            "PROJECT-INFO\u{11}šop",
+        ),
+        (
+            ProjectUpsertRequest {
+                settings_json: r#"{"name":"shop"}"#,
+            }
+            .serialize(),
+            "PROJECT-UPSERT\u{11}{\"name\":\"shop\"}",
+        ),
+        (
+            ProjectStartRequest {
+                project_name: "shop",
+                env: env.clone(),
+            }
+            .serialize(),
+            "PROJECT-START\u{11}shop\u{11}{\"PATH\":\"/bin\"}",
+        ),
+        (
+            ProjectRestartRequest {
+                project_name: "shop",
+                env: String::from("{}"),
+            }
+            .serialize(),
+            "PROJECT-RESTART\u{11}shop\u{11}{}",
+        ),
+        (
+            ProjectStopRequest {
+                project_name: "shop",
+            }
+            .serialize(),
+            "PROJECT-STOP\u{11}shop",
+        ),
+        (
+            ProjectRemoveRequest {
+                project_name: "shop",
+            }
+            .serialize(),
+            "PROJECT-REMOVE\u{11}shop",
+        ),
+        (
+            ProjectClearLogsRequest {
+                project_name: "shop",
+            }
+            .serialize(),
+            "PROJECT-CLEAR-LOGS\u{11}shop",
+        ),
+        (
+            ServicesNamesRequest {
+                project_name: "shop",
+            }
+            .serialize(),
+            "SERVICES-NAMES\u{11}shop",
+        ),
+        (
+            ServiceInfoRequest {
+                project_name: "shop",
+                service_name: "api",
+            }
+            .serialize(),
+            "SERVICE-INFO\u{11}shop\u{11}api",
+        ),
+        (
+            ServiceStartRequest {
+                project_name: "shop",
+                service_name: "api",
+                env,
+            }
+            .serialize(),
+            "SERVICE-START\u{11}shop\u{11}api\u{11}{\"PATH\":\"/bin\"}",
+        ),
+        (
+            ServiceRestartRequest {
+                project_name: "shop",
+                service_name: "api",
+                env: String::from("{}"),
+            }
+            .serialize(),
+            "SERVICE-RESTART\u{11}shop\u{11}api\u{11}{}",
+        ),
+        (
+            ServiceStopRequest {
+                project_name: "shop",
+                service_name: "api",
+            }
+            .serialize(),
+            "SERVICE-STOP\u{11}shop\u{11}api",
+        ),
+        (
+            ServiceClearLogsRequest {
+                project_name: "shop",
+                service_name: "api",
+            }
+            .serialize(),
+            "SERVICE-CLEAR-LOGS\u{11}shop\u{11}api",
+        ),
+    ];
+
+    for (actual, expected) in transcripts {
+        assert_eq!(actual, expected);
+    }
+}
+
+#[test]
+fn error_responses() {
+    let transcripts = [
+        ("ERROR\u{11}project_not_found", "project_not_found"),
+        ("ERROR\u{11}service_not_found", "service_not_found"),
+        ("ERROR\u{11}unauthorized", "unauthorized"),
+        ("ERROR\u{11}settings.parse", "client"),
+        ("ERROR\u{11}settings.service.api.name.invalid", "client"),
+        ("ERROR\u{11}env.parse", "client"),
+        ("ERROR\u{11}unknown_command", "client"),
+        ("ERROR\u{11}invalid_argument_count", "client"),
+        ("ERROR\u{11}driver_error", "daemon"),
+        ("ERROR\u{11}manager_error", "daemon"),
+        ("ERROR\u{11}unknown-code--7", "daemon"),
+        ("ERROR\u{11}something_new", "malformed"),
+        ("ERROR", "malformed"),
+        ("ERROR\u{11}", "malformed"),
+        ("error\u{11}project_not_found", "malformed"),
+        ("", "malformed"),
+        ("\u{11}\u{11}", "malformed"),
+    ];
+
+    for (raw, expected) in transcripts.iter() {
+        let parts: Vec<String> = raw.split(ARG_SEPARATOR_STR).map(String::from).collect();
+        assert_eq!(
+            error_kind(&ErrorResponse::from(parts)),
+            *expected,
+            "response {:?}",
+            raw
+        );
+    }
+}
+
+#[test]
+fn error_keeps_the_raw_response() {
+    let err = parse_response::<NoContentResponse>("ERROR\u{11}driver_error").unwrap_err();
+    assert!(matches!(err, ErrorResponse::Daemon(raw) if raw == "ERROR driver_error"));
+}
+
+#[test]
+fn name_list_responses() {
+    check_transcripts::<NameListResponse, _>(
+        &[
+            ("OK", Ok(vec![])),
+            ("OK\u{11}shop", Ok(strings(&["shop"]))),
+            ("OK\u{11}shop\u{11}blog", Ok(strings(&["shop", "blog"]))),
+            ("OK\u{11}čaj\u{11}日本", Ok(strings(&["čaj", "日本"]))),
+            ("OK\u{11}", Ok(strings(&[""]))),
+            ("ERROR\u{11}project_not_found", Err("project_not_found")),
+            ("", Err("malformed")),
+            ("NOPE\u{11}shop", Err("malformed")),
+        ],
+        |res| res.values,
+    );
+}
+
+#[test]
+fn project_settings_responses() {
+    check_transcripts::<ProjectSettingsResponse, _>(
+        &[
+            (
+                "OK\u{11}{\"name\":\"shop\"}",
+                Ok(String::from("{\"name\":\"shop\"}")),
+            ),
+            (
+                "OK\u{11}{\"name\":\"šop ☕\"}",
+                Ok(String::from("{\"name\":\"šop ☕\"}")),
+            ),
+            ("OK", Err("malformed")),
+            ("OK\u{11}{}\u{11}{}", Err("malformed")),
+            ("ERROR\u{11}project_not_found", Err("project_not_found")),
+        ],
+        |res| res.value,
+    );
+}
+
+#[test]
+fn projects_settings_responses() {
+    let pair = |name: &str, json: &str| (name.to_string(), json.to_string());
+    check_transcripts::<ProjectsSettingsResponse, _>(
+        &[
+            ("OK", Ok(vec![])),
+            (
+                "OK\u{11}shop {\"name\":\"shop\"}\u{11}blog {\"cwd\":\"/a b\"}",
+                Ok(vec![
+                    pair("shop", "{\"name\":\"shop\"}"),
+                    pair("blog", "{\"cwd\":\"/a b\"}"),
+                ]),
+            ),
+            ("OK\u{11}shop", Err("malformed")),
+            ("OK\u{11}", Err("malformed")),
+            ("ERROR\u{11}manager_error", Err("daemon")),
+        ],
+        |res| res.values,
+    );
+}
+
+#[test]
+fn service_info_responses() {
+    check_transcripts::<ServiceInfoResponse, _>(
+        &[
+            (
+                "OK\u{11}api RUNNING 1234 1700000000 1700000100 /run/logs/api.log",
+                Ok(service(
+                    "api",
+                    ServiceStatus::RUNNING,
+                    1234,
+                    "/run/logs/api.log",
+                )),
+            ),
+            (
+                "OK\u{11}api IDLE -1 0 0 -",
+                Ok(service("api", ServiceStatus::IDLE, -1, "/dev/null")),
+            ),
+            (
+                "OK\u{11}api STOPPED 0 1700000000 1700000100 /home/me/My Logs/api.log",
+                Ok(service(
+                    "api",
+                    ServiceStatus::STOPPED,
+                    0,
+                    "/home/me/My Logs/api.log",
+                )),
+            ),
+            (
+                "OK\u{11}api EXITED 0 1700000000 1700000100 /logs/☕.log",
+                Ok(service("api", ServiceStatus::EXITED, 0, "/logs/☕.log")),
+            ),
+            (
+                "OK\u{11}api RUNNING 1234 1700000000 1700000100",
+                Err("malformed"),
+            ),
+            ("OK\u{11}api RUNNING 1234", Err("malformed")),
+            ("OK\u{11}api running 1 0 0 -", Err("malformed")),
+            ("OK\u{11}api RUNNING x 0 0 -", Err("malformed")),
+            ("OK\u{11}api RUNNING 1 -5 0 -", Err("malformed")),
+            ("OK\u{11}api  RUNNING 1 0 0 -", Err("malformed")),
+            ("OK", Err("malformed")),
+            ("ERROR\u{11}service_not_found", Err("service_not_found")),
+        ],
+        |res| service_fields(res.value),
+    );
+}
+
+#[test]
+fn project_info_responses() {
+    check_transcripts::<ProjectInfoResponse, _>(
+        &[
+            ("OK\u{11}shop", Ok((String::from("shop"), vec![]))),
+            (
+                "OK\u{11}shop\u{11}api RUNNING 12 1700000000 1700000100 -\u{11}web IDLE -1 0 0 -",
+                Ok((
+                    String::from("shop"),
+                    vec![
+                        service("api", ServiceStatus::RUNNING, 12, "/dev/null"),
+                        service("web", ServiceStatus::IDLE, -1, "/dev/null"),
+                    ],
+                )),
+            ),
+            ("OK\u{11}日本", Ok((String::from("日本"), vec![]))),
+            // names can not contain spaces, a service line in place of the name is rejected
+            ("OK\u{11}api IDLE -1 0 0 -", Err("malformed")),
+            ("OK\u{11}shop\u{11}api IDLE -1 0", Err("malformed")),
+            ("OK\u{11}shop\u{11}", Err("malformed")),
+            ("OK", Err("malformed")),
+            ("ERROR\u{11}project_not_found", Err("project_not_found")),
+        ],
+        |res| {
+            let info = res.value;
+            (
+                info.name,
+                info.services.into_iter().map(service_fields).collect(),
+            )
+        },
+    );
+}
+
+#[test]
+fn projects_info_responses() {
+    check_transcripts::<ProjectsInfoResponse, _>(
+        &[
+            ("OK", Ok(vec![])),
+            (
+                "OK\u{11}shop\u{11}api IDLE -1 0 0 -\u{11}\u{11}blog\u{11}app RUNNING 7 1700000000 1700000100 /l",
+                Ok(vec![
+                    (
+                        String::from("shop"),
+                        vec![service("api", ServiceStatus::IDLE, -1, "/dev/null")],
+                    ),
+                    (
+                        String::from("blog"),
+                        vec![service("app", ServiceStatus::RUNNING, 7, "/l")],
+                    ),
+                ]),
+            ),
+            (
+                "OK\u{11}shop\u{11}\u{11}blog",
+                Ok(vec![
+                    (String::from("shop"), vec![]),
+                    (String::from("blog"), vec![]),
+                ]),
+            ),
+            ("OK\u{11}shop\u{11}api IDLE", Err("malformed")),
+            ("ERROR\u{11}driver_error", Err("daemon")),
+        ],
+        |res| {
+            res.values
+                .into_iter()
+                .map(|info| {
+                    (
+                        info.name,
+                        info.services.into_iter().map(service_fields).collect(),
+                    )
+                })
+                .collect::<Vec<(String, Vec<ServiceFields>)>>()
+        },
+    );
+}
+
+#[test]
+fn no_content_responses() {
+    check_transcripts::<NoContentResponse, _>(
+        &[
+            ("OK", Ok(())),
+            ("OK\u{11}ignored", Ok(())),
+            ("", Err("malformed")),
+            ("ERROR\u{11}project_not_found", Err("project_not_found")),
+        ],
+        |_| (),
+    );
+}
+
+/// Parts the daemon produces and the ones which are close to them, random strings find the rest
+fn response_part() -> impl Strategy<Value = String> {
+    let service_line = (
+        "[a-z_-]{0,8}",
+        prop_oneof![
+            Just("IDLE"),
+            Just("RUNNING"),
+            Just("STOPPED"),
+            Just("EXITED"),
+            Just("running")
+        ],
+        any::<i64>(),
+        any::<i64>(),
+        any::<u64>(),
+        "[-/a-z ☕]{0,10}",
+        0..7usize,
+    )
+        .prop_map(|(name, status, pid, start, stop, logfile, fields)| {
+            let parts = [
+                name,
+                status.to_string(),
+                pid.to_string(),
+                start.to_string(),
+                stop.to_string(),
+                logfile,
+            ];
+            // truncated lines are created by keeping only some of the fields
+            parts[..fields.min(parts.len())].join(" ")
+        });
+
+    prop_oneof![
+        Just(String::from("OK")),
+        Just(String::from("ERROR")),
+        Just(String::new()),
+        Just(String::from("-")),
+        Just(String::from("project_not_found")),
+        "[a-z]{1,8} \\{.{0,8}\\}",
+        service_line,
+        "\\PC{0,12}",
+        ".{0,12}",
+    ]
+}
+
+fn response_parts() -> impl Strategy<Value = Vec<String>> {
+    prop::collection::vec(response_part(), 0..8)
+}
+
+/// Parsing must not panic and a response which is not accepted has to become an error
+fn assert_parsed_or_error<R: Response>(raw: &str) {
+    if let Err(err) = parse_response::<R>(raw) {
+        let is_error_response = raw.starts_with("ERROR");
+        match err {
+            ErrorResponse::Malformed(_) => {}
+            _ => assert!(is_error_response, "{:?} became {:?}", raw, err),
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn parsing_never_panics(parts in response_parts()) {
+        let raw = parts.join(ARG_SEPARATOR_STR);
+
+        assert_parsed_or_error::<NameListResponse>(&raw);
+        assert_parsed_or_error::<ProjectSettingsResponse>(&raw);
+        assert_parsed_or_error::<ProjectsSettingsResponse>(&raw);
+        assert_parsed_or_error::<ServiceInfoResponse>(&raw);
+        assert_parsed_or_error::<ProjectInfoResponse>(&raw);
+        assert_parsed_or_error::<ProjectsInfoResponse>(&raw);
+        assert_parsed_or_error::<NoContentResponse>(&raw);
+        let _ = ErrorResponse::from(parts.clone());
+        for part in parts.iter() {
+            let _ = ServiceInfo::try_from(part.as_str());
+            let _ = ServiceStatus::try_from(part.as_str());
+        }
+    }
+
+    #[test]
+    fn parsing_arbitrary_bytes_never_panics(raw in ".{0,64}") {
+        assert_parsed_or_error::<NameListResponse>(&raw);
+        assert_parsed_or_error::<ProjectSettingsResponse>(&raw);
+        assert_parsed_or_error::<ProjectsSettingsResponse>(&raw);
+        assert_parsed_or_error::<ServiceInfoResponse>(&raw);
+        assert_parsed_or_error::<ProjectInfoResponse>(&raw);
+        assert_parsed_or_error::<ProjectsInfoResponse>(&raw);
+        assert_parsed_or_error::<NoContentResponse>(&raw);
+    }
+
+    #[test]
+    fn error_responses_are_never_parsed_as_values(code in ".{0,12}") {
+        let raw = format!("ERROR{}{}", ARG_SEPARATOR_STR, code);
+
+        prop_assert!(parse_response::<NameListResponse>(&raw).is_err());
+        prop_assert!(parse_response::<ProjectSettingsResponse>(&raw).is_err());
+        prop_assert!(parse_response::<ProjectsSettingsResponse>(&raw).is_err());
+        prop_assert!(parse_response::<ServiceInfoResponse>(&raw).is_err());
+        prop_assert!(parse_response::<ProjectInfoResponse>(&raw).is_err());
+        prop_assert!(parse_response::<ProjectsInfoResponse>(&raw).is_err());
+        prop_assert!(parse_response::<NoContentResponse>(&raw).is_err());
+    }
+
+    #[test]
+    fn service_lines_of_the_daemon_are_parsed(
+        name in "[a-zA-Z0-9_-]{1,12}",
+        status in prop_oneof![
+            Just(ServiceStatus::IDLE),
+            Just(ServiceStatus::RUNNING),
+            Just(ServiceStatus::STOPPED),
+            Just(ServiceStatus::EXITED)
+        ],
+        pid in any::<i32>(),
+        start_time in any::<u64>(),
+        stop_time in any::<u64>(),
+        logfile_path in "/[^\u{11}]{0,20}",
+    ) {
+        let line = format!(
+            "{} {} {} {} {} {}",
+            name,
+            status.to_string().to_uppercase(),
+            pid,
+            start_time,
+            stop_time,
+            logfile_path
+        );
+
+        let info = ServiceInfo::try_from(line.as_str()).unwrap();
+        prop_assert_eq!(
+            service_fields(info),
+            (name, status, pid, start_time, stop_time, logfile_path)
+        );
+    }
+
+    #[test]
+    fn projects_of_the_daemon_are_parsed(
+        projects in prop::collection::vec(
+            ("[a-zA-Z0-9_-]{1,12}", prop::collection::vec("[a-zA-Z0-9_-]{1,12}", 0..4)),
+            0..4
+        )
+    ) {
+        let mut parts = vec![String::from("OK")];
+        for (i, (name, services)) in projects.iter().enumerate() {
+            if i > 0 {
+                parts.push(String::new());
+            }
+            parts.push(name.clone());
+            parts.extend(services.iter().map(|service| format!("{} IDLE -1 0 0 -", service)));
+        }
+
+        let res = parse_response::<ProjectsInfoResponse>(&parts.join(ARG_SEPARATOR_STR)).unwrap();
+        let parsed: Vec<(String, Vec<String>)> = res
+            .values
+            .into_iter()
+            .map(|info| (info.name, info.services.into_iter().map(|s| s.name).collect()))
+            .collect();
+        prop_assert_eq!(parsed, projects);
+    }
+}
@@ -9,8 +9,7 @@ use crate::{
             ProjectsSettingsRequest, Request, ServiceClearLogsRequest, ServiceInfoRequest,
             ServiceRestartRequest, ServiceStartRequest, ServiceStopRequest, ServicesNamesRequest,
         },
-        responses::ErrorResponse,
-        ARG_SEPARATOR_STR,
+        responses::{parse_response, ErrorResponse},
     },
     socket_client::SocketClient,
     ProjectInfo, Response, ServiceInfo,
@@ -73,6 +72,19 @@ impl Requester {
         .map(|res| res.value)
     }
 
+    /// Starts only the provided services of the project, in the provided order
+    pub fn start_project_services(
+        &self,
+        project_name: &str,
+        service_names: &[String],
+    ) -> Res<ProjectInfo> {
+        for service_name in service_names {
+            self.start_service(project_name, service_name)?;
+        }
+
+        self.get_project_info(project_name)
+    }
+
     pub fn restart_project(&self, project_name: &str) -> Res<ProjectInfo> {
         self.send_request(ProjectRestartRequest {
             project_name,
@@ -146,8 +158,7 @@ impl Requester {
     fn send_request<R: Response>(&self, req: impl Request<R>) -> Res<R> {
         let req_string = req.serialize();
         let resp = self.socket_client.send(req_string.as_bytes())?;
-        let parts: Vec<String> = resp.split(ARG_SEPARATOR_STR).map(String::from).collect();
-        R::try_from(parts.clone()).map_err(|_| ErrorResponse::from(parts))
+        parse_response(&resp)
     }
 
     fn get_request_env(&self) -> String {
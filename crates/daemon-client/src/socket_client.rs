@@ -1,57 +1,102 @@
-use std::fs;
-use std::io::{self, Read, Write};
-use std::net::Shutdown;
-use std::os::unix::fs::FileTypeExt;
-use std::os::unix::net::UnixStream;
+use std::io::{self, ErrorKind, Read, Write};
 use std::str;
+use std::sync::Arc;
+
+use crate::protocol::ARG_SEPARATOR_STR;
+use crate::transport::{create_transport, Transport, TransportOptions, REQUEST_TIMEOUT};
+use crate::ErrorResponse;
 
 #[derive(Debug, Clone)]
 pub struct SocketClient {
-    pub socket_path: String,
+    transport: Arc<dyn Transport>,
 }
 
 impl SocketClient {
-    pub fn new(socket_path: &str) -> Self {
+    /// Client of a daemon reachable without credentials, e.g. through a local socket
+    pub fn new(address: &str) -> Self {
+        Self::with_options(address, &TransportOptions::default())
+    }
+
+    pub fn with_options(address: &str, options: &TransportOptions) -> Self {
         Self {
-            socket_path: socket_path.to_string(),
+            transport: create_transport(address, options),
         }
     }
 
+    pub fn address(&self) -> String {
+        self.transport.address()
+    }
+
+    pub fn unreachable_hint(&self) -> &'static str {
+        self.transport.unreachable_hint()
+    }
+
     pub fn is_alive(&self) -> bool {
-        let socket_exists = fs::metadata(&self.socket_path)
-            .map(|stat| stat.file_type().is_socket())
-            .unwrap_or(false);
+        self.health_check().is_ok()
+    }
 
-        if !socket_exists {
-            return false;
+    /// Daemon responds to the health check with an empty message
+    pub fn health_check(&self) -> io::Result<()> {
+        let res = self.send(b"\0")?;
+        if res.is_empty() {
+            return Ok(());
         }
 
-        let health_check = self.send(b"\0");
-        match health_check {
-            Ok(res) => res.is_empty(),
-            Err(_) => false,
-        }
+        let parts: Vec<String> = res.split(ARG_SEPARATOR_STR).map(String::from).collect();
+        let error = ErrorResponse::from(parts);
+        let kind = match error {
+            ErrorResponse::Unauthorized(_) => ErrorKind::PermissionDenied,
+            _ => ErrorKind::InvalidData,
+        };
+        Err(io::Error::new(kind, error.to_string()))
     }
 
     pub fn send(&self, message: &[u8]) -> io::Result<String> {
-        let mut unix_stream = UnixStream::connect(&self.socket_path)?;
-
-        unix_stream.write_all(message)?;
-        unix_stream.write_all(b"\0")?;
-        unix_stream.shutdown(Shutdown::Write)?;
+        let mut connection = self.transport.connect()?;
 
-        let mut response = String::new();
+        // message and its terminator are written at once, the daemon may respond
+        // and close the connection as soon as it reads the terminator
+        let mut payload = Vec::with_capacity(message.len() + 1);
+        payload.extend_from_slice(message);
+        payload.push(b'\0');
+        connection.write_all(&payload).map_err(describe_timeout)?;
+        connection.close_write().map_err(describe_timeout)?;
 
-        unix_stream.read_to_string(&mut response)?;
-
-        let last_char = response.chars().last();
+        // response ends with '\0', reading stops there as TLS proxies may close
+        // the connection without notifying about it
+        let mut response = vec![];
+        let mut buffer = [0; 4096];
+        loop {
+            let len = match connection.read(&mut buffer) {
+                Ok(len) => len,
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Err(describe_timeout(err)),
+            };
+            if len == 0 {
+                break;
+            }
 
-        if let Some(lc) = last_char {
-            if lc == '\0' {
-                response.truncate(response.len() - 1);
+            response.extend_from_slice(&buffer[..len]);
+            if response.last() == Some(&b'\0') {
+                response.pop();
+                break;
             }
         }
 
-        Ok(response)
+        String::from_utf8(response).map_err(|err| io::Error::new(ErrorKind::InvalidData, err))
+    }
+}
+
+/// Timeouts of the remote transports are reported as 'WouldBlock' on unix, which reads as a bug
+fn describe_timeout(err: io::Error) -> io::Error {
+    match err.kind() {
+        ErrorKind::WouldBlock | ErrorKind::TimedOut => io::Error::new(
+            ErrorKind::TimedOut,
+            format!(
+                "daemon did not respond within {} seconds",
+                REQUEST_TIMEOUT.as_secs()
+            ),
+        ),
+        _ => err,
     }
 }
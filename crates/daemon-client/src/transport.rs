@@ -0,0 +1,530 @@
+use std::convert::TryFrom;
+use std::fmt::Debug;
+use std::fs;
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, ServerName};
+use rustls::{ClientConfig, ClientConnection, RootCertStore, StreamOwned};
+
+const UNIX_SCHEME: &str = "unix://";
+const TCP_SCHEME: &str = "tcp://";
+const TLS_SCHEME: &str = "tls://";
+const SSH_SCHEME: &str = "ssh://";
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Longest wait for a remote daemon to accept or send a part of a message, local sockets do not time out
+pub(crate) const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+const SSH_TUNNEL_TIMEOUT: Duration = Duration::from_secs(15);
+const SSH_TUNNEL_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+static SSH_TUNNEL_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Credentials of the transports which reach the daemon over the network
+#[derive(Debug, Clone, Default)]
+pub struct TransportOptions {
+    /// token sent before every request over TCP
+    pub token: Option<String>,
+    /// PEM file with certificates trusted besides the system ones, used by TLS
+    pub ca_file: Option<String>,
+}
+
+/// Connection carrying a single request and its response
+pub trait Connection: Read + Write {
+    /// Signals the end of the request, the daemon responds once it reads all of it
+    fn close_write(&mut self) -> io::Result<()>;
+}
+
+/// Way of reaching the daemon, every request opens a new connection
+pub trait Transport: Debug + Send + Sync {
+    fn connect(&self) -> io::Result<Box<dyn Connection>>;
+    /// Address of the daemon as shown to the user
+    fn address(&self) -> String;
+    /// Most likely causes of a failed connection
+    fn unreachable_hint(&self) -> &'static str;
+}
+
+/// Creates the transport from a socket path or an address with one of the schemes
+/// `unix://`, `tcp://`, `tls://` or `ssh://`. Invalid addresses create a transport
+/// which fails every connection with the reason.
+pub fn create_transport(address: &str, options: &TransportOptions) -> Arc<dyn Transport> {
+    match parse_transport(address, options) {
+        Ok(transport) => transport,
+        Err(reason) => Arc::new(InvalidTransport {
+            address: address.to_string(),
+            reason,
+        }),
+    }
+}
+
+fn parse_transport(
+    address: &str,
+    options: &TransportOptions,
+) -> Result<Arc<dyn Transport>, String> {
+    if let Some(path) = address.strip_prefix(UNIX_SCHEME) {
+        return Ok(Arc::new(UnixTransport::new(path)));
+    }
+
+    if let Some(host_port) = address.strip_prefix(TCP_SCHEME) {
+        return Ok(Arc::new(TcpTransport::new(host_port, options, None)?));
+    }
+
+    if let Some(host_port) = address.strip_prefix(TLS_SCHEME) {
+        let tls = TlsSettings::new(host_port, options)?;
+        return Ok(Arc::new(TcpTransport::new(host_port, options, Some(tls))?));
+    }
+
+    if let Some(rest) = address.strip_prefix(SSH_SCHEME) {
+        return Ok(Arc::new(SshTransport::new(rest)?));
+    }
+
+    match address.contains("://") {
+        true => Err(String::from("unknown scheme")),
+        false => Ok(Arc::new(UnixTransport::new(address))),
+    }
+}
+
+#[derive(Debug)]
+struct InvalidTransport {
+    address: String,
+    reason: String,
+}
+
+impl Transport for InvalidTransport {
+    fn connect(&self) -> io::Result<Box<dyn Connection>> {
+        Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            format!("unable to use the daemon address: {}", self.reason),
+        ))
+    }
+
+    fn address(&self) -> String {
+        self.address.clone()
+    }
+
+    fn unreachable_hint(&self) -> &'static str {
+        "Use a socket path or an address starting with unix://, tcp://, tls:// or ssh://, and check the daemon token and CA file."
+    }
+}
+
+#[derive(Debug)]
+struct UnixTransport {
+    socket_path: PathBuf,
+}
+
+impl UnixTransport {
+    fn new(socket_path: &str) -> Self {
+        Self {
+            socket_path: PathBuf::from(socket_path),
+        }
+    }
+}
+
+impl Transport for UnixTransport {
+    fn connect(&self) -> io::Result<Box<dyn Connection>> {
+        let is_socket = fs::metadata(&self.socket_path)
+            .map(|stat| stat.file_type().is_socket())
+            .unwrap_or(false);
+        if !is_socket {
+            return Err(io::Error::new(
+                ErrorKind::NotFound,
+                "socket file does not exist",
+            ));
+        }
+
+        Ok(Box::new(UnixStream::connect(&self.socket_path)?))
+    }
+
+    fn address(&self) -> String {
+        format!("{}{}", UNIX_SCHEME, self.socket_path.to_string_lossy())
+    }
+
+    fn unreachable_hint(&self) -> &'static str {
+        "Daemon is not running or is using different work directory."
+    }
+}
+
+impl Connection for UnixStream {
+    fn close_write(&mut self) -> io::Result<()> {
+        self.shutdown(Shutdown::Write)
+    }
+}
+
+#[derive(Debug)]
+struct TlsSettings {
+    config: Arc<ClientConfig>,
+    server_name: ServerName<'static>,
+}
+
+impl TlsSettings {
+    /// Trusts the system certificates and the ones from the CA file
+    fn new(host_port: &str, options: &TransportOptions) -> Result<Self, String> {
+        let (host, _) = split_host_port(host_port)?;
+        let server_name = ServerName::try_from(host.trim_matches(|c| c == '[' || c == ']'))
+            .map_err(|err| format!("invalid host name: {}", err))?
+            .to_owned();
+
+        let mut roots = RootCertStore::empty();
+        roots.add_parsable_certificates(rustls_native_certs::load_native_certs().certs);
+        if let Some(ca_file) = &options.ca_file {
+            let certs = CertificateDer::pem_file_iter(ca_file)
+                .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+                .map_err(|err| format!("unable to read the CA file '{}': {}", ca_file, err))?;
+            roots.add_parsable_certificates(certs);
+        }
+
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let config = ClientConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .map_err(|err| err.to_string())?
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+
+        Ok(Self {
+            config: Arc::new(config),
+            server_name,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct TcpTransport {
+    host_port: String,
+    token: Option<String>,
+    tls: Option<TlsSettings>,
+    request_timeout: Duration,
+}
+
+impl TcpTransport {
+    fn new(
+        host_port: &str,
+        options: &TransportOptions,
+        tls: Option<TlsSettings>,
+    ) -> Result<Self, String> {
+        split_host_port(host_port)?;
+
+        Ok(Self {
+            host_port: host_port.to_string(),
+            token: options.token.clone(),
+            tls,
+            request_timeout: REQUEST_TIMEOUT,
+        })
+    }
+
+    fn connect_tcp(&self) -> io::Result<TcpStream> {
+        let mut last_error = io::Error::new(ErrorKind::NotFound, "address was not resolved");
+        for addr in self.host_port.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT) {
+                Ok(stream) => {
+                    stream.set_nodelay(true)?;
+                    stream.set_read_timeout(Some(self.request_timeout))?;
+                    stream.set_write_timeout(Some(self.request_timeout))?;
+                    return Ok(stream);
+                }
+                Err(err) => last_error = err,
+            }
+        }
+
+        Err(last_error)
+    }
+}
+
+impl Transport for TcpTransport {
+    fn connect(&self) -> io::Result<Box<dyn Connection>> {
+        let stream = self.connect_tcp()?;
+
+        let mut connection: Box<dyn Connection> = match &self.tls {
+            Some(tls) => {
+                let client = ClientConnection::new(tls.config.clone(), tls.server_name.clone())
+                    .map_err(io::Error::other)?;
+                Box::new(TlsConnection(StreamOwned::new(client, stream)))
+            }
+            None => Box::new(stream),
+        };
+
+        // the daemon reads the token terminated by '\0' before the message
+        if let Some(token) = &self.token {
+            let mut frame = Vec::with_capacity(token.len() + 1);
+            frame.extend_from_slice(token.as_bytes());
+            frame.push(b'\0');
+            connection.write_all(&frame)?;
+        }
+
+        Ok(connection)
+    }
+
+    fn address(&self) -> String {
+        let scheme = match self.tls {
+            Some(_) => TLS_SCHEME,
+            None => TCP_SCHEME,
+        };
+        format!("{}{}", scheme, self.host_port)
+    }
+
+    fn unreachable_hint(&self) -> &'static str {
+        "Daemon is not listening on the address or it rejected the token or the certificate."
+    }
+}
+
+impl Connection for TcpStream {
+    fn close_write(&mut self) -> io::Result<()> {
+        self.shutdown(Shutdown::Write)
+    }
+}
+
+struct TlsConnection(StreamOwned<ClientConnection, TcpStream>);
+
+impl Read for TlsConnection {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl Write for TlsConnection {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl Connection for TlsConnection {
+    fn close_write(&mut self) -> io::Result<()> {
+        self.0.conn.send_close_notify();
+        self.0.flush()?;
+        self.0.sock.shutdown(Shutdown::Write)
+    }
+}
+
+/// Forwards a local socket to the remote daemon socket through an `ssh -L` subprocess,
+/// the tunnel is opened by the first request and closed when the transport is dropped
+#[derive(Debug)]
+struct SshTransport {
+    destination: String,
+    port: Option<String>,
+    remote_socket_path: String,
+    tunnel: Mutex<Option<SshTunnel>>,
+}
+
+#[derive(Debug)]
+struct SshTunnel {
+    child: Child,
+    local_socket_path: PathBuf,
+}
+
+impl SshTransport {
+    /// Parses `[user@]host[:port]/path/to/conc.sock`
+    fn new(rest: &str) -> Result<Self, String> {
+        let (authority, path) = rest
+            .find('/')
+            .map(|index| rest.split_at(index))
+            .ok_or_else(|| String::from("missing path of the remote socket"))?;
+
+        let (destination, port) = match authority.rsplit_once(':') {
+            Some((destination, port)) => {
+                port.parse::<u16>()
+                    .map_err(|_| format!("invalid port '{}'", port))?;
+                (destination, Some(port.to_string()))
+            }
+            None => (authority, None),
+        };
+
+        if destination.is_empty() || destination.ends_with('@') {
+            return Err(String::from("missing host"));
+        }
+
+        Ok(Self {
+            destination: destination.to_string(),
+            port,
+            remote_socket_path: path.to_string(),
+            tunnel: Mutex::new(None),
+        })
+    }
+
+    fn open_tunnel(&self) -> io::Result<SshTunnel> {
+        let local_socket_path = std::env::temp_dir().join(format!(
+            "conc-ssh-{}-{}.sock",
+            std::process::id(),
+            SSH_TUNNEL_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = fs::remove_file(&local_socket_path);
+
+        let mut command = Command::new("ssh");
+        command
+            .args(["-N", "-T"])
+            .args(["-o", "BatchMode=yes"])
+            .args(["-o", "ExitOnForwardFailure=yes"])
+            .args(["-o", "StreamLocalBindUnlink=yes"]);
+        if let Some(port) = &self.port {
+            command.args(["-p", port]);
+        }
+        command
+            .arg("-L")
+            .arg(format!(
+                "{}:{}",
+                local_socket_path.to_string_lossy(),
+                self.remote_socket_path
+            ))
+            .arg(&self.destination)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped());
+
+        let mut tunnel = SshTunnel {
+            child: command.spawn()?,
+            local_socket_path,
+        };
+
+        let started_at = Instant::now();
+        loop {
+            if let Some(status) = tunnel.child.try_wait()? {
+                let mut stderr = String::new();
+                if let Some(mut pipe) = tunnel.child.stderr.take() {
+                    let _ = pipe.read_to_string(&mut stderr);
+                }
+                return Err(io::Error::new(
+                    ErrorKind::ConnectionRefused,
+                    format!("ssh exited with {}: {}", status, stderr.trim()),
+                ));
+            }
+
+            let is_ready = fs::metadata(&tunnel.local_socket_path)
+                .map(|stat| stat.file_type().is_socket())
+                .unwrap_or(false);
+            if is_ready {
+                // ssh keeps writing warnings while the tunnel is open, it would block on a full pipe
+                if let Some(mut pipe) = tunnel.child.stderr.take() {
+                    thread::spawn(move || io::copy(&mut pipe, &mut io::sink()));
+                }
+                return Ok(tunnel);
+            }
+
+            if started_at.elapsed() > SSH_TUNNEL_TIMEOUT {
+                return Err(io::Error::new(
+                    ErrorKind::TimedOut,
+                    "ssh tunnel was not opened in time",
+                ));
+            }
+
+            thread::sleep(SSH_TUNNEL_POLL_INTERVAL);
+        }
+    }
+}
+
+impl Transport for SshTransport {
+    fn connect(&self) -> io::Result<Box<dyn Connection>> {
+        let mut tunnel = self
+            .tunnel
+            .lock()
+            .map_err(|_| io::Error::other("ssh tunnel lock is poisoned"))?;
+
+        // tunnel is opened again when ssh exited, e.g. after the network was down
+        let is_running = match tunnel.as_mut() {
+            Some(tunnel) => tunnel.child.try_wait()?.is_none(),
+            None => false,
+        };
+        if !is_running {
+            *tunnel = Some(self.open_tunnel()?);
+        }
+
+        let local_socket_path = &tunnel.as_ref().unwrap().local_socket_path;
+        let stream = UnixStream::connect(local_socket_path)?;
+        stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+        stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+        Ok(Box::new(stream))
+    }
+
+    fn address(&self) -> String {
+        let port = self
+            .port
+            .as_ref()
+            .map(|port| format!(":{}", port))
+            .unwrap_or_default();
+        format!(
+            "{}{}{}{}",
+            SSH_SCHEME, self.destination, port, self.remote_socket_path
+        )
+    }
+
+    fn unreachable_hint(&self) -> &'static str {
+        "The ssh tunnel could not be opened or the daemon is not running on the remote host, the host has to accept key based authentication."
+    }
+}
+
+impl Drop for SshTunnel {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = fs::remove_file(&self.local_socket_path);
+    }
+}
+
+fn split_host_port(host_port: &str) -> Result<(&str, u16), String> {
+    let (host, port) = host_port
+        .rsplit_once(':')
+        .ok_or_else(|| String::from("expected <host>:<port>"))?;
+    let port = port
+        .parse()
+        .map_err(|_| format!("invalid port '{}'", port))?;
+
+    match host.is_empty() {
+        true => Err(String::from("missing host")),
+        false => Ok((host, port)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+
+    use super::*;
+
+    #[test]
+    fn tcp_read_times_out_when_the_daemon_does_not_respond() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let host_port = listener.local_addr().unwrap().to_string();
+        let mut transport =
+            TcpTransport::new(&host_port, &TransportOptions::default(), None).unwrap();
+        transport.request_timeout = Duration::from_millis(100);
+
+        let mut connection = transport.connect().unwrap();
+        let (_stream, _) = listener.accept().unwrap();
+        connection.write_all(b"\0").unwrap();
+
+        let started_at = Instant::now();
+        let err = connection.read(&mut [0; 16]).unwrap_err();
+
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::WouldBlock | ErrorKind::TimedOut
+        ));
+        assert!(started_at.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn parses_ssh_addresses() {
+        let transport = SshTransport::new("alice@example.com:2222/run/conc.sock").unwrap();
+
+        assert_eq!(transport.destination, "alice@example.com");
+        assert_eq!(transport.port.as_deref(), Some("2222"));
+        assert_eq!(transport.remote_socket_path, "/run/conc.sock");
+        assert_eq!(
+            transport.address(),
+            "ssh://alice@example.com:2222/run/conc.sock"
+        );
+        assert!(SshTransport::new("example.com").is_err());
+        assert!(SshTransport::new("alice@/run/conc.sock").is_err());
+        assert!(SshTransport::new("example.com:ssh/run/conc.sock").is_err());
+    }
+}
@@ -0,0 +1,267 @@
+use std::{
+    ffi::{CStr, CString},
+    os::raw::c_char,
+    ptr,
+};
+
+use daemon_client::{
+    ffi::*,
+    mock::{MockDaemon, MockProject},
+};
+
+fn shop_daemon() -> MockDaemon {
+    MockDaemon::start()
+        .unwrap()
+        .with_project(MockProject::new("shop", &["api", "web"]))
+}
+
+fn c_string(value: &str) -> CString {
+    CString::new(value).unwrap()
+}
+
+unsafe fn read(value: *const c_char) -> String {
+    CStr::from_ptr(value).to_str().unwrap().to_string()
+}
+
+unsafe fn last_error() -> String {
+    let message = conc_last_error_message();
+    let value = read(message);
+    conc_string_free(message);
+    value
+}
+
+unsafe fn requester_of(daemon: &MockDaemon) -> *mut ConcRequester {
+    let address = c_string(daemon.socket_path());
+    conc_requester_new(address.as_ptr(), ptr::null(), ptr::null(), false)
+}
+
+fn empty_project() -> ConcProjectInfo {
+    ConcProjectInfo {
+        name: ptr::null_mut(),
+        services: ptr::null_mut(),
+        service_count: 0,
+    }
+}
+
+#[test]
+fn reports_abi_version() {
+    assert_eq!(conc_abi_version(), CONC_ABI_VERSION);
+}
+
+#[test]
+fn lists_project_names() {
+    let daemon = shop_daemon().with_project(MockProject::new("blog", &["app"]));
+
+    unsafe {
+        let requester = requester_of(&daemon);
+        assert!(conc_requester_is_alive(requester));
+
+        let mut names = ConcStringList {
+            values: ptr::null_mut(),
+            count: 0,
+        };
+        assert_eq!(
+            conc_get_project_names(requester, &mut names),
+            ConcErrorCode::Ok
+        );
+        assert_eq!(names.count, 2);
+        assert_eq!(read(*names.values), "shop");
+        assert_eq!(read(*names.values.add(1)), "blog");
+
+        conc_string_list_free(&mut names);
+        assert!(names.values.is_null());
+        assert_eq!(names.count, 0);
+        conc_requester_free(requester);
+    }
+}
+
+#[test]
+fn starts_project_and_service() {
+    let daemon = shop_daemon();
+    let project = c_string("shop");
+    let service = c_string("web");
+
+    unsafe {
+        let requester = requester_of(&daemon);
+
+        let mut info = empty_project();
+        assert_eq!(
+            conc_start_project(requester, project.as_ptr(), &mut info),
+            ConcErrorCode::Ok
+        );
+        assert_eq!(read(info.name), "shop");
+        assert_eq!(info.service_count, 2);
+        let services = std::slice::from_raw_parts(info.services, info.service_count);
+        assert!(services
+            .iter()
+            .all(|s| s.status == ConcServiceStatus::Running && s.pid > 0));
+        assert_eq!(read(services[1].name), "web");
+        assert_eq!(read(services[1].logfile_path), "/dev/null");
+        conc_project_info_free(&mut info);
+        assert!(info.name.is_null());
+
+        let mut web = ConcServiceInfo {
+            name: ptr::null_mut(),
+            status: ConcServiceStatus::Idle,
+            pid: 0,
+            start_time: 0,
+            stop_time: 0,
+            logfile_path: ptr::null_mut(),
+        };
+        assert_eq!(
+            conc_stop_service(requester, project.as_ptr(), service.as_ptr(), &mut web),
+            ConcErrorCode::Ok
+        );
+        assert_eq!(web.status, ConcServiceStatus::Stopped);
+        conc_service_info_free(&mut web);
+
+        conc_requester_free(requester);
+    }
+}
+
+#[test]
+fn lists_projects_info_and_settings() {
+    let daemon = shop_daemon().with_project(MockProject::new("blog", &["app"]));
+
+    unsafe {
+        let requester = requester_of(&daemon);
+
+        let mut projects = ConcProjectInfoList {
+            projects: ptr::null_mut(),
+            count: 0,
+        };
+        assert_eq!(
+            conc_get_projects_info(requester, &mut projects),
+            ConcErrorCode::Ok
+        );
+        assert_eq!(projects.count, 2);
+        assert_eq!(read((*projects.projects.add(1)).name), "blog");
+        conc_project_info_list_free(&mut projects);
+
+        let mut settings = ConcProjectSettingsList {
+            projects: ptr::null_mut(),
+            count: 0,
+        };
+        assert_eq!(
+            conc_get_projects_settings(requester, &mut settings),
+            ConcErrorCode::Ok
+        );
+        assert_eq!(read((*settings.projects).name), "shop");
+        assert!(read((*settings.projects).settings_json).contains("\"api\""));
+        conc_project_settings_list_free(&mut settings);
+
+        conc_requester_free(requester);
+    }
+}
+
+#[test]
+fn upserts_and_removes_project() {
+    let daemon = MockDaemon::start().unwrap();
+    let settings = c_string(r#"{"name":"docs","services":[{"name":"serve","command":["true"]}]}"#);
+    let project = c_string("docs");
+
+    unsafe {
+        let requester = requester_of(&daemon);
+
+        let mut info = empty_project();
+        assert_eq!(
+            conc_upsert_project(requester, settings.as_ptr(), &mut info),
+            ConcErrorCode::Ok
+        );
+        conc_project_info_free(&mut info);
+
+        let mut json = ptr::null_mut();
+        assert_eq!(
+            conc_get_project_settings(requester, project.as_ptr(), &mut json),
+            ConcErrorCode::Ok
+        );
+        assert_eq!(read(json), settings.to_str().unwrap());
+        conc_string_free(json);
+
+        assert_eq!(
+            conc_remove_project(requester, project.as_ptr()),
+            ConcErrorCode::Ok
+        );
+        assert!(daemon.projects().is_empty());
+
+        conc_requester_free(requester);
+    }
+}
+
+#[test]
+fn errors_mirror_error_responses() {
+    let daemon = shop_daemon();
+    let missing = c_string("missing");
+    let project = c_string("shop");
+
+    unsafe {
+        let requester = requester_of(&daemon);
+
+        let mut info = empty_project();
+        assert_eq!(
+            conc_get_project_info(requester, missing.as_ptr(), &mut info),
+            ConcErrorCode::ProjectNotFound
+        );
+        assert_eq!(last_error(), "Provided project was not found.");
+        assert!(info.name.is_null());
+
+        assert_eq!(
+            conc_clear_service_logs(requester, project.as_ptr(), missing.as_ptr()),
+            ConcErrorCode::ServiceNotFound
+        );
+
+        daemon.respond_with("PROJECT-CLEAR-LOGS", "ERROR\u{11}driver_error");
+        assert_eq!(
+            conc_clear_project_logs(requester, project.as_ptr()),
+            ConcErrorCode::Daemon
+        );
+
+        assert_eq!(
+            conc_get_project_info(requester, ptr::null(), &mut info),
+            ConcErrorCode::InvalidArgument
+        );
+        assert_eq!(
+            conc_get_project_info(requester, project.as_ptr(), ptr::null_mut()),
+            ConcErrorCode::InvalidArgument
+        );
+        assert_eq!(
+            conc_get_project_info(ptr::null(), project.as_ptr(), &mut info),
+            ConcErrorCode::InvalidArgument
+        );
+
+        conc_requester_free(requester);
+    }
+}
+
+#[test]
+fn unreachable_daemon_is_a_socket_error() {
+    let daemon = shop_daemon();
+
+    unsafe {
+        let requester = requester_of(&daemon);
+        drop(daemon);
+
+        assert!(!conc_requester_is_alive(requester));
+        let mut names = ConcStringList {
+            values: ptr::null_mut(),
+            count: 0,
+        };
+        assert_eq!(
+            conc_get_project_names(requester, &mut names),
+            ConcErrorCode::Socket
+        );
+
+        conc_requester_free(requester);
+    }
+}
+
+#[test]
+fn invalid_address_creates_no_handle() {
+    unsafe {
+        assert!(conc_requester_new(ptr::null(), ptr::null(), ptr::null(), false).is_null());
+
+        let invalid_utf8 = [0xffu8, 0];
+        let address = invalid_utf8.as_ptr() as *const c_char;
+        assert!(conc_requester_new(address, ptr::null(), ptr::null(), false).is_null());
+    }
+}
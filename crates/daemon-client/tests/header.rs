@@ -0,0 +1,27 @@
+use std::{env, fs, path::Path};
+
+const HEADER_PATH: &str = "include/conc_client.h";
+
+/// The committed header has to match the C API, `make header` writes the generated one instead
+#[test]
+fn header_matches_c_api() {
+    let crate_dir = env!("CARGO_MANIFEST_DIR");
+    let mut generated = Vec::new();
+    cbindgen::generate(crate_dir)
+        .expect("Unable to generate the C header")
+        .write(&mut generated);
+    let generated = String::from_utf8(generated).unwrap();
+
+    let header_path = Path::new(crate_dir).join(HEADER_PATH);
+    if env::var_os("UPDATE_HEADER").is_some() {
+        fs::write(&header_path, generated).unwrap();
+        return;
+    }
+
+    let committed = fs::read_to_string(&header_path).unwrap_or_default();
+    assert!(
+        committed == generated,
+        "'{}' does not match the C API, regenerate it with 'make header'",
+        HEADER_PATH
+    );
+}
@@ -0,0 +1,197 @@
+use daemon_client::{
+    mock::{MockDaemon, MockProject, MockRequest},
+    ErrorResponse, ServiceStatus,
+};
+
+fn shop_daemon() -> MockDaemon {
+    MockDaemon::start()
+        .unwrap()
+        .with_project(MockProject::new("shop", &["api", "web"]))
+        .with_project(MockProject::new("blog", &["app"]))
+}
+
+#[test]
+fn health_check_succeeds() {
+    let daemon = shop_daemon();
+
+    assert!(daemon.client().is_alive());
+    assert!(daemon.requests().is_empty());
+}
+
+#[test]
+fn client_is_not_alive_after_daemon_is_dropped() {
+    let daemon = shop_daemon();
+    let client = daemon.client();
+    drop(daemon);
+
+    assert!(!client.is_alive());
+}
+
+#[test]
+fn lists_projects_and_services() {
+    let daemon = shop_daemon();
+    let requester = daemon.requester();
+
+    assert_eq!(requester.get_project_names().unwrap(), vec!["shop", "blog"]);
+    assert_eq!(
+        requester.get_service_names("shop").unwrap(),
+        vec!["api", "web"]
+    );
+
+    let infos = requester.get_projects_info().unwrap();
+    assert_eq!(infos.len(), 2);
+    assert_eq!(infos[0].name, "shop");
+    assert_eq!(infos[0].services.len(), 2);
+    assert_eq!(infos[1].name, "blog");
+    assert_eq!(infos[1].services[0].status, ServiceStatus::IDLE);
+    assert_eq!(infos[1].services[0].pid, -1);
+}
+
+#[test]
+fn empty_daemon_responds_with_empty_lists() {
+    let daemon = MockDaemon::start().unwrap();
+    let requester = daemon.requester();
+
+    assert!(requester.get_project_names().unwrap().is_empty());
+    assert!(requester.get_projects_info().unwrap().is_empty());
+    assert!(requester.get_projects_settings().unwrap().is_empty());
+}
+
+#[test]
+fn starts_and_stops_project() {
+    let daemon = shop_daemon();
+    let requester = daemon.requester();
+
+    let info = requester.start_project("shop").unwrap();
+    assert!(info
+        .services
+        .iter()
+        .all(|s| s.status == ServiceStatus::RUNNING && s.pid > 0));
+
+    let info = requester.stop_project("shop").unwrap();
+    assert!(info
+        .services
+        .iter()
+        .all(|s| s.status == ServiceStatus::STOPPED && s.pid == 0));
+}
+
+#[test]
+fn starts_single_service() {
+    let daemon = shop_daemon();
+    let requester = daemon.requester();
+
+    let service = requester.start_service("shop", "web").unwrap();
+    assert_eq!(service.name, "web");
+    assert_eq!(service.status, ServiceStatus::RUNNING);
+
+    let api = requester.get_services_info("shop", "api").unwrap();
+    assert_eq!(api.status, ServiceStatus::IDLE);
+}
+
+#[test]
+fn records_requests_with_arguments() {
+    let daemon = shop_daemon();
+    let requester = daemon.requester();
+
+    requester.stop_service("shop", "api").unwrap();
+    requester.clear_project_logs("blog").unwrap();
+
+    assert_eq!(
+        daemon.requests(),
+        vec![
+            MockRequest {
+                command: String::from("SERVICE-STOP"),
+                args: vec![String::from("shop"), String::from("api")],
+            },
+            MockRequest {
+                command: String::from("PROJECT-CLEAR-LOGS"),
+                args: vec![String::from("blog")],
+            },
+        ]
+    );
+}
+
+#[test]
+fn start_sends_caller_env_as_json() {
+    let daemon = shop_daemon();
+    let requester = daemon.requester();
+
+    requester.start_project("blog").unwrap();
+
+    let request = &daemon.requests()[0];
+    assert_eq!(request.command, "PROJECT-START");
+    assert_eq!(request.args, vec!["blog", "{}"]);
+}
+
+#[test]
+fn upserts_and_removes_project() {
+    let daemon = MockDaemon::start().unwrap();
+    let requester = daemon.requester();
+    let settings = r#"{"name":"docs","services":[{"name":"serve","command":["true"]}]}"#;
+
+    let info = requester.upsert_project(settings).unwrap();
+    assert_eq!(info.name, "docs");
+    assert_eq!(info.services[0].name, "serve");
+    assert_eq!(requester.get_project_settings("docs").unwrap(), settings);
+    assert_eq!(
+        requester.get_projects_settings().unwrap(),
+        vec![(String::from("docs"), String::from(settings))]
+    );
+
+    requester.remove_project("docs").unwrap();
+    assert!(daemon.projects().is_empty());
+}
+
+#[test]
+fn invalid_settings_are_client_errors() {
+    let daemon = MockDaemon::start().unwrap();
+    let requester = daemon.requester();
+
+    let err = requester.upsert_project(r#"{"name":"docs"}"#).unwrap_err();
+    assert!(matches!(err, ErrorResponse::Client(raw) if raw.contains("settings.services.missing")));
+
+    let err = requester.upsert_project("not json").unwrap_err();
+    assert!(matches!(err, ErrorResponse::Client(_)));
+}
+
+#[test]
+fn unknown_project_and_service_are_reported() {
+    let daemon = shop_daemon();
+    let requester = daemon.requester();
+
+    let err = requester.get_project_info("missing").unwrap_err();
+    assert!(matches!(err, ErrorResponse::ProjectNotFound(_)));
+
+    let err = requester.start_service("shop", "missing").unwrap_err();
+    assert!(matches!(err, ErrorResponse::ServiceNotFound(_)));
+
+    let err = requester.start_service("missing", "api").unwrap_err();
+    assert!(matches!(err, ErrorResponse::ProjectNotFound(_)));
+}
+
+#[test]
+fn scripted_responses_replace_handling() {
+    let daemon = shop_daemon();
+    let requester = daemon.requester();
+
+    daemon.respond_with("PROJECT-START", "ERROR\u{11}driver_error");
+    let err = requester.start_project("shop").unwrap_err();
+    assert!(matches!(err, ErrorResponse::Daemon(_)));
+
+    daemon.respond_with("PROJECTS-NAMES", "garbage");
+    let err = requester.get_project_names().unwrap_err();
+    assert!(matches!(err, ErrorResponse::Malformed(_)));
+}
+
+#[test]
+fn service_status_can_be_changed_by_the_test() {
+    let daemon = shop_daemon();
+    let requester = daemon.requester();
+
+    assert!(daemon.set_service_status("shop", "api", ServiceStatus::EXITED));
+    assert!(!daemon.set_service_status("shop", "missing", ServiceStatus::EXITED));
+
+    let api = requester.get_services_info("shop", "api").unwrap();
+    assert_eq!(api.status, ServiceStatus::EXITED);
+    assert!(api.stop_time > 0);
+}
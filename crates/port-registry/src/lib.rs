@@ -0,0 +1,377 @@
+use daemon_client::{ErrorResponse, Requester, ServiceStatus};
+use process_metrics::{process_group_id, process_name};
+use project_settings::{ProjectSettings, ProjectSettingsError};
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::TryFrom,
+    fmt::Display,
+    fs,
+    path::Path,
+};
+
+const PROC_DIR: &str = "/proc";
+const TCP_TABLES: [&str; 2] = ["/proc/net/tcp", "/proc/net/tcp6"];
+const TCP_LISTEN_STATE: &str = "0A";
+
+#[derive(Debug, thiserror::Error)]
+pub enum PortRegistryError {
+    #[error("invalid project settings: {inner}")]
+    SettingsError { inner: ProjectSettingsError },
+    #[error("{inner}")]
+    RequestError { inner: ErrorResponse },
+    #[error("{}", format_conflicts(conflicts))]
+    PortConflict { conflicts: Vec<PortConflict> },
+}
+
+impl From<ProjectSettingsError> for PortRegistryError {
+    fn from(value: ProjectSettingsError) -> Self {
+        PortRegistryError::SettingsError { inner: value }
+    }
+}
+
+impl From<ErrorResponse> for PortRegistryError {
+    fn from(value: ErrorResponse) -> Self {
+        PortRegistryError::RequestError { inner: value }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServiceRef {
+    pub project_name: String,
+    pub service_name: String,
+}
+
+impl Display for ServiceRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.project_name, self.service_name)
+    }
+}
+
+/// Process listening on a port, pid is not known when the socket belongs to other user
+#[derive(Debug, Clone)]
+pub struct PortOwner {
+    pub pid: Option<i32>,
+    pub process_name: Option<String>,
+    pub service: Option<ServiceRef>,
+}
+
+impl Display for PortOwner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.service, self.pid, &self.process_name) {
+            (Some(service), Some(pid), _) => write!(f, "the service '{}' (pid {})", service, pid),
+            (None, Some(pid), Some(name)) => write!(f, "the process '{}' (pid {})", name, pid),
+            (None, Some(pid), None) => write!(f, "the process with pid {}", pid),
+            _ => write!(f, "a process of another user"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PortConflict {
+    pub port: u16,
+    pub service: ServiceRef,
+    pub owner: PortOwner,
+}
+
+impl Display for PortConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "port {} of the service '{}' is already used by {}",
+            self.port, self.service, self.owner
+        )
+    }
+}
+
+/// Port declared by services of the projects or bound by a running service
+#[derive(Debug, Clone)]
+pub struct PortEntry {
+    pub port: u16,
+    pub declared_by: Vec<ServiceRef>,
+    pub owner: Option<PortOwner>,
+}
+
+/// Checks that the declared ports of the services are free, without services all services of the project are used.
+/// Ports bound by the service itself are not reported so restarts are not blocked.
+pub fn check_ports(
+    requester: &Requester,
+    project_name: &str,
+    service_names: Option<&[String]>,
+) -> Result<(), PortRegistryError> {
+    let json = requester.get_project_settings(project_name)?;
+    let settings = ProjectSettings::try_from(json.as_str())?;
+
+    let declared: Vec<(&str, u16)> = settings
+        .services
+        .iter()
+        .filter(|service| match service_names {
+            Some(names) => names.contains(&service.name),
+            None => true,
+        })
+        .flat_map(|service| {
+            service
+                .ports
+                .iter()
+                .map(move |port| (service.name.as_str(), *port))
+        })
+        .collect();
+    if declared.is_empty() {
+        return Ok(());
+    }
+
+    let owners = resolve_owners(requester, listening_ports())?;
+
+    let mut conflicts = vec![];
+    for (service_name, port) in declared {
+        let service = ServiceRef {
+            project_name: String::from(project_name),
+            service_name: String::from(service_name),
+        };
+
+        if let Some(owner) = owners.get(&port) {
+            if owner.service.as_ref() != Some(&service) {
+                conflicts.push(PortConflict {
+                    port,
+                    service,
+                    owner: owner.clone(),
+                });
+            }
+        }
+    }
+
+    match conflicts.is_empty() {
+        true => Ok(()),
+        false => Err(PortRegistryError::PortConflict { conflicts }),
+    }
+}
+
+/// Lists ports declared in all projects together with ports bound by the running services, sorted by the port
+pub fn list_ports(requester: &Requester) -> Result<Vec<PortEntry>, PortRegistryError> {
+    let mut entries: BTreeMap<u16, PortEntry> = BTreeMap::new();
+
+    for (project_name, json) in requester.get_projects_settings()? {
+        let settings = ProjectSettings::try_from(json.as_str())?;
+        for service in settings.services.iter() {
+            for port in service.ports.iter() {
+                entries
+                    .entry(*port)
+                    .or_insert_with(|| PortEntry {
+                        port: *port,
+                        declared_by: vec![],
+                        owner: None,
+                    })
+                    .declared_by
+                    .push(ServiceRef {
+                        project_name: project_name.clone(),
+                        service_name: service.name.clone(),
+                    });
+            }
+        }
+    }
+
+    for (port, owner) in resolve_owners(requester, listening_ports())? {
+        match entries.get_mut(&port) {
+            Some(entry) => entry.owner = Some(owner),
+            // ports of foreign processes are only interesting when some service declares them
+            None if owner.service.is_some() => {
+                entries.insert(
+                    port,
+                    PortEntry {
+                        port,
+                        declared_by: vec![],
+                        owner: Some(owner),
+                    },
+                );
+            }
+            None => {}
+        }
+    }
+
+    Ok(entries.into_values().collect())
+}
+
+/// Maps listening ports to the processes holding them and to the services which started those processes
+fn resolve_owners(
+    requester: &Requester,
+    ports: HashMap<u16, u64>,
+) -> Result<HashMap<u16, PortOwner>, PortRegistryError> {
+    if ports.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    // services are started in their own session so their pid is the group id of all their processes
+    let mut services = HashMap::new();
+    for project in requester.get_projects_info()? {
+        for service in project.services {
+            if service.status == ServiceStatus::RUNNING {
+                let service_ref = ServiceRef {
+                    project_name: project.name.clone(),
+                    service_name: service.name,
+                };
+                services.insert(service.pid, service_ref);
+            }
+        }
+    }
+
+    let socket_pids = socket_pids();
+
+    let owners = ports
+        .into_iter()
+        .map(|(port, inode)| {
+            let pid = socket_pids.get(&inode).copied();
+            let service = pid
+                .and_then(process_group_id)
+                .and_then(|pgid| services.get(&pgid).cloned());
+            let owner = PortOwner {
+                pid,
+                process_name: pid.and_then(process_name),
+                service,
+            };
+            (port, owner)
+        })
+        .collect();
+
+    Ok(owners)
+}
+
+/// Returns listening TCP ports with inodes of their sockets
+fn listening_ports() -> HashMap<u16, u64> {
+    let mut ports = HashMap::new();
+
+    for table in TCP_TABLES.iter() {
+        let content = match fs::read_to_string(table) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+
+        // the first line is the header of the table
+        for (port, inode) in content.lines().skip(1).filter_map(parse_listening_socket) {
+            ports.entry(port).or_insert(inode);
+        }
+    }
+
+    ports
+}
+
+/// Parses a line of /proc/net/tcp or tcp6, returns the local port and the inode of listening sockets
+fn parse_listening_socket(line: &str) -> Option<(u16, u64)> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() < 10 || fields[3] != TCP_LISTEN_STATE {
+        return None;
+    }
+
+    let port = fields[1]
+        .rsplit(':')
+        .next()
+        .and_then(|port| u16::from_str_radix(port, 16).ok())?;
+    let inode = fields[9].parse::<u64>().ok()?;
+
+    Some((port, inode))
+}
+
+/// Maps socket inodes to pids of the processes which have them open
+fn socket_pids() -> HashMap<u64, i32> {
+    let mut sockets = HashMap::new();
+
+    let entries = match fs::read_dir(PROC_DIR) {
+        Ok(entries) => entries,
+        Err(_) => return sockets,
+    };
+
+    for entry in entries.flatten() {
+        let pid = match entry
+            .file_name()
+            .to_str()
+            .and_then(|s| s.parse::<i32>().ok())
+        {
+            Some(pid) => pid,
+            None => continue,
+        };
+
+        // fds of processes owned by other users are not readable
+        let fds = match fs::read_dir(Path::new(PROC_DIR).join(pid.to_string()).join("fd")) {
+            Ok(fds) => fds,
+            Err(_) => continue,
+        };
+
+        for fd in fds.flatten() {
+            let inode = fs::read_link(fd.path())
+                .ok()
+                .and_then(|link| socket_inode(link.to_str()?));
+
+            if let Some(inode) = inode {
+                sockets.entry(inode).or_insert(pid);
+            }
+        }
+    }
+
+    sockets
+}
+
+/// Parses the target of a fd link such as 'socket:[12345]'
+fn socket_inode(link: &str) -> Option<u64> {
+    link.strip_prefix("socket:[")?
+        .strip_suffix(']')?
+        .parse::<u64>()
+        .ok()
+}
+
+fn format_conflicts(conflicts: &[PortConflict]) -> String {
+    conflicts
+        .iter()
+        .map(|conflict| conflict.to_string())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TCP_TABLE: &str = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 41234 1 0000000000000000 100 0 0 10 0
+   1: 00000000:0016 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 1876 1 0000000000000000 100 0 0 10 0
+   2: 0100007F:1F90 0100007F:D2C4 01 00000000:00000000 00:00000000 00000000  1000        0 52311 1 0000000000000000 20 4 30 10 -1
+   3: 0100007F:A4B1 0100007F:1F90 06 00000000:00000000 03:00000F5A 00000000     0        0 0 3 0000000000000000";
+
+    const TCP6_LINE: &str = "   0: 00000000000000000000000001000000:24D4 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 60021 1 0000000000000000 100 0 0 10 0";
+
+    #[test]
+    fn parses_listening_sockets_of_the_tcp_table() {
+        let sockets: Vec<(u16, u64)> = TCP_TABLE
+            .lines()
+            .skip(1)
+            .filter_map(parse_listening_socket)
+            .collect();
+
+        assert_eq!(sockets, vec![(8080, 41234), (22, 1876)]);
+    }
+
+    #[test]
+    fn parses_listening_sockets_of_the_tcp6_table() {
+        assert_eq!(parse_listening_socket(TCP6_LINE), Some((9428, 60021)));
+    }
+
+    #[test]
+    fn skips_malformed_lines() {
+        assert_eq!(parse_listening_socket(""), None);
+        assert_eq!(
+            parse_listening_socket("   0: 0100007F:1F90 00000000:0000 0A"),
+            None
+        );
+        assert_eq!(
+            parse_listening_socket(
+                "   0: 0100007F:XYZ 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 41234"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn parses_socket_inodes_of_fd_links() {
+        assert_eq!(socket_inode("socket:[41234]"), Some(41234));
+        assert_eq!(socket_inode("pipe:[41234]"), None);
+        assert_eq!(socket_inode("/dev/null"), None);
+        assert_eq!(socket_inode("socket:[]"), None);
+    }
+}
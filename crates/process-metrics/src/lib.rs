@@ -0,0 +1,333 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fs,
+    path::Path,
+    time::Instant,
+};
+
+const PROC_DIR: &str = "/proc";
+
+/// Resource usage of a whole process group, services are started in their own session so the group
+/// id is the same as the pid of the service
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ProcessMetrics {
+    pub cpu_percent: f64,
+    /// CPU time used by the living processes of the group
+    pub cpu_seconds: f64,
+    pub rss_bytes: u64,
+    pub threads: u64,
+    pub fds: u64,
+    pub processes: u64,
+}
+
+impl ProcessMetrics {
+    pub fn format_cpu(&self) -> String {
+        format!("{:.1}%", self.cpu_percent)
+    }
+
+    pub fn format_rss(&self) -> String {
+        format_bytes(self.rss_bytes)
+    }
+}
+
+/// Collects metrics of process groups, CPU usage is computed from the difference between two
+/// consecutive collections of the same group
+#[derive(Debug, Default)]
+pub struct MetricsCollector {
+    previous: HashMap<i32, (Instant, u64)>,
+}
+
+impl MetricsCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns None when the group has no living process or process table is not available
+    pub fn collect(&mut self, pgid: i32) -> Option<ProcessMetrics> {
+        let now = Instant::now();
+        let group = read_process_group(pgid);
+
+        let group = match group {
+            Some(group) => group,
+            None => {
+                self.previous.remove(&pgid);
+                return None;
+            }
+        };
+
+        let cpu_percent = match self.previous.get(&pgid) {
+            Some((collected_at, cpu_ticks)) => {
+                let elapsed = now.duration_since(*collected_at).as_secs_f64();
+                let used = group.cpu_ticks.saturating_sub(*cpu_ticks) as f64 / clock_ticks();
+                match elapsed > 0.0 {
+                    true => used / elapsed * 100.0,
+                    false => 0.0,
+                }
+            }
+            None => group.lifetime_cpu_percent(),
+        };
+
+        self.previous.insert(pgid, (now, group.cpu_ticks));
+        Some(group.into_metrics(cpu_percent))
+    }
+
+    /// Drops state of groups which are not in the provided list
+    pub fn retain(&mut self, pgids: &[i32]) {
+        self.previous.retain(|pgid, _| pgids.contains(pgid));
+    }
+}
+
+/// Collects metrics of a process group from a single look at the process table,
+/// CPU usage is averaged over the lifetime of the group leader same as in 'ps'
+pub fn collect_once(pgid: i32) -> Option<ProcessMetrics> {
+    read_process_group(pgid).map(|group| {
+        let cpu_percent = group.lifetime_cpu_percent();
+        group.into_metrics(cpu_percent)
+    })
+}
+
+pub fn process_group_id(pid: i32) -> Option<i32> {
+    read_stat(&Path::new(PROC_DIR).join(pid.to_string())).map(|stat| stat.pgrp)
+}
+
+pub fn process_name(pid: i32) -> Option<String> {
+    fs::read_to_string(Path::new(PROC_DIR).join(pid.to_string()).join("comm"))
+        .ok()
+        .map(|name| String::from(name.trim_end()))
+}
+
+/// Keeps the last collected metrics of services for displaying their recent history
+#[derive(Debug)]
+pub struct MetricsHistory {
+    capacity: usize,
+    entries: HashMap<String, VecDeque<ProcessMetrics>>,
+}
+
+impl MetricsHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+        }
+    }
+
+    pub fn push(&mut self, key: &str, metrics: ProcessMetrics) {
+        let entry = self.entries.entry(String::from(key)).or_default();
+        entry.push_back(metrics);
+        while entry.len() > self.capacity {
+            entry.pop_front();
+        }
+    }
+
+    pub fn clear(&mut self, key: &str) {
+        self.entries.remove(key);
+    }
+
+    pub fn last(&self, key: &str) -> Option<&ProcessMetrics> {
+        self.entries.get(key).and_then(|entry| entry.back())
+    }
+
+    pub fn get(&self, key: &str) -> Vec<ProcessMetrics> {
+        self.entries
+            .get(key)
+            .map(|entry| entry.iter().copied().collect())
+            .unwrap_or_default()
+    }
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    match unit {
+        0 => format!("{} {}", bytes, UNITS[unit]),
+        _ => format!("{:.1} {}", value, UNITS[unit]),
+    }
+}
+
+struct ProcessGroup {
+    cpu_ticks: u64,
+    rss_pages: u64,
+    threads: u64,
+    fds: u64,
+    processes: u64,
+    leader_start_ticks: Option<u64>,
+}
+
+impl ProcessGroup {
+    fn lifetime_cpu_percent(&self) -> f64 {
+        let uptime = match (read_uptime(), self.leader_start_ticks) {
+            (Some(uptime), Some(start_ticks)) => uptime - start_ticks as f64 / clock_ticks(),
+            _ => return 0.0,
+        };
+
+        match uptime > 0.0 {
+            true => self.cpu_ticks as f64 / clock_ticks() / uptime * 100.0,
+            false => 0.0,
+        }
+    }
+
+    fn into_metrics(self, cpu_percent: f64) -> ProcessMetrics {
+        ProcessMetrics {
+            cpu_percent,
+            cpu_seconds: self.cpu_ticks as f64 / clock_ticks(),
+            rss_bytes: self.rss_pages * page_size(),
+            threads: self.threads,
+            fds: self.fds,
+            processes: self.processes,
+        }
+    }
+}
+
+struct ProcessStat {
+    pgrp: i32,
+    cpu_ticks: u64,
+    threads: u64,
+    start_ticks: u64,
+    rss_pages: u64,
+}
+
+fn read_process_group(pgid: i32) -> Option<ProcessGroup> {
+    if pgid <= 0 {
+        return None;
+    }
+
+    let mut group = ProcessGroup {
+        cpu_ticks: 0,
+        rss_pages: 0,
+        threads: 0,
+        fds: 0,
+        processes: 0,
+        leader_start_ticks: None,
+    };
+
+    for entry in fs::read_dir(PROC_DIR).ok()?.flatten() {
+        let pid = match entry
+            .file_name()
+            .to_str()
+            .and_then(|s| s.parse::<i32>().ok())
+        {
+            Some(pid) => pid,
+            None => continue,
+        };
+
+        // processes may exit while the table is being read
+        let stat = match read_stat(&entry.path()) {
+            Some(stat) if stat.pgrp == pgid => stat,
+            _ => continue,
+        };
+
+        group.cpu_ticks += stat.cpu_ticks;
+        group.rss_pages += stat.rss_pages;
+        group.threads += stat.threads;
+        group.fds += count_fds(&entry.path());
+        group.processes += 1;
+        if pid == pgid {
+            group.leader_start_ticks = Some(stat.start_ticks);
+        }
+    }
+
+    match group.processes {
+        0 => None,
+        _ => Some(group),
+    }
+}
+
+fn read_stat(process_dir: &Path) -> Option<ProcessStat> {
+    let content = fs::read_to_string(process_dir.join("stat")).ok()?;
+    parse_stat(&content)
+}
+
+/// Parses the content of /proc/<pid>/stat, see proc_pid_stat(5) for the numbers of the fields
+fn parse_stat(content: &str) -> Option<ProcessStat> {
+    // command name is enclosed in parenthesis and can contain spaces, fields are counted after it
+    let fields: Vec<&str> = content[content.rfind(')')? + 1..]
+        .split_whitespace()
+        .collect();
+    let field = |n: usize| fields.get(n - 3).and_then(|f| f.parse::<u64>().ok());
+
+    Some(ProcessStat {
+        pgrp: fields.get(5 - 3)?.parse().ok()?,
+        cpu_ticks: field(14)? + field(15)?,
+        threads: field(20)?,
+        start_ticks: field(22)?,
+        rss_pages: field(24)?,
+    })
+}
+
+fn count_fds(process_dir: &Path) -> u64 {
+    // fds of processes owned by other users are not readable
+    fs::read_dir(process_dir.join("fd"))
+        .map(|entries| entries.count() as u64)
+        .unwrap_or_default()
+}
+
+fn read_uptime() -> Option<f64> {
+    fs::read_to_string(Path::new(PROC_DIR).join("uptime"))
+        .ok()?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
+
+fn clock_ticks() -> f64 {
+    match unsafe { libc::sysconf(libc::_SC_CLK_TCK) } {
+        ticks if ticks > 0 => ticks as f64,
+        _ => 100.0,
+    }
+}
+
+fn page_size() -> u64 {
+    match unsafe { libc::sysconf(libc::_SC_PAGESIZE) } {
+        size if size > 0 => size as u64,
+        _ => 4096,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stat_line(comm: &str) -> String {
+        format!(
+            "4242 ({}) S 1 4240 4240 0 -1 4194560 120 0 0 0 75 25 3 1 20 0 4 0 98765 2375680 512 18446744073709551615 1 1 0 0 0 0 0 0 0 0 0 0 17 3 0 0 0 0 0\n",
+            comm
+        )
+    }
+
+    #[test]
+    fn parses_stat_fields() {
+        let stat = parse_stat(&stat_line("sleep")).unwrap();
+
+        assert_eq!(stat.pgrp, 4240);
+        // children's times are not included, they are counted when the children are read
+        assert_eq!(stat.cpu_ticks, 100);
+        assert_eq!(stat.threads, 4);
+        assert_eq!(stat.start_ticks, 98765);
+        assert_eq!(stat.rss_pages, 512);
+    }
+
+    #[test]
+    fn parses_command_names_with_spaces_and_parentheses() {
+        for comm in ["tmux: server", "my (app)", ") S 1 2 3", "(("] {
+            let stat = parse_stat(&stat_line(comm)).unwrap();
+
+            assert_eq!(stat.pgrp, 4240, "comm '{}'", comm);
+            assert_eq!(stat.rss_pages, 512, "comm '{}'", comm);
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_stat() {
+        assert!(parse_stat("").is_none());
+        assert!(parse_stat("4242 (sleep) S 1 4240").is_none());
+        assert!(parse_stat("4242 sleep S 1 4240 4240 0 -1 4194560").is_none());
+    }
+}
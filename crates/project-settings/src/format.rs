@@ -0,0 +1,48 @@
+use std::path::{Path, PathBuf};
+
+use crate::ProjectSettingsError;
+
+const SETTINGS_FORMATS: [SettingsFormat; 2] = [SettingsFormat::Json, SettingsFormat::Toml];
+
+/// Formats of the settings file, json is preferred when a directory contains both files
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SettingsFormat {
+    Json,
+    Toml,
+}
+
+impl SettingsFormat {
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            SettingsFormat::Json => "conc.json",
+            SettingsFormat::Toml => "conc.toml",
+        }
+    }
+
+    pub(crate) fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => SettingsFormat::Toml,
+            _ => SettingsFormat::Json,
+        }
+    }
+
+    /// Converts the content of a settings file into the json used by the daemon
+    pub(crate) fn to_json(self, content: String) -> Result<String, ProjectSettingsError> {
+        match self {
+            SettingsFormat::Json => Ok(content),
+            SettingsFormat::Toml => {
+                let value = toml::from_str::<serde_json::Value>(&content)?;
+                Ok(serde_json::to_string(&value)?)
+            }
+        }
+    }
+
+    /// Path of the settings file in the directory, conc.json is returned when there is none
+    pub(crate) fn find_in_dir(dir: &Path) -> PathBuf {
+        SETTINGS_FORMATS
+            .iter()
+            .map(|format| dir.join(format.file_name()))
+            .find(|path| path.is_file())
+            .unwrap_or_else(|| dir.join(SettingsFormat::Json.file_name()))
+    }
+}
@@ -2,10 +2,12 @@ use serde::{Deserialize, Serialize, Serializer};
 use std::{
     collections::{BTreeMap, HashMap, HashSet},
     convert::TryFrom,
-    path::Path,
+    path::{Path, PathBuf},
 };
 
-const SETTINGS_FILE_RELATIVE_NAME: &str = "conc.json";
+pub use format::SettingsFormat;
+
+mod format;
 
 #[derive(Debug, thiserror::Error)]
 pub enum ProjectSettingsError {
@@ -15,6 +17,10 @@ pub enum ProjectSettingsError {
     NotFound { path: String },
     #[error("unable to parse the settings: {inner}")]
     ParserError { inner: serde_json::Error },
+    #[error("unable to parse the toml settings: {inner}")]
+    TomlParserError { inner: toml::de::Error },
+    #[error("unable to serialize the toml settings: {inner}")]
+    TomlSerializerError { inner: toml::ser::Error },
     #[error("project name is empty")]
     EmptyProjectName,
     #[error("project name is invalid, characters must be alphanumeric or '-' or '_'")]
@@ -29,6 +35,46 @@ pub enum ProjectSettingsError {
     DuplicateServiceName { service_name: String },
     #[error("service '{service_name}' has an empty command")]
     EmptyCommand { service_name: String },
+    #[error("profile '{profile_name}' is invalid, characters must be alphanumeric or '-' or '_'")]
+    InvalidProfileName { profile_name: String },
+    #[error("profile '{profile_name}' references unknown service '{service_name}'")]
+    UnknownProfileService {
+        profile_name: String,
+        service_name: String,
+    },
+    #[error("profile '{profile_name}' does not exist")]
+    ProfileNotFound { profile_name: String },
+    #[error("service '{service_name}' does not exist")]
+    ServiceNotFound { service_name: String },
+    #[error("task name is empty")]
+    EmptyTaskName,
+    #[error("task '{task_name}' is invalid, characters must be alphanumeric or '-' or '_'")]
+    InvalidTaskName { task_name: String },
+    #[error("task '{task_name}' is declared more than once")]
+    DuplicateTaskName { task_name: String },
+    #[error("task '{task_name}' has an empty command")]
+    EmptyTaskCommand { task_name: String },
+    #[error("task '{task_name}' does not exist")]
+    TaskNotFound { task_name: String },
+    #[error("service '{service_name}' declares invalid port 0")]
+    InvalidPort { service_name: String },
+    #[error("service '{service_name}' declares port {port} more than once")]
+    DuplicateServicePort { service_name: String, port: u16 },
+    #[error("service '{service_name}' depends on unknown task '{task_name}'")]
+    UnknownBeforeStartTask {
+        service_name: String,
+        task_name: String,
+    },
+    #[error("project '{project_name}' was not upserted from a settings file")]
+    UnknownSource { project_name: String },
+    #[error("settings file '{path}' of the project '{project_name}' was moved or deleted")]
+    SourceNotFound { project_name: String, path: String },
+    #[error("settings file '{path}' now belongs to the project '{found}' instead of '{expected}'")]
+    SourceProjectChanged {
+        path: String,
+        expected: String,
+        found: String,
+    },
 }
 
 impl From<serde_json::Error> for ProjectSettingsError {
@@ -37,6 +83,18 @@ impl From<serde_json::Error> for ProjectSettingsError {
     }
 }
 
+impl From<toml::de::Error> for ProjectSettingsError {
+    fn from(value: toml::de::Error) -> Self {
+        ProjectSettingsError::TomlParserError { inner: value }
+    }
+}
+
+impl From<toml::ser::Error> for ProjectSettingsError {
+    fn from(value: toml::ser::Error) -> Self {
+        ProjectSettingsError::TomlSerializerError { inner: value }
+    }
+}
+
 impl From<std::io::Error> for ProjectSettingsError {
     fn from(value: std::io::Error) -> Self {
         ProjectSettingsError::IoError { inner: value }
@@ -50,10 +108,10 @@ pub enum EnvValue {
     Num(i32),
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ProjectSettings {
     pub name: String,
-    #[serde(default = "String::new")]
+    #[serde(default = "String::new", skip_serializing_if = "String::is_empty")]
     pub cwd: String,
     pub services: Vec<ServiceSettings>,
     #[serde(
@@ -62,9 +120,20 @@ pub struct ProjectSettings {
         skip_serializing_if = "HashMap::is_empty"
     )]
     pub env: HashMap<String, EnvValue>,
+    #[serde(
+        default = "HashMap::new",
+        serialize_with = "ordered_map",
+        skip_serializing_if = "HashMap::is_empty"
+    )]
+    pub profiles: HashMap<String, Vec<String>>,
+    #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
+    pub tasks: Vec<TaskSettings>,
+    /// Absolute path of the settings file the project was upserted from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_path: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ServiceSettings {
     pub name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -76,6 +145,31 @@ pub struct ServiceSettings {
         skip_serializing_if = "HashMap::is_empty"
     )]
     pub env: HashMap<String, EnvValue>,
+    #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
+    pub before_start: Vec<String>,
+    #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
+    pub ports: Vec<u16>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TaskSettings {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pwd: Option<String>,
+    pub command: Vec<String>,
+    #[serde(
+        default = "HashMap::new",
+        serialize_with = "ordered_map",
+        skip_serializing_if = "HashMap::is_empty"
+    )]
+    pub env: HashMap<String, EnvValue>,
+}
+
+/// Working directory and environment a process is started with, env is ordered from the lowest precedence
+#[derive(Debug)]
+pub struct ExecutionContext {
+    pub pwd: String,
+    pub env: Vec<(String, String)>,
 }
 
 impl TryFrom<&ProjectSettings> for String {
@@ -87,10 +181,128 @@ impl TryFrom<&ProjectSettings> for String {
     }
 }
 
+/// Parses and validates the json the same way as the settings read from a file
+impl TryFrom<&str> for ProjectSettings {
+    type Error = ProjectSettingsError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        try_parse(value)
+    }
+}
+
 impl ProjectSettings {
     pub fn prettify_json(data: &str) -> Result<String, serde_json::Error> {
         serde_json::from_str::<Self>(data).and_then(|d| serde_json::to_string_pretty(&d))
     }
+
+    /// Serializes the settings in a form intended to be stored in the settings file
+    pub fn to_file_content(&self, format: SettingsFormat) -> Result<String, ProjectSettingsError> {
+        match format {
+            SettingsFormat::Json => Ok(serde_json::to_string_pretty(self)? + "\n"),
+            SettingsFormat::Toml => Ok(toml::to_string_pretty(self)?),
+        }
+    }
+
+    pub fn profile_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.profiles.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Returns names of the services in the profile, ordered as they are declared in the project
+    pub fn profile_services(
+        &self,
+        profile_name: &str,
+    ) -> Result<Vec<String>, ProjectSettingsError> {
+        let profile = self.profiles.get(profile_name).ok_or_else(|| {
+            ProjectSettingsError::ProfileNotFound {
+                profile_name: String::from(profile_name),
+            }
+        })?;
+
+        Ok(self
+            .services
+            .iter()
+            .filter(|service| profile.contains(&service.name))
+            .map(|service| service.name.clone())
+            .collect())
+    }
+}
+
+impl ProjectSettings {
+    /// Resolves the context the same way the daemon does when it starts the service,
+    /// without a service the context of the project itself is returned
+    pub fn execution_context(
+        &self,
+        service_name: Option<&str>,
+    ) -> Result<ExecutionContext, ProjectSettingsError> {
+        let service = service_name
+            .map(|service_name| {
+                self.services
+                    .iter()
+                    .find(|service| service.name == service_name)
+                    .ok_or_else(|| ProjectSettingsError::ServiceNotFound {
+                        service_name: String::from(service_name),
+                    })
+            })
+            .transpose()?;
+
+        Ok(match service {
+            Some(service) => self.create_execution_context(&service.pwd, &service.env),
+            None => self.create_execution_context(&None, &HashMap::new()),
+        })
+    }
+
+    pub fn task_execution_context(
+        &self,
+        task_name: &str,
+    ) -> Result<ExecutionContext, ProjectSettingsError> {
+        let task = self.task(task_name)?;
+        Ok(self.create_execution_context(&task.pwd, &task.env))
+    }
+
+    pub fn task(&self, task_name: &str) -> Result<&TaskSettings, ProjectSettingsError> {
+        self.tasks
+            .iter()
+            .find(|task| task.name == task_name)
+            .ok_or_else(|| ProjectSettingsError::TaskNotFound {
+                task_name: String::from(task_name),
+            })
+    }
+
+    /// Returns names of the tasks that have to run before the services are started, each at most once
+    pub fn before_start_tasks(&self, service_names: &[String]) -> Vec<String> {
+        let mut tasks: Vec<String> = vec![];
+        for service in self
+            .services
+            .iter()
+            .filter(|service| service_names.contains(&service.name))
+        {
+            for task in service.before_start.iter() {
+                if !tasks.contains(task) {
+                    tasks.push(task.clone());
+                }
+            }
+        }
+
+        tasks
+    }
+
+    fn create_execution_context(
+        &self,
+        pwd: &Option<String>,
+        env: &HashMap<String, EnvValue>,
+    ) -> ExecutionContext {
+        let pwd = match pwd {
+            Some(pwd) => resolve_cwd(self.cwd.clone(), pwd.clone()),
+            None => self.cwd.clone(),
+        };
+
+        let mut pairs = env_pairs(&self.env);
+        pairs.extend(env_pairs(env));
+
+        ExecutionContext { pwd, env: pairs }
+    }
 }
 
 impl ProjectSettings {
@@ -98,13 +310,53 @@ impl ProjectSettings {
         let (path, json) = resolve_settings_path_and_json(pwd)?;
         let mut settings = try_parse(json.as_str())?;
 
-        settings.cwd = resolve_cwd(path, settings.cwd);
+        let dir = path
+            .parent()
+            .and_then(|path| path.to_str())
+            .unwrap_or_default();
+        settings.cwd = resolve_cwd(String::from(dir), settings.cwd);
+        settings.source_path = path.to_str().map(String::from);
         settings.env = populate_env(settings.env);
 
         for service in &mut settings.services {
             service.env = populate_env(service.env.clone());
         }
 
+        for task in &mut settings.tasks {
+            task.env = populate_env(task.env.clone());
+        }
+
+        Ok(settings)
+    }
+
+    /// Reads the settings again from the file the project was upserted from,
+    /// the file has to still describe the same project
+    pub fn reload(&self) -> Result<Self, ProjectSettingsError> {
+        let path = match &self.source_path {
+            Some(path) => path,
+            None => {
+                return Err(ProjectSettingsError::UnknownSource {
+                    project_name: self.name.clone(),
+                })
+            }
+        };
+
+        if !Path::new(path).is_file() {
+            return Err(ProjectSettingsError::SourceNotFound {
+                project_name: self.name.clone(),
+                path: path.clone(),
+            });
+        }
+
+        let settings = Self::find_parse_and_populate(Some(path.clone()))?;
+        if settings.name != self.name {
+            return Err(ProjectSettingsError::SourceProjectChanged {
+                path: path.clone(),
+                expected: self.name.clone(),
+                found: settings.name,
+            });
+        }
+
         Ok(settings)
     }
 }
@@ -122,7 +374,8 @@ fn resolve_cwd(settings_path: String, provided_cwd: String) -> String {
     String::from(Path::new(&settings_path).join(cwd_path).to_str().unwrap())
 }
 
-fn try_parse(value: &str) -> Result<ProjectSettings, ProjectSettingsError> {
+/// Parses the json settings and validates them the same way as the settings file is validated
+pub fn try_parse(value: &str) -> Result<ProjectSettings, ProjectSettingsError> {
     let settings = serde_json::from_str::<ProjectSettings>(value)?;
 
     if settings.name.is_empty() {
@@ -157,15 +410,80 @@ fn try_parse(value: &str) -> Result<ProjectSettings, ProjectSettingsError> {
             return Err(ProjectSettingsError::DuplicateServiceName { service_name: name });
         }
 
+        // services may share a port, the conflicts are reported when the services are started
+        let mut port_cache = HashSet::with_capacity(service.ports.len());
+        for port in service.ports.iter() {
+            if *port == 0 {
+                return Err(ProjectSettingsError::InvalidPort { service_name: name });
+            }
+            if !port_cache.insert(*port) {
+                return Err(ProjectSettingsError::DuplicateServicePort {
+                    service_name: name,
+                    port: *port,
+                });
+            }
+        }
+
         name_cache.insert(name);
     }
 
+    let mut task_name_cache = HashSet::with_capacity(settings.tasks.len());
+
+    for task in &settings.tasks {
+        if task.name.is_empty() {
+            return Err(ProjectSettingsError::EmptyTaskName);
+        }
+
+        let name = task.name.clone();
+
+        if !is_name_valid(&name) {
+            return Err(ProjectSettingsError::InvalidTaskName { task_name: name });
+        }
+
+        if task.command.is_empty() {
+            return Err(ProjectSettingsError::EmptyTaskCommand { task_name: name });
+        }
+        if task_name_cache.contains(&name) {
+            return Err(ProjectSettingsError::DuplicateTaskName { task_name: name });
+        }
+
+        task_name_cache.insert(name);
+    }
+
+    for service in &settings.services {
+        if let Some(task_name) = service
+            .before_start
+            .iter()
+            .find(|task| !task_name_cache.contains(*task))
+        {
+            return Err(ProjectSettingsError::UnknownBeforeStartTask {
+                service_name: service.name.clone(),
+                task_name: task_name.clone(),
+            });
+        }
+    }
+
+    for (profile_name, services) in &settings.profiles {
+        if !is_name_valid(profile_name) || profile_name.is_empty() {
+            return Err(ProjectSettingsError::InvalidProfileName {
+                profile_name: profile_name.clone(),
+            });
+        }
+
+        if let Some(service_name) = services.iter().find(|s| !name_cache.contains(*s)) {
+            return Err(ProjectSettingsError::UnknownProfileService {
+                profile_name: profile_name.clone(),
+                service_name: service_name.clone(),
+            });
+        }
+    }
+
     Ok(settings)
 }
 
 fn resolve_settings_path_and_json(
     pwd: Option<String>,
-) -> Result<(String, String), ProjectSettingsError> {
+) -> Result<(PathBuf, String), ProjectSettingsError> {
     let mut path = match pwd {
         Some(pwd) => {
             let path = Path::new(&pwd);
@@ -178,20 +496,15 @@ fn resolve_settings_path_and_json(
     }?;
 
     if path.is_dir() {
-        path = path.join(SETTINGS_FILE_RELATIVE_NAME);
+        path = SettingsFormat::find_in_dir(&path);
     };
 
-    std::fs::read_to_string(&path)
-        .map(|str| {
-            let cwd = path
-                .parent()
-                .and_then(|path| path.to_str())
-                .unwrap_or_default();
-            (String::from(cwd), str)
-        })
-        .map_err(|_| ProjectSettingsError::NotFound {
-            path: String::from(path.to_str().unwrap_or_default()),
-        })
+    let content = std::fs::read_to_string(&path).map_err(|_| ProjectSettingsError::NotFound {
+        path: String::from(path.to_str().unwrap_or_default()),
+    })?;
+    let json = SettingsFormat::from_path(&path).to_json(content)?;
+
+    Ok((path, json))
 }
 
 fn populate_env(envs: HashMap<String, EnvValue>) -> HashMap<String, EnvValue> {
@@ -207,6 +520,21 @@ fn populate_env(envs: HashMap<String, EnvValue>) -> HashMap<String, EnvValue> {
         .collect()
 }
 
+fn env_pairs(envs: &HashMap<String, EnvValue>) -> Vec<(String, String)> {
+    let ordered: BTreeMap<_, _> = envs.iter().collect();
+    ordered
+        .into_iter()
+        .map(|(key, value)| {
+            let val = match value {
+                EnvValue::Str(value) => value.clone(),
+                EnvValue::Num(value) => value.to_string(),
+            };
+
+            (key.clone(), val)
+        })
+        .collect()
+}
+
 fn is_name_valid(name: &str) -> bool {
     name.chars()
         .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
@@ -219,3 +547,135 @@ fn ordered_map<S: Serializer, K: Ord + Serialize, V: Serialize>(
     let ordered: BTreeMap<_, _> = value.iter().collect();
     ordered.serialize(serializer)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings_json(tasks: &str, before_start: &str) -> String {
+        format!(
+            r#"{{
+                "name": "shop",
+                "cwd": "/srv/shop",
+                "env": {{ "MODE": "dev" }},
+                "services": [
+                    {{ "name": "api", "command": ["api"], "before_start": {} }},
+                    {{ "name": "worker", "command": ["worker"], "before_start": ["seed", "build"] }}
+                ],
+                "tasks": {}
+            }}"#,
+            before_start, tasks
+        )
+    }
+
+    const TASKS: &str = r#"[
+        { "name": "build", "command": ["make"] },
+        { "name": "migrate", "command": ["migrate"], "pwd": "db", "env": { "MODE": "migrate", "RETRIES": 3 } },
+        { "name": "seed", "command": ["seed"] }
+    ]"#;
+
+    #[test]
+    fn services_may_share_a_port_but_not_repeat_it() {
+        let settings = |ports: &str| {
+            try_parse(&format!(
+                r#"{{
+                    "name": "shop",
+                    "services": [
+                        {{ "name": "blue", "command": ["serve"], "ports": [8080] }},
+                        {{ "name": "green", "command": ["serve"], "ports": {} }}
+                    ]
+                }}"#,
+                ports
+            ))
+        };
+
+        assert!(settings("[8080, 8081]").is_ok());
+        assert!(matches!(
+            settings("[8081, 8080, 8081]").unwrap_err(),
+            ProjectSettingsError::DuplicateServicePort { service_name, port }
+                if service_name == "green" && port == 8081
+        ));
+        assert!(matches!(
+            settings("[0]").unwrap_err(),
+            ProjectSettingsError::InvalidPort { service_name } if service_name == "green"
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_tasks() {
+        let parse = |tasks: &str| try_parse(&settings_json(tasks, "[]")).unwrap_err();
+
+        assert!(matches!(
+            parse(r#"[{ "name": "", "command": ["make"] }]"#),
+            ProjectSettingsError::EmptyTaskName
+        ));
+        assert!(matches!(
+            parse(r#"[{ "name": "db migrate", "command": ["make"] }]"#),
+            ProjectSettingsError::InvalidTaskName { task_name } if task_name == "db migrate"
+        ));
+        assert!(matches!(
+            parse(r#"[{ "name": "build", "command": [] }]"#),
+            ProjectSettingsError::EmptyTaskCommand { task_name } if task_name == "build"
+        ));
+        assert!(matches!(
+            parse(r#"[{ "name": "build", "command": ["make"] }, { "name": "build", "command": ["cargo"] }]"#),
+            ProjectSettingsError::DuplicateTaskName { task_name } if task_name == "build"
+        ));
+    }
+
+    #[test]
+    fn rejects_unknown_before_start_task() {
+        let err = try_parse(&settings_json(TASKS, r#"["migrate", "deploy"]"#)).unwrap_err();
+
+        assert!(matches!(
+            err,
+            ProjectSettingsError::UnknownBeforeStartTask { service_name, task_name }
+                if service_name == "api" && task_name == "deploy"
+        ));
+    }
+
+    #[test]
+    fn before_start_tasks_follow_the_services_and_run_once() {
+        let settings = try_parse(&settings_json(TASKS, r#"["migrate", "build"]"#)).unwrap();
+        let services = |names: &[&str]| {
+            names
+                .iter()
+                .map(|name| String::from(*name))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            settings.before_start_tasks(&services(&["api", "worker"])),
+            vec!["migrate", "build", "seed"]
+        );
+        assert_eq!(
+            settings.before_start_tasks(&services(&["worker"])),
+            vec!["seed", "build"]
+        );
+        assert!(settings.before_start_tasks(&[]).is_empty());
+    }
+
+    #[test]
+    fn resolves_the_task_execution_context() {
+        let settings = try_parse(&settings_json(TASKS, "[]")).unwrap();
+
+        let context = settings.task_execution_context("migrate").unwrap();
+        assert_eq!(context.pwd, "/srv/shop/db");
+        assert_eq!(
+            context.env,
+            vec![
+                (String::from("MODE"), String::from("dev")),
+                (String::from("MODE"), String::from("migrate")),
+                (String::from("RETRIES"), String::from("3")),
+            ]
+        );
+
+        let context = settings.task_execution_context("build").unwrap();
+        assert_eq!(context.pwd, "/srv/shop");
+
+        assert!(matches!(
+            settings.task_execution_context("deploy").unwrap_err(),
+            ProjectSettingsError::TaskNotFound { task_name } if task_name == "deploy"
+        ));
+    }
+}
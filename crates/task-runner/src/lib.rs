@@ -0,0 +1,526 @@
+use daemon_client::{ErrorResponse, Requester};
+use project_settings::{ProjectSettings, ProjectSettingsError};
+use serde::{Deserialize, Serialize};
+use std::{
+    convert::TryFrom,
+    fmt::Display,
+    fs::{self, File},
+    io::Write,
+    os::unix::process::ExitStatusExt,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const LOGFILE_NAME: &str = "log";
+const STATE_FILE_NAME: &str = "state.json";
+
+#[derive(Debug, thiserror::Error)]
+pub enum TaskRunnerError {
+    #[error("error while interacting with file system: {inner}")]
+    IoError { inner: std::io::Error },
+    #[error("unable to parse the task state: {inner}")]
+    StateError { inner: serde_json::Error },
+    #[error("invalid project settings: {inner}")]
+    SettingsError { inner: ProjectSettingsError },
+    #[error("{inner}")]
+    RequestError { inner: ErrorResponse },
+    #[error("unable to start the task '{task_name}': {inner}")]
+    StartError {
+        task_name: String,
+        inner: std::io::Error,
+    },
+    #[error("task '{task_name}' has an empty command")]
+    EmptyCommand { task_name: String },
+    #[error("task '{task_name}' {status}, check its logs at '{logfile_path}'")]
+    TaskFailed {
+        task_name: String,
+        status: String,
+        logfile_path: String,
+    },
+}
+
+impl From<std::io::Error> for TaskRunnerError {
+    fn from(value: std::io::Error) -> Self {
+        TaskRunnerError::IoError { inner: value }
+    }
+}
+
+impl From<serde_json::Error> for TaskRunnerError {
+    fn from(value: serde_json::Error) -> Self {
+        TaskRunnerError::StateError { inner: value }
+    }
+}
+
+impl From<ProjectSettingsError> for TaskRunnerError {
+    fn from(value: ProjectSettingsError) -> Self {
+        TaskRunnerError::SettingsError { inner: value }
+    }
+}
+
+impl From<ErrorResponse> for TaskRunnerError {
+    fn from(value: ErrorResponse) -> Self {
+        TaskRunnerError::RequestError { inner: value }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Deserialize, Serialize)]
+pub enum TaskStatus {
+    Running,
+    Succeeded,
+    Failed,
+    /// Task was marked as running but its process does not exist anymore
+    Interrupted,
+}
+
+impl Display for TaskStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            TaskStatus::Running => "Running",
+            TaskStatus::Succeeded => "Succeeded",
+            TaskStatus::Failed => "Failed",
+            TaskStatus::Interrupted => "Interrupted",
+        };
+
+        write!(f, "{}", str)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TaskRun {
+    pub project_name: String,
+    pub task_name: String,
+    pub status: TaskStatus,
+    pub pid: u32,
+    pub started_at: u64,
+    pub finished_at: Option<u64>,
+    /// exit code of the finished task, 128 + the signal number when it was killed by a signal like in shells
+    pub exit_code: Option<i32>,
+    /// signal which killed the task, state written by older versions does not have it
+    #[serde(default)]
+    pub signal: Option<i32>,
+    pub logfile_path: String,
+}
+
+impl TaskRun {
+    pub fn describe_result(&self) -> String {
+        match (self.status, self.signal, self.exit_code) {
+            (TaskStatus::Running, _, _) => String::from("is still running"),
+            (TaskStatus::Interrupted, _, _) => String::from("was interrupted"),
+            (_, Some(signal), _) => format!("was terminated by the signal {}", signal),
+            (_, None, Some(code)) => format!("finished with exit code {}", code),
+            (_, None, None) => String::from("could not be started"),
+        }
+    }
+}
+
+/// Runs tasks of projects on the client side and keeps the state of their last run in the tasks directory
+#[derive(Debug, Clone)]
+pub struct TaskRunner {
+    tasks_dir: String,
+    use_caller_env: bool,
+}
+
+impl TaskRunner {
+    pub fn new(tasks_dir: &str, use_caller_env: bool) -> Self {
+        Self {
+            tasks_dir: tasks_dir.to_string(),
+            use_caller_env,
+        }
+    }
+
+    /// Runs the task and blocks until it finishes, failure of the task itself is reported in the returned run
+    pub fn run(
+        &self,
+        settings: &ProjectSettings,
+        task_name: &str,
+    ) -> Result<TaskRun, TaskRunnerError> {
+        let task = settings.task(task_name)?;
+        let context = settings.task_execution_context(task_name)?;
+        let (program, args) =
+            task.command
+                .split_first()
+                .ok_or_else(|| TaskRunnerError::EmptyCommand {
+                    task_name: String::from(task_name),
+                })?;
+
+        let task_dir = self.task_dir(&settings.name, task_name);
+        fs::create_dir_all(&task_dir)?;
+
+        let logfile_path = task_dir.join(LOGFILE_NAME);
+        let mut logfile = File::create(&logfile_path)?;
+
+        let use_caller_env = self.use_caller_env;
+        let env = context
+            .env
+            .into_iter()
+            .filter(|(key, _)| !use_caller_env || std::env::var_os(key).is_none());
+
+        let mut run = TaskRun {
+            project_name: settings.name.clone(),
+            task_name: String::from(task_name),
+            status: TaskStatus::Running,
+            pid: 0,
+            started_at: now_secs(),
+            finished_at: None,
+            exit_code: None,
+            signal: None,
+            logfile_path: path_to_string(&logfile_path),
+        };
+
+        let spawned = Command::new(program)
+            .args(args)
+            .current_dir(context.pwd)
+            .envs(env)
+            .stdin(Stdio::null())
+            .stdout(logfile.try_clone()?)
+            .stderr(logfile.try_clone()?)
+            .spawn();
+
+        // failure to start is recorded as well so it is visible for the callers not waiting for the result
+        let mut child = match spawned {
+            Ok(child) => child,
+            Err(err) => {
+                writeln!(logfile, "Unable to start the task '{}': {}", task_name, err)?;
+                run.status = TaskStatus::Failed;
+                run.finished_at = Some(now_secs());
+                self.write_state(&run)?;
+                return Err(TaskRunnerError::StartError {
+                    task_name: String::from(task_name),
+                    inner: err,
+                });
+            }
+        };
+
+        run.pid = child.id();
+        self.write_state(&run)?;
+
+        let exit_status = child.wait()?;
+
+        run.finished_at = Some(now_secs());
+        run.signal = exit_status.signal();
+        run.exit_code = exit_status
+            .code()
+            .or_else(|| run.signal.map(|signal| 128 + signal));
+        run.status = match exit_status.success() {
+            true => TaskStatus::Succeeded,
+            false => TaskStatus::Failed,
+        };
+        self.write_state(&run)?;
+
+        Ok(run)
+    }
+
+    pub fn last_run(
+        &self,
+        project_name: &str,
+        task_name: &str,
+    ) -> Result<Option<TaskRun>, TaskRunnerError> {
+        let state_path = self.task_dir(project_name, task_name).join(STATE_FILE_NAME);
+        if !state_path.exists() {
+            return Ok(None);
+        }
+
+        let mut run: TaskRun = serde_json::from_str(&fs::read_to_string(state_path)?)?;
+
+        // process table is only checked where it is available
+        let proc_dir = Path::new("/proc");
+        if run.status == TaskStatus::Running
+            && proc_dir.is_dir()
+            && !proc_dir.join(run.pid.to_string()).exists()
+        {
+            run.status = TaskStatus::Interrupted;
+        }
+
+        Ok(Some(run))
+    }
+
+    /// Runs all 'before_start' tasks of the services, without services all services of the project are used
+    pub fn run_before_start(
+        &self,
+        requester: &Requester,
+        project_name: &str,
+        service_names: Option<&[String]>,
+    ) -> Result<(), TaskRunnerError> {
+        let json = requester.get_project_settings(project_name)?;
+        let settings = ProjectSettings::try_from(json.as_str())?;
+
+        let service_names = match service_names {
+            Some(service_names) => service_names.to_vec(),
+            None => settings
+                .services
+                .iter()
+                .map(|service| service.name.clone())
+                .collect(),
+        };
+
+        for task_name in settings.before_start_tasks(&service_names) {
+            let run = self.run(&settings, &task_name)?;
+            if run.status != TaskStatus::Succeeded {
+                return Err(TaskRunnerError::TaskFailed {
+                    task_name,
+                    status: run.describe_result(),
+                    logfile_path: run.logfile_path,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn logfile_path(&self, project_name: &str, task_name: &str) -> String {
+        path_to_string(&self.task_dir(project_name, task_name).join(LOGFILE_NAME))
+    }
+
+    fn write_state(&self, run: &TaskRun) -> Result<(), TaskRunnerError> {
+        let task_dir = self.task_dir(&run.project_name, &run.task_name);
+        let tmp_path = task_dir.join(format!("{}.tmp", STATE_FILE_NAME));
+
+        fs::write(&tmp_path, serde_json::to_string(run)?)?;
+        fs::rename(tmp_path, task_dir.join(STATE_FILE_NAME))?;
+
+        Ok(())
+    }
+
+    fn task_dir(&self, project_name: &str, task_name: &str) -> PathBuf {
+        Path::new(&self.tasks_dir)
+            .join(project_name)
+            .join(task_name)
+    }
+}
+
+fn path_to_string(path: &Path) -> String {
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    String::from(path.to_str().unwrap_or_default())
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use daemon_client::mock::{MockDaemon, MockProject};
+    use project_settings::try_parse;
+
+    use super::*;
+
+    fn tasks_dir(name: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("conc-tasks-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        path_to_string(&dir)
+    }
+
+    /// Project whose tasks run the shell scripts, services depend on the listed tasks
+    fn settings_json(tasks: &[(&str, &str)], services: &[(&str, &[&str])]) -> String {
+        let tasks: Vec<serde_json::Value> = tasks
+            .iter()
+            .map(|(name, script)| serde_json::json!({ "name": name, "command": ["sh", "-c", script] }))
+            .collect();
+        let services: Vec<serde_json::Value> = services
+            .iter()
+            .map(|(name, before_start)| {
+                serde_json::json!({ "name": name, "command": ["true"], "before_start": before_start })
+            })
+            .collect();
+
+        serde_json::json!({
+            "name": "shop",
+            "cwd": std::env::temp_dir(),
+            "services": services,
+            "tasks": tasks,
+        })
+        .to_string()
+    }
+
+    fn settings(tasks: &[(&str, &str)]) -> ProjectSettings {
+        try_parse(&settings_json(tasks, &[("api", &[])])).unwrap()
+    }
+
+    #[test]
+    fn successful_run_is_stored_as_the_last_run() {
+        let runner = TaskRunner::new(&tasks_dir("success"), false);
+        let settings = settings(&[("migrate", "echo migrated")]);
+
+        assert!(runner.last_run("shop", "migrate").unwrap().is_none());
+
+        let run = runner.run(&settings, "migrate").unwrap();
+
+        assert_eq!(run.status, TaskStatus::Succeeded);
+        assert_eq!(run.exit_code, Some(0));
+        assert_eq!(run.signal, None);
+        assert!(run.finished_at.is_some());
+        assert_eq!(fs::read_to_string(&run.logfile_path).unwrap(), "migrated\n");
+
+        let last_run = runner.last_run("shop", "migrate").unwrap().unwrap();
+        assert_eq!(last_run.status, TaskStatus::Succeeded);
+        assert_eq!(last_run.pid, run.pid);
+        // the state is written to a temporary file which replaces the state file
+        let task_dir = runner.task_dir("shop", "migrate");
+        assert!(!task_dir.join("state.json.tmp").exists());
+
+        let _ = fs::remove_dir_all(&runner.tasks_dir);
+    }
+
+    #[test]
+    fn exit_code_and_signal_are_kept_apart() {
+        let runner = TaskRunner::new(&tasks_dir("failure"), false);
+        let settings = settings(&[("exit", "exit 3"), ("killed", "kill -TERM $$")]);
+
+        let run = runner.run(&settings, "exit").unwrap();
+        assert_eq!(run.status, TaskStatus::Failed);
+        assert_eq!(run.exit_code, Some(3));
+        assert_eq!(run.signal, None);
+        assert_eq!(run.describe_result(), "finished with exit code 3");
+
+        let run = runner.run(&settings, "killed").unwrap();
+        assert_eq!(run.status, TaskStatus::Failed);
+        assert_eq!(run.exit_code, Some(128 + 15));
+        assert_eq!(run.signal, Some(15));
+        assert_eq!(run.describe_result(), "was terminated by the signal 15");
+
+        let _ = fs::remove_dir_all(&runner.tasks_dir);
+    }
+
+    #[test]
+    fn run_which_could_not_start_is_recorded() {
+        let runner = TaskRunner::new(&tasks_dir("start"), false);
+        let json = serde_json::json!({
+            "name": "shop",
+            "cwd": std::env::temp_dir(),
+            "services": [{ "name": "api", "command": ["true"] }],
+            "tasks": [{ "name": "missing", "command": ["/nonexistent/conc-task"] }],
+        })
+        .to_string();
+        let settings = try_parse(&json).unwrap();
+
+        let err = runner.run(&settings, "missing").unwrap_err();
+        assert!(matches!(err, TaskRunnerError::StartError { .. }));
+
+        let last_run = runner.last_run("shop", "missing").unwrap().unwrap();
+        assert_eq!(last_run.status, TaskStatus::Failed);
+        assert_eq!(last_run.exit_code, None);
+        assert_eq!(last_run.describe_result(), "could not be started");
+        assert!(fs::read_to_string(&last_run.logfile_path)
+            .unwrap()
+            .contains("Unable to start the task 'missing'"));
+
+        let _ = fs::remove_dir_all(&runner.tasks_dir);
+    }
+
+    #[test]
+    fn running_task_without_a_process_is_interrupted() {
+        let runner = TaskRunner::new(&tasks_dir("interrupted"), false);
+        fs::create_dir_all(runner.task_dir("shop", "seed")).unwrap();
+
+        let mut exited = Command::new("true").spawn().unwrap();
+        exited.wait().unwrap();
+        let mut run = TaskRun {
+            project_name: String::from("shop"),
+            task_name: String::from("seed"),
+            status: TaskStatus::Running,
+            pid: exited.id(),
+            started_at: now_secs(),
+            finished_at: None,
+            exit_code: None,
+            signal: None,
+            logfile_path: runner.logfile_path("shop", "seed"),
+        };
+        runner.write_state(&run).unwrap();
+
+        let last_run = runner.last_run("shop", "seed").unwrap().unwrap();
+        assert_eq!(last_run.status, TaskStatus::Interrupted);
+        assert_eq!(last_run.describe_result(), "was interrupted");
+
+        run.pid = std::process::id();
+        runner.write_state(&run).unwrap();
+        let last_run = runner.last_run("shop", "seed").unwrap().unwrap();
+        assert_eq!(last_run.status, TaskStatus::Running);
+
+        let _ = fs::remove_dir_all(&runner.tasks_dir);
+    }
+
+    #[test]
+    fn state_without_the_signal_is_read() {
+        let runner = TaskRunner::new(&tasks_dir("old-state"), false);
+        let task_dir = runner.task_dir("shop", "seed");
+        fs::create_dir_all(&task_dir).unwrap();
+        fs::write(
+            task_dir.join(STATE_FILE_NAME),
+            r#"{"project_name":"shop","task_name":"seed","status":"Failed","pid":1,"started_at":1,"finished_at":2,"exit_code":137,"logfile_path":"log"}"#,
+        )
+        .unwrap();
+
+        let last_run = runner.last_run("shop", "seed").unwrap().unwrap();
+
+        assert_eq!(last_run.signal, None);
+        assert_eq!(last_run.describe_result(), "finished with exit code 137");
+
+        let _ = fs::remove_dir_all(&runner.tasks_dir);
+    }
+
+    #[test]
+    fn before_start_tasks_run_once_in_the_order_of_the_services() {
+        let runner = TaskRunner::new(&tasks_dir("before-start"), false);
+        let order_path = Path::new(&runner.tasks_dir).join("order");
+        fs::create_dir_all(&runner.tasks_dir).unwrap();
+        let script = |name: &str| format!("echo {} >> '{}'", name, order_path.display());
+        let (build, migrate, seed) = (script("build"), script("migrate"), script("seed"));
+        let json = settings_json(
+            &[("build", &build), ("migrate", &migrate), ("seed", &seed)],
+            &[
+                ("api", &["migrate", "build"]),
+                ("worker", &["build", "seed"]),
+                ("web", &["seed"]),
+            ],
+        );
+        let daemon = MockDaemon::start()
+            .unwrap()
+            .with_project(MockProject::from_settings(&json).unwrap());
+        let requester = daemon.requester();
+
+        runner.run_before_start(&requester, "shop", None).unwrap();
+        assert_eq!(
+            fs::read_to_string(&order_path).unwrap(),
+            "migrate\nbuild\nseed\n"
+        );
+
+        fs::remove_file(&order_path).unwrap();
+        let services = [String::from("web"), String::from("worker")];
+        runner
+            .run_before_start(&requester, "shop", Some(&services))
+            .unwrap();
+        assert_eq!(fs::read_to_string(&order_path).unwrap(), "build\nseed\n");
+
+        let _ = fs::remove_dir_all(&runner.tasks_dir);
+    }
+
+    #[test]
+    fn failed_before_start_task_stops_the_remaining_ones() {
+        let runner = TaskRunner::new(&tasks_dir("before-start-failure"), false);
+        let marker = Path::new(&runner.tasks_dir).join("seeded");
+        let seed = format!("touch '{}'", marker.display());
+        let json = settings_json(
+            &[("migrate", "exit 1"), ("seed", &seed)],
+            &[("api", &["migrate", "seed"])],
+        );
+        let daemon = MockDaemon::start()
+            .unwrap()
+            .with_project(MockProject::from_settings(&json).unwrap());
+
+        let err = runner
+            .run_before_start(&daemon.requester(), "shop", None)
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            TaskRunnerError::TaskFailed { ref task_name, .. } if task_name == "migrate"
+        ));
+        assert!(!marker.exists());
+
+        let _ = fs::remove_dir_all(&runner.tasks_dir);
+    }
+}
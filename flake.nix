@@ -95,6 +95,28 @@
           };
         };
 
+        packages.gateway = pkgs.rustPlatform.buildRustPackage {
+          pname = "conch";
+          version = version;
+          src = ./.;
+          buildAndTestSubdir = "apps/conch";
+
+          cargoLock = {
+            lockFile = ./Cargo.lock;
+          };
+
+          hardeningDisable = [ "all" ];
+
+          meta = {
+            homepage = "https://github.com/prixladi/conc";
+            description = "HTTP gateway for conc - process manager";
+            license = pkgs.lib.licenses.mit;
+            maintainers = [ ];
+            platforms = pkgs.lib.platforms.all;
+            mainProgram = "conch";
+          };
+        };
+
         services.daemon = {
           unitConfig = {
             Description = "Conc service daemon";